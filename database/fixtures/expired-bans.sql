@@ -0,0 +1,20 @@
+INSERT INTO
+  Bans (
+    player_id,
+    player_ip,
+    reason,
+    admin_id,
+    plugin_version_id,
+    created_on,
+    expires_on
+  )
+VALUES
+  (
+    76561198118681904,
+    "::1",
+    "auto_bhop",
+    76561198282622073,
+    1,
+    NOW() - INTERVAL 2 DAY,
+    NOW() - INTERVAL 1 DAY
+  );
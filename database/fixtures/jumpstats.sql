@@ -0,0 +1,269 @@
+INSERT INTO
+  Jumpstats (
+    type,
+    mode,
+    strafes,
+    distance,
+    sync,
+    pre,
+    max,
+    overlap,
+    bad_angles,
+    dead_air,
+    height,
+    airpath,
+    deviation,
+    average_width,
+    airtime,
+    player_id,
+    server_id,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    1,
+    1,
+    5,
+    270.1,
+    0.8,
+    276.3,
+    310.9,
+    0.03,
+    0.01,
+    0.02,
+    63.2,
+    0.95,
+    0.4,
+    12.5,
+    0.73,
+    76561198282622073,
+    1,
+    1,
+    "2024-02-01 12:00:00"
+  );
+
+INSERT INTO
+  Jumpstats (
+    type,
+    mode,
+    strafes,
+    distance,
+    sync,
+    pre,
+    max,
+    overlap,
+    bad_angles,
+    dead_air,
+    height,
+    airpath,
+    deviation,
+    average_width,
+    airtime,
+    player_id,
+    server_id,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    1,
+    1,
+    5,
+    250.4,
+    0.8,
+    276.3,
+    310.9,
+    0.03,
+    0.01,
+    0.02,
+    63.2,
+    0.95,
+    0.4,
+    12.5,
+    0.73,
+    76561198282622073,
+    1,
+    1,
+    "2024-02-02 12:00:00"
+  );
+
+INSERT INTO
+  Jumpstats (
+    type,
+    mode,
+    strafes,
+    distance,
+    sync,
+    pre,
+    max,
+    overlap,
+    bad_angles,
+    dead_air,
+    height,
+    airpath,
+    deviation,
+    average_width,
+    airtime,
+    player_id,
+    server_id,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    1,
+    1,
+    5,
+    260.7,
+    0.8,
+    276.3,
+    310.9,
+    0.03,
+    0.01,
+    0.02,
+    63.2,
+    0.95,
+    0.4,
+    12.5,
+    0.73,
+    76561198264939817,
+    1,
+    1,
+    "2024-02-03 12:00:00"
+  );
+
+INSERT INTO
+  Jumpstats (
+    type,
+    mode,
+    strafes,
+    distance,
+    sync,
+    pre,
+    max,
+    overlap,
+    bad_angles,
+    dead_air,
+    height,
+    airpath,
+    deviation,
+    average_width,
+    airtime,
+    player_id,
+    server_id,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    1,
+    1,
+    5,
+    280.2,
+    0.8,
+    276.3,
+    310.9,
+    0.03,
+    0.01,
+    0.02,
+    63.2,
+    0.95,
+    0.4,
+    12.5,
+    0.73,
+    76561198118681904,
+    1,
+    1,
+    "2024-02-04 12:00:00"
+  );
+
+INSERT INTO
+  Jumpstats (
+    type,
+    mode,
+    strafes,
+    distance,
+    sync,
+    pre,
+    max,
+    overlap,
+    bad_angles,
+    dead_air,
+    height,
+    airpath,
+    deviation,
+    average_width,
+    airtime,
+    player_id,
+    server_id,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    2,
+    1,
+    5,
+    300.3,
+    0.8,
+    276.3,
+    310.9,
+    0.03,
+    0.01,
+    0.02,
+    63.2,
+    0.95,
+    0.4,
+    12.5,
+    0.73,
+    76561198282622073,
+    1,
+    1,
+    "2024-02-05 12:00:00"
+  );
+
+INSERT INTO
+  Jumpstats (
+    type,
+    mode,
+    strafes,
+    distance,
+    sync,
+    pre,
+    max,
+    overlap,
+    bad_angles,
+    dead_air,
+    height,
+    airpath,
+    deviation,
+    average_width,
+    airtime,
+    player_id,
+    server_id,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    2,
+    1,
+    5,
+    310.9,
+    0.8,
+    276.3,
+    310.9,
+    0.03,
+    0.01,
+    0.02,
+    63.2,
+    0.95,
+    0.4,
+    12.5,
+    0.73,
+    76561198264939817,
+    1,
+    1,
+    "2024-02-06 12:00:00"
+  );
@@ -0,0 +1,319 @@
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 1
+        AND f.teleports = 0
+    ),
+    0,
+    0,
+    62.5,
+    76561198282622073,
+    1,
+    100,
+    60,
+    30,
+    1,
+    "2024-01-05 12:00:00"
+  );
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 1
+        AND f.teleports = 1
+    ),
+    0,
+    3,
+    58.1,
+    76561198282622073,
+    1,
+    110,
+    65,
+    32,
+    1,
+    "2024-01-20 18:30:00"
+  );
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 2
+        AND f.teleports = 0
+    ),
+    0,
+    0,
+    71.0,
+    76561198282622073,
+    1,
+    120,
+    70,
+    34,
+    1,
+    "2024-02-02 09:15:00"
+  );
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 1
+        AND f.teleports = 1
+    ),
+    0,
+    1,
+    64.9,
+    76561198282622073,
+    1,
+    130,
+    75,
+    36,
+    1,
+    "2024-02-14 21:45:00"
+  );
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 2
+        AND f.teleports = 0
+    ),
+    0,
+    0,
+    69.3,
+    76561198282622073,
+    1,
+    140,
+    80,
+    38,
+    1,
+    "2024-03-01 00:00:00"
+  );
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 1
+        AND f.teleports = 0
+    ),
+    0,
+    0,
+    60.2,
+    76561198282622073,
+    1,
+    150,
+    85,
+    40,
+    1,
+    "2024-03-10 14:20:00"
+  );
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 2
+        AND f.teleports = 1
+    ),
+    0,
+    2,
+    75.8,
+    76561198282622073,
+    1,
+    160,
+    90,
+    42,
+    1,
+    "2024-03-22 07:05:00"
+  );
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id,
+    created_on
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 1
+        AND f.teleports = 0
+    ),
+    0,
+    0,
+    59.0,
+    76561198282622073,
+    1,
+    170,
+    95,
+    44,
+    1,
+    "2024-04-01 16:40:00"
+  );
@@ -0,0 +1,18 @@
+UPDATE
+  `Servers`
+SET
+  `last_seen_on` = NOW() - INTERVAL 1 DAY;
+
+UPDATE
+  `Servers`
+SET
+  `last_seen_on` = NOW() - INTERVAL 1 MINUTE
+WHERE
+  `name` = "balls.kz EU 1";
+
+UPDATE
+  `Servers`
+SET
+  `last_seen_on` = NOW() - INTERVAL 7 MINUTE
+WHERE
+  `name` = "balls.kz EU 2";
@@ -0,0 +1,143 @@
+INSERT INTO
+  Players (id, name, ip_address)
+VALUES
+  (76561198000000001, "kiwinator", "::1");
+
+INSERT INTO
+  Players (id, name, ip_address)
+VALUES
+  (76561198000000002, "xkiwix", "::1");
+
+INSERT INTO
+  Players (id, name, ip_address)
+VALUES
+  (76561198000000003, "kiwi", "::1");
+
+INSERT INTO
+  Players (id, name, ip_address)
+VALUES
+  (76561198000000004, "kiwi_fan", "::1");
+
+INSERT INTO
+  Players (id, name, ip_address)
+VALUES
+  (76561198000000005, "ikiwi", "::1");
+
+INSERT INTO
+  Players (id, name, ip_address)
+VALUES
+  (76561198000000006, "banana", "::1");
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 1
+        AND f.teleports = 0
+    ),
+    0,
+    0,
+    60.5,
+    76561198000000001,
+    1,
+    100,
+    60,
+    30,
+    1
+  );
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 1
+        AND f.teleports = 0
+    ),
+    0,
+    0,
+    61.5,
+    76561198000000001,
+    1,
+    100,
+    60,
+    30,
+    1
+  );
+
+INSERT INTO
+  Records (
+    filter_id,
+    styles,
+    teleports,
+    time,
+    player_id,
+    server_id,
+    bhops,
+    perfs,
+    perfect_perfs,
+    plugin_version_id
+  )
+VALUES
+  (
+    (
+      SELECT
+        f.id
+      FROM
+        CourseFilters f
+        JOIN Courses c ON c.id = f.course_id
+        JOIN Maps m ON m.id = c.map_id
+      WHERE
+        m.name = "kz_checkmate"
+        AND f.mode = 1
+        AND f.teleports = 0
+    ),
+    0,
+    0,
+    60.5,
+    76561198000000005,
+    1,
+    100,
+    60,
+    30,
+    1
+  );
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS `IdempotencyKeys` (
+  `key` VARCHAR(255) NOT NULL,
+  `request_hash` BINARY(16) NOT NULL,
+  `response_status` INT2 UNSIGNED,
+  `response_headers` JSON,
+  `response_body` MEDIUMBLOB,
+  `created_on` TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`key`),
+  CONSTRAINT `non_empty_key` CHECK(`key` != '')
+);
@@ -0,0 +1,4 @@
+ALTER TABLE
+  `Servers`
+DROP
+  COLUMN IF EXISTS `key_expires_on`;
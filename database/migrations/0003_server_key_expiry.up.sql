@@ -0,0 +1,6 @@
+ALTER TABLE
+  `Servers`
+ADD
+  COLUMN `key_expires_on` TIMESTAMP NULL DEFAULT NULL
+AFTER
+  `key`;
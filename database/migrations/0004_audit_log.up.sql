@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS `AuditLog` (
+  `id` INT8 UNSIGNED NOT NULL AUTO_INCREMENT,
+  `actor_id` INT8 UNSIGNED,
+  `entity_type` VARCHAR(32) NOT NULL,
+  `entity_id` INT8 UNSIGNED NOT NULL,
+  `action` VARCHAR(64) NOT NULL,
+  `details` JSON NOT NULL,
+  `created_on` TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  FOREIGN KEY (`actor_id`) REFERENCES `Players` (`id`),
+  INDEX (`entity_type`, `entity_id`),
+  INDEX (`created_on`)
+);
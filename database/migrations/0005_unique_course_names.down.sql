@@ -0,0 +1,4 @@
+ALTER TABLE
+  `Courses`
+DROP
+  INDEX IF EXISTS `unique_course_name`;
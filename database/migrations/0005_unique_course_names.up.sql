@@ -0,0 +1,30 @@
+-- Maps may already have several courses with the same name, which would make
+-- adding the constraint fail. The first course keeps its name; every other one
+-- gets its ID appended (e.g. `main-12`), shortened to fit into 16 characters.
+UPDATE
+  `Courses` c
+  JOIN (
+    SELECT
+      `id`,
+      ROW_NUMBER() OVER (
+        PARTITION BY `map_id`,
+        `name`
+        ORDER BY
+          `id`
+      ) n
+    FROM
+      `Courses`
+  ) dupes ON dupes.`id` = c.`id`
+SET
+  c.`name` = CONCAT(
+    LEFT(c.`name`, 16 - CHAR_LENGTH(CONCAT('-', c.`id`))),
+    '-',
+    c.`id`
+  )
+WHERE
+  dupes.n > 1;
+
+ALTER TABLE
+  `Courses`
+ADD
+  CONSTRAINT `unique_course_name` UNIQUE (`map_id`, `name`);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS `RefreshTokens` (
+  `id` UUID NOT NULL,
+  `family_id` UUID NOT NULL,
+  `session_id` UUID NOT NULL,
+  `player_id` INT8 UNSIGNED NOT NULL,
+  `created_on` TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `expires_on` TIMESTAMP NOT NULL,
+  `used_on` TIMESTAMP NULL,
+  PRIMARY KEY (`id`),
+  FOREIGN KEY (`session_id`) REFERENCES `LoginSessions` (`id`),
+  FOREIGN KEY (`player_id`) REFERENCES `Players` (`id`),
+  INDEX (`family_id`)
+);
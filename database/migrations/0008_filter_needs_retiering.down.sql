@@ -0,0 +1,4 @@
+ALTER TABLE
+  `CourseFilters`
+DROP
+  COLUMN IF EXISTS `needs_retiering`;
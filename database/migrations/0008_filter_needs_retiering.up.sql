@@ -0,0 +1,4 @@
+ALTER TABLE
+  `CourseFilters`
+ADD
+  COLUMN IF NOT EXISTS `needs_retiering` BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+ALTER TABLE
+  `PluginVersions`
+DROP
+  COLUMN IF EXISTS `yanked`;
@@ -0,0 +1,4 @@
+ALTER TABLE
+  `PluginVersions`
+ADD
+  COLUMN IF NOT EXISTS `yanked` BOOLEAN NOT NULL DEFAULT FALSE;
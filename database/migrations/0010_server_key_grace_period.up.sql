@@ -0,0 +1,10 @@
+ALTER TABLE
+  `Servers`
+ADD
+  COLUMN `previous_key` UUID UNIQUE NULL DEFAULT NULL
+AFTER
+  `key_expires_on`,
+ADD
+  COLUMN `previous_key_expires_on` TIMESTAMP NULL DEFAULT NULL
+AFTER
+  `previous_key`;
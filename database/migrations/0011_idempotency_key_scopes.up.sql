@@ -0,0 +1,16 @@
+-- Stored responses are only kept for a day, so there is nothing worth
+-- migrating.
+DROP TABLE IF EXISTS `IdempotencyKeys`;
+
+CREATE TABLE `IdempotencyKeys` (
+  `caller` BINARY(16) NOT NULL,
+  `key` VARCHAR(255) NOT NULL,
+  `request_hash` BINARY(16) NOT NULL,
+  `response_status` INT2 UNSIGNED,
+  `response_headers` JSON,
+  `response_body` MEDIUMBLOB,
+  `created_on` TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`caller`, `key`),
+  INDEX (`created_on`),
+  CONSTRAINT `non_empty_key` CHECK(`key` != '')
+);
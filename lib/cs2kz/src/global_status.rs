@@ -49,6 +49,16 @@ impl GlobalStatus
 		matches!(*self, Self::InTesting)
 	}
 
+	/// Checks whether a map with this status may be changed to `next`.
+	///
+	/// Maps can always be degloballed or sent back into testing, but a map
+	/// that has been degloballed has to go through testing again before it can
+	/// become global. Keeping the current status is always allowed.
+	pub const fn can_transition_to(self, next: Self) -> bool
+	{
+		!matches!((self, next), (Self::NotGlobal, Self::Global))
+	}
+
 	/// Returns a string representation of this [GlobalStatus], as accepted by
 	/// the API.
 	pub const fn as_str(&self) -> &'static str
@@ -245,3 +255,29 @@ mod utoipa_impls
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests
+{
+	use super::GlobalStatus;
+
+	#[test]
+	fn transitions()
+	{
+		use GlobalStatus::{Global, InTesting, NotGlobal};
+
+		for (from, to, allowed) in [
+			(NotGlobal, NotGlobal, true),
+			(NotGlobal, InTesting, true),
+			(NotGlobal, Global, false),
+			(InTesting, NotGlobal, true),
+			(InTesting, InTesting, true),
+			(InTesting, Global, true),
+			(Global, NotGlobal, true),
+			(Global, InTesting, true),
+			(Global, Global, true),
+		] {
+			assert_eq!(from.can_transition_to(to), allowed, "{from} -> {to}");
+		}
+	}
+}
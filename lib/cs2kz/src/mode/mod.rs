@@ -135,18 +135,33 @@ impl TryFrom<u8> for Mode
 }
 
 /// An error that can occur when parsing a string into a [`Mode`].
+///
+/// The error message lists all accepted spellings.
 #[derive(Debug, Clone, PartialEq, Error)]
-#[error("`{0}` is not a known mode")]
-pub struct UnknownMode(pub String);
+#[error("`{0}` is not a known mode (expected one of {accepted})", accepted = Mode::ACCEPTED_NAMES)]
+pub struct ParseModeError(pub String);
+
+impl Mode
+{
+	/// All the strings accepted by [`Mode`]'s [`FromStr`] implementation, as
+	/// shown in [`ParseModeError`]'s error message.
+	///
+	/// Names are matched case-insensitively.
+	const ACCEPTED_NAMES: &'static str = "`vnl`, `vanilla`, `1`, `ckz`, `classic`, `2`";
+}
 
 impl FromStr for Mode
 {
-	type Err = UnknownMode;
+	type Err = ParseModeError;
 
+	/// Parses a [`Mode`] from its short name (`vnl`, `ckz`), its full name
+	/// (`vanilla`, `classic`), or its numeric ID (`1`, `2`).
+	///
+	/// Names are case-insensitive.
 	fn from_str(s: &str) -> Result<Self, Self::Err>
 	{
 		if let Ok(int) = s.parse::<u8>() {
-			return Self::try_from(int).map_err(|_| UnknownMode(s.to_owned()));
+			return Self::try_from(int).map_err(|_| ParseModeError(s.to_owned()));
 		}
 
 		if s.eq_ignore_ascii_case("vnl") || s.eq_ignore_ascii_case("vanilla") {
@@ -157,6 +172,6 @@ impl FromStr for Mode
 			return Ok(Self::Classic);
 		}
 
-		Err(UnknownMode(s.to_owned()))
+		Err(ParseModeError(s.to_owned()))
 	}
 }
@@ -1,5 +1,6 @@
 //! Unit tests.
 
+use super::ParseModeError;
 use crate::Mode;
 
 #[test]
@@ -43,6 +44,41 @@ fn parse_str()
 	assert_eq!("classic".parse::<Mode>(), Ok(Mode::Classic));
 }
 
+#[test]
+fn parse_every_spelling()
+{
+	for (spellings, mode) in [
+		(["vnl", "VNL", "Vnl", "vanilla", "VANILLA", "Vanilla", "1"], Mode::Vanilla),
+		(["ckz", "CKZ", "Ckz", "classic", "CLASSIC", "Classic", "2"], Mode::Classic),
+	] {
+		for spelling in spellings {
+			assert_eq!(spelling.parse::<Mode>(), Ok(mode), "{spelling}");
+		}
+	}
+
+	for mode in [Mode::Vanilla, Mode::Classic] {
+		assert_eq!(mode.as_str().parse::<Mode>(), Ok(mode));
+		assert_eq!(mode.as_str_short().parse::<Mode>(), Ok(mode));
+		assert_eq!(mode.as_str_capitalized().parse::<Mode>(), Ok(mode));
+		assert_eq!(u8::from(mode).to_string().parse::<Mode>(), Ok(mode));
+	}
+}
+
+#[test]
+fn parse_rejects_unknown()
+{
+	for input in ["", "kzt", "vnl ", "classicc", "0", "3", "-1"] {
+		assert_eq!(input.parse::<Mode>(), Err(ParseModeError(input.to_owned())));
+	}
+
+	let message = "skz".parse::<Mode>().unwrap_err().to_string();
+
+	assert_eq!(
+		message,
+		"`skz` is not a known mode (expected one of `vnl`, `vanilla`, `1`, `ckz`, `classic`, `2`)",
+	);
+}
+
 #[test]
 fn fmt_debug()
 {
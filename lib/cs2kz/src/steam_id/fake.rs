@@ -0,0 +1,19 @@
+//! Trait implementations for the [`fake`] crate.
+
+use fake::{Dummy, Faker, Rng};
+
+use crate::SteamID;
+
+/// Generates a random public-universe individual account.
+///
+/// Every non-zero 32-bit account ID maps to a valid [`SteamID`], so the result
+/// is always in `SteamID::MIN..=SteamID::MAX`.
+impl Dummy<Faker> for SteamID
+{
+	fn dummy_with_rng<R: Rng + ?Sized>(_: &Faker, rng: &mut R) -> Self
+	{
+		let account_id = rng.gen_range(1..=u32::MAX);
+
+		Self::from_account_id(account_id)
+	}
+}
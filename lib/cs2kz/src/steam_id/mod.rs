@@ -22,6 +22,9 @@ mod sqlx;
 #[cfg(feature = "utoipa")]
 mod utoipa;
 
+#[cfg(feature = "fake")]
+mod fake;
+
 #[cfg(test)]
 mod tests;
 
@@ -129,6 +132,31 @@ impl SteamID
 		value as u32
 	}
 
+	/// Returns the 32-bit account ID.
+	///
+	/// This is the `W` value used by Steam's Web API, which is equal to
+	/// `Z * 2 + Y`. It is the same as [`SteamID::as_u32()`].
+	pub const fn account_id(&self) -> u32
+	{
+		self.as_u32()
+	}
+
+	/// Creates a [`SteamID`] from a 32-bit account ID.
+	///
+	/// The remaining fields are set to the public universe, an individual
+	/// account type, and the default desktop instance.
+	///
+	/// # Panics
+	///
+	/// This function will panic if `account_id` is 0.
+	pub const fn from_account_id(account_id: u32) -> Self
+	{
+		match Self::from_u32(account_id) {
+			Some(steam_id) => steam_id,
+			None => panic!("account ID must be non-zero"),
+		}
+	}
+
 	/// Returns the `SteamID` in its "Steam3ID" representation.
 	pub fn as_id3(&self) -> String
 	{
@@ -227,6 +255,73 @@ impl SteamID
 
 		Self::from_u32(id).ok_or(ParseSteam3IDError::OutOfRange)
 	}
+
+	/// Parses a Steam Community profile URL into a [`SteamID`].
+	///
+	/// The expected input format is
+	/// `https://steamcommunity.com/profiles/76561198282622073`. The scheme and
+	/// `www.` subdomain are optional, and anything after the ID (trailing
+	/// slashes, subpages, query strings, fragments) is ignored.
+	///
+	/// URLs of the form `https://steamcommunity.com/id/<vanity>` are
+	/// recognized, but cannot be resolved without asking Steam, so they will
+	/// return [`ParseProfileUrlError::VanityUrl`].
+	pub fn parse_profile_url(value: &str) -> Result<Self, ParseProfileUrlError>
+	{
+		let value = value
+			.strip_prefix("https://")
+			.or_else(|| value.strip_prefix("http://"))
+			.unwrap_or(value);
+
+		let value = value.strip_prefix("www.").unwrap_or(value);
+		let (host, path) = value.split_once('/').unwrap_or((value, ""));
+
+		if host != "steamcommunity.com" {
+			return Err(ParseProfileUrlError::InvalidHost);
+		}
+
+		let path = path.split(['?', '#']).next().unwrap_or_default();
+		let mut segments = path.split('/');
+
+		match segments.next() {
+			Some("profiles") => {}
+			Some("id") => {
+				return Err(match segments.next() {
+					None | Some("") => ParseProfileUrlError::MissingID,
+					Some(vanity) => ParseProfileUrlError::VanityUrl { vanity: vanity.to_owned() },
+				});
+			}
+			_ => {
+				return Err(ParseProfileUrlError::UnknownPath);
+			}
+		}
+
+		let id = segments
+			.next()
+			.filter(|segment| !segment.is_empty())
+			.ok_or(ParseProfileUrlError::MissingID)?
+			.parse::<u64>()
+			.map_err(ParseProfileUrlError::InvalidID)?;
+
+		Self::new(id).ok_or(ParseProfileUrlError::OutOfRange)
+	}
+
+	/// Parses a hex-encoded 64-bit SteamID into a [`SteamID`].
+	///
+	/// The expected input format is `0x11000011336C479`, with an optional `0x`
+	/// or `0X` prefix. This is the inverse of [`SteamID`]'s [`fmt::LowerHex`]
+	/// and [`fmt::UpperHex`] implementations.
+	pub fn parse_hex(value: &str) -> Result<Self, ParseHexError>
+	{
+		let digits = value
+			.strip_prefix("0x")
+			.or_else(|| value.strip_prefix("0X"))
+			.unwrap_or(value);
+
+		let value = u64::from_str_radix(digits, 16).map_err(ParseHexError::InvalidHex)?;
+
+		Self::new(value).ok_or(ParseHexError::OutOfRange)
+	}
 }
 
 /// Potential errors that can occur when parsing a Steam3ID.
@@ -308,6 +403,56 @@ pub enum ParseSteam3IDError
 	OutOfRange,
 }
 
+/// Potential errors that can occur when parsing a Steam Community profile URL.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ParseProfileUrlError
+{
+	/// The URL did not point to `steamcommunity.com`.
+	#[error("not a `steamcommunity.com` URL")]
+	InvalidHost,
+
+	/// The URL's path was neither `/profiles/…` nor `/id/…`.
+	#[error("not a profile URL")]
+	UnknownPath,
+
+	/// The URL ended before the ID segment.
+	#[error("missing ID segment")]
+	MissingID,
+
+	/// The URL was a `/id/<vanity>` URL.
+	///
+	/// These need to be resolved using Steam's Web API, which we can't do
+	/// here.
+	#[error("`{vanity}` is a vanity URL")]
+	VanityUrl
+	{
+		/// The vanity name that was part of the URL.
+		vanity: String,
+	},
+
+	/// Parsing the ID segment failed, because it was not a valid `u64`.
+	#[error("invalid 64-bit SteamID: {0}")]
+	InvalidID(ParseIntError),
+
+	/// Parsing the ID segment failed, because the value was out of range for a
+	/// legal SteamID.
+	#[error("64-bit SteamID out of range")]
+	OutOfRange,
+}
+
+/// Potential errors that can occur when parsing a hex-encoded SteamID.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ParseHexError
+{
+	/// The input was not a valid hexadecimal `u64`.
+	#[error("invalid hex value: {0}")]
+	InvalidHex(ParseIntError),
+
+	/// The value was out of range for a legal SteamID.
+	#[error("64-bit SteamID out of range")]
+	OutOfRange,
+}
+
 impl fmt::Display for SteamID
 {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
@@ -539,6 +684,17 @@ impl FromStr for SteamID
 			return Ok(steam_id);
 		}
 
+		if let Ok(steam_id) = Self::parse_profile_url(s) {
+			return Ok(steam_id);
+		}
+
+		// Without a prefix, hex is ambiguous with the decimal formats above.
+		if s.starts_with("0x") || s.starts_with("0X") {
+			if let Ok(steam_id) = Self::parse_hex(s) {
+				return Ok(steam_id);
+			}
+		}
+
 		Err(InvalidSteamID::UnrecognizedSteamIDFormat)
 	}
 }
@@ -1,7 +1,5 @@
 //! Trait implementations for the [`serde`] crate.
 
-use std::str::FromStr;
-
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use super::SteamID;
@@ -66,17 +64,19 @@ impl SteamID
 	where
 		D: Deserializer<'de>,
 	{
-		<&'de str>::deserialize(deserializer)
+		String::deserialize(deserializer)
 			.map(Self::from_standard)?
 			.map_err(de::Error::custom)
 	}
 
 	/// Deserialize as the "Steam3ID" format.
+	///
+	/// Both `U:1:322356345` and `[U:1:322356345]` are accepted.
 	pub fn deserialize_id3<'de, D>(deserializer: D) -> Result<Self, D::Error>
 	where
 		D: Deserializer<'de>,
 	{
-		<&'de str>::deserialize(deserializer)
+		String::deserialize(deserializer)
 			.map(Self::from_id3)?
 			.map_err(de::Error::custom)
 	}
@@ -96,8 +96,8 @@ impl SteamID
 	where
 		D: Deserializer<'de>,
 	{
-		<&'de str>::deserialize(deserializer)
-			.map(<u64 as FromStr>::from_str)?
+		String::deserialize(deserializer)
+			.map(|value| value.parse::<u64>())?
 			.map_err(de::Error::custom)
 			.map(Self::try_from)?
 			.map_err(de::Error::custom)
@@ -114,6 +114,8 @@ impl SteamID
 	}
 }
 
+/// Accepts bare 32-bit and 64-bit integers, as well as any string format
+/// supported by [`SteamID`]'s [`FromStr`](std::str::FromStr) implementation.
 impl<'de> Deserialize<'de> for SteamID
 {
 	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
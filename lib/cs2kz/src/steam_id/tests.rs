@@ -1,5 +1,6 @@
 //! Unit tests.
 
+use super::{ParseHexError, ParseProfileUrlError};
 use crate::SteamID;
 
 const ALPHAKEKS: SteamID = unsafe { SteamID::new_unchecked(76561198282622073_u64) };
@@ -52,6 +53,26 @@ fn try_from_u32()
 	assert!(SteamID::try_from((super::MAX - super::MAGIC_OFFSET + 1) as u32).is_err());
 }
 
+#[test]
+fn account_id()
+{
+	assert_eq!(ALPHAKEKS.account_id(), 322356345);
+	assert_eq!(ALPHAKEKS.account_id(), ALPHAKEKS.as_u32());
+	assert_eq!(SteamID::MIN.account_id(), 1);
+	assert_eq!(SteamID::MAX.account_id(), u32::MAX);
+
+	for steam_id in [ALPHAKEKS, SteamID::MIN, SteamID::MAX] {
+		assert_eq!(SteamID::from_account_id(steam_id.account_id()), steam_id);
+	}
+}
+
+#[test]
+#[should_panic = "account ID must be non-zero"]
+fn from_account_id_rejects_zero()
+{
+	SteamID::from_account_id(0);
+}
+
 #[test]
 fn parse_u64()
 {
@@ -99,3 +120,163 @@ fn parse_id3()
 	assert!("U:1:322356345]".parse::<SteamID>().is_err());
 	assert!("[U:1:322356345".parse::<SteamID>().is_err());
 }
+
+#[test]
+fn parse_profile_url()
+{
+	for url in [
+		"https://steamcommunity.com/profiles/76561198282622073",
+		"https://steamcommunity.com/profiles/76561198282622073/",
+		"https://steamcommunity.com/profiles/76561198282622073?xml=1",
+		"https://steamcommunity.com/profiles/76561198282622073/?l=german",
+		"https://steamcommunity.com/profiles/76561198282622073#comments",
+		"https://steamcommunity.com/profiles/76561198282622073/inventory/",
+		"http://www.steamcommunity.com/profiles/76561198282622073",
+		"steamcommunity.com/profiles/76561198282622073",
+	] {
+		assert_eq!(SteamID::parse_profile_url(url), Ok(ALPHAKEKS), "{url}");
+		assert_eq!(url.parse::<SteamID>(), Ok(ALPHAKEKS), "{url}");
+	}
+
+	assert_eq!(
+		SteamID::parse_profile_url("https://steamcommunity.com/id/alphakeks/"),
+		Err(ParseProfileUrlError::VanityUrl { vanity: String::from("alphakeks") }),
+	);
+
+	assert_eq!(
+		SteamID::parse_profile_url("https://steamcommunity.com/profiles/"),
+		Err(ParseProfileUrlError::MissingID),
+	);
+
+	assert_eq!(
+		SteamID::parse_profile_url(
+			"https://steamcommunity.com.evil.com/profiles/76561198282622073"
+		),
+		Err(ParseProfileUrlError::InvalidHost),
+	);
+
+	assert_eq!(
+		SteamID::parse_profile_url("https://steamcommunity.com/groups/cs2kz"),
+		Err(ParseProfileUrlError::UnknownPath),
+	);
+
+	assert_eq!(
+		SteamID::parse_profile_url("https://steamcommunity.com/profiles/76561197960265728"),
+		Err(ParseProfileUrlError::OutOfRange),
+	);
+
+	assert!(SteamID::parse_profile_url("https://steamcommunity.com/profiles/alphakeks").is_err());
+}
+
+#[test]
+fn parse_hex()
+{
+	assert_eq!(SteamID::parse_hex("0x11000011336c479"), Ok(ALPHAKEKS));
+	assert_eq!(SteamID::parse_hex("0X11000011336C479"), Ok(ALPHAKEKS));
+	assert_eq!(SteamID::parse_hex("11000011336c479"), Ok(ALPHAKEKS));
+
+	for steam_id in [ALPHAKEKS, SteamID::MIN, SteamID::MAX] {
+		assert_eq!(SteamID::parse_hex(&format!("{steam_id:#x}")), Ok(steam_id));
+		assert_eq!(SteamID::parse_hex(&format!("{steam_id:#X}")), Ok(steam_id));
+		assert_eq!(format!("{steam_id:#x}").parse::<SteamID>(), Ok(steam_id));
+	}
+
+	assert!(format!("{ALPHAKEKS:x}").parse::<SteamID>().is_err());
+
+	assert_eq!(SteamID::parse_hex("0x1"), Err(ParseHexError::OutOfRange));
+	assert!(matches!(SteamID::parse_hex("0x"), Err(ParseHexError::InvalidHex(_))));
+	assert!(matches!(SteamID::parse_hex("0xkz"), Err(ParseHexError::InvalidHex(_))));
+}
+
+#[test]
+fn decimal_is_never_parsed_as_hex()
+{
+	// `0x110000100000001` is `SteamID::MIN`, but as a decimal number this is
+	// out of range.
+	assert!(SteamID::parse_hex("110000100000001").is_ok());
+	assert!("110000100000001".parse::<SteamID>().is_err());
+	assert_eq!("0x110000100000001".parse::<SteamID>(), Ok(SteamID::MIN));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn deserialize()
+{
+	assert_eq!(serde_json::from_str::<SteamID>("76561198282622073").unwrap(), ALPHAKEKS);
+	assert_eq!(serde_json::from_str::<SteamID>("322356345").unwrap(), ALPHAKEKS);
+
+	for json in [
+		r#""76561198282622073""#,
+		r#""STEAM_1:1:161178172""#,
+		r#""U:1:322356345""#,
+		r#""[U:1:322356345]""#,
+	] {
+		assert_eq!(serde_json::from_str::<SteamID>(json).unwrap(), ALPHAKEKS, "{json}");
+	}
+
+	assert!(serde_json::from_str::<SteamID>(r#""[U:1:322356345""#).is_err());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_round_trip()
+{
+	use serde::{Deserialize, Serialize};
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Formats
+	{
+		#[serde(
+			serialize_with = "SteamID::serialize_u64_stringified",
+			deserialize_with = "SteamID::deserialize_u64_stringified"
+		)]
+		u64_stringified: SteamID,
+
+		#[serde(
+			serialize_with = "SteamID::serialize_standard",
+			deserialize_with = "SteamID::deserialize_standard"
+		)]
+		standard: SteamID,
+
+		#[serde(
+			serialize_with = "SteamID::serialize_id3",
+			deserialize_with = "SteamID::deserialize_id3"
+		)]
+		id3: SteamID,
+	}
+
+	let formats = Formats { u64_stringified: ALPHAKEKS, standard: ALPHAKEKS, id3: ALPHAKEKS };
+	let json = serde_json::to_value(&formats).unwrap();
+
+	assert_eq!(json["u64_stringified"], "76561198282622073");
+	assert_eq!(json["standard"], "STEAM_1:1:161178172");
+	assert_eq!(json["id3"], "U:1:322356345");
+	assert_eq!(serde_json::from_value::<Formats>(json.clone()).unwrap(), formats);
+
+	// Every format should also be accepted by the default implementation.
+	for value in json.as_object().unwrap().values() {
+		assert_eq!(serde_json::from_value::<SteamID>(value.clone()).unwrap(), ALPHAKEKS);
+	}
+
+	let bracketed = serde_json::json!({
+		"u64_stringified": "76561198282622073",
+		"standard": "STEAM_1:1:161178172",
+		"id3": "[U:1:322356345]",
+	});
+
+	assert_eq!(serde_json::from_value::<Formats>(bracketed).unwrap(), formats);
+}
+
+#[test]
+#[cfg(feature = "fake")]
+fn fake()
+{
+	use fake::{Fake, Faker};
+
+	for _ in 0..1000 {
+		let steam_id = Faker.fake::<SteamID>();
+
+		assert!(SteamID::new(steam_id.as_u64()).is_some(), "{steam_id:?}");
+		assert_eq!(steam_id.as_u64().to_string().parse::<SteamID>(), Ok(steam_id));
+	}
+}
@@ -47,6 +47,12 @@ impl<Item: ?Sized> Iter<Item>
 	{
 		Iter { bits: self.bits, _marker: PhantomData }
 	}
+
+	/// Switches the iterator to iterate over individual styles.
+	pub const fn styles(self) -> Iter<Styles>
+	{
+		Iter { bits: self.bits, _marker: PhantomData }
+	}
 }
 
 impl Iterator for Iter<u32>
@@ -84,3 +90,18 @@ impl Iterator for Iter<str>
 		})
 	}
 }
+
+impl Iterator for Iter<Styles>
+{
+	type Item = Styles;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		let mut bits = (*self).bits();
+
+		bits.next().map(|item| {
+			*self = bits.styles();
+			Styles(item)
+		})
+	}
+}
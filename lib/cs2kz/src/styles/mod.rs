@@ -24,6 +24,9 @@ mod sqlx;
 #[cfg(feature = "utoipa")]
 mod utoipa;
 
+#[cfg(test)]
+mod tests;
+
 /// All official gameplay styles included in the CS2KZ plugin.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Styles(u32);
@@ -76,12 +79,50 @@ impl Styles
 		}
 	}
 
+	/// Checks if no styles are set.
+	pub const fn is_empty(self) -> bool
+	{
+		self.0 == 0
+	}
+
 	/// Checks if `other` is a subset of `self`.
 	pub const fn contains(self, other: Self) -> bool
 	{
 		(self.0 & other.0) == other.0
 	}
 
+	/// Returns all the styles that are set in either `self` or `other`.
+	pub const fn union(self, other: Self) -> Self
+	{
+		Self(self.0 | other.0)
+	}
+
+	/// Returns the styles that are set in both `self` and `other`.
+	pub const fn intersection(self, other: Self) -> Self
+	{
+		Self(self.0 & other.0)
+	}
+
+	/// Adds the styles in `other` to `self`.
+	pub fn insert(&mut self, other: Self)
+	{
+		*self = self.union(other);
+	}
+
+	/// Removes the styles in `other` from `self`.
+	pub fn remove(&mut self, other: Self)
+	{
+		self.0 &= !other.0;
+	}
+
+	/// Creates an iterator over the individual styles.
+	///
+	/// Every item has exactly one bit set.
+	pub const fn iter(self) -> Iter<Styles>
+	{
+		Iter::new(self).styles()
+	}
+
 	/// Creates an iterator over the style bits.
 	pub const fn iter_bits(self) -> Iter<u32>
 	{
@@ -95,6 +136,17 @@ impl Styles
 	}
 }
 
+impl IntoIterator for Styles
+{
+	type Item = Self;
+	type IntoIter = Iter<Self>;
+
+	fn into_iter(self) -> Self::IntoIter
+	{
+		self.iter()
+	}
+}
+
 impl fmt::Display for Styles
 {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
@@ -42,7 +42,7 @@ impl<'de> Deserialize<'de> for Styles
 			Helper::Words(words) => words
 				.into_iter()
 				.flat_map(|word| word.parse::<Self>())
-				.fold(Self::NONE, |acc, curr| (acc | curr)),
+				.fold(Self::NONE, |acc, curr| acc | curr),
 		})
 	}
 }
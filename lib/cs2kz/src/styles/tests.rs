@@ -0,0 +1,57 @@
+//! Unit tests.
+
+use crate::Styles;
+
+#[test]
+fn empty()
+{
+	let styles = Styles::NONE;
+
+	assert!(styles.is_empty());
+	assert!(styles.contains(Styles::NONE));
+	assert!(!styles.contains(Styles::AUTO_BHOP));
+	assert_eq!(styles.iter().count(), 0);
+	assert_eq!(styles.union(Styles::NONE), Styles::NONE);
+	assert_eq!(styles.intersection(Styles::ALL), Styles::NONE);
+}
+
+#[test]
+fn single()
+{
+	let mut styles = Styles::NONE;
+
+	styles.insert(Styles::AUTO_BHOP);
+
+	assert!(!styles.is_empty());
+	assert!(styles.contains(Styles::AUTO_BHOP));
+	assert_eq!(styles.iter().collect::<Vec<_>>(), [Styles::AUTO_BHOP]);
+	assert_eq!(styles.iter_names().collect::<Vec<_>>(), ["auto_bhop"]);
+	assert_eq!(Styles::NONE | Styles::AUTO_BHOP, styles);
+	assert_eq!(styles & Styles::NONE, Styles::NONE);
+
+	styles.remove(Styles::AUTO_BHOP);
+
+	assert_eq!(styles, Styles::NONE);
+}
+
+#[test]
+fn all()
+{
+	let styles = Styles::ALL;
+
+	assert!(styles.contains(Styles::AUTO_BHOP));
+	assert_eq!(styles.union(Styles::NONE), Styles::ALL);
+	assert_eq!(styles.intersection(Styles::AUTO_BHOP), Styles::AUTO_BHOP);
+	assert_eq!(Styles::new_checked(u32::MAX), None);
+
+	let mut combined = Styles::NONE;
+
+	for style in styles {
+		assert_eq!(style.bits().count_ones(), 1);
+		assert!(styles.contains(style));
+
+		combined |= style;
+	}
+
+	assert_eq!(combined, Styles::ALL);
+}
@@ -54,6 +54,50 @@ pub enum Tier
 
 impl Tier
 {
+	/// Every tier, from easiest to hardest.
+	pub const ALL: [Self; 10] = [
+		Self::VeryEasy,
+		Self::Easy,
+		Self::Medium,
+		Self::Advanced,
+		Self::Hard,
+		Self::VeryHard,
+		Self::Extreme,
+		Self::Death,
+		Self::Unfeasible,
+		Self::Impossible,
+	];
+
+	/// Returns an iterator over every tier, from easiest to hardest.
+	pub fn all() -> impl DoubleEndedIterator<Item = Self> + ExactSizeIterator
+	{
+		Self::ALL.into_iter()
+	}
+
+	/// Returns the next harder tier, if any.
+	pub fn next(self) -> Option<Self>
+	{
+		Self::try_from(u8::from(self) + 1).ok()
+	}
+
+	/// Returns the next easier tier, if any.
+	pub fn prev(self) -> Option<Self>
+	{
+		Self::try_from(u8::from(self) - 1).ok()
+	}
+
+	/// Checks whether a human can reasonably be expected to complete a course
+	/// of this tier.
+	///
+	/// This is the case for everything up to and including [Death] (tier 8).
+	/// Only filters of these tiers can be ranked.
+	///
+	/// [Death]: type@Tier::Death
+	pub fn is_humanly_possible(&self) -> bool
+	{
+		*self <= Self::Death
+	}
+
 	/// Returns a string representation of this [Tier], as accepted by the API.
 	pub const fn as_str(&self) -> &'static str
 	{
@@ -100,8 +144,13 @@ impl FromStr for Tier
 {
 	type Err = UnknownTier;
 
+	/// Parses either a tier's name, like `very_easy`, or its number, like `1`.
 	fn from_str(s: &str) -> Result<Self, Self::Err>
 	{
+		if let Ok(value) = s.parse::<u8>() {
+			return Self::try_from(value).map_err(|_| UnknownTier(s.to_owned()));
+		}
+
 		let s = s.to_lowercase();
 
 		match s.as_str() {
@@ -298,3 +347,48 @@ mod utoipa_impls
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests
+{
+	use super::Tier;
+
+	#[test]
+	fn parse_names()
+	{
+		for tier in Tier::all() {
+			assert_eq!(tier.as_str().parse::<Tier>().ok(), Some(tier));
+			assert_eq!(tier.as_str().to_uppercase().parse::<Tier>().ok(), Some(tier));
+		}
+
+		assert_eq!("very easy".parse::<Tier>().ok(), Some(Tier::VeryEasy));
+		assert_eq!("very hard".parse::<Tier>().ok(), Some(Tier::VeryHard));
+		assert!("super_hard".parse::<Tier>().is_err());
+	}
+
+	#[test]
+	fn parse_numbers()
+	{
+		for (n, tier) in (1..).zip(Tier::all()) {
+			assert_eq!(n.to_string().parse::<Tier>().ok(), Some(tier));
+			assert_eq!(u8::from(tier), n);
+		}
+
+		for invalid in ["0", "11", "255", "256", "-1"] {
+			assert!(invalid.parse::<Tier>().is_err(), "{invalid}");
+		}
+	}
+
+	#[test]
+	fn stepping()
+	{
+		assert_eq!(Tier::VeryEasy.prev(), None);
+		assert_eq!(Tier::VeryEasy.next(), Some(Tier::Easy));
+		assert_eq!(Tier::Death.next(), Some(Tier::Unfeasible));
+		assert_eq!(Tier::Impossible.prev(), Some(Tier::Unfeasible));
+		assert_eq!(Tier::Impossible.next(), None);
+
+		assert!(Tier::all().is_sorted());
+		assert_eq!(Tier::all().rfind(Tier::is_humanly_possible), Some(Tier::Death));
+	}
+}
@@ -13,8 +13,78 @@ macro_rules! error {
 
 pub(crate) use error;
 
+/// Derives metadata for an enum of problem types.
+///
+/// Every variant can have a `#[status = ...]` attribute with the HTTP status
+/// code for that problem; it defaults to 500. Problem types are identified by
+/// a URI pointing at their documentation, unless a variant specifies one
+/// explicitly using `#[problem(type = "...")]`. Titles are generated from the
+/// variant name, unless overridden with `#[problem(title = "...")]`.
+///
+/// ```
+/// use cs2kz_api_macros::ProblemType;
+/// use url::Url;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, ProblemType)]
+/// enum Problem
+/// {
+///     /// The resource does not exist.
+///     #[status = 404]
+///     NotFound,
+///
+///     /// The resource has been deleted.
+///     #[status = 410]
+///     #[problem(type = "https://errors.cs2kz.org/gone")]
+///     Gone,
+///
+///     /// Something went wrong.
+///     #[problem(title = "Oops")]
+///     Internal,
+/// }
+///
+/// assert_eq!(Problem::NotFound.title(), "Not Found");
+/// assert_eq!(Problem::Internal.title(), "Oops");
+/// assert_eq!(Problem::Internal.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+///
+/// let docs_url = Url::parse("https://api.cs2kz.org/docs/problem-types").unwrap();
+///
+/// assert_eq!(
+///     Problem::NotFound.uri(&docs_url).as_str(),
+///     "https://api.cs2kz.org/docs/problem-types#not-found",
+/// );
+/// assert_eq!(Problem::Gone.uri(&docs_url).as_str(), "https://errors.cs2kz.org/gone");
+/// ```
+///
+/// The URI is validated at compile time:
+///
+/// ```compile_fail
+/// use cs2kz_api_macros::ProblemType;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, ProblemType)]
+/// enum Problem
+/// {
+///     /// The resource has been deleted.
+///     #[status = 410]
+///     #[problem(type = "errors.cs2kz.org/gone")]
+///     Gone,
+/// }
+/// ```
+///
+/// So are status codes:
+///
+/// ```compile_fail
+/// use cs2kz_api_macros::ProblemType;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, ProblemType)]
+/// enum Problem
+/// {
+///     /// The resource does not exist.
+///     #[status = 4040]
+///     NotFound,
+/// }
+/// ```
 #[proc_macro_error]
-#[proc_macro_derive(ProblemType, attributes(status))]
+#[proc_macro_derive(ProblemType, attributes(status, problem))]
 pub fn problem_type(item: TokenStream) -> TokenStream
 {
 	let item = parse_macro_input!(item as ItemEnum);
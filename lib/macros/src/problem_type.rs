@@ -1,12 +1,17 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::spanned::Spanned as _;
-use syn::{Attribute, Expr, ExprLit, Ident, ItemEnum, Lit, Meta, MetaNameValue};
+use syn::{Attribute, Expr, ExprLit, Ident, ItemEnum, Lit, LitStr, Meta, MetaNameValue};
 
 use crate::error;
 
 const DOCS: &str = include_str!("../../../static/problem-types.html");
 
+/// The status code used for variants without a `#[status]` attribute.
+const DEFAULT_STATUS: u16 = 500;
+
+/// The range of valid HTTP status codes.
+const STATUS_RANGE: std::ops::RangeInclusive<u16> = 100..=599;
+
 pub fn expand(item: ItemEnum) -> syn::Result<TokenStream>
 {
 	let name = &item.ident;
@@ -27,7 +32,7 @@ pub fn expand(item: ItemEnum) -> syn::Result<TokenStream>
 			let name = variant.ident.to_string();
 			let slug = heck::AsKebabCase(&name).to_string();
 			let docs = doc_attrs(&variant.attrs)?;
-			let (status, span) = variant
+			let status = variant
 				.attrs
 				.iter()
 				.map(|attr| &attr.meta)
@@ -38,16 +43,32 @@ pub fn expand(item: ItemEnum) -> syn::Result<TokenStream>
 						.map(|_| value),
 
 					_ => None,
-				})
-				.ok_or_else(|| syn::Error::new(variant.ident.span(), "missing `status` attribute"))
-				.and_then(|status| match status {
-					Expr::Lit(ExprLit { lit: Lit::Int(int), .. }) => {
-						Ok((int.base10_parse::<u16>()?, status.span()))
+				});
+
+			let (status, span) = match status {
+				None => (DEFAULT_STATUS, variant.ident.span()),
+				Some(Expr::Lit(ExprLit { lit: Lit::Int(int), .. })) => {
+					let code = int.base10_parse::<u16>()?;
+
+					if !STATUS_RANGE.contains(&code) {
+						let message = format!(
+							"`status` must be between {} and {}, but got {code}",
+							STATUS_RANGE.start(),
+							STATUS_RANGE.end(),
+						);
+
+						return Err(syn::Error::new(int.span(), message));
 					}
-					_ => error!(status, "`status` attribute must be an integer literal"),
-				})?;
 
-			Ok((Problem { name, slug, docs, status }, span))
+					(code, int.span())
+				}
+				Some(status) => error!(status, "`status` attribute must be an integer literal"),
+			};
+
+			let ProblemAttrs { uri, title } = problem_attrs(&variant.attrs)?;
+			let title = title.unwrap_or_else(|| heck::AsTitleCase(&name).to_string());
+
+			Ok((Problem { name, slug, title, docs, status, uri }, span))
 		})
 		.collect::<syn::Result<Vec<_>>>()?;
 
@@ -72,11 +93,10 @@ pub fn expand(item: ItemEnum) -> syn::Result<TokenStream>
 
 	let name_match_arms = problems
 		.iter()
-		.map(|(Problem { name, .. }, _)| {
+		.map(|(Problem { name, title, .. }, _)| {
 			let variant = syn::parse_str::<Ident>(name)?;
-			let name = heck::AsTitleCase(name).to_string();
 
-			Ok(quote!(Self::#variant => #name))
+			Ok(quote!(Self::#variant => #title))
 		})
 		.collect::<syn::Result<Vec<_>>>()?;
 
@@ -89,6 +109,26 @@ pub fn expand(item: ItemEnum) -> syn::Result<TokenStream>
 		})
 		.collect::<syn::Result<Vec<_>>>()?;
 
+	let uri_match_arms = problems
+		.iter()
+		.map(|(Problem { name, slug, uri, .. }, _)| {
+			let variant = syn::parse_str::<Ident>(name)?;
+
+			Ok(match uri {
+				Some(uri) => quote! {
+					Self::#variant => ::url::Url::parse(#uri).expect("validated at compile time")
+				},
+				None => quote! {
+					Self::#variant => {
+						let mut uri = docs_url.clone();
+						uri.set_fragment(Some(#slug));
+						uri
+					}
+				},
+			})
+		})
+		.collect::<syn::Result<Vec<_>>>()?;
+
 	let status_match_arms = problems
 		.iter()
 		.map(|(Problem { name, status, .. }, status_span)| {
@@ -189,6 +229,17 @@ pub fn expand(item: ItemEnum) -> syn::Result<TokenStream>
 				}
 			}
 
+			/// Returns the URI identifying a given problem type.
+			///
+			/// This is the URI given by the `#[problem(type = "...")]` attribute,
+			/// or `docs_url` with the problem type's slug as the fragment.
+			pub fn uri(&self, docs_url: &::url::Url) -> ::url::Url
+			{
+				match self {
+					#(#uri_match_arms),*
+				}
+			}
+
 			/// Returns the HTTP status code that the resulting response should have.
 			pub const fn status(&self) -> ::http::StatusCode
 			{
@@ -206,8 +257,56 @@ struct Problem
 {
 	name: String,
 	slug: String,
+	title: String,
 	docs: Vec<String>,
 	status: u16,
+	uri: Option<String>,
+}
+
+/// The values of a variant's `#[problem(...)]` attributes.
+#[derive(Debug, Default)]
+struct ProblemAttrs
+{
+	uri: Option<String>,
+	title: Option<String>,
+}
+
+/// Parses the `#[problem(...)]` attributes of a variant.
+fn problem_attrs(attrs: &[Attribute]) -> syn::Result<ProblemAttrs>
+{
+	let mut problem = ProblemAttrs::default();
+
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident("problem")) {
+		attr.parse_nested_meta(|meta| {
+			let (slot, name) = if meta.path.is_ident("type") {
+				(&mut problem.uri, "type")
+			} else if meta.path.is_ident("title") {
+				(&mut problem.title, "title")
+			} else {
+				return Err(meta.error("unknown `problem` attribute; expected `type` or `title`"));
+			};
+
+			if slot.is_some() {
+				return Err(meta.error(format_args!("duplicate `{name}` attribute")));
+			}
+
+			let value = meta.value()?.parse::<LitStr>()?;
+
+			if name == "type" {
+				if let Err(error) = url::Url::parse(&value.value()) {
+					let message = format!("`type` must be an absolute URI: {error}");
+
+					return Err(syn::Error::new(value.span(), message));
+				}
+			}
+
+			*slot = Some(value.value());
+
+			Ok(())
+		})?;
+	}
+
+	Ok(problem)
 }
 
 fn doc_attrs(attrs: &[Attribute]) -> syn::Result<Vec<String>>
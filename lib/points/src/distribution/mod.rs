@@ -0,0 +1,253 @@
+//! The [normal-inverse Gaussian][nig] distribution.
+//!
+//! [nig]: https://en.wikipedia.org/wiki/Normal-inverse_Gaussian_distribution
+
+use std::f64::consts::PI;
+
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::{bessel, integrate};
+
+#[cfg(test)]
+mod tests;
+
+/// The absolute error tolerance used when integrating the density.
+const CDF_TOLERANCE: f64 = 1e-10;
+
+/// A normal-inverse Gaussian distribution.
+///
+/// The parameters follow the usual `(α, β, μ, δ)` convention:
+///
+/// - `alpha` is the tail heaviness
+/// - `beta` is the asymmetry
+/// - `loc` (μ) is the location
+/// - `scale` (δ) is the scale
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distribution
+{
+	/// Tail heaviness.
+	pub alpha: f64,
+
+	/// Asymmetry.
+	pub beta: f64,
+
+	/// Location.
+	pub loc: f64,
+
+	/// Scale.
+	pub scale: f64,
+}
+
+/// Errors that can occur when creating a [`Distribution`].
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+pub enum FitError
+{
+	/// At least 4 samples are required to estimate 4 parameters.
+	#[error("need at least 4 samples, got {0}")]
+	NotEnoughSamples(usize),
+
+	/// One of the samples was NaN or infinite.
+	#[error("samples must be finite")]
+	NonFiniteSample,
+
+	/// All the samples had (almost) the same value.
+	#[error("samples have no variance")]
+	NoVariance,
+
+	/// The samples' moments cannot be matched by a normal-inverse Gaussian.
+	///
+	/// This happens when the excess kurtosis is too low relative to the
+	/// skewness (`3 * kurtosis <= 5 * skewness²`), i.e. the data has lighter
+	/// tails than any NIG distribution.
+	#[error("samples are not heavy-tailed enough for a normal-inverse Gaussian")]
+	TailsTooLight,
+
+	/// The parameters don't satisfy `scale > 0` and `alpha > |beta|`.
+	#[error("invalid distribution parameters")]
+	InvalidParameters,
+}
+
+impl Distribution
+{
+	/// Creates a new [`Distribution`] from its parameters.
+	pub fn new(alpha: f64, beta: f64, loc: f64, scale: f64) -> Result<Self, FitError>
+	{
+		let valid = [alpha, beta, loc, scale].iter().all(|x| x.is_finite())
+			&& scale > 0.0
+			&& alpha > beta.abs();
+
+		if !valid {
+			return Err(FitError::InvalidParameters);
+		}
+
+		Ok(Self { alpha, beta, loc, scale })
+	}
+
+	/// Fits a [`Distribution`] to the given samples using the method of
+	/// moments.
+	///
+	/// The four parameters are chosen such that the distribution has the same
+	/// mean, variance, skewness, and excess kurtosis as the samples.
+	pub fn fit(samples: &[f64]) -> Result<Self, FitError>
+	{
+		if samples.len() < 4 {
+			return Err(FitError::NotEnoughSamples(samples.len()));
+		}
+
+		if !samples.iter().all(|x| x.is_finite()) {
+			return Err(FitError::NonFiniteSample);
+		}
+
+		let n = samples.len() as f64;
+		let mean = samples.iter().sum::<f64>() / n;
+		let central_moment = |k: i32| samples.iter().map(|x| (x - mean).powi(k)).sum::<f64>() / n;
+
+		let variance = central_moment(2);
+
+		if variance <= f64::EPSILON * mean.abs().max(1.0) {
+			return Err(FitError::NoVariance);
+		}
+
+		let skewness = central_moment(3) / variance.powf(1.5);
+		let kurtosis = central_moment(4) / variance.powi(2) - 3.0;
+		let denominator = 3.0 * kurtosis - 4.0 * skewness.powi(2);
+
+		if 3.0 * kurtosis <= 5.0 * skewness.powi(2) {
+			return Err(FitError::TailsTooLight);
+		}
+
+		// With `ξ = β / α` and `ζ = δγ`, where `γ = sqrt(α² - β²)`:
+		//
+		//   skewness = 3ξ / sqrt(ζ)
+		//   kurtosis = 3(1 + 4ξ²) / ζ
+		//   variance = ζ / (α²(1 - ξ²)²)
+		//   mean     = μ + δβ / γ
+		let zeta = 9.0 / denominator;
+		let xi = skewness / denominator.sqrt();
+		let alpha = (zeta / variance).sqrt() / (1.0 - xi.powi(2));
+		let beta = xi * alpha;
+		let gamma = alpha * (1.0 - xi.powi(2)).sqrt();
+		let scale = zeta / gamma;
+		let loc = mean - scale * beta / gamma;
+
+		Self::new(alpha, beta, loc, scale)
+	}
+
+	/// Fits a [`Distribution`] to each of the given leaderboards.
+	///
+	/// Every fit is independent of the others, so they are spread across
+	/// [`rayon`]'s thread pool. The results are in the same order as the input.
+	pub fn fit_many(leaderboards: &[&[f64]]) -> Vec<Result<Self, FitError>>
+	{
+		leaderboards
+			.par_iter()
+			.map(|samples| Self::fit(samples))
+			.collect()
+	}
+
+	/// `sqrt(α² - β²)`
+	fn gamma(&self) -> f64
+	{
+		(self.alpha.powi(2) - self.beta.powi(2)).sqrt()
+	}
+
+	/// Returns the distribution's mean.
+	pub fn mean(&self) -> f64
+	{
+		self.loc + self.scale * self.beta / self.gamma()
+	}
+
+	/// Returns the distribution's variance.
+	pub fn variance(&self) -> f64
+	{
+		self.scale * self.alpha.powi(2) / self.gamma().powi(3)
+	}
+
+	/// Returns the distribution's skewness.
+	pub fn skewness(&self) -> f64
+	{
+		3.0 * self.beta / (self.alpha * (self.scale * self.gamma()).sqrt())
+	}
+
+	/// Returns the distribution's excess kurtosis.
+	pub fn kurtosis(&self) -> f64
+	{
+		3.0 * (1.0 + 4.0 * (self.beta / self.alpha).powi(2)) / (self.scale * self.gamma())
+	}
+
+	/// Evaluates the probability density function at `x`.
+	pub fn pdf(&self, x: f64) -> f64
+	{
+		let Self { alpha, beta, loc, scale } = *self;
+		let offset = x - loc;
+		let q = scale.hypot(offset);
+
+		// `K1(αq) = k1_scaled(αq) * e^(-αq)`, which is folded into the exponent
+		// so it can't underflow.
+		let exponent = scale * self.gamma() + beta * offset - alpha * q;
+
+		alpha * scale * bessel::k1_scaled(alpha * q) / (PI * q) * exponent.exp()
+	}
+
+	/// Evaluates the cumulative distribution function at `x`, i.e. the
+	/// probability of a sample being `<= x`.
+	///
+	/// There is no closed form for this, so the density is integrated
+	/// numerically.
+	pub fn cdf(&self, x: f64) -> f64
+	{
+		if x <= self.mean() {
+			self.lower_tail(x)
+		} else {
+			1.0 - self.upper_tail(x)
+		}
+	}
+
+	/// Evaluates the survival function at `x`, i.e. the probability of a
+	/// sample being `> x`.
+	pub fn sf(&self, x: f64) -> f64
+	{
+		if x <= self.mean() {
+			1.0 - self.lower_tail(x)
+		} else {
+			self.upper_tail(x)
+		}
+	}
+
+	/// Integrates the density over `(-∞, x]`.
+	///
+	/// The infinite interval is mapped onto `(0, 1]` by substituting
+	/// `t = x - s(1 - u) / u`.
+	fn lower_tail(&self, x: f64) -> f64
+	{
+		let s = self.variance().sqrt();
+		let integrand = |u: f64| {
+			if u <= 0.0 {
+				return 0.0;
+			}
+
+			self.pdf(x - s * (1.0 - u) / u) * s / u.powi(2)
+		};
+
+		integrate::adaptive_simpson(integrand, 0.0, 1.0, CDF_TOLERANCE).clamp(0.0, 1.0)
+	}
+
+	/// Integrates the density over `[x, ∞)`.
+	///
+	/// The infinite interval is mapped onto `(0, 1]` by substituting
+	/// `t = x + s(1 - u) / u`.
+	fn upper_tail(&self, x: f64) -> f64
+	{
+		let s = self.variance().sqrt();
+		let integrand = |u: f64| {
+			if u <= 0.0 {
+				return 0.0;
+			}
+
+			self.pdf(x + s * (1.0 - u) / u) * s / u.powi(2)
+		};
+
+		integrate::adaptive_simpson(integrand, 0.0, 1.0, CDF_TOLERANCE).clamp(0.0, 1.0)
+	}
+}
@@ -0,0 +1,121 @@
+//! Unit tests.
+
+use super::{Distribution, FitError};
+
+/// A leaderboard's worth of times, drawn from a NIG with
+/// `(α, β, μ, δ) = (0.5, 0.3, 60, 4)`.
+const TIMES: [f64; 40] = [
+	56.08, 56.7, 57.19, 58.16, 58.18, 59.57, 59.77, 60.01, 60.03, 60.21, 60.41, 60.57, 61.56,
+	61.61, 61.65, 61.66, 62.15, 62.33, 62.38, 62.4, 62.4, 62.43, 62.7, 62.71, 63.27, 63.41, 63.46,
+	63.64, 63.89, 64.08, 64.28, 64.5, 65.46, 66.87, 67.1, 68.42, 68.45, 68.49, 70.15, 76.49,
+];
+
+/// Asserts that `actual` is within `tolerance` of `expected`, relatively.
+#[track_caller]
+fn assert_close(actual: f64, expected: f64, tolerance: f64)
+{
+	let error = ((actual - expected) / expected.abs().max(f64::MIN_POSITIVE)).abs();
+
+	assert!(error < tolerance, "{actual} is not within {tolerance} of {expected}");
+}
+
+#[test]
+fn fit_matches_sample_moments()
+{
+	let dist = Distribution::fit(&TIMES).unwrap();
+
+	assert_close(dist.mean(), 62.8705, 1e-9);
+	assert_close(dist.variance(), 15.12358475, 1e-9);
+	assert_close(dist.skewness(), 1.0610028752574183, 1e-9);
+	assert_close(dist.kurtosis(), 2.1183284446762087, 1e-9);
+}
+
+#[test]
+fn pdf_reference_value()
+{
+	// α = δ = 1, β = μ = 0  =>  pdf(0) = K1(1) * e / π
+	let dist = Distribution::new(1.0, 0.0, 0.0, 1.0).unwrap();
+
+	assert_close(dist.pdf(0.0), 0.520_803_829_991_67, 1e-6);
+}
+
+#[test]
+fn cdf_symmetric()
+{
+	let dist = Distribution::new(1.0, 0.0, 0.0, 1.0).unwrap();
+
+	assert_close(dist.cdf(0.0), 0.5, 1e-8);
+
+	for x in [0.1, 0.5, 1.0, 2.5, 10.0] {
+		assert_close(dist.cdf(x) + dist.cdf(-x), 1.0, 1e-8);
+	}
+}
+
+#[test]
+fn cdf_properties()
+{
+	let dist = Distribution::fit(&TIMES).unwrap();
+
+	assert!(dist.cdf(0.0) < 1e-9);
+	assert!(dist.sf(1000.0) < 1e-9);
+
+	let mut previous = 0.0;
+
+	for x in (500..=800).map(|x| f64::from(x) / 10.0) {
+		let cdf = dist.cdf(x);
+
+		assert!(cdf >= previous, "cdf should be monotonic ({x})");
+		assert_close(cdf + dist.sf(x), 1.0, 1e-9);
+
+		previous = cdf;
+	}
+
+	// The fraction of times below the median should be roughly 50%.
+	assert!((dist.cdf(62.4) - 0.5).abs() < 0.1);
+}
+
+#[test]
+fn cdf_matches_integrated_pdf()
+{
+	let dist = Distribution::fit(&TIMES).unwrap();
+	let expected = crate::integrate::adaptive_simpson(|x| dist.pdf(x), 58.0, 66.0, 1e-12);
+
+	assert_close(dist.cdf(66.0) - dist.cdf(58.0), expected, 1e-6);
+}
+
+#[test]
+fn fit_many_matches_serial()
+{
+	let leaderboards = (0..TIMES.len() - 4)
+		.map(|start| &TIMES[start..])
+		.chain([&TIMES[..2]])
+		.collect::<Vec<_>>();
+
+	let serial = leaderboards
+		.iter()
+		.map(|times| Distribution::fit(times))
+		.collect::<Vec<_>>();
+
+	assert_eq!(Distribution::fit_many(&leaderboards), serial);
+}
+
+#[test]
+fn fit_errors()
+{
+	assert_eq!(Distribution::fit(&TIMES[..3]), Err(FitError::NotEnoughSamples(3)));
+	assert_eq!(Distribution::fit(&[1.0, 2.0, f64::NAN, 4.0]), Err(FitError::NonFiniteSample));
+	assert_eq!(Distribution::fit(&[60.0; 10]), Err(FitError::NoVariance));
+
+	let uniform = (1..=10).map(f64::from).collect::<Vec<_>>();
+
+	assert_eq!(Distribution::fit(&uniform), Err(FitError::TailsTooLight));
+}
+
+#[test]
+fn new_validates_parameters()
+{
+	assert!(Distribution::new(1.0, 0.5, 0.0, 1.0).is_ok());
+	assert_eq!(Distribution::new(1.0, 1.0, 0.0, 1.0), Err(FitError::InvalidParameters));
+	assert_eq!(Distribution::new(1.0, 0.0, 0.0, 0.0), Err(FitError::InvalidParameters));
+	assert_eq!(Distribution::new(f64::NAN, 0.0, 0.0, 1.0), Err(FitError::InvalidParameters));
+}
@@ -0,0 +1,79 @@
+//! Numerical integration.
+
+/// The maximum recursion depth for [`adaptive_simpson()`].
+const MAX_DEPTH: u32 = 48;
+
+/// Integrates `f` over `[a, b]` using adaptive Simpson's rule, until the
+/// estimated error is below `tolerance`.
+pub(crate) fn adaptive_simpson(f: impl Fn(f64) -> f64, a: f64, b: f64, tolerance: f64) -> f64
+{
+	let fa = f(a);
+	let fb = f(b);
+	let m = (a + b) / 2.0;
+	let fm = f(m);
+	let whole = simpson(a, b, fa, fm, fb);
+
+	recurse(&f, a, b, fa, fm, fb, whole, tolerance, MAX_DEPTH)
+}
+
+/// Simpson's rule for a single interval.
+fn simpson(a: f64, b: f64, fa: f64, fm: f64, fb: f64) -> f64
+{
+	(b - a) / 6.0 * (fa + 4.0 * fm + fb)
+}
+
+/// The recursive step of [`adaptive_simpson()`].
+#[allow(
+	clippy::too_many_arguments,
+	reason = "private helper; cached values avoid re-evaluating `f`"
+)]
+fn recurse(
+	f: &impl Fn(f64) -> f64,
+	a: f64,
+	b: f64,
+	fa: f64,
+	fm: f64,
+	fb: f64,
+	whole: f64,
+	tolerance: f64,
+	depth: u32,
+) -> f64
+{
+	let m = (a + b) / 2.0;
+	let lm = (a + m) / 2.0;
+	let rm = (m + b) / 2.0;
+	let flm = f(lm);
+	let frm = f(rm);
+	let left = simpson(a, m, fa, flm, fm);
+	let right = simpson(m, b, fm, frm, fb);
+	let delta = left + right - whole;
+
+	if depth == 0 || delta.abs() <= 15.0 * tolerance {
+		return left + right + delta / 15.0;
+	}
+
+	recurse(f, a, m, fa, flm, fm, left, tolerance / 2.0, depth - 1)
+		+ recurse(f, m, b, fm, frm, fb, right, tolerance / 2.0, depth - 1)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::adaptive_simpson;
+
+	#[test]
+	fn polynomials_are_exact()
+	{
+		let integral = adaptive_simpson(|x| x.powi(3) - 2.0 * x, 0.0, 2.0, 1e-12);
+
+		assert!((integral - 0.0).abs() < 1e-12, "{integral}");
+	}
+
+	#[test]
+	fn gaussian()
+	{
+		let integral = adaptive_simpson(|x: f64| (-x * x).exp(), -10.0, 10.0, 1e-12);
+
+		assert!((integral - std::f64::consts::PI.sqrt()).abs() < 1e-10, "{integral}");
+	}
+}
@@ -0,0 +1,19 @@
+//! Points calculation for CS2KZ.
+//!
+//! The points a record is worth depend on where its time falls within the
+//! distribution of all the times on the same leaderboard. That distribution
+//! is modeled as a [normal-inverse Gaussian][nig] distribution, which is
+//! fitted to the leaderboard's times.
+//!
+//! Everything in here is implemented in plain Rust, so it runs anywhere the
+//! API runs.
+//!
+//! [nig]: https://en.wikipedia.org/wiki/Normal-inverse_Gaussian_distribution
+
+mod bessel;
+mod integrate;
+
+pub mod distribution;
+
+#[doc(inline)]
+pub use distribution::Distribution;
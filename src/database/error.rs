@@ -1,5 +1,13 @@
 //! This module contains the [`SqlErrorExt`] trait.
 
+use sqlx::mysql::MySqlDatabaseError;
+
+/// MySQL's error number for "deadlock found when trying to get lock".
+const ER_LOCK_DEADLOCK: u16 = 1213;
+
+/// MySQL's error number for "lock wait timeout exceeded".
+const ER_LOCK_WAIT_TIMEOUT: u16 = 1205;
+
 /// Extension trait for [`sqlx::Error`].
 ///
 /// This makes it easier to check for common error conditions that are
@@ -12,6 +20,16 @@ pub trait SqlErrorExt
 
 	/// Checks if the error is a foreign key violation of a specific key.
 	fn is_fk_violation(&self, fk: &str) -> bool;
+
+	/// Checks if the error is a violation of a specific unique constraint.
+	///
+	/// `constraint` is the name of the unique index, which is the name of its
+	/// first column if it wasn't named explicitly.
+	fn is_unique_violation(&self, constraint: &str) -> bool;
+
+	/// Checks if the error is transient, i.e. a deadlock or lock wait timeout,
+	/// and the failed operation can be retried.
+	fn is_retryable(&self) -> bool;
 }
 
 #[sealed]
@@ -28,4 +46,27 @@ impl SqlErrorExt for sqlx::Error
 		self.as_database_error()
 			.is_some_and(|e| e.is_foreign_key_violation() && e.message().contains(fk))
 	}
+
+	fn is_unique_violation(&self, constraint: &str) -> bool
+	{
+		// MySQL reports violations as "Duplicate entry '…' for key '<constraint>'",
+		// possibly with the key qualified by its table name.
+		let unquoted = format!("'{constraint}'");
+		let qualified = format!(".{constraint}'");
+
+		self.as_database_error().is_some_and(|e| {
+			e.is_unique_violation()
+				&& (e.message().ends_with(&unquoted) || e.message().ends_with(&qualified))
+		})
+	}
+
+	fn is_retryable(&self) -> bool
+	{
+		self.as_database_error().is_some_and(|e| {
+			// "serialization failure", which is what deadlocks are reported as
+			e.code().as_deref() == Some("40001")
+				|| e.try_downcast_ref::<MySqlDatabaseError>()
+					.is_some_and(|e| matches!(e.number(), ER_LOCK_DEADLOCK | ER_LOCK_WAIT_TIMEOUT))
+		})
+	}
 }
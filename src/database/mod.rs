@@ -1,10 +1,13 @@
 //! This module contains general database utilities.
 //!
 //! Most notably, it exports extension traits like [`SqlErrorExt`] and
-//! [`TransactionExt`] which add extra methods to [`sqlx`] types.
+//! [`TransactionExt`] which add extra methods to [`sqlx`] types,
+//! [`with_retry()`] for retrying operations that hit transient errors, and
+//! [`escape_like()`] for using user input in `LIKE` patterns.
 
 use std::num::NonZero;
 use std::thread;
+use std::time::Duration;
 
 use sqlx::pool::PoolOptions;
 use sqlx::{MySql, Pool};
@@ -17,6 +20,9 @@ pub use error::SqlErrorExt;
 mod transaction;
 pub use transaction::TransactionExt;
 
+mod retry;
+pub use retry::with_retry;
+
 /// Creates a database connection pool and runs migrations.
 pub async fn create_pool(config: &DatabaseConfig) -> sqlx::Result<Pool<MySql>>
 {
@@ -27,6 +33,17 @@ pub async fn create_pool(config: &DatabaseConfig) -> sqlx::Result<Pool<MySql>>
 	let pool = PoolOptions::new()
 		.min_connections(config.min_connections)
 		.max_connections(max_connections)
+		.acquire_timeout(Duration::from_secs(config.acquire_timeout.get()))
+		.idle_timeout(
+			config
+				.idle_timeout
+				.map(|secs| Duration::from_secs(secs.get())),
+		)
+		.max_lifetime(
+			config
+				.max_lifetime
+				.map(|secs| Duration::from_secs(secs.get())),
+		)
 		.connect(config.url.as_str())
 		.await?;
 
@@ -35,6 +52,24 @@ pub async fn create_pool(config: &DatabaseConfig) -> sqlx::Result<Pool<MySql>>
 	Ok(pool)
 }
 
+/// Escapes `LIKE` wildcards in `value`, so it only matches itself.
+///
+/// This uses `\`, which is the default escape character.
+pub fn escape_like(value: &str) -> String
+{
+	let mut escaped = String::with_capacity(value.len());
+
+	for char in value.chars() {
+		if matches!(char, '\\' | '%' | '_') {
+			escaped.push('\\');
+		}
+
+		escaped.push(char);
+	}
+
+	escaped
+}
+
 /// The maximum number of database pool connections to use.
 fn max_connections() -> u32
 {
@@ -44,3 +79,19 @@ fn max_connections() -> u32
 
 	(available * 2) as u32
 }
+
+#[cfg(test)]
+mod tests
+{
+	use crate::testing;
+
+	#[test]
+	fn escape_like() -> color_eyre::Result<()>
+	{
+		testing::assert_eq!(super::escape_like("kz_checkmate"), r"kz\_checkmate");
+		testing::assert_eq!(super::escape_like(r"100%\"), r"100\%\\");
+		testing::assert_eq!(super::escape_like("grotto"), "grotto");
+
+		Ok(())
+	}
+}
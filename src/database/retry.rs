@@ -0,0 +1,216 @@
+//! This module contains [`with_retry()`].
+
+use std::error::Error;
+use std::future::Future;
+use std::hash::{BuildHasher, RandomState};
+use std::iter;
+use std::time::Duration;
+
+use super::SqlErrorExt;
+
+/// How many times an operation is attempted before giving up.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// The delay before the first retry.
+///
+/// Every subsequent retry waits twice as long as the previous one.
+const BASE_DELAY: Duration = Duration::from_millis(50);
+
+/// Runs `op` and retries it if it fails with a transient database error.
+///
+/// Transient errors are deadlocks and lock wait timeouts (see
+/// [`SqlErrorExt::is_retryable()`]). `op` is attempted up to 3 times, with a
+/// jittered exponential backoff in between. Any other error is returned right
+/// away.
+///
+/// `op` should perform a complete unit of work, usually a transaction, so
+/// retrying it is safe. The error may be a [`sqlx::Error`] or any error that
+/// has one in its [source chain].
+///
+/// [source chain]: std::error::Error::source
+pub async fn with_retry<F, Fut, T, E>(mut op: F) -> Result<T, E>
+where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = Result<T, E>>,
+	E: Error + 'static,
+{
+	let mut attempt = 1;
+
+	loop {
+		match op().await {
+			Err(error) if attempt < MAX_ATTEMPTS && is_retryable(&error) => {
+				let delay = backoff(attempt);
+
+				tracing::warn!(
+					target: "cs2kz_api::database",
+					attempt,
+					?delay,
+					%error,
+					"transient database error; retrying",
+				);
+
+				tokio::time::sleep(delay).await;
+				attempt += 1;
+			}
+			result => return result,
+		}
+	}
+}
+
+/// Checks whether `error` is, or was caused by, a retryable [`sqlx::Error`].
+fn is_retryable(error: &(dyn Error + 'static)) -> bool
+{
+	iter::successors(Some(error), |&error| error.source())
+		.filter_map(|error| error.downcast_ref::<sqlx::Error>())
+		.any(SqlErrorExt::is_retryable)
+}
+
+/// Returns how long to wait before the retry following attempt number
+/// `attempt`.
+///
+/// The delay is randomized between 50% and 100% of the exponential backoff,
+/// so concurrent retries don't collide again.
+fn backoff(attempt: u32) -> Duration
+{
+	let delay = BASE_DELAY * 2_u32.pow(attempt - 1);
+	let jitter = RandomState::new().hash_one(attempt) % 1000;
+
+	delay / 2 + (delay / 2).mul_f64(jitter as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::borrow::Cow;
+	use std::fmt;
+	use std::sync::atomic::{AtomicU32, Ordering};
+
+	use sqlx::error::{DatabaseError, ErrorKind};
+
+	use super::*;
+	use crate::testing;
+
+	/// A fake database error that looks like a MySQL deadlock.
+	#[derive(Debug)]
+	struct Deadlock;
+
+	impl fmt::Display for Deadlock
+	{
+		fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+		{
+			f.write_str("Deadlock found when trying to get lock")
+		}
+	}
+
+	impl Error for Deadlock {}
+
+	impl DatabaseError for Deadlock
+	{
+		fn message(&self) -> &str
+		{
+			"Deadlock found when trying to get lock"
+		}
+
+		fn code(&self) -> Option<Cow<'_, str>>
+		{
+			Some(Cow::Borrowed("40001"))
+		}
+
+		fn as_error(&self) -> &(dyn Error + Send + Sync + 'static)
+		{
+			self
+		}
+
+		fn as_error_mut(&mut self) -> &mut (dyn Error + Send + Sync + 'static)
+		{
+			self
+		}
+
+		fn into_error(self: Box<Self>) -> Box<dyn Error + Send + Sync + 'static>
+		{
+			self
+		}
+
+		fn kind(&self) -> ErrorKind
+		{
+			ErrorKind::Other
+		}
+	}
+
+	/// A service-style error wrapping a [`sqlx::Error`].
+	#[derive(Debug, thiserror::Error)]
+	#[error("something went wrong")]
+	struct Wrapped(#[from] sqlx::Error);
+
+	/// Runs `with_retry()` over an operation that fails with `error()` the
+	/// first `failures` times, and returns the result and number of calls.
+	async fn run<E>(failures: u32, error: impl Fn() -> E + Sync) -> (Result<u32, E>, u32)
+	where
+		E: Error + Send + 'static,
+	{
+		let calls = AtomicU32::new(0);
+		let result = with_retry(|| async {
+			let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+
+			if call <= failures {
+				Err(error())
+			} else {
+				Ok(call)
+			}
+		})
+		.await;
+
+		(result, calls.into_inner())
+	}
+
+	#[tokio::test]
+	async fn deadlocks_are_retried() -> color_eyre::Result<()>
+	{
+		let (result, calls) = run(1, || sqlx::Error::Database(Box::new(Deadlock))).await;
+
+		testing::assert_matches!(result, Ok(2));
+		testing::assert_eq!(calls, 2);
+
+		let (result, calls) = run(1, || Wrapped(sqlx::Error::Database(Box::new(Deadlock)))).await;
+
+		testing::assert_matches!(result, Ok(2));
+		testing::assert_eq!(calls, 2);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn gives_up_eventually() -> color_eyre::Result<()>
+	{
+		let (result, calls) = run(u32::MAX, || sqlx::Error::Database(Box::new(Deadlock))).await;
+
+		testing::assert!(result.is_err());
+		testing::assert_eq!(calls, MAX_ATTEMPTS);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn other_errors_are_not_retried() -> color_eyre::Result<()>
+	{
+		let (result, calls) = run(1, || sqlx::Error::RowNotFound).await;
+
+		testing::assert_matches!(result, Err(sqlx::Error::RowNotFound));
+		testing::assert_eq!(calls, 1);
+
+		Ok(())
+	}
+
+	#[test]
+	fn backoff_is_jittered_and_grows() -> color_eyre::Result<()>
+	{
+		for attempt in 1..MAX_ATTEMPTS {
+			let max = BASE_DELAY * 2_u32.pow(attempt - 1);
+			let delay = backoff(attempt);
+
+			testing::assert!(delay >= max / 2 && delay <= max);
+		}
+
+		Ok(())
+	}
+}
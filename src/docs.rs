@@ -2,17 +2,20 @@
 
 use axum::response::{Html, IntoResponse};
 use axum::{routing, Router};
+use url::Url;
 
 use crate::http::problem_details::ProblemType;
 use crate::openapi;
 
 /// Returns a router for the `/docs` endpoint.
-pub fn router() -> Router
+///
+/// `public_url` is advertised as the API's base URL in the OpenAPI spec.
+pub fn router(public_url: &Url) -> Router
 {
 	Router::new()
 		.route("/docs/problem-types", routing::get(html))
 		.route("/docs/static/problem-types.css", routing::get(css))
-		.merge(openapi::Schema::swagger_ui())
+		.merge(openapi::Schema::swagger_ui(public_url))
 }
 
 /// Returns the HTML for the problem types page.
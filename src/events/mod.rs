@@ -0,0 +1,355 @@
+//! Domain events.
+//!
+//! Services dispatch [`Event`]s through an [`EventBus`] whenever something
+//! noteworthy happens, e.g. a player getting banned. Anything interested in
+//! these events (notifiers, webhooks, etc.) can [subscribe] to the bus without
+//! the services having to know about them.
+//!
+//! [subscribe]: EventBus::subscribe
+
+use std::fmt;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::Arc;
+
+use cs2kz::SteamID;
+use serde::Serialize;
+use thiserror::Error;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+
+use crate::services::bans::{BanID, BanReason, UnbanID, UnbanReason};
+use crate::services::maps::FilterID;
+use crate::services::records::RecordID;
+use crate::services::servers::ServerID;
+use crate::time::Seconds;
+
+pub mod webhook;
+
+/// An event emitted by one of the API's services.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event
+{
+	/// A player was banned.
+	PlayerBanned
+	{
+		/// The ban's ID.
+		ban_id: BanID,
+
+		/// The player's SteamID.
+		player_id: SteamID,
+
+		/// The admin who issued the ban, if it wasn't issued by a server.
+		admin_id: Option<SteamID>,
+
+		/// The reason for the ban.
+		reason: BanReason,
+
+		/// How long the ban will last.
+		///
+		/// This is `None` for permanent bans.
+		duration: Option<Seconds>,
+	},
+
+	/// A ban was reverted.
+	PlayerUnbanned
+	{
+		/// The reverted ban's ID.
+		ban_id: BanID,
+
+		/// The unban's ID.
+		unban_id: UnbanID,
+
+		/// The player's SteamID.
+		player_id: SteamID,
+
+		/// The admin who reverted the ban.
+		admin_id: SteamID,
+
+		/// The reason for the unban.
+		reason: UnbanReason,
+	},
+
+	/// A new record was submitted.
+	///
+	/// The leaderboard (and points) of its filter need to be recalculated.
+	NewRecord
+	{
+		/// The record's ID.
+		record_id: RecordID,
+
+		/// The filter the record was submitted on.
+		filter_id: FilterID,
+
+		/// The player who set the record.
+		player_id: SteamID,
+	},
+
+	/// A player's records were deleted.
+	///
+	/// The leaderboards (and points) of every affected filter need to be
+	/// recalculated.
+	RecordsDeleted
+	{
+		/// The player's SteamID.
+		player_id: SteamID,
+
+		/// The admin who deleted the records.
+		admin_id: Option<SteamID>,
+
+		/// The filters that had records deleted.
+		filter_ids: Vec<FilterID>,
+	},
+
+	/// A server's API key was about to expire and has been replaced.
+	///
+	/// The event does not include the new key; the owner can fetch it from
+	/// `GET /servers/{server_id}/key`. The old key keeps working for a grace
+	/// period so the server can be updated in the meantime.
+	ServerKeyRotated
+	{
+		/// The server's ID.
+		server_id: ServerID,
+
+		/// The server owner's SteamID.
+		owner_id: SteamID,
+
+		/// When the new key expires.
+		#[serde(with = "time::serde::rfc3339::option")]
+		expires_on: Option<OffsetDateTime>,
+	},
+}
+
+/// A channel for dispatching [`Event`]s to any number of subscribers.
+///
+/// The channel is bounded: every subscriber can fall behind by at most
+/// [`capacity`] events. If it falls further behind, it misses the oldest
+/// events and is told how many it missed the next time it receives.
+///
+/// Cloning an [`EventBus`] is cheap; all clones share the same subscribers.
+///
+/// [`capacity`]: EventBus::with_capacity
+#[derive(Clone)]
+pub struct EventBus
+{
+	/// The sending half of the underlying channel.
+	sender: broadcast::Sender<Event>,
+
+	/// How many events subscribers have missed in total.
+	dropped: Arc<AtomicU64>,
+}
+
+impl fmt::Debug for EventBus
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		f.debug_struct("EventBus")
+			.field("subscribers", &self.sender.receiver_count())
+			.field("dropped", &self.dropped_events())
+			.finish_non_exhaustive()
+	}
+}
+
+impl Default for EventBus
+{
+	fn default() -> Self
+	{
+		Self::new()
+	}
+}
+
+impl EventBus
+{
+	/// The capacity used by [`EventBus::new()`].
+	pub const DEFAULT_CAPACITY: usize = 256;
+
+	/// Creates a new [`EventBus`] with the default capacity.
+	pub fn new() -> Self
+	{
+		Self::with_capacity(Self::DEFAULT_CAPACITY)
+	}
+
+	/// Creates a new [`EventBus`] that buffers up to `capacity` events per
+	/// subscriber.
+	///
+	/// # Panics
+	///
+	/// This function will panic if `capacity` is 0.
+	pub fn with_capacity(capacity: usize) -> Self
+	{
+		assert_ne!(capacity, 0, "event bus capacity must be non-zero");
+
+		Self { sender: broadcast::Sender::new(capacity), dropped: Arc::default() }
+	}
+
+	/// Dispatches an event to all current subscribers.
+	///
+	/// If there are no subscribers, the event is discarded.
+	#[tracing::instrument(level = "trace")]
+	pub fn dispatch(&self, event: Event)
+	{
+		if self.sender.send(event).is_err() {
+			tracing::trace!("no subscribers for event");
+		}
+	}
+
+	/// Subscribes to all events dispatched after this call.
+	pub fn subscribe(&self) -> Subscriber
+	{
+		Subscriber { receiver: self.sender.subscribe(), dropped: Arc::clone(&self.dropped) }
+	}
+
+	/// Returns how many events subscribers have missed by lagging behind.
+	///
+	/// Every subscriber counts separately, so an event missed by two
+	/// subscribers counts twice.
+	pub fn dropped_events(&self) -> u64
+	{
+		self.dropped.load(atomic::Ordering::Relaxed)
+	}
+}
+
+/// A subscription to an [`EventBus`].
+///
+/// Returned by [`EventBus::subscribe()`].
+#[derive(Debug)]
+pub struct Subscriber
+{
+	/// The receiving half of the underlying channel.
+	receiver: broadcast::Receiver<Event>,
+
+	/// See [`EventBus::dropped`].
+	dropped: Arc<AtomicU64>,
+}
+
+/// The errors that can occur when receiving events from a [`Subscriber`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum RecvError
+{
+	/// The subscriber fell behind and missed this many events.
+	///
+	/// Receiving again will return the oldest event that is still buffered.
+	#[error("subscriber lagged behind and missed {0} events")]
+	Lagged(u64),
+
+	/// The [`EventBus`] was dropped and all buffered events have been
+	/// received.
+	#[error("event bus has been closed")]
+	Closed,
+}
+
+impl Subscriber
+{
+	/// Waits for the next event.
+	pub async fn recv(&mut self) -> Result<Event, RecvError>
+	{
+		match self.receiver.recv().await {
+			Ok(event) => Ok(event),
+			Err(broadcast::error::RecvError::Closed) => Err(RecvError::Closed),
+			Err(broadcast::error::RecvError::Lagged(skipped)) => Err(self.lagged(skipped)),
+		}
+	}
+
+	/// Returns the next event if one is buffered, without waiting.
+	pub fn try_recv(&mut self) -> Result<Option<Event>, RecvError>
+	{
+		match self.receiver.try_recv() {
+			Ok(event) => Ok(Some(event)),
+			Err(broadcast::error::TryRecvError::Empty) => Ok(None),
+			Err(broadcast::error::TryRecvError::Closed) => Err(RecvError::Closed),
+			Err(broadcast::error::TryRecvError::Lagged(skipped)) => Err(self.lagged(skipped)),
+		}
+	}
+
+	/// Returns whether there are no buffered events.
+	pub fn is_empty(&self) -> bool
+	{
+		self.receiver.is_empty()
+	}
+
+	/// Records that this subscriber missed `skipped` events.
+	fn lagged(&self, skipped: u64) -> RecvError
+	{
+		self.dropped.fetch_add(skipped, atomic::Ordering::Relaxed);
+		tracing::warn!(skipped, "event subscriber lagged behind");
+
+		RecvError::Lagged(skipped)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing::{self, ALPHAKEKS_ID};
+
+	#[test]
+	fn dispatch_without_subscribers()
+	{
+		let events = EventBus::new();
+
+		events.dispatch(Event::PlayerUnbanned {
+			ban_id: 1.into(),
+			unban_id: 1.into(),
+			player_id: ALPHAKEKS_ID,
+			admin_id: ALPHAKEKS_ID,
+			reason: UnbanReason::FalseBan,
+		});
+	}
+
+	#[test]
+	fn every_subscriber_receives_events() -> color_eyre::Result<()>
+	{
+		let events = EventBus::new();
+		let mut first = events.subscribe();
+		let mut second = events.subscribe();
+		let event = Event::PlayerBanned {
+			ban_id: 1.into(),
+			player_id: ALPHAKEKS_ID,
+			admin_id: None,
+			reason: BanReason::Macro,
+			duration: None,
+		};
+
+		events.dispatch(event.clone());
+
+		let (received, also_received) = (first.try_recv()?, second.try_recv()?);
+
+		testing::assert_eq!(received, Some(event.clone()));
+		testing::assert_eq!(also_received, Some(event.clone()));
+		testing::assert!(first.is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn lagging_subscriber_is_notified() -> color_eyre::Result<()>
+	{
+		let events = EventBus::with_capacity(2);
+		let mut subscriber = events.subscribe();
+		let event = |n: u64| Event::PlayerBanned {
+			ban_id: n.into(),
+			player_id: ALPHAKEKS_ID,
+			admin_id: None,
+			reason: BanReason::Macro,
+			duration: None,
+		};
+
+		for n in 1..=5 {
+			events.dispatch(event(n));
+		}
+
+		let lagged = subscriber.try_recv();
+
+		testing::assert_eq!(lagged, Err::<Option<Event>, _>(RecvError::Lagged(3)));
+		testing::assert_eq!(events.dropped_events(), 3);
+
+		let (fourth, fifth) = (subscriber.try_recv()?, subscriber.try_recv()?);
+
+		testing::assert_eq!(fourth, Some(event(4)));
+		testing::assert_eq!(fifth, Some(event(5)));
+		testing::assert!(subscriber.is_empty());
+
+		Ok(())
+	}
+}
@@ -0,0 +1,437 @@
+//! Delivery of [`Event`]s to an HTTP webhook.
+//!
+//! A [`Sink`] subscribes to an [`EventBus`] and `POST`s every event it
+//! receives, serialized as JSON, to a fixed URL. Events are buffered in a
+//! bounded queue while earlier deliveries are still in flight; if the receiver
+//! falls too far behind, the oldest events are dropped.
+//!
+//! Events are not sent as-is; every field that leaves the API has to be listed
+//! in [`Payload`], so adding a field to an [`Event`] can never leak it to the
+//! webhook by accident.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cs2kz::SteamID;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use url::Url;
+
+use super::{Event, EventBus, RecvError};
+use crate::services::bans::{BanID, BanReason, UnbanID, UnbanReason};
+use crate::services::maps::FilterID;
+use crate::services::records::RecordID;
+use crate::services::servers::ServerID;
+use crate::time::Seconds;
+
+/// The default for [`Sink::queue_capacity()`].
+const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// How often we try to deliver a single event before giving up on it.
+const MAX_ATTEMPTS: u8 = 5;
+
+/// How long we wait before retrying a failed delivery for the first time.
+///
+/// This is doubled after every failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// How long a single delivery attempt may take.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An [`EventBus`] subscriber that forwards events to a webhook.
+#[derive(Debug, Clone)]
+pub struct Sink
+{
+	/// The URL to `POST` events to.
+	url: Url,
+
+	/// HTTP client for making requests.
+	http_client: reqwest::Client,
+
+	/// The maximum amount of undelivered events to keep around.
+	queue_capacity: usize,
+
+	/// How long to wait before the first retry.
+	initial_backoff: Duration,
+}
+
+impl Sink
+{
+	/// Creates a new [`Sink`] delivering events to `url`.
+	pub fn new(url: Url, http_client: reqwest::Client) -> Self
+	{
+		Self {
+			url,
+			http_client,
+			queue_capacity: DEFAULT_QUEUE_CAPACITY,
+			initial_backoff: INITIAL_BACKOFF,
+		}
+	}
+
+	/// Sets the maximum amount of undelivered events to keep around.
+	///
+	/// Once the queue is full, the oldest event is dropped for every new one.
+	///
+	/// # Panics
+	///
+	/// This function will panic if `capacity` is 0.
+	pub fn queue_capacity(mut self, capacity: usize) -> Self
+	{
+		assert_ne!(capacity, 0, "webhook queue capacity must be non-zero");
+		self.queue_capacity = capacity;
+		self
+	}
+
+	/// Subscribes to `events` and starts delivering them in the background.
+	///
+	/// Only events dispatched after this call are delivered. The returned task
+	/// completes once the bus has been dropped and all queued events have been
+	/// processed.
+	pub fn spawn(self, events: &EventBus) -> JoinHandle<()>
+	{
+		let mut receiver = events.subscribe();
+		let queue = Arc::new(Queue::new(self.queue_capacity));
+
+		tokio::spawn({
+			let queue = Arc::clone(&queue);
+			let url = self.url.clone();
+
+			async move {
+				loop {
+					match receiver.recv().await {
+						Ok(event) => queue.push(event),
+						Err(RecvError::Lagged(skipped)) => {
+							tracing::warn!(skipped, %url, "webhook sink missed events");
+						}
+						Err(RecvError::Closed) => break,
+					}
+				}
+
+				queue.close();
+			}
+		});
+
+		tokio::spawn(async move {
+			while let Some(event) = queue.pop().await {
+				self.deliver(&event).await;
+			}
+		})
+	}
+
+	/// Tries to deliver a single event, retrying with exponential backoff.
+	#[tracing::instrument(level = "debug", skip(self), fields(url = %self.url))]
+	async fn deliver(&self, event: &Event)
+	{
+		let mut backoff = self.initial_backoff;
+
+		for attempt in 1..=MAX_ATTEMPTS {
+			let result = self
+				.http_client
+				.post(self.url.clone())
+				.timeout(REQUEST_TIMEOUT)
+				.json(&Payload::from(event))
+				.send()
+				.await
+				.and_then(|response| response.error_for_status());
+
+			match result {
+				Ok(_) => {
+					tracing::trace!(attempt, "delivered event");
+					return;
+				}
+				Err(error) if attempt < MAX_ATTEMPTS => {
+					tracing::debug!(%error, attempt, ?backoff, "failed to deliver event; retrying");
+					tokio::time::sleep(backoff).await;
+					backoff *= 2;
+				}
+				Err(error) => {
+					tracing::warn!(%error, "failed to deliver event; giving up");
+				}
+			}
+		}
+	}
+}
+
+/// The request body for a single event.
+///
+/// This mirrors [`Event`], but only contains the fields that are safe to send
+/// to a third party.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::missing_docs_in_private_items)]
+enum Payload<'a>
+{
+	PlayerBanned
+	{
+		ban_id: BanID,
+		player_id: SteamID,
+		admin_id: Option<SteamID>,
+		reason: &'a BanReason,
+		duration: Option<Seconds>,
+	},
+
+	PlayerUnbanned
+	{
+		ban_id: BanID,
+		unban_id: UnbanID,
+		player_id: SteamID,
+		admin_id: SteamID,
+		reason: &'a UnbanReason,
+	},
+
+	NewRecord
+	{
+		record_id: RecordID, filter_id: FilterID, player_id: SteamID
+	},
+
+	RecordsDeleted
+	{
+		player_id: SteamID, admin_id: Option<SteamID>, filter_ids: &'a [FilterID]
+	},
+
+	ServerKeyRotated
+	{
+		server_id: ServerID,
+		owner_id: SteamID,
+		#[serde(with = "time::serde::rfc3339::option")]
+		expires_on: Option<OffsetDateTime>,
+	},
+}
+
+impl<'a> From<&'a Event> for Payload<'a>
+{
+	fn from(event: &'a Event) -> Self
+	{
+		match *event {
+			Event::PlayerBanned { ban_id, player_id, admin_id, ref reason, duration } => {
+				Self::PlayerBanned { ban_id, player_id, admin_id, reason, duration }
+			}
+			Event::PlayerUnbanned { ban_id, unban_id, player_id, admin_id, ref reason } => {
+				Self::PlayerUnbanned { ban_id, unban_id, player_id, admin_id, reason }
+			}
+			Event::NewRecord { record_id, filter_id, player_id } => {
+				Self::NewRecord { record_id, filter_id, player_id }
+			}
+			Event::RecordsDeleted { player_id, admin_id, ref filter_ids } => {
+				Self::RecordsDeleted { player_id, admin_id, filter_ids }
+			}
+			Event::ServerKeyRotated { server_id, owner_id, expires_on } => {
+				Self::ServerKeyRotated { server_id, owner_id, expires_on }
+			}
+		}
+	}
+}
+
+/// A bounded FIFO queue that drops its oldest element on overflow.
+#[derive(Debug)]
+struct Queue
+{
+	/// The queued events.
+	events: Mutex<VecDeque<Event>>,
+
+	/// The maximum amount of events to hold.
+	capacity: usize,
+
+	/// Wakes up [`Queue::pop()`] when an event is pushed or the queue is
+	/// closed.
+	notify: Notify,
+
+	/// Whether more events can arrive.
+	closed: AtomicBool,
+}
+
+impl Queue
+{
+	/// Creates an empty queue.
+	fn new(capacity: usize) -> Self
+	{
+		Self {
+			events: Mutex::new(VecDeque::with_capacity(capacity)),
+			capacity,
+			notify: Notify::new(),
+			closed: AtomicBool::new(false),
+		}
+	}
+
+	/// Appends an event, dropping the oldest one if the queue is full.
+	fn push(&self, event: Event)
+	{
+		let mut events = self.events.lock().expect("poisoned");
+
+		if events.len() == self.capacity {
+			let dropped = events.pop_front();
+			tracing::warn!(?dropped, "webhook queue is full; dropping oldest event");
+		}
+
+		events.push_back(event);
+		drop(events);
+		self.notify.notify_one();
+	}
+
+	/// Marks the queue as closed; [`Queue::pop()`] will return `None` once
+	/// the remaining events have been taken out.
+	fn close(&self)
+	{
+		self.closed.store(true, Ordering::Release);
+		self.notify.notify_one();
+	}
+
+	/// Waits for the next event.
+	async fn pop(&self) -> Option<Event>
+	{
+		loop {
+			if let Some(event) = self.events.lock().expect("poisoned").pop_front() {
+				return Some(event);
+			}
+
+			if self.closed.load(Ordering::Acquire) {
+				return None;
+			}
+
+			self.notify.notified().await;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use axum::extract::State;
+	use axum::http::StatusCode;
+	use axum::routing::post;
+	use axum::Router;
+	use tokio::sync::mpsc;
+
+	use super::*;
+	use crate::services::bans::BanReason;
+	use crate::testing::{self, ALPHAKEKS_ID};
+
+	/// Creates a distinguishable event.
+	fn event(n: u64) -> Event
+	{
+		Event::PlayerBanned {
+			ban_id: n.into(),
+			player_id: ALPHAKEKS_ID,
+			admin_id: None,
+			reason: BanReason::AutoBhop,
+			duration: None,
+		}
+	}
+
+	/// State for [`receive()`].
+	#[derive(Clone)]
+	struct Receiver
+	{
+		/// How many more requests to reject.
+		failures: Arc<Mutex<usize>>,
+
+		/// Where to forward successfully received bodies.
+		bodies: mpsc::UnboundedSender<String>,
+	}
+
+	/// Handler for the mock webhook.
+	async fn receive(State(receiver): State<Receiver>, body: String) -> StatusCode
+	{
+		let mut failures = receiver.failures.lock().unwrap();
+
+		if *failures > 0 {
+			*failures -= 1;
+			return StatusCode::SERVICE_UNAVAILABLE;
+		}
+
+		receiver.bodies.send(body).unwrap();
+		StatusCode::NO_CONTENT
+	}
+
+	/// Spawns a mock webhook that rejects the first `failures` requests and
+	/// forwards the bodies of all successful ones.
+	async fn receiver(failures: usize)
+		-> color_eyre::Result<(Url, mpsc::UnboundedReceiver<String>)>
+	{
+		let (tx, rx) = mpsc::unbounded_channel();
+		let state = Receiver { failures: Arc::new(Mutex::new(failures)), bodies: tx };
+		let router = Router::new()
+			.route("/webhook", post(receive))
+			.with_state(state);
+
+		let url = testing::spawn_mock_server(router).await?.join("webhook")?;
+
+		Ok((url, rx))
+	}
+
+	#[tokio::test]
+	async fn delivers_burst_in_order() -> color_eyre::Result<()>
+	{
+		let (url, mut bodies) = receiver(2).await?;
+		let events = EventBus::new();
+		let sink = Sink {
+			initial_backoff: Duration::from_millis(10),
+			..Sink::new(url, reqwest::Client::new())
+		};
+		let task = sink.spawn(&events);
+
+		for n in 1..=20 {
+			events.dispatch(event(n));
+		}
+
+		drop(events);
+		task.await?;
+
+		for n in 1..=20 {
+			let body = bodies.try_recv()?;
+			let expected = serde_json::to_string(&Payload::from(&event(n)))?;
+
+			testing::assert_eq!(body, expected);
+		}
+
+		testing::assert!(bodies.try_recv().is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn payload_only_contains_allowed_fields() -> color_eyre::Result<()>
+	{
+		let event = Event::ServerKeyRotated {
+			server_id: 1.into(),
+			owner_id: ALPHAKEKS_ID,
+			expires_on: None,
+		};
+		let payload = serde_json::to_value(Payload::from(&event))?;
+
+		testing::assert_eq!(
+			payload,
+			serde_json::json!({
+				"type": "server_key_rotated",
+				"server_id": 1,
+				"owner_id": ALPHAKEKS_ID,
+				"expires_on": null,
+			})
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn queue_drops_oldest() -> color_eyre::Result<()>
+	{
+		let queue = Queue::new(2);
+
+		queue.push(event(1));
+		queue.push(event(2));
+		queue.push(event(3));
+		queue.close();
+
+		let (first, second, third) = (queue.pop().await, queue.pop().await, queue.pop().await);
+
+		testing::assert_eq!(first, Some(event(2)));
+		testing::assert_eq!(second, Some(event(3)));
+		testing::assert!(third.is_none());
+
+		Ok(())
+	}
+}
@@ -0,0 +1,131 @@
+//! Support for conditional `GET` requests using entity tags.
+//!
+//! Responses wrapped in [`ETagged`] carry a weak `ETag` header derived from
+//! their JSON body. Clients that already have the current version of a
+//! resource can send that tag back in an `If-None-Match` header, and will get
+//! an empty `304 Not Modified` response instead of the full body.
+
+use std::collections::BTreeMap;
+
+use axum::response::{IntoResponse, Response};
+use axum_extra::headers::{ETag, HeaderMapExt, IfNoneMatch};
+use axum_extra::TypedHeader;
+use http::{header, HeaderValue, StatusCode};
+use serde::Serialize;
+use utoipa::openapi::response::{Response as ResponseSchema, ResponseBuilder};
+use utoipa::openapi::RefOr;
+
+use crate::http::problem_details::ProblemType;
+use crate::http::ProblemDetails;
+
+/// A JSON response that can be answered with `304 Not Modified`.
+#[derive(Debug)]
+pub struct ETagged<T>
+{
+	/// The response body.
+	body: T,
+
+	/// The client's `If-None-Match` header, if any.
+	if_none_match: Option<IfNoneMatch>,
+}
+
+impl<T> ETagged<T>
+{
+	/// Wraps `body` so it will be compared against `if_none_match` when turned
+	/// into a response.
+	pub fn new(body: T, if_none_match: Option<TypedHeader<IfNoneMatch>>) -> Self
+	{
+		Self { body, if_none_match: if_none_match.map(|TypedHeader(header)| header) }
+	}
+}
+
+impl<T> IntoResponse for ETagged<T>
+where
+	T: Serialize,
+{
+	fn into_response(self) -> Response
+	{
+		let body = match serde_json::to_vec(&self.body) {
+			Ok(body) => body,
+			Err(error) => {
+				tracing::error!(%error, "failed to serialize response body");
+				return ProblemDetails::new(ProblemType::Internal).into_response();
+			}
+		};
+
+		let etag = weak_etag(&body);
+		let is_fresh = self
+			.if_none_match
+			.is_some_and(|if_none_match| !if_none_match.precondition_passes(&etag));
+
+		let mut response = if is_fresh {
+			StatusCode::NOT_MODIFIED.into_response()
+		} else {
+			([(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))], body)
+				.into_response()
+		};
+
+		response.headers_mut().typed_insert(etag);
+		response
+	}
+}
+
+impl<T> utoipa::IntoResponses for ETagged<T>
+where
+	T: utoipa::IntoResponses,
+{
+	fn responses() -> BTreeMap<String, RefOr<ResponseSchema>>
+	{
+		let mut responses = T::responses();
+
+		responses.insert(
+			String::from("304"),
+			ResponseBuilder::new()
+				.description("The resource did not change since you last fetched it.")
+				.into(),
+		);
+
+		responses
+	}
+}
+
+/// Computes a weak entity tag for a response body.
+fn weak_etag(body: &[u8]) -> ETag
+{
+	format!("W/\"{:x}\"", md5::compute(body))
+		.parse::<ETag>()
+		.expect("hex digits are valid etag characters")
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing;
+
+	/// Turns `body` into a response, given the client's `If-None-Match`.
+	fn respond(body: &str, if_none_match: Option<&ETag>) -> Response
+	{
+		let if_none_match = if_none_match
+			.cloned()
+			.map(IfNoneMatch::from)
+			.map(TypedHeader);
+
+		ETagged::new(body, if_none_match).into_response()
+	}
+
+	#[test]
+	fn matching_etag_is_not_modified() -> color_eyre::Result<()>
+	{
+		let first = respond("hello", None);
+		let etag = first.headers().typed_get::<ETag>().expect("etag is set");
+		let second = respond("hello", Some(&etag));
+		let changed = respond("goodbye", Some(&etag));
+
+		testing::assert_eq!(first.status(), StatusCode::OK);
+		testing::assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+		testing::assert_eq!(changed.status(), StatusCode::OK);
+
+		Ok(())
+	}
+}
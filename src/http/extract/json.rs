@@ -127,5 +127,5 @@ fn has_json_content_type(headers: &http::HeaderMap) -> bool
 	};
 
 	mime.type_() == "application"
-		&& (mime.subtype() == "json" || mime.suffix().map_or(false, |name| name == "json"))
+		&& (mime.subtype() == "json" || mime.suffix().is_some_and(|name| name == "json"))
 }
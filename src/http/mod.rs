@@ -4,3 +4,9 @@ pub mod problem_details;
 pub use problem_details::ProblemDetails;
 
 pub mod extract;
+
+pub mod etag;
+pub use etag::ETagged;
+
+pub mod pagination;
+pub use pagination::Paginated;
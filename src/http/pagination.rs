@@ -0,0 +1,212 @@
+//! Support for `Link` headers on paginated responses.
+//!
+//! Responses wrapped in [`Paginated`] carry an [RFC 8288] `Link` header
+//! pointing at the next and previous pages, so clients can page through a
+//! resource without building URLs themselves. The links are relative to the
+//! request's URI; only `limit` and `offset` are changed, all other query
+//! parameters are kept as they are.
+//!
+//! [RFC 8288]: https://www.rfc-editor.org/rfc/rfc8288
+
+use std::collections::BTreeMap;
+
+use axum::response::{IntoResponse, Response};
+use http::{header, HeaderValue, Uri};
+use utoipa::openapi::header::HeaderBuilder;
+use utoipa::openapi::response::Response as ResponseSchema;
+use utoipa::openapi::{ObjectBuilder, RefOr, SchemaType};
+
+/// A response for a single page of a paginated resource.
+#[derive(Debug)]
+pub struct Paginated<T>
+{
+	/// The response.
+	inner: T,
+
+	/// The value of the `Link` header, if there are any other pages.
+	links: Option<HeaderValue>,
+}
+
+/// The position of a page within a paginated resource.
+#[derive(Debug, Clone, Copy)]
+pub struct Page<'a>
+{
+	/// The URI of the current request.
+	pub uri: &'a Uri,
+
+	/// The page size.
+	pub limit: u64,
+
+	/// How many items come before this page.
+	pub offset: u64,
+
+	/// How many items there are in total.
+	pub total: u64,
+}
+
+impl<T> Paginated<T>
+{
+	/// Wraps `inner` so it will include links for the pages around `page`.
+	pub fn new(inner: T, page: Page<'_>) -> Self
+	{
+		Self { inner, links: links(page) }
+	}
+
+	/// Wraps `inner` without any links.
+	///
+	/// This is useful for responses that were not paginated by offset, e.g.
+	/// because the client used a cursor instead.
+	pub fn without_links(inner: T) -> Self
+	{
+		Self { inner, links: None }
+	}
+
+	/// Transforms the wrapped response, keeping the links.
+	pub fn map<U, F>(self, f: F) -> Paginated<U>
+	where
+		F: FnOnce(T) -> U,
+	{
+		Paginated { inner: f(self.inner), links: self.links }
+	}
+
+	/// Transforms the wrapped response with a fallible function, keeping the
+	/// links.
+	pub fn try_map<U, E, F>(self, f: F) -> Result<Paginated<U>, E>
+	where
+		F: FnOnce(T) -> Result<U, E>,
+	{
+		Ok(Paginated { inner: f(self.inner)?, links: self.links })
+	}
+}
+
+impl<T> IntoResponse for Paginated<T>
+where
+	T: IntoResponse,
+{
+	fn into_response(self) -> Response
+	{
+		let mut response = self.inner.into_response();
+
+		if let Some(links) = self.links {
+			response.headers_mut().insert(header::LINK, links);
+		}
+
+		response
+	}
+}
+
+impl<T> utoipa::IntoResponses for Paginated<T>
+where
+	T: utoipa::IntoResponses,
+{
+	fn responses() -> BTreeMap<String, RefOr<ResponseSchema>>
+	{
+		let mut responses = T::responses();
+
+		if let Some(RefOr::T(ok)) = responses.get_mut("200") {
+			ok.headers.insert(
+				String::from("Link"),
+				HeaderBuilder::new()
+					.schema(ObjectBuilder::new().schema_type(SchemaType::String))
+					.description(Some("links to the next and previous pages (RFC 8288)"))
+					.build(),
+			);
+		}
+
+		responses
+	}
+}
+
+/// Builds the `Link` header value for the pages around `page`.
+///
+/// Returns `None` if there are no other pages.
+fn links(Page { uri, limit, offset, total }: Page<'_>) -> Option<HeaderValue>
+{
+	if limit == 0 {
+		return None;
+	}
+
+	let next = offset.checked_add(limit).filter(|&next| next < total);
+
+	let prev = (offset > 0).then(|| offset.saturating_sub(limit));
+
+	let links = [(next, "next"), (prev, "prev")]
+		.into_iter()
+		.filter_map(|(offset, rel)| Some((offset?, rel)))
+		.map(|(offset, rel)| format!("<{}>; rel=\"{rel}\"", page_uri(uri, limit, offset)))
+		.collect::<Vec<_>>();
+
+	if links.is_empty() {
+		return None;
+	}
+
+	HeaderValue::try_from(links.join(", "))
+		.inspect_err(|error| tracing::error!(%error, "generated invalid `Link` header"))
+		.ok()
+}
+
+/// Returns `uri` with its `limit` and `offset` query parameters replaced.
+fn page_uri(uri: &Uri, limit: u64, offset: u64) -> String
+{
+	let mut query =
+		serde_urlencoded::from_str::<Vec<(String, String)>>(uri.query().unwrap_or_default())
+			.unwrap_or_default();
+
+	query.retain(|(key, _)| key != "limit" && key != "offset");
+	query.push((String::from("limit"), limit.to_string()));
+	query.push((String::from("offset"), offset.to_string()));
+
+	let query = serde_urlencoded::to_string(&query).expect("query parameters are strings");
+
+	format!("{}?{query}", uri.path())
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing;
+
+	/// Returns the `Link` header of a response for `page`.
+	fn link_header(page: Page<'_>) -> Option<String>
+	{
+		Paginated::new((), page)
+			.into_response()
+			.headers()
+			.get(header::LINK)
+			.map(|value| value.to_str().expect("ascii").to_owned())
+	}
+
+	#[test]
+	fn middle_page_links_both_ways() -> color_eyre::Result<()>
+	{
+		let uri = "/maps?name=kz&limit=10&offset=10".parse::<Uri>()?;
+		let links = link_header(Page { uri: &uri, limit: 10, offset: 10, total: 35 });
+
+		testing::assert_eq!(
+			links.as_deref(),
+			Some(
+				"</maps?name=kz&limit=10&offset=20>; rel=\"next\", \
+				 </maps?name=kz&limit=10&offset=0>; rel=\"prev\""
+			)
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn edge_pages_only_link_inwards() -> color_eyre::Result<()>
+	{
+		let uri = "/records".parse::<Uri>()?;
+
+		let first = link_header(Page { uri: &uri, limit: 10, offset: 0, total: 25 });
+		let last = link_header(Page { uri: &uri, limit: 10, offset: 20, total: 25 });
+		let only = link_header(Page { uri: &uri, limit: 10, offset: 0, total: 5 });
+
+		testing::assert_eq!(first.as_deref(), Some("</records?limit=10&offset=10>; rel=\"next\""));
+		testing::assert_eq!(last.as_deref(), Some("</records?limit=10&offset=10>; rel=\"prev\""));
+		testing::assert!(only.is_none());
+
+		Ok(())
+	}
+}
@@ -4,13 +4,13 @@
 
 use std::fmt;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// [RFC 9457] [Extension Members].
 ///
 /// [RFC 9457]: https://www.rfc-editor.org/rfc/rfc9457.html
 /// [Extension Members]: https://www.rfc-editor.org/rfc/rfc9457.html#name-extension-members
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct ExtensionMembers
 {
@@ -40,6 +40,12 @@ impl ExtensionMembers
 
 		self.obj.insert(name.into(), value);
 	}
+
+	/// Returns the extension member with the given `name`, if any.
+	pub fn get(&self, name: &str) -> Option<&serde_json::Value>
+	{
+		self.obj.get(name)
+	}
 }
 
 impl fmt::Debug for ExtensionMembers
@@ -17,6 +17,16 @@ pub trait IntoProblemDetails: std::error::Error
 	/// Returns the problem type for this error.
 	fn problem_type(&self) -> ProblemType;
 
+	/// Returns a human-readable message describing this particular problem.
+	///
+	/// This defaults to the error's [`Display`] implementation.
+	///
+	/// [`Display`]: std::fmt::Display
+	fn detail(&self) -> String
+	{
+		self.to_string()
+	}
+
 	/// Adds [extension members] to the HTTP response.
 	///
 	/// [extension members]: https://www.rfc-editor.org/rfc/rfc9457.html#name-extension-members
@@ -38,7 +48,10 @@ impl IntoProblemDetails for sqlx::Error
 {
 	fn problem_type(&self) -> ProblemType
 	{
-		ProblemType::Internal
+		match self {
+			Self::PoolTimedOut => ProblemType::DatabaseUnavailable,
+			_ => ProblemType::Internal,
+		}
 	}
 }
 
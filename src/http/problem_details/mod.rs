@@ -4,13 +4,25 @@
 //! [`IntoResponse`]. It can be created from any error type that implements
 //! [`IntoProblemDetails`].
 //!
+//! Response bodies are JSON by default. Clients can ask for XML instead using
+//! the `Accept` header, which is honored for any request handled by the
+//! [`ProblemFormatLayer`] middleware.
+//!
+//! [`ProblemFormatLayer`]: crate::middleware::ProblemFormatLayer
+//!
 //! [RFC 9457]: https://www.rfc-editor.org/rfc/rfc9457.html
 
+use std::future::Future;
 use std::panic::Location;
 
 use axum::response::{IntoResponse, Response};
-use serde::Serialize;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Deserializer, Serialize};
 use tap::Tap;
+use utoipa::openapi::schema::{KnownFormat, ObjectBuilder, Schema, SchemaFormat, SchemaType};
+use utoipa::openapi::RefOr;
+
+use crate::middleware::request_id::RequestId;
 
 pub(crate) mod problem_type;
 pub use problem_type::ProblemType;
@@ -21,6 +33,132 @@ pub use extension_members::ExtensionMembers;
 mod into_problem_details;
 pub use into_problem_details::IntoProblemDetails;
 
+mod xml;
+
+tokio::task_local! {
+	/// The format negotiated for the request currently being handled.
+	static FORMAT: Format;
+}
+
+/// The formats a [`ProblemDetails`] response body can be serialized as.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format
+{
+	/// `application/problem+json`
+	#[default]
+	Json,
+
+	/// `application/problem+xml`
+	Xml,
+}
+
+impl Format
+{
+	/// Picks a format based on a request's `Accept` header.
+	///
+	/// XML is only chosen if it was explicitly requested with a higher q-value
+	/// than JSON. Missing headers, wildcards, and ties all result in JSON.
+	/// Media ranges with malformed q-values are ignored.
+	pub fn from_accept(headers: &http::HeaderMap) -> Self
+	{
+		let media_ranges = headers
+			.get_all(http::header::ACCEPT)
+			.iter()
+			.filter_map(|value| value.to_str().ok())
+			.flat_map(|value| value.split(','))
+			.filter_map(parse_media_range)
+			.collect::<Vec<_>>();
+
+		let json = quality_of(&media_ranges, "json");
+		let xml = quality_of(&media_ranges, "xml");
+
+		if xml > json {
+			Self::Xml
+		} else {
+			Self::Json
+		}
+	}
+
+	/// Returns the format negotiated for the request currently being handled.
+	///
+	/// This is [`Format::Json`] outside of [`Format::scope()`].
+	pub fn current() -> Self
+	{
+		FORMAT.try_with(|&format| format).unwrap_or_default()
+	}
+
+	/// Runs `future` with `self` as the [current](Format::current) format.
+	pub fn scope<F>(self, future: F) -> impl Future<Output = F::Output>
+	where
+		F: Future,
+	{
+		FORMAT.scope(self, future)
+	}
+
+	/// Returns the `Content-Type` for this format.
+	pub fn content_type(&self) -> mime::Mime
+	{
+		match self {
+			Self::Json => ProblemDetails::content_type_json(),
+			Self::Xml => ProblemDetails::content_type_xml(),
+		}
+	}
+}
+
+/// Parses a single media range of an `Accept` header into its (lowercase)
+/// media type and q-value.
+fn parse_media_range(range: &str) -> Option<(String, f32)>
+{
+	let mut parts = range.split(';');
+	let media_type = parts.next()?.trim().to_ascii_lowercase();
+
+	if media_type.is_empty() {
+		return None;
+	}
+
+	let mut quality = 1.0;
+
+	for param in parts {
+		let Some((name, value)) = param.split_once('=') else {
+			continue;
+		};
+
+		if name.trim().eq_ignore_ascii_case("q") {
+			quality = value
+				.trim()
+				.parse::<f32>()
+				.ok()
+				.filter(|q| (0.0..=1.0).contains(q))?;
+		}
+	}
+
+	Some((media_type, quality))
+}
+
+/// Returns the q-value of the most specific media range matching `suffix`
+/// (`json` or `xml`), or `0.0` if there is none.
+fn quality_of(media_ranges: &[(String, f32)], suffix: &str) -> f32
+{
+	let problem = format!("application/problem+{suffix}");
+	let plain = format!("application/{suffix}");
+
+	media_ranges
+		.iter()
+		.filter_map(|(media_type, quality)| {
+			let specificity = match media_type.as_str() {
+				ty if ty == problem => 3,
+				ty if ty == plain => 2,
+				"application/*" => 1,
+				"*/*" => 0,
+				_ => return None,
+			};
+
+			Some((specificity, *quality))
+		})
+		.max_by_key(|&(specificity, _)| specificity)
+		.map_or(0.0, |(_, quality)| quality)
+}
+
 /// HTTP Problem Details, as described in [RFC 9457].
 ///
 /// [RFC 9457]: https://www.rfc-editor.org/rfc/rfc9457.html
@@ -41,6 +179,10 @@ pub struct ProblemDetails
 	/// Short, human-readable, error message describing this particular problem.
 	detail: String,
 
+	/// URI reference identifying this specific occurrence of the problem.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	instance: Option<String>,
+
 	/// Any extra details that will be included in the response body.
 	#[serde(flatten)]
 	extra: ExtensionMembers,
@@ -58,12 +200,215 @@ where
 		let problem_type = error.problem_type();
 		let status = problem_type.status();
 		let title = problem_type.title();
-		let detail = error.to_string();
+		let detail = match problem_type {
+			// This goes away on its own, so tell the user to try again instead
+			// of the generic "something went wrong".
+			ProblemType::DatabaseUnavailable => {
+				String::from("the database is under heavy load; please try again later")
+			}
+			_ => error.detail(),
+		};
 		let extra = ExtensionMembers::new().tap_mut(|ext| {
 			error.add_extension_members(ext);
 		});
 
-		Self { problem_type, status, title, detail, extra }
+		Self { problem_type, status, title, detail, instance: None, extra }
+	}
+}
+
+impl ProblemDetails
+{
+	/// Creates a new [`ProblemDetails`] for the given problem type.
+	///
+	/// The detail message defaults to the problem type's title.
+	pub fn new(problem_type: ProblemType) -> Self
+	{
+		Self {
+			problem_type,
+			status: problem_type.status(),
+			title: problem_type.title(),
+			detail: problem_type.title().to_owned(),
+			instance: None,
+			extra: ExtensionMembers::new(),
+		}
+	}
+
+	/// Sets the error message describing this particular problem.
+	pub fn set_detail(&mut self, detail: impl Into<String>)
+	{
+		self.detail = detail.into();
+	}
+
+	/// Sets the URI reference identifying this occurrence of the problem.
+	pub fn set_instance(&mut self, instance: impl Into<String>)
+	{
+		self.instance = Some(instance.into());
+	}
+
+	/// Adds an extension member.
+	///
+	/// # Panics
+	///
+	/// This function will panic if `value` cannot be serialized into JSON.
+	pub fn add_extension<V>(&mut self, name: impl Into<String>, value: &V)
+	where
+		V: Serialize + ?Sized,
+	{
+		self.extra.add(name, value);
+	}
+
+	/// Builder-style version of [`ProblemDetails::set_detail()`].
+	pub fn with_detail(mut self, detail: impl Into<String>) -> Self
+	{
+		self.set_detail(detail);
+		self
+	}
+
+	/// Builder-style version of [`ProblemDetails::set_instance()`].
+	pub fn with_instance(mut self, instance: impl Into<String>) -> Self
+	{
+		self.set_instance(instance);
+		self
+	}
+
+	/// Builder-style version of [`ProblemDetails::add_extension()`].
+	///
+	/// # Panics
+	///
+	/// This function will panic if `value` cannot be serialized into JSON.
+	pub fn with_extension<V>(mut self, name: impl Into<String>, value: &V) -> Self
+	where
+		V: Serialize + ?Sized,
+	{
+		self.add_extension(name, value);
+		self
+	}
+
+	/// Returns the `Content-Type` of JSON responses.
+	pub fn content_type_json() -> mime::Mime
+	{
+		"application/problem+json".parse().expect("valid mime type")
+	}
+
+	/// Returns the `Content-Type` of XML responses.
+	pub fn content_type_xml() -> mime::Mime
+	{
+		"application/problem+xml".parse().expect("valid mime type")
+	}
+
+	/// Serializes `self` as XML.
+	pub fn to_xml_vec(&self) -> Vec<u8>
+	{
+		let serde_json::Value::Object(members) =
+			serde_json::to_value(self).expect("problem details should be valid JSON")
+		else {
+			unreachable!("problem details are serialized as a JSON object");
+		};
+
+		xml::to_xml(&members).into_bytes()
+	}
+
+	/// Creates an HTTP response with a body in the given `format`.
+	///
+	/// If this is called while handling a request, the request's ID is
+	/// included as the `request_id` extension member.
+	pub fn into_response_as(mut self, format: Format) -> Response
+	{
+		if self.extra.get("request_id").is_none() {
+			if let Some(request_id) = RequestId::current() {
+				self.add_extension("request_id", request_id.as_str());
+			}
+		}
+
+		let status = self.status;
+		let headers = [(http::header::CONTENT_TYPE, format.content_type().to_string())];
+
+		match format {
+			Format::Json => (status, headers, crate::http::extract::Json(self)).into_response(),
+			Format::Xml => (status, headers, self.to_xml_vec()).into_response(),
+		}
+	}
+
+	/// Returns the problem type.
+	pub fn problem_type(&self) -> ProblemType
+	{
+		self.problem_type
+	}
+
+	/// Returns the HTTP status code the response should have.
+	pub fn status(&self) -> http::StatusCode
+	{
+		self.status
+	}
+
+	/// Returns the error message describing this particular problem.
+	pub fn detail(&self) -> &str
+	{
+		&self.detail
+	}
+
+	/// Returns the URI reference identifying this occurrence of the problem.
+	pub fn instance(&self) -> Option<&str>
+	{
+		self.instance.as_deref()
+	}
+
+	/// Returns the extension members that will be included in the response
+	/// body.
+	pub fn extension_members(&self) -> &ExtensionMembers
+	{
+		&self.extra
+	}
+}
+
+impl<'de> Deserialize<'de> for ProblemDetails
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		/// The JSON representation of [`ProblemDetails`].
+		///
+		/// `title` and `status` are fully determined by the problem type, so
+		/// they are accepted, but otherwise ignored.
+		#[derive(Deserialize)]
+		struct Helper
+		{
+			/// The problem type.
+			#[serde(rename = "type")]
+			problem_type: ProblemType,
+
+			/// Ignored; derived from `problem_type`.
+			#[serde(default, rename = "title")]
+			_title: Option<IgnoredAny>,
+
+			/// Ignored; derived from `problem_type`.
+			#[serde(default, rename = "status")]
+			_status: Option<IgnoredAny>,
+
+			/// Error message describing this particular problem.
+			detail: String,
+
+			/// URI reference identifying this occurrence of the problem.
+			#[serde(default)]
+			instance: Option<String>,
+
+			/// Any keys not covered by the RFC.
+			#[serde(flatten)]
+			extra: ExtensionMembers,
+		}
+
+		let Helper { problem_type, detail, instance, extra, .. } =
+			Helper::deserialize(deserializer)?;
+
+		Ok(Self {
+			problem_type,
+			status: problem_type.status(),
+			title: problem_type.title(),
+			detail,
+			instance,
+			extra,
+		})
 	}
 }
 
@@ -71,27 +416,110 @@ impl IntoResponse for ProblemDetails
 {
 	fn into_response(self) -> Response
 	{
-		let status = self.status;
-		let content_type = "application/problem+json";
-		let headers = [(http::header::CONTENT_TYPE, content_type)];
-		let body = crate::http::extract::Json(self);
+		self.into_response_as(Format::current())
+	}
+}
 
-		(status, headers, body).into_response()
+impl<'s> utoipa::ToSchema<'s> for ProblemDetails
+{
+	fn schema() -> (&'s str, RefOr<Schema>)
+	{
+		let string = || ObjectBuilder::new().schema_type(SchemaType::String);
+		let schema = ObjectBuilder::new()
+			.description(Some("HTTP Problem Details, as described in RFC 9457."))
+			.property(
+				"type",
+				string()
+					.format(Some(SchemaFormat::KnownFormat(KnownFormat::Uri)))
+					.description(Some("URI identifying the problem type.")),
+			)
+			.required("type")
+			.property(
+				"title",
+				string()
+					.description(Some("Short, human-readable, description of the problem type.")),
+			)
+			.required("title")
+			.property(
+				"detail",
+				string().description(Some(
+					"Short, human-readable, error message describing this particular problem.",
+				)),
+			)
+			.required("detail")
+			.property(
+				"instance",
+				string().description(Some(
+					"URI reference identifying this specific occurrence of the problem.",
+				)),
+			)
+			.additional_properties(Some(RefOr::T(Schema::Object(ObjectBuilder::new().build()))));
+
+		(utoipa_impls::SCHEMA_NAME, Schema::Object(schema.build()).into())
 	}
 }
 
 /// Trait implementations for [`utoipa`].
-mod utoipa_impls
+pub(crate) mod utoipa_impls
 {
 	use std::collections::BTreeMap;
 
 	use itertools::Itertools;
+	use utoipa::openapi::content::ContentBuilder;
 	use utoipa::openapi::response::{Response, ResponseBuilder, ResponsesBuilder};
-	use utoipa::openapi::RefOr;
+	use utoipa::openapi::{Ref, RefOr};
 	use utoipa::IntoResponses;
 
 	use super::{ProblemDetails, ProblemType};
 
+	/// The name of the [`ProblemDetails`] schema component.
+	pub(crate) const SCHEMA_NAME: &str = "ProblemDetails";
+
+	/// Problems common enough to get their own response component.
+	///
+	/// Each entry consists of the component's name, the problem type, and an
+	/// example `detail` message. These components are shared by every route,
+	/// so the examples must not mention any specific resource.
+	const COMMON_PROBLEMS: [(&str, ProblemType, &str); 5] = [
+		("NotFound", ProblemType::ResourceNotFound, "resource does not exist"),
+		("Conflict", ProblemType::ResourceAlreadyExists, "resource already exists"),
+		(
+			"UnprocessableEntity",
+			ProblemType::InvalidRequestBody,
+			"missing field `name` at line 1 column 2",
+		),
+		("TooManyRequests", ProblemType::TooManyRequests, "You made too many requests"),
+		("InternalServerError", ProblemType::Internal, "something went wrong"),
+	];
+
+	/// Returns the reusable response components for common problems.
+	pub(crate) fn common_responses() -> impl Iterator<Item = (&'static str, Response)>
+	{
+		COMMON_PROBLEMS
+			.into_iter()
+			.map(|(name, problem_type, detail)| {
+				let example = ProblemDetails::new(problem_type).with_detail(detail);
+				let example = serde_json::to_value(example).expect("valid json");
+				let description = problem_type.status().canonical_reason().unwrap_or_default();
+
+				(name, response(description, Some(example)))
+			})
+	}
+
+	/// Builds a response containing a [`ProblemDetails`] body.
+	fn response(description: &str, example: Option<serde_json::Value>) -> Response
+	{
+		let content = ContentBuilder::new()
+			.schema(Ref::from_schema_name(SCHEMA_NAME))
+			.example(example)
+			.build();
+
+		ResponseBuilder::new()
+			.description(description)
+			.content(ProblemDetails::content_type_json().as_ref(), content)
+			.build()
+	}
+
 	impl IntoResponses for ProblemDetails
 	{
 		fn responses() -> BTreeMap<String, RefOr<Response>>
@@ -102,10 +530,22 @@ mod utoipa_impls
 				.map(|problem| problem.status())
 				.collect_vec();
 
-			let responses = statuses
-				.iter()
-				.map(|status| (status.as_str(), status.canonical_reason().unwrap_or_default()))
-				.map(|(code, reason)| (code, ResponseBuilder::new().description(reason).build()));
+			let responses = statuses.iter().map(|status| {
+				let common = COMMON_PROBLEMS
+					.iter()
+					.find(|(_, problem_type, _)| problem_type.status() == *status);
+
+				let description = status.canonical_reason().unwrap_or_default();
+				let response = match common {
+					Some((name, ..)) => RefOr::Ref(Ref::from_response_name(*name)),
+					None if status.is_success() => {
+						RefOr::T(ResponseBuilder::new().description(description).build())
+					}
+					None => RefOr::T(response(description, None)),
+				};
+
+				(status.as_str(), response)
+			});
 
 			ResponsesBuilder::new()
 				.responses_from_iter(responses)
@@ -114,3 +554,229 @@ mod utoipa_impls
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests
+{
+	use thiserror::Error;
+
+	use super::*;
+	use crate::testing;
+
+	#[derive(Debug, Error)]
+	#[error("map `{0}` does not exist")]
+	struct UnknownMap(&'static str);
+
+	impl IntoProblemDetails for UnknownMap
+	{
+		fn problem_type(&self) -> ProblemType
+		{
+			ProblemType::ResourceNotFound
+		}
+
+		fn add_extension_members(&self, ext: &mut ExtensionMembers)
+		{
+			ext.add("map", self.0);
+		}
+	}
+
+	#[derive(Debug, Error)]
+	#[error("something went wrong")]
+	struct ServiceError(#[from] sqlx::Error);
+
+	impl IntoProblemDetails for ServiceError
+	{
+		fn problem_type(&self) -> ProblemType
+		{
+			self.0.problem_type()
+		}
+	}
+
+	#[test]
+	fn unavailable_database_has_retry_hint() -> color_eyre::Result<()>
+	{
+		let direct = ProblemDetails::from(sqlx::Error::PoolTimedOut);
+		let wrapped = ProblemDetails::from(ServiceError::from(sqlx::Error::PoolTimedOut));
+		let other = ProblemDetails::from(ServiceError::from(sqlx::Error::RowNotFound));
+
+		testing::assert_eq!(direct.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+		testing::assert!(direct.detail().contains("try again later"));
+		testing::assert_eq!(wrapped.problem_type(), ProblemType::DatabaseUnavailable);
+		testing::assert_eq!(wrapped.detail(), direct.detail());
+		testing::assert_eq!(other.problem_type(), ProblemType::Internal);
+		testing::assert_eq!(other.detail(), "something went wrong");
+
+		Ok(())
+	}
+
+	#[test]
+	fn builder() -> color_eyre::Result<()>
+	{
+		let problem = ProblemDetails::new(ProblemType::ResourceNotFound)
+			.with_detail("map does not exist")
+			.with_instance("/maps/kz_checkmate")
+			.with_extension("map", "kz_checkmate");
+
+		testing::assert_eq!(problem.status(), http::StatusCode::NOT_FOUND);
+		testing::assert_eq!(problem.detail(), "map does not exist");
+		testing::assert_eq!(problem.instance(), Some("/maps/kz_checkmate"));
+
+		let json = serde_json::to_value(&problem)?;
+
+		testing::assert_eq!(json["title"], "Resource Not Found");
+		testing::assert_eq!(json["instance"], "/maps/kz_checkmate");
+		testing::assert_eq!(json["map"], "kz_checkmate");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn into_response_as() -> color_eyre::Result<()>
+	{
+		let res = ProblemDetails::new(ProblemType::ResourceNotFound).into_response_as(Format::Xml);
+
+		testing::assert_eq!(res.status(), http::StatusCode::NOT_FOUND);
+		testing::assert_eq!(res.headers()[http::header::CONTENT_TYPE], "application/problem+xml");
+
+		let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+		let body = String::from_utf8(body.to_vec())?;
+
+		testing::assert!(body.contains("<title>Resource Not Found</title>"));
+
+		let res = ProblemDetails::new(ProblemType::ResourceNotFound).into_response();
+
+		testing::assert_eq!(res.headers()[http::header::CONTENT_TYPE], "application/problem+json");
+
+		Ok(())
+	}
+
+	#[test]
+	fn format_from_accept() -> color_eyre::Result<()>
+	{
+		let format_for = |accept: Option<&str>| {
+			let mut headers = http::HeaderMap::new();
+
+			if let Some(accept) = accept {
+				headers.insert(http::header::ACCEPT, accept.parse().unwrap());
+			}
+
+			Format::from_accept(&headers)
+		};
+
+		testing::assert_eq!(format_for(None), Format::Json);
+		testing::assert_eq!(format_for(Some("*/*")), Format::Json);
+		testing::assert_eq!(format_for(Some("application/problem+xml")), Format::Xml);
+		testing::assert_eq!(format_for(Some("application/xml, */*;q=0.1")), Format::Xml);
+		testing::assert_eq!(
+			format_for(Some("application/problem+xml;q=0.5, application/json")),
+			Format::Json,
+		);
+		testing::assert_eq!(
+			format_for(Some("application/problem+json;q=0.5, application/problem+xml")),
+			Format::Xml,
+		);
+		testing::assert_eq!(
+			format_for(Some("application/problem+xml, application/problem+json")),
+			Format::Json,
+		);
+		testing::assert_eq!(format_for(Some("application/problem+xml;q=abc")), Format::Json);
+		testing::assert_eq!(format_for(Some("application/problem+xml;q=2")), Format::Json);
+		testing::assert_eq!(
+			format_for(Some("application/*;q=0.2, application/problem+xml;q=0.9")),
+			Format::Xml,
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn every_problem_type_serializes() -> color_eyre::Result<()>
+	{
+		let mut slugs = std::collections::HashSet::new();
+
+		for &problem_type in ProblemType::all() {
+			let problem = ProblemDetails::new(problem_type);
+			let json = serde_json::to_value(&problem)?;
+			let uri = format!("https://api.cs2kz.org/docs/problem-types#{}", problem_type.slug());
+			let is_new_slug = slugs.insert(problem_type.slug());
+			let status = problem.into_response().status();
+
+			testing::assert_eq!(json["type"], uri);
+			testing::assert_eq!(json["title"], problem_type.title());
+			testing::assert_eq!(status, problem_type.status());
+			testing::assert!(is_new_slug, "duplicate slug `{}`", problem_type.slug());
+		}
+
+		// These URIs are linked to from clients, so they must not change.
+		let not_found = serde_json::to_value(ProblemType::ResourceNotFound)?;
+		let unauthorized = serde_json::to_value(ProblemType::Unauthorized)?;
+		let too_many_requests = serde_json::to_value(ProblemType::TooManyRequests)?;
+
+		testing::assert_eq!(
+			not_found,
+			"https://api.cs2kz.org/docs/problem-types#resource-not-found"
+		);
+		testing::assert_eq!(unauthorized, "https://api.cs2kz.org/docs/problem-types#unauthorized");
+		testing::assert_eq!(
+			too_many_requests,
+			"https://api.cs2kz.org/docs/problem-types#too-many-requests"
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn deserialize_round_trip() -> color_eyre::Result<()>
+	{
+		let problem = ProblemDetails::from(UnknownMap("kz_checkmate"));
+		let json = serde_json::to_value(&problem)?;
+		let parsed = serde_json::from_value::<ProblemDetails>(json.clone())?;
+
+		testing::assert_eq!(parsed.problem_type(), ProblemType::ResourceNotFound);
+		testing::assert_eq!(parsed.status(), http::StatusCode::NOT_FOUND);
+		testing::assert_eq!(parsed.detail(), "map `kz_checkmate` does not exist");
+		testing::assert!(parsed.instance().is_none());
+		testing::assert_eq!(parsed.extension_members().get("map"), Some(&json["map"]));
+		testing::assert_eq!(serde_json::to_value(&parsed)?, json);
+
+		Ok(())
+	}
+
+	#[test]
+	fn deserialize_ignores_rfc_members() -> color_eyre::Result<()>
+	{
+		let json = serde_json::json!({
+			"type": "https://example.org/docs/problem-types#resource-not-found",
+			"title": "Not Found",
+			"status": 404,
+			"detail": "map does not exist",
+			"instance": "/maps/kz_checkmate",
+			"map": "kz_checkmate",
+		});
+
+		let parsed = serde_json::from_value::<ProblemDetails>(json)?;
+
+		testing::assert_eq!(parsed.problem_type(), ProblemType::ResourceNotFound);
+		testing::assert_eq!(parsed.instance(), Some("/maps/kz_checkmate"));
+		testing::assert!(parsed.extension_members().get("title").is_none());
+		testing::assert!(parsed.extension_members().get("status").is_none());
+		testing::assert!(parsed.extension_members().get("map").is_some());
+
+		Ok(())
+	}
+
+	#[test]
+	fn deserialize_rejects_unknown_problem_type() -> color_eyre::Result<()>
+	{
+		let json = serde_json::json!({
+			"type": "https://api.cs2kz.org/docs/problem-types#does-not-exist",
+			"detail": "?",
+		});
+
+		let result = serde_json::from_value::<ProblemDetails>(json);
+
+		testing::assert!(result.is_err());
+
+		Ok(())
+	}
+}
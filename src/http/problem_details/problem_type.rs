@@ -5,8 +5,7 @@
 
 use std::sync::OnceLock;
 
-use serde::{Serialize, Serializer};
-use tap::Tap;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use url::Url;
 
 /// The base URL for the problem type documentation.
@@ -19,6 +18,17 @@ pub(crate) fn set_base_url(url: Url)
 	assert!(BASE_URL.set(url).is_ok(), "called `set_base_url()` twice!");
 }
 
+/// Returns the URL of the problem type documentation.
+fn docs_url() -> Url
+{
+	BASE_URL
+		.get()
+		.cloned()
+		.unwrap_or_else(|| "https://api.cs2kz.org".parse::<Url>().expect("valid url"))
+		.join("/docs/problem-types")
+		.expect("valid url")
+}
+
 /// A problem type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, cs2kz_api_macros::ProblemType)]
 pub enum ProblemType
@@ -46,6 +56,12 @@ pub enum ProblemType
 	#[status = 401]
 	InvalidOpenIDPayload,
 
+	/// A CS2 server tried to authenticate while running a plugin version that
+	/// has been yanked. The server has to be updated before it can
+	/// authenticate again.
+	#[status = 401]
+	PluginVersionYanked,
+
 	/// A requested resource could not be found.
 	#[status = 404]
 	ResourceNotFound,
@@ -85,6 +101,11 @@ pub enum ProblemType
 	#[status = 409]
 	ActionAlreadyPerformed,
 
+	/// You sent a request with an `Idempotency-Key` that is still being used
+	/// by another request that hasn't finished yet.
+	#[status = 409]
+	IdempotencyKeyInUse,
+
 	/// You provided a timestamp that did not make sense.
 	///
 	/// For example, when providing an expiration date, it cannot be before the
@@ -111,6 +132,20 @@ pub enum ProblemType
 	#[status = 409]
 	WorkshopItemNotAMap,
 
+	/// You submitted or updated a map and told us which checksum to expect,
+	/// but the map we downloaded from the workshop has a different checksum.
+	///
+	/// This usually means the workshop item was changed after you reviewed it.
+	#[status = 409]
+	ChecksumMismatch,
+
+	/// You requested to change the status of a resource, but its current status
+	/// cannot be changed to the one you provided. For example, a map that has
+	/// been degloballed has to go through testing again before it can be
+	/// globalled.
+	#[status = 409]
+	InvalidStatusTransition,
+
 	/// You provided path parameters which could not be deserialized.
 	#[status = 422]
 	InvalidPathParameters,
@@ -127,6 +162,13 @@ pub enum ProblemType
 	#[status = 422]
 	InvalidRequestBody,
 
+	/// You made too many requests in a short amount of time.
+	///
+	/// The `Retry-After` header tells you how many seconds to wait before
+	/// trying again.
+	#[status = 429]
+	TooManyRequests,
+
 	/// We made a request to an external service and failed to decode the
 	/// response.
 	#[status = 500]
@@ -146,6 +188,19 @@ pub enum ProblemType
 	/// We failed to communicate with an external service, such as Steam.
 	#[status = 502]
 	ExternalService,
+
+	/// The database is currently too busy to handle your request.
+	///
+	/// This is usually temporary, so try again in a bit.
+	#[status = 503]
+	DatabaseUnavailable,
+
+	/// Your request took too long to process and was aborted.
+	///
+	/// This usually happens when an external service we depend on, such as
+	/// Steam, is not responding.
+	#[status = 504]
+	Timeout,
 }
 
 impl Serialize for ProblemType
@@ -155,13 +210,26 @@ impl Serialize for ProblemType
 	where
 		S: Serializer,
 	{
-		BASE_URL
-			.get()
-			.cloned()
-			.unwrap_or_else(|| "https://api.cs2kz.org".parse::<Url>().expect("valid url"))
-			.join("/docs/problem-types")
-			.expect("valid url")
-			.tap_mut(|url| url.set_fragment(Some(self.slug())))
-			.serialize(serializer)
+		self.uri(&docs_url()).serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for ProblemType
+{
+	// Deserialize from a URI as produced by the `Serialize` impl above.
+	//
+	// Problem types without an explicit URI are only matched by their fragment,
+	// so this works regardless of which base URL the API was configured with.
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let url = Url::deserialize(deserializer)?;
+
+		Self::all()
+			.iter()
+			.copied()
+			.find(|problem_type| problem_type.uri(&url) == url)
+			.ok_or_else(|| de::Error::custom(format!("unknown problem type `{url}`")))
 	}
 }
@@ -0,0 +1,164 @@
+//! XML serialization for [`ProblemDetails`], as described in
+//! [Appendix B of RFC 9457][appendix].
+//!
+//! [`ProblemDetails`]: super::ProblemDetails
+//! [appendix]: https://www.rfc-editor.org/rfc/rfc9457.html#name-the-application-problemxml-
+
+use std::fmt::{self, Write};
+
+use serde_json::{Map, Value};
+
+/// The XML namespace of the `<problem>` root element.
+const NAMESPACE: &str = "urn:ietf:rfc:7807";
+
+/// The members defined by the RFC, in the order they are emitted in.
+///
+/// Extension members follow after these.
+const RFC_MEMBERS: [&str; 5] = ["type", "title", "status", "detail", "instance"];
+
+/// Serializes the JSON representation of a problem details object as XML.
+///
+/// Extension members are nested as child elements; arrays are encoded as
+/// repeated `<i>` elements, as shown in the RFC's example.
+pub(super) fn to_xml(members: &Map<String, Value>) -> String
+{
+	let mut xml = String::new();
+
+	write_document(&mut xml, members).expect("writing to a `String` cannot fail");
+
+	xml
+}
+
+/// Writes the full XML document into `xml`.
+fn write_document(xml: &mut String, members: &Map<String, Value>) -> fmt::Result
+{
+	writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+	writeln!(xml, r#"<problem xmlns="{NAMESPACE}">"#)?;
+
+	let rfc_members = RFC_MEMBERS
+		.iter()
+		.filter_map(|&name| members.get_key_value(name));
+
+	let extension_members = members
+		.iter()
+		.filter(|(name, _)| !RFC_MEMBERS.contains(&name.as_str()));
+
+	for (name, value) in rfc_members.chain(extension_members) {
+		write_element(xml, name, value, 1)?;
+	}
+
+	writeln!(xml, "</problem>")
+}
+
+/// Writes a single `<name>value</name>` element into `xml`.
+fn write_element(xml: &mut String, name: &str, value: &Value, depth: usize) -> fmt::Result
+{
+	let indent = "  ".repeat(depth);
+
+	match value {
+		Value::Null => writeln!(xml, "{indent}<{name}/>"),
+		Value::Bool(_) | Value::Number(_) => writeln!(xml, "{indent}<{name}>{value}</{name}>"),
+		Value::String(text) => writeln!(xml, "{indent}<{name}>{}</{name}>", Escaped(text)),
+		Value::Array(items) => {
+			writeln!(xml, "{indent}<{name}>")?;
+
+			for item in items {
+				write_element(xml, "i", item, depth + 1)?;
+			}
+
+			writeln!(xml, "{indent}</{name}>")
+		}
+		Value::Object(fields) => {
+			writeln!(xml, "{indent}<{name}>")?;
+
+			for (field, value) in fields {
+				write_element(xml, field, value, depth + 1)?;
+			}
+
+			writeln!(xml, "{indent}</{name}>")
+		}
+	}
+}
+
+/// Escapes XML special characters when formatted.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_>
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		for char in self.0.chars() {
+			match char {
+				'&' => f.write_str("&amp;")?,
+				'<' => f.write_str("&lt;")?,
+				'>' => f.write_str("&gt;")?,
+				'"' => f.write_str("&quot;")?,
+				'\'' => f.write_str("&apos;")?,
+				char => f.write_char(char)?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn rfc_example() -> color_eyre::Result<()>
+	{
+		let Value::Object(members) = serde_json::json!({
+			"type": "https://example.com/probs/out-of-credit",
+			"title": "You do not have enough credit.",
+			"detail": "Your current balance is 30, but that costs 50.",
+			"instance": "https://example.net/account/12345/msgs/abc",
+			"balance": 30,
+			"accounts": [
+				"https://example.net/account/12345",
+				"https://example.net/account/67890"
+			]
+		}) else {
+			unreachable!();
+		};
+
+		// Extension members are emitted in the order of the underlying map, which
+		// is sorted, so `accounts` comes before `balance` here.
+		let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<problem xmlns="urn:ietf:rfc:7807">
+  <type>https://example.com/probs/out-of-credit</type>
+  <title>You do not have enough credit.</title>
+  <detail>Your current balance is 30, but that costs 50.</detail>
+  <instance>https://example.net/account/12345/msgs/abc</instance>
+  <accounts>
+    <i>https://example.net/account/12345</i>
+    <i>https://example.net/account/67890</i>
+  </accounts>
+  <balance>30</balance>
+</problem>
+"#;
+
+		testing::assert_eq!(to_xml(&members), expected);
+
+		Ok(())
+	}
+
+	#[test]
+	fn escapes_text() -> color_eyre::Result<()>
+	{
+		let Value::Object(members) = serde_json::json!({
+			"detail": "map `<kz_checkmate>` & friends",
+		}) else {
+			unreachable!();
+		};
+
+		let xml = to_xml(&members);
+
+		testing::assert!(xml.contains("<detail>map `&lt;kz_checkmate&gt;` &amp; friends</detail>"));
+
+		Ok(())
+	}
+}
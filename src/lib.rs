@@ -32,11 +32,13 @@ mod serde;
 mod time;
 mod util;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "testing"))]
 mod testing;
 
 pub mod database;
+pub mod events;
 pub mod http;
+pub mod metrics;
 pub mod middleware;
 pub mod runtime;
 pub mod services;
@@ -53,8 +55,9 @@ pub type Server =
 /// When the returned service is called, it will return a new service that can
 /// handle an incoming connection.
 ///
-/// You'll likely just pass the return value to [`axum::serve()`] to run the
-/// server.
+/// You'll likely just pass the returned service to [`axum::serve()`] to run the
+/// server. The returned [`runtime::reload::Handle`] can be used to build a
+/// [`runtime::Reloader`].
 #[tracing::instrument(target = "cs2kz_api::runtime", name = "start", err(Debug))]
 pub async fn server(
 	runtime_config: runtime::config::RuntimeConfig,
@@ -62,10 +65,16 @@ pub async fn server(
 	http_config: runtime::config::HttpConfig,
 	secrets: runtime::config::Secrets,
 	steam_config: runtime::config::SteamConfig,
-) -> Result<Server, setup::Error>
+	events_config: runtime::config::EventsConfig,
+	servers_config: runtime::config::ServersConfig,
+) -> Result<(Server, runtime::reload::Handle), setup::Error>
 {
+	use std::time::Duration;
+
+	use self::services::auth::JwtKeys;
 	use self::services::{
 		AdminService,
+		AuditService,
 		AuthService,
 		BanService,
 		HealthService,
@@ -79,9 +88,19 @@ pub async fn server(
 	};
 
 	self::http::problem_details::problem_type::set_base_url(http_config.public_url.clone());
+	self::middleware::cors::set_allowlist(middleware::cors::Allowlist::from_config(
+		&http_config.cors,
+	)?);
+
+	let docs = docs::router(&http_config.public_url);
 
 	let http_client = reqwest::Client::new();
 	let database = database::create_pool(&database_config).await?;
+	let events = events::EventBus::with_capacity(events_config.capacity.get());
+
+	if let Some(webhook_url) = events_config.webhook_url {
+		events::webhook::Sink::new(webhook_url, http_client.clone()).spawn(&events);
+	}
 
 	let steam_svc = SteamService::new(
 		http_config.public_url,
@@ -89,30 +108,59 @@ pub async fn server(
 		steam_config.workshop_artifacts_path,
 		steam_config.depot_downloader_path,
 		http_client.clone(),
+	)
+	.map_name_cache(
+		Duration::from_secs(steam_config.map_name_cache_ttl.get()),
+		steam_config.map_name_cache_capacity,
+	)
+	.downloads(
+		Duration::from_secs(steam_config.download_timeout.get()),
+		steam_config.max_concurrent_downloads,
 	);
 
 	let auth_svc = AuthService::new(
 		database.clone(),
 		http_client.clone(),
 		steam_svc.clone(),
-		secrets.jwt_key,
+		JwtKeys::from_config(&secrets),
 		http_config.cookie_domain,
+		http_config.cookies,
 	);
 
-	let health_svc = HealthService::new();
+	let health_svc = HealthService::new(database.clone(), steam_svc.clone());
 	let player_svc = PlayerService::new(database.clone(), auth_svc.clone(), steam_svc.clone());
 	let map_svc = MapService::new(database.clone(), auth_svc.clone(), steam_svc.clone());
-	let server_svc = ServerService::new(database.clone(), auth_svc.clone());
-	let record_svc = RecordService::new(database.clone(), auth_svc.clone());
+	let server_svc = ServerService::new(database.clone(), auth_svc.clone(), events.clone())
+		.heartbeat_window(Duration::from_secs(servers_config.heartbeat_window.get()))
+		.key_lifetime(
+			servers_config
+				.key_lifetime
+				.map(|secs| Duration::from_secs(secs.get())),
+		)
+		.key_grace_period(Duration::from_secs(servers_config.key_grace_period));
+	let record_svc = RecordService::new(database.clone(), auth_svc.clone(), events.clone());
 	let jumpstat_svc = JumpstatService::new(database.clone(), auth_svc.clone());
-	let ban_svc = BanService::new(database.clone(), auth_svc.clone());
+	let ban_svc = BanService::new(database.clone(), auth_svc.clone(), events.clone());
 	let admin_svc = AdminService::new(database.clone(), auth_svc.clone());
+	let audit_svc = AuditService::new(database.clone(), auth_svc.clone());
 	let plugin_svc = PluginService::new(database.clone());
 
-	let docs = docs::router();
+	if servers_config.key_lifetime.is_some() {
+		let within = Duration::from_secs(servers_config.key_rotation_window.get());
+		server_svc.clone().spawn_key_rotation(within);
+	}
 
+	let metrics = metrics::Metrics::new(database.clone(), events.clone());
+
+	let record_metrics = middleware::MetricsLayer::new(metrics.clone());
+	let request_timeout = middleware::RequestTimeoutLayer::new(http_config.request_timeout);
+	let rate_limit = middleware::RateLimitLayer::new(http_config.rate_limit, auth_svc.clone());
 	let panic_handler = middleware::panic_handler::layer();
 	let logging = middleware::logging::layer!();
+	let request_id = middleware::RequestIdLayer::new();
+	let problem_format = middleware::ProblemFormatLayer::new();
+
+	let reload_handle = runtime::reload::Handle::new(rate_limit.clone(), server_svc.clone());
 
 	let server = axum::Router::new()
 		.merge(health_svc)
@@ -124,11 +172,18 @@ pub async fn server(
 		.nest("/bans", ban_svc.into())
 		.nest("/auth", auth_svc.into())
 		.nest("/admins", admin_svc.into())
+		.nest("/audit", audit_svc.into())
 		.nest("/plugin", plugin_svc.into())
+		.layer(request_timeout)
+		.layer(record_metrics)
+		.layer(rate_limit)
 		.layer(panic_handler)
 		.layer(logging)
+		.layer(problem_format)
+		.layer(request_id)
 		.merge(docs)
+		.merge(metrics.router())
 		.into_make_service_with_connect_info::<std::net::SocketAddr>();
 
-	Ok(server)
+	Ok((server, reload_handle))
 }
@@ -16,19 +16,30 @@
 
 #![expect(clippy::disallowed_types)]
 
-use std::fs;
+use std::future::Future;
 use std::net::IpAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::Duration;
+use std::{fs, io};
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use color_eyre::eyre::WrapErr;
+use futures::future;
 use similar::TextDiff;
 use tap::Tap;
 use tokio::net::TcpListener;
+use tokio::sync::watch;
+use url::Url;
 
 mod tracing;
 
+/// The binary's tests only need some of these helpers.
+#[cfg(test)]
+#[path = "testing/shared.rs"]
+#[allow(unused_imports, unused_macros)]
+mod testing;
+
 /// The main server entrypoint for the API.
 fn main() -> color_eyre::Result<ExitCode>
 {
@@ -40,7 +51,9 @@ fn main() -> color_eyre::Result<ExitCode>
 				eprintln!("WARNING: no `.env` file found");
 			}
 
-			let mut config = cs2kz_api::runtime::Config::load(config).context("load config")?;
+			let config_path = config;
+			let mut config =
+				cs2kz_api::runtime::Config::load(&config_path).context("load config")?;
 
 			if let Some(ip) = ip_address {
 				config.http.listen_addr = ip;
@@ -72,11 +85,19 @@ fn main() -> color_eyre::Result<ExitCode>
 			runtime
 				.build()
 				.context("build tokio runtime")?
-				.block_on(serve(config))?;
+				.block_on(serve(config, config_path))?;
 		}
 
-		Action::GenerateSchema { check } => {
-			return generate_schema(check);
+		Action::CheckConfig { config } => {
+			if dotenvy::dotenv().is_err() {
+				eprintln!("WARNING: no `.env` file found");
+			}
+
+			return Ok(check_config(&config));
+		}
+
+		Action::GenerateSchema { check, public_url, format } => {
+			return generate_schema(check, &public_url, format);
 		}
 	}
 
@@ -84,7 +105,9 @@ fn main() -> color_eyre::Result<ExitCode>
 }
 
 /// Serves the API on the given `ip_address` and `port` with the given `config`.
-async fn serve(config: cs2kz_api::runtime::Config) -> color_eyre::Result<()>
+///
+/// `config_path` is re-read whenever we receive `SIGHUP`.
+async fn serve(config: cs2kz_api::runtime::Config, config_path: PathBuf) -> color_eyre::Result<()>
 {
 	cs2kz_api::runtime::panic_hook::install();
 
@@ -94,29 +117,134 @@ async fn serve(config: cs2kz_api::runtime::Config) -> color_eyre::Result<()>
 		.await
 		.context("bind tcp listener")?;
 
-	let server = cs2kz_api::server(
+	let shutdown_timeout = Duration::from_secs(config.http.shutdown_timeout.get());
+
+	let (server, reload_handle) = cs2kz_api::server(
 		config.runtime,
 		config.database,
 		config.http,
 		config.secrets,
 		config.steam,
+		config.events,
+		config.servers,
 	)
 	.await
 	.context("run server")?;
 
+	let reloader = cs2kz_api::runtime::Reloader::new(config_path, reload_handle)
+		.context("initialize config reloader")?;
+
+	let reloader = match &_tracing_guard {
+		Some(guard) => reloader.apply_filter(guard.filter_reloader()),
+		None => reloader,
+	};
+
+	#[cfg(unix)]
+	tokio::spawn(cs2kz_api::runtime::signals::reload_on_sighup(reloader));
+
 	::tracing::info!("listening on {}", tcp_listener.local_addr()?);
 
-	axum::serve(tcp_listener, server)
-		.with_graceful_shutdown(cs2kz_api::runtime::signals::sigint())
+	serve_until(tcp_listener, server, cs2kz_api::runtime::signals::sigint(), shutdown_timeout)
 		.await
 		.context("run axum")
 }
 
+/// Serves `server` on `tcp_listener` until `signal` resolves.
+///
+/// Once `signal` resolves, axum stops accepting new connections and waits for
+/// in-flight requests to finish. We don't want to wait forever though, so we
+/// give up after `shutdown_timeout` and return an error, so the process exits
+/// with a non-zero status.
+async fn serve_until(
+	tcp_listener: TcpListener,
+	server: cs2kz_api::Server,
+	signal: impl Future<Output = ()> + Send + 'static,
+	shutdown_timeout: Duration,
+) -> io::Result<()>
+{
+	let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+	let server = axum::serve(tcp_listener, server).with_graceful_shutdown(async move {
+		signal.await;
+		shutdown_tx.send_replace(true);
+	});
+
+	let drain_timeout = async move {
+		if shutdown_rx.wait_for(|&shutdown| shutdown).await.is_err() {
+			return future::pending().await;
+		}
+
+		tokio::time::sleep(shutdown_timeout).await;
+	};
+
+	tokio::select! {
+		result = server => result,
+		() = drain_timeout => Err(io::Error::new(
+			io::ErrorKind::TimedOut,
+			format!("in-flight requests did not finish within {shutdown_timeout:?}"),
+		)),
+	}
+}
+
+/// Loads and validates the config file at `path`, printing a summary of it.
+fn check_config(path: &Path) -> ExitCode
+{
+	let config = match cs2kz_api::runtime::Config::load(path) {
+		Ok(config) => config,
+		Err(error) => {
+			eprintln!("{path:?} is invalid: {error}");
+			return ExitCode::FAILURE;
+		}
+	};
+
+	let database = &config.database.url;
+	let depot_downloader = config.steam.depot_downloader();
+
+	println!("config file:          {path:?}");
+	println!("listening on:         {}", config.http.socket_addr());
+	println!("public url:           {}", config.http.public_url);
+	println!(
+		"database:             {}://{}{}",
+		database.scheme(),
+		database.host_str().unwrap_or_default(),
+		database.path(),
+	);
+	println!("workshop artifacts:   {:?}", config.steam.workshop_artifacts_path);
+	println!(
+		"DepotDownloader:      {:?}",
+		depot_downloader
+			.as_deref()
+			.unwrap_or(&config.steam.depot_downloader_path),
+	);
+	println!("log directory:        {:?}", config.tracing.files.path);
+
+	let problems = config.validate();
+
+	if problems.is_empty() {
+		println!("\nno problems found");
+		return ExitCode::SUCCESS;
+	}
+
+	eprintln!("\nfound {} problem(s):", problems.len());
+
+	for problem in &problems {
+		eprintln!("  - {problem}");
+	}
+
+	ExitCode::FAILURE
+}
+
 /// Generates the API's OpenAPI schema and either writes it to stdout, or diffs
 /// it against an existing file.
-fn generate_schema(check_against: Option<PathBuf>) -> color_eyre::Result<ExitCode>
+fn generate_schema(
+	check_against: Option<PathBuf>,
+	public_url: &Url,
+	format: SchemaFormat,
+) -> color_eyre::Result<ExitCode>
 {
-	let schema = cs2kz_api::openapi::Schema::json();
+	let schema = match format {
+		SchemaFormat::Json => cs2kz_api::openapi::Schema::json(public_url),
+		SchemaFormat::Yaml => cs2kz_api::openapi::Schema::yaml(public_url),
+	};
 
 	let Some(path) = check_against else {
 		print!("{schema}");
@@ -148,6 +276,7 @@ struct Args
 	action: Action,
 }
 
+/// The actions the CLI can perform.
 #[derive(Debug, Subcommand)]
 #[allow(clippy::large_enum_variant)]
 enum Action
@@ -169,6 +298,17 @@ enum Action
 		config: PathBuf,
 	},
 
+	/// Validate a configuration file without starting the server.
+	///
+	/// The database URL is read from `DATABASE_URL`, just like when serving.
+	CheckConfig
+	{
+		/// Path to the configuration file to check.
+		#[clap(default_value = ".config/config.toml")]
+		#[arg(long)]
+		config: PathBuf,
+	},
+
 	/// Generate the API's OpenAPI schema.
 	GenerateSchema
 	{
@@ -178,5 +318,124 @@ enum Action
 		/// exit code.
 		#[arg(long, name = "FILE")]
 		check: Option<PathBuf>,
+
+		/// The public URL of the API, listed under `servers` in the schema.
+		#[clap(default_value = cs2kz_api::openapi::DEFAULT_PUBLIC_URL)]
+		#[arg(long)]
+		public_url: Url,
+
+		/// The format to generate the schema in.
+		///
+		/// If `--check` is specified, the file is expected to be in the same
+		/// format.
+		#[clap(default_value = "json")]
+		#[arg(long, value_enum)]
+		format: SchemaFormat,
 	},
 }
+
+/// The formats the OpenAPI schema can be generated in.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SchemaFormat
+{
+	/// JSON
+	Json,
+
+	/// YAML
+	Yaml,
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::net::SocketAddr;
+	use std::sync::Arc;
+
+	use axum::{routing, Router};
+	use tokio::sync::{oneshot, Notify};
+	use tokio::task::JoinHandle;
+
+	use super::*;
+	use crate::testing;
+
+	/// Serves a single route that takes `request_duration` to respond, and
+	/// sends a request to it.
+	///
+	/// Returns once the request is being processed.
+	async fn serve_slow_request(
+		request_duration: Duration,
+		shutdown_timeout: Duration,
+	) -> color_eyre::Result<(
+		oneshot::Sender<()>,
+		JoinHandle<io::Result<()>>,
+		JoinHandle<reqwest::Result<String>>,
+	)>
+	{
+		let tcp_listener = TcpListener::bind("127.0.0.1:0").await?;
+		let url = format!("http://{}/", tcp_listener.local_addr()?);
+		let started = Arc::new(Notify::new());
+		let router = Router::new().route(
+			"/",
+			routing::get({
+				let started = Arc::clone(&started);
+
+				move || async move {
+					started.notify_one();
+					tokio::time::sleep(request_duration).await;
+					"done"
+				}
+			}),
+		);
+
+		let (shutdown_tx, shutdown_rx) = oneshot::channel();
+		let server = tokio::spawn(serve_until(
+			tcp_listener,
+			router.into_make_service_with_connect_info::<SocketAddr>(),
+			async move {
+				_ = shutdown_rx.await;
+			},
+			shutdown_timeout,
+		));
+
+		let request = tokio::spawn(async move { reqwest::get(url).await?.text().await });
+
+		started.notified().await;
+
+		Ok((shutdown_tx, server, request))
+	}
+
+	#[tokio::test]
+	async fn in_flight_requests_finish() -> color_eyre::Result<()>
+	{
+		let (shutdown_tx, server, request) =
+			serve_slow_request(Duration::from_millis(200), Duration::from_secs(10)).await?;
+
+		_ = shutdown_tx.send(());
+
+		let body = request.await??;
+
+		testing::assert_eq!(body, "done");
+
+		tokio::time::timeout(Duration::from_secs(5), server).await???;
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn drain_timeout_is_enforced() -> color_eyre::Result<()>
+	{
+		let (shutdown_tx, server, request) =
+			serve_slow_request(Duration::from_secs(60), Duration::from_millis(100)).await?;
+
+		_ = shutdown_tx.send(());
+
+		let result = tokio::time::timeout(Duration::from_secs(5), server).await??;
+
+		testing::assert_matches!(result, Err(ref error) if error.kind() == io::ErrorKind::TimedOut);
+		testing::assert!(!request.is_finished());
+
+		request.abort();
+
+		Ok(())
+	}
+}
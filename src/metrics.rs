@@ -0,0 +1,390 @@
+//! This module contains the `/metrics` endpoint.
+//!
+//! [`Metrics`] is a small registry that collects per-route request counts and
+//! latencies (fed by [`MetricsLayer`]) and reports the state of the database
+//! connection pool and the [event bus]. Everything is rendered in the
+//! [Prometheus text format] when `/metrics` is scraped.
+//!
+//! The endpoint is only reachable from localhost; everyone else gets a `404`.
+//!
+//! [`MetricsLayer`]: crate::middleware::MetricsLayer
+//! [event bus]: crate::events::EventBus
+//! [Prometheus text format]: https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{ConnectInfo, State};
+use axum::response::{IntoResponse, Response};
+use axum::{routing, Router};
+use http::{header, HeaderValue, Method, StatusCode};
+use sqlx::{MySql, Pool};
+
+use crate::events::EventBus;
+use crate::http::problem_details::ProblemType;
+use crate::http::ProblemDetails;
+
+/// Upper bounds (in seconds) of the request latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// The `Content-Type` of the Prometheus text format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// A registry of metrics about the API.
+#[derive(Clone)]
+pub struct Metrics
+{
+	/// The shared state.
+	inner: Arc<Inner>,
+}
+
+/// The state shared between all clones of a [`Metrics`] registry.
+struct Inner
+{
+	/// The database pool we report connection gauges for.
+	database: Pool<MySql>,
+
+	/// The event bus we report dropped events for.
+	events: EventBus,
+
+	/// Request metrics, keyed by method and route.
+	routes: Mutex<BTreeMap<(&'static str, String), RouteMetrics>>,
+}
+
+/// Metrics collected for a single method + route combination.
+#[derive(Debug, Default)]
+struct RouteMetrics
+{
+	/// Amount of responses per status code.
+	statuses: BTreeMap<u16, u64>,
+
+	/// Amount of requests that took at most the corresponding
+	/// [`LATENCY_BUCKETS`] entry.
+	buckets: [u64; LATENCY_BUCKETS.len()],
+
+	/// The total time spent handling requests, in seconds.
+	sum: f64,
+
+	/// The total amount of requests.
+	count: u64,
+}
+
+impl fmt::Debug for Metrics
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		f.debug_struct("Metrics").finish_non_exhaustive()
+	}
+}
+
+impl Metrics
+{
+	/// Creates a new [`Metrics`] registry.
+	pub fn new(database: Pool<MySql>, events: EventBus) -> Self
+	{
+		Self { inner: Arc::new(Inner { database, events, routes: Mutex::default() }) }
+	}
+
+	/// Records a handled request.
+	///
+	/// `route` should be the route's path *pattern* (e.g. `/maps/:map`), not
+	/// the actual request path, to keep the amount of label values bounded.
+	/// For the same reason, non-standard methods are all recorded as `other`.
+	pub fn record(&self, method: &Method, route: &str, status: StatusCode, elapsed: Duration)
+	{
+		let elapsed = elapsed.as_secs_f64();
+		let mut routes = self.inner.routes.lock().expect("mutex is not poisoned");
+		let metrics = routes
+			.entry((method_label(method), route.to_owned()))
+			.or_default();
+
+		*metrics.statuses.entry(status.as_u16()).or_default() += 1;
+
+		for (bucket, &upper_bound) in metrics.buckets.iter_mut().zip(&LATENCY_BUCKETS) {
+			if elapsed <= upper_bound {
+				*bucket += 1;
+			}
+		}
+
+		metrics.sum += elapsed;
+		metrics.count += 1;
+	}
+
+	/// Renders all metrics in the Prometheus text format.
+	pub fn render(&self) -> String
+	{
+		let mut out = String::new();
+
+		self.render_into(&mut out)
+			.expect("writing to a string never fails");
+
+		out
+	}
+
+	/// Renders all metrics into `out`.
+	fn render_into(&self, out: &mut String) -> fmt::Result
+	{
+		let routes = self.inner.routes.lock().expect("mutex is not poisoned");
+
+		writeln!(out, "# HELP http_requests_total Total number of handled HTTP requests.")?;
+		writeln!(out, "# TYPE http_requests_total counter")?;
+
+		for ((method, route), metrics) in routes.iter() {
+			for (status, count) in &metrics.statuses {
+				writeln!(
+					out,
+					"http_requests_total{{method=\"{}\",route=\"{}\",status=\"{status}\"}} {count}",
+					Escaped(method),
+					Escaped(route),
+				)?;
+			}
+		}
+
+		writeln!(out, "# HELP http_request_duration_seconds HTTP request latencies.")?;
+		writeln!(out, "# TYPE http_request_duration_seconds histogram")?;
+
+		for ((method, route), metrics) in routes.iter() {
+			let labels = format!("method=\"{}\",route=\"{}\"", Escaped(method), Escaped(route));
+
+			for (count, upper_bound) in metrics.buckets.iter().zip(LATENCY_BUCKETS) {
+				writeln!(
+					out,
+					"http_request_duration_seconds_bucket{{{labels},le=\"{upper_bound}\"}} {count}",
+				)?;
+			}
+
+			writeln!(
+				out,
+				"http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+				metrics.count,
+			)?;
+			writeln!(out, "http_request_duration_seconds_sum{{{labels}}} {}", metrics.sum)?;
+			writeln!(out, "http_request_duration_seconds_count{{{labels}}} {}", metrics.count)?;
+		}
+
+		drop(routes);
+
+		let database = &self.inner.database;
+		let total = u64::from(database.size());
+		let idle = database.num_idle() as u64;
+
+		writeln!(out, "# HELP db_pool_connections Open database connections.")?;
+		writeln!(out, "# TYPE db_pool_connections gauge")?;
+		writeln!(out, "db_pool_connections{{state=\"idle\"}} {idle}")?;
+		writeln!(out, "db_pool_connections{{state=\"active\"}} {}", total.saturating_sub(idle))?;
+		writeln!(out, "# HELP db_pool_max_connections Maximum amount of database connections.")?;
+		writeln!(out, "# TYPE db_pool_max_connections gauge")?;
+		writeln!(out, "db_pool_max_connections {}", database.options().get_max_connections())?;
+		writeln!(out, "# HELP events_dropped_total Events missed by lagging event subscribers.")?;
+		writeln!(out, "# TYPE events_dropped_total counter")?;
+		writeln!(out, "events_dropped_total {}", self.inner.events.dropped_events())?;
+
+		Ok(())
+	}
+
+	/// Returns a router for the `/metrics` endpoint.
+	pub fn router(self) -> Router
+	{
+		Router::new()
+			.route("/metrics", routing::get(scrape))
+			.with_state(self)
+	}
+}
+
+/// Renders the current metrics, if the request came from localhost.
+async fn scrape(
+	State(metrics): State<Metrics>,
+	connect_info: Option<ConnectInfo<SocketAddr>>,
+) -> Response
+{
+	let is_local = connect_info.is_some_and(|ConnectInfo(addr)| addr.ip().is_loopback());
+
+	if !is_local {
+		return ProblemDetails::new(ProblemType::ResourceNotFound).into_response();
+	}
+
+	([(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE))], metrics.render())
+		.into_response()
+}
+
+/// Returns the label value for `method`.
+///
+/// Clients can send arbitrary extension methods, so anything that isn't a
+/// standard method is lumped together.
+fn method_label(method: &Method) -> &'static str
+{
+	match *method {
+		Method::GET => "GET",
+		Method::HEAD => "HEAD",
+		Method::POST => "POST",
+		Method::PUT => "PUT",
+		Method::PATCH => "PATCH",
+		Method::DELETE => "DELETE",
+		Method::OPTIONS => "OPTIONS",
+		Method::CONNECT => "CONNECT",
+		Method::TRACE => "TRACE",
+		_ => "other",
+	}
+}
+
+/// Escapes a label value according to the Prometheus text format.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_>
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		for char in self.0.chars() {
+			match char {
+				'\\' => f.write_str("\\\\")?,
+				'"' => f.write_str("\\\"")?,
+				'\n' => f.write_str("\\n")?,
+				char => f.write_char(char)?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use axum::body::Body;
+	use tower::ServiceExt;
+
+	use super::*;
+	use crate::events::Event;
+	use crate::middleware::MetricsLayer;
+	use crate::services::bans::UnbanReason;
+	use crate::testing::{self, ALPHAKEKS_ID};
+
+	/// Creates a registry around a pool that never actually connects.
+	fn metrics() -> Metrics
+	{
+		Metrics::new(testing::lazy_pool(), EventBus::new())
+	}
+
+	/// Scrapes `/metrics` as if the request came from `addr`.
+	async fn scrape_from(metrics: &Metrics, addr: [u8; 4]) -> color_eyre::Result<Response>
+	{
+		let mut req = http::Request::builder()
+			.uri("/metrics")
+			.body(Body::empty())?;
+
+		req.extensions_mut()
+			.insert(ConnectInfo(SocketAddr::from((addr, 3000))));
+
+		Ok(metrics.clone().router().oneshot(req).await?)
+	}
+
+	#[tokio::test]
+	async fn scrape_includes_requests_and_pool() -> color_eyre::Result<()>
+	{
+		let metrics = metrics();
+		let app = Router::new()
+			.route("/maps/:map", routing::get(|| async { "kz_checkmate" }))
+			.layer(MetricsLayer::new(metrics.clone()));
+
+		let req = http::Request::builder()
+			.uri("/maps/kz_checkmate")
+			.body(Body::empty())?;
+
+		app.oneshot(req).await?;
+
+		let res = scrape_from(&metrics, [127, 0, 0, 1]).await?;
+
+		testing::assert_eq!(res.status(), StatusCode::OK);
+
+		let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+		let body = String::from_utf8(body.to_vec())?;
+
+		testing::assert!(
+			body.contains(r#"http_requests_total{method="GET",route="/maps/:map",status="200"} 1"#)
+		);
+		testing::assert!(body.contains(
+			r#"http_request_duration_seconds_bucket{method="GET",route="/maps/:map",le="+Inf"} 1"#
+		));
+		testing::assert!(body
+			.contains(r#"http_request_duration_seconds_count{method="GET",route="/maps/:map"} 1"#));
+		testing::assert!(body.contains(r#"db_pool_connections{state="idle"} 0"#));
+		testing::assert!(body.contains(r#"db_pool_connections{state="active"} 0"#));
+
+		let max_connections = metrics.inner.database.options().get_max_connections();
+
+		testing::assert!(body.contains(&format!("db_pool_max_connections {max_connections}")));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn dropped_events_are_counted() -> color_eyre::Result<()>
+	{
+		let events = EventBus::with_capacity(1);
+		let metrics = Metrics::new(testing::lazy_pool(), events.clone());
+		let mut subscriber = events.subscribe();
+
+		for n in 1..=3 {
+			events.dispatch(Event::PlayerUnbanned {
+				ban_id: n.into(),
+				unban_id: n.into(),
+				player_id: ALPHAKEKS_ID,
+				admin_id: ALPHAKEKS_ID,
+				reason: UnbanReason::FalseBan,
+			});
+		}
+
+		testing::assert!(subscriber.try_recv().is_err());
+		testing::assert!(metrics.render().contains("events_dropped_total 2"));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn scrape_is_localhost_only() -> color_eyre::Result<()>
+	{
+		let res = scrape_from(&metrics(), [1, 1, 1, 1]).await?;
+
+		testing::assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn extension_methods_share_a_label() -> color_eyre::Result<()>
+	{
+		let metrics = metrics();
+		let elapsed = Duration::from_millis(1);
+
+		for method in ["FOO", "BAR1", "GET"] {
+			let method = Method::from_bytes(method.as_bytes())?;
+
+			metrics.record(&method, "/maps", StatusCode::OK, elapsed);
+		}
+
+		let body = metrics.render();
+
+		testing::assert!(
+			body.contains(r#"http_requests_total{method="other",route="/maps",status="200"} 2"#)
+		);
+		testing::assert!(
+			body.contains(r#"http_requests_total{method="GET",route="/maps",status="200"} 1"#)
+		);
+		testing::assert!(!body.contains("FOO"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn label_values_are_escaped() -> color_eyre::Result<()>
+	{
+		let escaped = Escaped("a \"quoted\"\\path\n").to_string();
+
+		testing::assert_eq!(escaped, r#"a \"quoted\"\\path\n"#);
+
+		Ok(())
+	}
+}
@@ -1,10 +1,33 @@
 //! This module contains configuration presets for [CORS] middleware.
 //!
+//! Which origins may use [the dashboard] endpoints is configured at startup
+//! via [`set_allowlist()`]; see [`CorsConfig`].
+//!
 //! [CORS]: https://developer.mozilla.org/en-US/docs/Web/HTTP/CORS
+//! [the dashboard]: https://github.com/KZGlobalTeam/cs2kz-api-dashboard
 
+use std::str::FromStr;
+use std::sync::OnceLock;
 use std::time::Duration;
 
-use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer, MaxAge};
+use http::{HeaderName, HeaderValue, Method};
+use thiserror::Error;
+use tower_http::cors::{AllowHeaders, AllowOrigin, CorsLayer, MaxAge};
+use url::Url;
+
+use crate::runtime::config::CorsConfig;
+
+/// The allowlist used by [`dashboard()`].
+static ALLOWLIST: OnceLock<Allowlist> = OnceLock::new();
+
+/// Sets the allowlist used by [`dashboard()`].
+///
+/// If this is never called, the default [`CorsConfig`] is used.
+#[doc(hidden)]
+pub(crate) fn set_allowlist(allowlist: Allowlist)
+{
+	assert!(ALLOWLIST.set(allowlist).is_ok(), "called `set_allowlist()` twice!");
+}
 
 /// Returns a permissive CORS middleware suitable for `GET` endpoints.
 pub fn permissive() -> CorsLayer
@@ -19,19 +42,199 @@ pub fn permissive() -> CorsLayer
 
 /// Returns a CORS middleware suitable for endpoints used by [the dashboard].
 ///
+/// `methods` are the methods supported by the route(s) this layer is applied
+/// to; any of them not allowed by the config are dropped.
+///
 /// [the dashboard]: https://github.com/KZGlobalTeam/cs2kz-api-dashboard
-pub fn dashboard(methods: impl Into<AllowMethods>) -> CorsLayer
+pub fn dashboard(methods: impl IntoIterator<Item = Method>) -> CorsLayer
 {
-	CorsLayer::new()
-		.allow_credentials(true)
-		.allow_headers([http::header::AUTHORIZATION, http::header::CONTENT_TYPE])
-		.allow_methods(methods)
-		.allow_origin(if cfg!(feature = "production") {
-			AllowOrigin::exact(http::HeaderValue::from_static("https://dashboard.cs2kz.org"))
-		} else {
-			AllowOrigin::predicate(is_localhost)
+	ALLOWLIST
+		.get_or_init(|| {
+			Allowlist::from_config(&CorsConfig::default()).expect("default config is valid")
 		})
-		.max_age(MaxAge::exact(Duration::MAX))
+		.layer(methods)
+}
+
+/// An allowed origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin
+{
+	/// Any origin (`*`).
+	Any,
+
+	/// A specific origin, like `https://dashboard.cs2kz.org`.
+	Exact(HeaderValue),
+}
+
+impl FromStr for Origin
+{
+	type Err = InvalidOrigin;
+
+	fn from_str(origin: &str) -> Result<Self, Self::Err>
+	{
+		if origin == "*" {
+			return Ok(Self::Any);
+		}
+
+		let invalid = |reason| InvalidOrigin { origin: origin.to_owned(), reason };
+		let url = Url::parse(origin).map_err(|_| invalid("not a valid URL"))?;
+
+		if !matches!(url.scheme(), "http" | "https") {
+			return Err(invalid("scheme must be `http` or `https`"));
+		}
+
+		if url.host().is_none() {
+			return Err(invalid("missing host"));
+		}
+
+		// `Url` normalizes an empty path to `/`, so we check the input instead.
+		let has_path = origin
+			.split_once("://")
+			.is_some_and(|(_, rest)| rest.contains(['/', '?', '#']));
+
+		if has_path || !url.username().is_empty() {
+			return Err(invalid("must only consist of scheme, host, and port"));
+		}
+
+		let value = HeaderValue::try_from(url.origin().ascii_serialization())
+			.map_err(|_| invalid("not a valid header value"))?;
+
+		Ok(Self::Exact(value))
+	}
+}
+
+/// Validated CORS settings for [`dashboard()`].
+#[derive(Debug, Clone)]
+pub struct Allowlist
+{
+	/// Allowed origins.
+	origins: Vec<Origin>,
+
+	/// Whether any `localhost` origin is allowed.
+	allow_localhost: bool,
+
+	/// Whether credentials are allowed.
+	allow_credentials: bool,
+
+	/// Allowed request headers.
+	allow_headers: Vec<HeaderName>,
+
+	/// Allowed methods.
+	allow_methods: Vec<Method>,
+}
+
+impl Allowlist
+{
+	/// Validates a [`CorsConfig`].
+	pub fn from_config(config: &CorsConfig) -> Result<Self, CorsConfigError>
+	{
+		let origins = config
+			.origins
+			.iter()
+			.map(|origin| origin.parse::<Origin>())
+			.collect::<Result<Vec<_>, _>>()?;
+
+		if config.allow_credentials && origins.contains(&Origin::Any) {
+			return Err(CorsConfigError::WildcardWithCredentials);
+		}
+
+		let allow_headers = config
+			.allow_headers
+			.iter()
+			.map(|header| {
+				header
+					.parse::<HeaderName>()
+					.map_err(|_| CorsConfigError::InvalidHeader(header.clone()))
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let allow_methods = config
+			.allow_methods
+			.iter()
+			.map(|method| {
+				method
+					.parse::<Method>()
+					.map_err(|_| CorsConfigError::InvalidMethod(method.clone()))
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(Self {
+			origins,
+			allow_localhost: config.allow_localhost,
+			allow_credentials: config.allow_credentials,
+			allow_headers,
+			allow_methods,
+		})
+	}
+
+	/// Creates a [`CorsLayer`] allowing `methods`, restricted by this
+	/// allowlist.
+	pub fn layer(&self, methods: impl IntoIterator<Item = Method>) -> CorsLayer
+	{
+		let methods = methods
+			.into_iter()
+			.filter(|method| self.allow_methods.contains(method))
+			.collect::<Vec<_>>();
+
+		let allow_origin = if self.origins.contains(&Origin::Any) {
+			AllowOrigin::any()
+		} else {
+			let allowlist = self.clone();
+
+			AllowOrigin::predicate(move |origin, req| allowlist.is_allowed(origin, req))
+		};
+
+		CorsLayer::new()
+			.allow_credentials(self.allow_credentials)
+			.allow_headers(self.allow_headers.clone())
+			.allow_methods(methods)
+			.allow_origin(allow_origin)
+			.max_age(MaxAge::exact(Duration::MAX))
+	}
+
+	/// Checks whether `origin` is on the allowlist.
+	fn is_allowed(&self, origin: &HeaderValue, req: &http::request::Parts) -> bool
+	{
+		let is_listed = self
+			.origins
+			.iter()
+			.any(|allowed| matches!(allowed, Origin::Exact(allowed) if allowed == origin));
+
+		is_listed || (self.allow_localhost && is_localhost(origin, req))
+	}
+}
+
+/// An invalid entry in `http.cors.origins`.
+#[derive(Debug, Error)]
+#[error("invalid CORS origin `{origin}`: {reason}")]
+pub struct InvalidOrigin
+{
+	/// The configured value.
+	pub origin: String,
+
+	/// Why it is invalid.
+	pub reason: &'static str,
+}
+
+/// Errors that can occur when validating a [`CorsConfig`].
+#[derive(Debug, Error)]
+pub enum CorsConfigError
+{
+	/// One of the origins is invalid.
+	#[error(transparent)]
+	InvalidOrigin(#[from] InvalidOrigin),
+
+	/// `*` was used as an origin while also allowing credentials.
+	#[error("the `*` origin cannot be combined with `allow-credentials`")]
+	WildcardWithCredentials,
+
+	/// One of the headers is not a valid header name.
+	#[error("invalid CORS header `{0}`")]
+	InvalidHeader(String),
+
+	/// One of the methods is not a valid HTTP method.
+	#[error("invalid CORS method `{0}`")]
+	InvalidMethod(String),
 }
 
 #[tracing::instrument(level = "trace", ret(level = "debug"), skip_all, fields(
@@ -66,3 +269,137 @@ fn is_localhost(origin: &http::HeaderValue, req: &http::request::Parts) -> bool
 	tracing::warn!("allowing sensitive request from localhost");
 	true
 }
+
+#[cfg(test)]
+mod tests
+{
+	use axum::body::Body;
+	use axum::{routing, Router};
+	use http::header;
+	use tower::ServiceExt;
+
+	use super::*;
+	use crate::testing;
+
+	/// Returns a router guarded by `allowlist`.
+	fn router(allowlist: &Allowlist) -> Router
+	{
+		Router::new()
+			.route("/", routing::get(|| async {}))
+			.route_layer(allowlist.layer([Method::OPTIONS, Method::GET, Method::DELETE]))
+	}
+
+	/// Sends a request from `origin` and returns the
+	/// `Access-Control-Allow-Origin` header of the response, if any.
+	async fn allowed_origin(
+		allowlist: &Allowlist,
+		origin: &str,
+	) -> color_eyre::Result<Option<HeaderValue>>
+	{
+		let req = http::Request::builder()
+			.uri("/")
+			.header(header::ORIGIN, origin)
+			.body(Body::empty())?;
+
+		let res = router(allowlist).oneshot(req).await?;
+
+		Ok(res
+			.headers()
+			.get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+			.cloned())
+	}
+
+	/// Creates an [`Allowlist`] for the given origins.
+	fn allowlist(origins: &[&str], allow_credentials: bool) -> Result<Allowlist, CorsConfigError>
+	{
+		Allowlist::from_config(&CorsConfig {
+			origins: origins.iter().map(|&origin| origin.to_owned()).collect(),
+			allow_localhost: false,
+			allow_credentials,
+			..Default::default()
+		})
+	}
+
+	#[tokio::test]
+	async fn only_allowlisted_origins_are_reflected() -> color_eyre::Result<()>
+	{
+		let allowlist =
+			allowlist(&["https://dashboard.cs2kz.org", "https://staging.cs2kz.org:8443"], true)?;
+
+		testing::assert_eq!(
+			allowed_origin(&allowlist, "https://dashboard.cs2kz.org").await?,
+			Some(HeaderValue::from_static("https://dashboard.cs2kz.org"))
+		);
+
+		testing::assert_eq!(
+			allowed_origin(&allowlist, "https://staging.cs2kz.org:8443").await?,
+			Some(HeaderValue::from_static("https://staging.cs2kz.org:8443"))
+		);
+
+		testing::assert!(allowed_origin(&allowlist, "https://evil.example.com")
+			.await?
+			.is_none());
+
+		testing::assert!(allowed_origin(&allowlist, "http://localhost:3000")
+			.await?
+			.is_none());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn wildcard_without_credentials() -> color_eyre::Result<()>
+	{
+		let allowlist = allowlist(&["*"], false)?;
+
+		testing::assert_eq!(
+			allowed_origin(&allowlist, "https://anything.example.com").await?,
+			Some(HeaderValue::from_static("*"))
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn invalid_configs_are_rejected() -> color_eyre::Result<()>
+	{
+		testing::assert_matches!(
+			allowlist(&["*"], true),
+			Err(CorsConfigError::WildcardWithCredentials)
+		);
+
+		for origin in ["dashboard.cs2kz.org", "ftp://cs2kz.org", "https://cs2kz.org/dashboard"] {
+			let result = allowlist(&[origin], true);
+
+			testing::assert!(
+				matches!(result, Err(CorsConfigError::InvalidOrigin(InvalidOrigin { .. }))),
+				"{origin}"
+			);
+		}
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn methods_are_restricted_by_config() -> color_eyre::Result<()>
+	{
+		let allowlist = Allowlist::from_config(&CorsConfig {
+			origins: vec![String::from("https://dashboard.cs2kz.org")],
+			allow_methods: vec![String::from("OPTIONS"), String::from("GET")],
+			..Default::default()
+		})?;
+
+		let req = http::Request::builder()
+			.method(Method::OPTIONS)
+			.uri("/")
+			.header(header::ORIGIN, "https://dashboard.cs2kz.org")
+			.header(header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE")
+			.body(Body::empty())?;
+
+		let res = router(&allowlist).oneshot(req).await?;
+
+		testing::assert_eq!(res.headers()[header::ACCESS_CONTROL_ALLOW_METHODS], "OPTIONS,GET");
+
+		Ok(())
+	}
+}
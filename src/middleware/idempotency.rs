@@ -0,0 +1,758 @@
+//! A middleware for making non-idempotent requests safe to retry.
+//!
+//! Clients can send an `Idempotency-Key` header with a unique value (e.g. a
+//! UUID) along with their request. The first request with a given key is
+//! executed as usual, and its response is stored in the database. Any
+//! following requests with the same key are not executed again, but instead
+//! receive the stored response.
+//!
+//! Keys are scoped to the caller that sent them (a user or an API key), so
+//! different callers can use the same key without ever seeing each other's
+//! responses. Keys are forgotten after 24 hours. Reusing a key for a different
+//! request (different route or body) is an error, as is sending a request
+//! while another one with the same key is still in progress. Responses with a
+//! `5xx` status are not stored, so the request can be retried.
+//!
+//! Routes whose responses contain secrets should use
+//! [`IdempotencyLayer::without_response_bodies()`]. The bodies of their
+//! successful responses are not stored, so instead of a replay, repeated
+//! requests are rejected with a hint on how to get the secret some other way.
+//! `Set-Cookie` headers are never stored.
+
+use std::convert::Infallible;
+use std::task::{self, Poll};
+use std::time::Duration;
+
+use axum::body::{Body, Bytes};
+use axum::extract::Request;
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::CookieJar;
+use futures::future::BoxFuture;
+use http::{header, HeaderName, HeaderValue, StatusCode};
+use sqlx::types::Json;
+use sqlx::{MySql, Pool};
+use thiserror::Error;
+
+use crate::http::problem_details::{IntoProblemDetails, ProblemType};
+use crate::http::ProblemDetails;
+use crate::services::auth::{session, ApiKey, Session};
+
+/// The header clients use to specify their idempotency key.
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// The header we attach to responses that were replayed from the database.
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// How long we remember keys.
+const TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// How long a request may be in progress before we consider it abandoned.
+///
+/// This is only relevant if the request was aborted without us getting the
+/// chance to clean up, e.g. because it timed out.
+const ABANDONED_AFTER: Duration = Duration::from_secs(60 * 10);
+
+/// The maximum size of request bodies we buffer for hashing.
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// The maximum length of an idempotency key.
+const MAX_KEY_LENGTH: usize = 255;
+
+/// A layer producing the [`Idempotency`] middleware.
+#[derive(Clone)]
+pub struct IdempotencyLayer
+{
+	/// Where we store responses.
+	database: Pool<MySql>,
+
+	/// If response bodies contain secrets, how to obtain them without a replay.
+	replay_hint: Option<&'static str>,
+}
+
+impl IdempotencyLayer
+{
+	/// Creates a new [`IdempotencyLayer`].
+	pub fn new(database: Pool<MySql>) -> Self
+	{
+		Self { database, replay_hint: None }
+	}
+
+	/// Does not store the bodies of successful responses.
+	///
+	/// Use this for routes that return secrets (such as API keys), which
+	/// should not sit in the database in plain text. Instead of replaying such
+	/// a response, repeated requests get a `409` with `hint` as the detail,
+	/// which should tell the caller how to obtain the secret some other way.
+	pub fn without_response_bodies(mut self, hint: &'static str) -> Self
+	{
+		self.replay_hint = Some(hint);
+		self
+	}
+}
+
+impl<S> tower::Layer<S> for IdempotencyLayer
+{
+	type Service = Idempotency<S>;
+
+	fn layer(&self, inner: S) -> Self::Service
+	{
+		Idempotency { database: self.database.clone(), replay_hint: self.replay_hint, inner }
+	}
+}
+
+/// A middleware that replays stored responses for repeated idempotency keys.
+///
+/// You can create an instance of this service using [`IdempotencyLayer`].
+#[derive(Clone)]
+pub struct Idempotency<S>
+{
+	/// Where we store responses.
+	database: Pool<MySql>,
+
+	/// If response bodies contain secrets, how to obtain them without a replay.
+	replay_hint: Option<&'static str>,
+
+	/// The inner service.
+	inner: S,
+}
+
+impl<S> tower::Service<Request> for Idempotency<S>
+where
+	S: tower::Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+	S::Future: Send,
+{
+	type Response = Response;
+	type Error = Infallible;
+	type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>>
+	{
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, req: Request) -> Self::Future
+	{
+		let database = self.database.clone();
+		let replay_hint = self.replay_hint;
+		let inner = self.inner.clone();
+
+		Box::pin(async move {
+			Ok(svc_impl(database, replay_hint, inner, req)
+				.await
+				.unwrap_or_else(IntoResponse::into_response))
+		})
+	}
+}
+
+/// The errors that can occur in the [`Idempotency`] middleware.
+#[derive(Debug, Error)]
+enum IdempotencyError
+{
+	/// The `Idempotency-Key` header had an invalid value.
+	#[error("`Idempotency-Key` must be 1-{MAX_KEY_LENGTH} visible ASCII characters")]
+	InvalidKey,
+
+	/// The key was already used for a different request.
+	#[error("this `Idempotency-Key` was already used for a different request")]
+	KeyReused,
+
+	/// The key is currently used by another request.
+	#[error("a request with this `Idempotency-Key` is still being processed")]
+	KeyInUse,
+
+	/// The stored response contained secrets, so it can't be replayed.
+	#[error("this request was already processed; {hint}")]
+	NotReplayable
+	{
+		/// How to obtain the secrets some other way.
+		hint: &'static str,
+
+		/// The `Location` header of the original response, if any.
+		location: Option<HeaderValue>,
+	},
+
+	/// We failed to buffer the request body.
+	#[error("failed to read request body")]
+	ReadRequestBody(#[source] axum::Error),
+
+	/// We failed to buffer the response body.
+	#[error("something went wrong")]
+	ReadResponseBody(#[source] axum::Error),
+
+	/// Something went wrong communicating with the database.
+	#[error("something went wrong")]
+	Database(#[from] sqlx::Error),
+}
+
+impl IntoProblemDetails for IdempotencyError
+{
+	fn problem_type(&self) -> ProblemType
+	{
+		match self {
+			Self::InvalidKey | Self::KeyReused => ProblemType::InvalidHeader,
+			Self::KeyInUse => ProblemType::IdempotencyKeyInUse,
+			Self::NotReplayable { .. } => ProblemType::ResourceAlreadyExists,
+			Self::ReadRequestBody(_) => ProblemType::InvalidRequestBody,
+			Self::ReadResponseBody(_) => ProblemType::Internal,
+			Self::Database(source) => source.problem_type(),
+		}
+	}
+}
+
+impl IntoResponse for IdempotencyError
+{
+	fn into_response(self) -> Response
+	{
+		let location = match self {
+			Self::NotReplayable { ref location, .. } => location.clone(),
+			_ => None,
+		};
+
+		let mut response = ProblemDetails::from(self).into_response();
+
+		if let Some(location) = location {
+			response.headers_mut().insert(header::LOCATION, location);
+		}
+
+		response
+	}
+}
+
+/// A response as it is stored in the database.
+#[allow(clippy::missing_docs_in_private_items)]
+struct StoredResponse
+{
+	request_hash: Vec<u8>,
+	response_status: Option<u16>,
+	response_headers: Option<Json<Vec<(String, String)>>>,
+	response_body: Option<Vec<u8>>,
+}
+
+/// The relevant implementation of `<Idempotency as tower::Service>::call()`.
+#[tracing::instrument(level = "debug", skip_all, err(Debug, level = "debug"))]
+async fn svc_impl<S>(
+	database: Pool<MySql>,
+	replay_hint: Option<&'static str>,
+	mut inner: S,
+	req: Request,
+) -> Result<Response, IdempotencyError>
+where
+	S: tower::Service<Request, Response = Response, Error = Infallible>,
+{
+	let Some(key) = req.headers().get(IDEMPOTENCY_KEY) else {
+		return Ok(call(&mut inner, req).await);
+	};
+
+	let key = parse_key(key)?;
+	let (parts, body) = req.into_parts();
+	let body = axum::body::to_bytes(body, MAX_BODY_SIZE)
+		.await
+		.map_err(IdempotencyError::ReadRequestBody)?;
+
+	let caller = caller_id(&parts);
+	let request_hash = request_hash(&parts, &body);
+
+	// Expired keys are purged here rather than by a background task; the index
+	// on `created_on` keeps this cheap.
+	sqlx::query! {
+		r"
+		DELETE FROM
+		  IdempotencyKeys
+		WHERE
+		  created_on < NOW() - INTERVAL ? SECOND
+		  OR (
+		    caller = ?
+		    AND `key` = ?
+		    AND response_status IS NULL
+		    AND created_on < NOW() - INTERVAL ? SECOND
+		  )
+		",
+		TTL.as_secs(),
+		&caller[..],
+		key,
+		ABANDONED_AFTER.as_secs(),
+	}
+	.execute(&database)
+	.await?;
+
+	let claimed = sqlx::query! {
+		r"
+		INSERT IGNORE INTO
+		  IdempotencyKeys (caller, `key`, request_hash)
+		VALUES
+		  (?, ?, ?)
+		",
+		&caller[..],
+		key,
+		&request_hash[..],
+	}
+	.execute(&database)
+	.await?
+	.rows_affected()
+	.eq(&1);
+
+	if !claimed {
+		return replay(&database, replay_hint, &caller, &key, &request_hash).await;
+	}
+
+	let req = Request::from_parts(parts, Body::from(body));
+	let response = call(&mut inner, req).await;
+
+	if response.status().is_server_error() {
+		sqlx::query! {
+			r"
+			DELETE FROM
+			  IdempotencyKeys
+			WHERE
+			  caller = ?
+			  AND `key` = ?
+			",
+			&caller[..],
+			key,
+		}
+		.execute(&database)
+		.await?;
+
+		return Ok(response);
+	}
+
+	let (parts, body) = response.into_parts();
+	let body = axum::body::to_bytes(body, usize::MAX)
+		.await
+		.map_err(IdempotencyError::ReadResponseBody)?;
+
+	let headers = parts
+		.headers
+		.iter()
+		.filter(|&(name, _)| name != header::SET_COOKIE)
+		.filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+		.collect::<Vec<_>>();
+
+	let stored = sqlx::query! {
+		r"
+		UPDATE
+		  IdempotencyKeys
+		SET
+		  response_status = ?,
+		  response_headers = ?,
+		  response_body = ?
+		WHERE
+		  caller = ?
+		  AND `key` = ?
+		",
+		parts.status.as_u16(),
+		Json(headers),
+		(replay_hint.is_none() || !parts.status.is_success()).then_some(&body[..]),
+		&caller[..],
+		key,
+	}
+	.execute(&database)
+	.await;
+
+	// The request has already been executed at this point, so the client
+	// should get its response either way.
+	if let Err(error) = stored {
+		tracing::error!(%error, key, "failed to store response for idempotency key");
+	}
+
+	Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Calls the inner service.
+async fn call<S>(inner: &mut S, req: Request) -> Response
+where
+	S: tower::Service<Request, Response = Response, Error = Infallible>,
+{
+	match inner.call(req).await {
+		Ok(response) => response,
+		Err(never) => match never {},
+	}
+}
+
+/// Validates the value of an `Idempotency-Key` header.
+fn parse_key(value: &HeaderValue) -> Result<String, IdempotencyError>
+{
+	let key = value.to_str().map_err(|_| IdempotencyError::InvalidKey)?;
+	let is_valid = !key.is_empty()
+		&& key.len() <= MAX_KEY_LENGTH
+		&& key.bytes().all(|byte| byte.is_ascii_graphic());
+
+	if !is_valid {
+		return Err(IdempotencyError::InvalidKey);
+	}
+
+	Ok(key.to_owned())
+}
+
+/// Identifies whoever sent a request.
+///
+/// Authenticated callers are identified by their SteamID or API key, so a
+/// user keeps the same scope across sessions. Anyone else is identified by the
+/// credentials they sent, if any.
+fn caller_id(parts: &http::request::Parts) -> [u8; 16]
+{
+	if let Some(session) = parts.extensions.get::<Session>() {
+		let steam_id = session.user().steam_id().to_string();
+
+		return hash(&[b"user", steam_id.as_bytes()]);
+	}
+
+	if let Some(api_key) = parts.extensions.get::<ApiKey>() {
+		let api_key = api_key.to_string();
+
+		return hash(&[b"api-key", api_key.as_bytes()]);
+	}
+
+	let session_id = CookieJar::from_headers(&parts.headers)
+		.get(session::COOKIE_NAME)
+		.map(|cookie| cookie.value().to_owned());
+
+	let authorization = parts
+		.headers
+		.get(header::AUTHORIZATION)
+		.map(HeaderValue::as_bytes);
+
+	hash(&[
+		b"anonymous",
+		authorization.unwrap_or_default(),
+		session_id.as_deref().unwrap_or_default().as_bytes(),
+	])
+}
+
+/// Hashes everything that identifies a request.
+fn request_hash(parts: &http::request::Parts, body: &Bytes) -> [u8; 16]
+{
+	let uri = parts.uri.to_string();
+
+	hash(&[parts.method.as_str().as_bytes(), uri.as_bytes(), body])
+}
+
+/// Hashes `fields`, prefixing each with its length so they can't run into
+/// each other.
+fn hash(fields: &[&[u8]]) -> [u8; 16]
+{
+	let mut hasher = md5::Context::new();
+
+	for field in fields {
+		hasher.consume((field.len() as u64).to_le_bytes());
+		hasher.consume(field);
+	}
+
+	hasher.compute().0
+}
+
+/// Replays the response stored for `key`.
+async fn replay(
+	database: &Pool<MySql>,
+	replay_hint: Option<&'static str>,
+	caller: &[u8; 16],
+	key: &str,
+	request_hash: &[u8; 16],
+) -> Result<Response, IdempotencyError>
+{
+	let stored = sqlx::query_as! {
+		StoredResponse,
+		r"
+		SELECT
+		  request_hash,
+		  response_status `response_status: u16`,
+		  response_headers `response_headers: Json<Vec<(String, String)>>`,
+		  response_body
+		FROM
+		  IdempotencyKeys
+		WHERE
+		  caller = ?
+		  AND `key` = ?
+		",
+		&caller[..],
+		key,
+	}
+	.fetch_optional(database)
+	.await?
+	.ok_or(IdempotencyError::KeyInUse)?;
+
+	if stored.request_hash != request_hash {
+		return Err(IdempotencyError::KeyReused);
+	}
+
+	let Some(status) = stored
+		.response_status
+		.and_then(|status| StatusCode::from_u16(status).ok())
+	else {
+		return Err(IdempotencyError::KeyInUse);
+	};
+
+	let headers = stored
+		.response_headers
+		.map(|Json(headers)| headers)
+		.unwrap_or_default();
+
+	let Some(body) = stored.response_body else {
+		let location = headers
+			.iter()
+			.find(|(name, _)| name.eq_ignore_ascii_case(header::LOCATION.as_str()))
+			.and_then(|(_, value)| value.parse::<HeaderValue>().ok());
+
+		return Err(IdempotencyError::NotReplayable {
+			hint: replay_hint.unwrap_or("send a new request with a different key"),
+			location,
+		});
+	};
+
+	tracing::debug!(key, %status, "replaying stored response");
+
+	let mut response = Response::new(Body::from(body));
+	*response.status_mut() = status;
+
+	for (name, value) in headers {
+		if let (Ok(name), Ok(value)) = (name.parse::<HeaderName>(), value.parse::<HeaderValue>()) {
+			response.headers_mut().append(name, value);
+		}
+	}
+
+	response
+		.headers_mut()
+		.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+
+	Ok(response)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::sync::atomic::{self, AtomicUsize};
+	use std::sync::Arc;
+
+	use axum::routing;
+	use sqlx::{MySql, Pool};
+	use tower::ServiceExt;
+
+	use super::*;
+	use crate::services::auth::session::user::Permissions;
+	use crate::services::auth::session::SessionID;
+	use crate::testing::{self, ALPHAKEKS_ID};
+
+	/// Sends a request with the testing session to the server routes.
+	async fn send(
+		database: &Pool<MySql>,
+		req: http::request::Builder,
+		body: Body,
+	) -> color_eyre::Result<(StatusCode, http::HeaderMap, Bytes)>
+	{
+		let router = axum::Router::from(testing::server_svc(database.clone()));
+		let req = req
+			.header(header::CONTENT_TYPE, "application/json")
+			.header(header::COOKIE, format!("{}={}", session::COOKIE_NAME, SessionID::TESTING))
+			.body(body)?;
+
+		let (parts, body) = router.oneshot(req).await?.into_parts();
+		let body = axum::body::to_bytes(body, usize::MAX).await?;
+
+		Ok((parts.status, parts.headers, body))
+	}
+
+	/// Registers a server through the HTTP API.
+	async fn register_server(
+		database: &Pool<MySql>,
+		idempotency_key: &str,
+		name: &str,
+	) -> color_eyre::Result<(StatusCode, http::HeaderMap, Bytes)>
+	{
+		let body = serde_json::json!({
+			"name": name,
+			"host": "127.0.0.1",
+			"port": 27015,
+			"owner_id": ALPHAKEKS_ID,
+		});
+
+		let req = http::Request::post("/").header(IDEMPOTENCY_KEY, idempotency_key);
+
+		send(database, req, Body::from(body.to_string())).await
+	}
+
+	/// Gives the testing user permission to register servers.
+	async fn allow_registering_servers(database: &Pool<MySql>) -> color_eyre::Result<()>
+	{
+		sqlx::query("UPDATE Players SET permissions = ? WHERE id = ?")
+			.bind(Permissions::ALL)
+			.bind(ALPHAKEKS_ID)
+			.execute(database)
+			.await?;
+
+		Ok(())
+	}
+
+	/// Counts the servers with the given name.
+	async fn count_servers(database: &Pool<MySql>, name: &str) -> color_eyre::Result<i64>
+	{
+		Ok(sqlx::query_scalar("SELECT COUNT(*) FROM Servers WHERE name = ?")
+			.bind(name)
+			.fetch_one(database)
+			.await?)
+	}
+
+	#[test]
+	fn validates_keys() -> color_eyre::Result<()>
+	{
+		let valid = parse_key(&HeaderValue::from_static("8a3b6b4e-8d0c-4a8e-b0c4-3f0b0c2f1e6d"));
+		let empty = parse_key(&HeaderValue::from_static(""));
+		let spaces = parse_key(&HeaderValue::from_static("foo bar"));
+		let too_long = parse_key(&HeaderValue::from_str(&"a".repeat(MAX_KEY_LENGTH + 1))?);
+
+		testing::assert!(valid.is_ok());
+		testing::assert_matches!(empty, Err(IdempotencyError::InvalidKey));
+		testing::assert_matches!(spaces, Err(IdempotencyError::InvalidKey));
+		testing::assert_matches!(too_long, Err(IdempotencyError::InvalidKey));
+
+		Ok(())
+	}
+
+	#[test]
+	fn keys_are_scoped_per_caller() -> color_eyre::Result<()>
+	{
+		let parts = |token: &str| -> color_eyre::Result<http::request::Parts> {
+			let (parts, ()) = http::Request::post("/")
+				.header(header::AUTHORIZATION, format!("Bearer {token}"))
+				.body(())?
+				.into_parts();
+
+			Ok(parts)
+		};
+
+		let alice = caller_id(&parts("alice")?);
+		let bob = caller_id(&parts("bob")?);
+
+		testing::assert_eq!(alice, caller_id(&parts("alice")?));
+		testing::assert_ne!(alice, bob);
+
+		let mut with_key = parts("alice")?;
+		with_key.extensions.insert(ApiKey::new());
+
+		testing::assert_ne!(caller_id(&with_key), alice);
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn replays_repeated_requests(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let calls = Arc::new(AtomicUsize::new(0));
+		let router = axum::Router::new()
+			.route(
+				"/",
+				routing::post({
+					let calls = Arc::clone(&calls);
+
+					move || async move {
+						let call = calls.fetch_add(1, atomic::Ordering::SeqCst);
+						(StatusCode::CREATED, format!("call #{call}"))
+					}
+				}),
+			)
+			.layer(IdempotencyLayer::new(database));
+
+		let req = || {
+			http::Request::post("/")
+				.header(header::AUTHORIZATION, "Bearer replay")
+				.header(IDEMPOTENCY_KEY, "replay-me")
+				.body(Body::empty())
+		};
+
+		let first = router.clone().oneshot(req()?).await?;
+		let second = router.oneshot(req()?).await?;
+
+		testing::assert_eq!(first.status(), StatusCode::CREATED);
+		testing::assert_eq!(second.status(), StatusCode::CREATED);
+		testing::assert!(first.headers().get(IDEMPOTENT_REPLAYED).is_none());
+		testing::assert!(second.headers().get(IDEMPOTENT_REPLAYED).is_some());
+
+		let first_body = axum::body::to_bytes(first.into_body(), usize::MAX).await?;
+		let second_body = axum::body::to_bytes(second.into_body(), usize::MAX).await?;
+
+		testing::assert_eq!(first_body, second_body);
+		testing::assert_eq!(calls.load(atomic::Ordering::SeqCst), 1);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../database/fixtures/session.sql")
+	)]
+	async fn retried_registrations_are_usable(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		allow_registering_servers(&database).await?;
+
+		let (first_status, first_headers, first_body) =
+			register_server(&database, "register-once", "idempotent server").await?;
+
+		let (second_status, second_headers, second_body) =
+			register_server(&database, "register-once", "idempotent server").await?;
+
+		testing::assert_eq!(first_status, StatusCode::CREATED);
+		testing::assert!(!first_body.is_empty());
+
+		// The response contains an API key, so it is not replayed.
+		testing::assert_eq!(second_status, StatusCode::CONFLICT);
+		testing::assert!(String::from_utf8_lossy(&second_body).contains("PUT /servers/{id}/key"));
+
+		let location = second_headers
+			.get(header::LOCATION)
+			.ok_or_else(|| color_eyre::eyre::eyre!("missing `Location` header"))?
+			.to_str()?
+			.to_owned();
+
+		testing::assert_eq!(
+			first_headers.get(header::LOCATION),
+			second_headers.get(header::LOCATION)
+		);
+
+		let servers = count_servers(&database, "idempotent server").await?;
+
+		testing::assert_eq!(servers, 1);
+
+		let stored_body = sqlx::query_scalar!(
+			"SELECT response_body FROM IdempotencyKeys WHERE `key` = 'register-once'"
+		)
+		.fetch_one(&database)
+		.await?;
+
+		testing::assert!(stored_body.is_none());
+
+		// Following the hint gets the caller a usable key.
+		let server_path = location.trim_start_matches("/servers");
+		let req = http::Request::put(format!("{server_path}/key"));
+		let (reset_status, _, reset_body) = send(&database, req, Body::empty()).await?;
+
+		testing::assert_eq!(reset_status, StatusCode::CREATED);
+
+		let reset = serde_json::from_slice::<serde_json::Value>(&reset_body)?;
+
+		testing::assert!(reset["key"]
+			.as_str()
+			.is_some_and(|key| key.parse::<ApiKey>().is_ok()));
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../database/fixtures/session.sql")
+	)]
+	async fn rejects_reused_keys(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		allow_registering_servers(&database).await?;
+
+		let (first_status, ..) = register_server(&database, "reused", "first server").await?;
+		let (second_status, ..) = register_server(&database, "reused", "second server").await?;
+
+		testing::assert_eq!(first_status, StatusCode::CREATED);
+		testing::assert_eq!(second_status, StatusCode::UNPROCESSABLE_ENTITY);
+
+		let servers = count_servers(&database, "second server").await?;
+
+		testing::assert_eq!(servers, 0);
+
+		Ok(())
+	}
+}
@@ -19,12 +19,13 @@ use std::time::Duration;
 use axum::extract::{ConnectInfo, Request};
 use axum::response::Response;
 use tower_http::classify::ServerErrorsFailureClass;
-use uuid::Uuid;
+
+use super::request_id::RequestId;
 
 /// Creates a middleware that will log incoming HTTP requests.
 ///
-/// It will attach a unique ID to every tracing span and log metadata such as
-/// the request head, and response status.
+/// It will attach the request's [`RequestId`] to every tracing span and log
+/// metadata such as the request head, and response status.
 macro_rules! layer {
 	() => {
 		tower_http::trace::TraceLayer::new_for_http()
@@ -44,10 +45,16 @@ pub(crate) fn make_span(request: &Request) -> tracing::Span
 		Some(ConnectInfo(addr)) => addr.to_string(),
 	};
 
+	let id = request
+		.extensions()
+		.get::<RequestId>()
+		.cloned()
+		.unwrap_or_else(RequestId::generate);
+
 	tracing::info_span! {
 		target: "cs2kz_api::http",
 		"request",
-		request.id = %Uuid::now_v7(),
+		request.id = %id,
 		request.ip = %ip,
 		request.method = %request.method(),
 		request.uri = %request.uri(),
@@ -0,0 +1,98 @@
+//! A middleware for recording request metrics.
+//!
+//! Every request that passes through [`RecordMetrics`] is counted in the
+//! [`Metrics`] registry under its route pattern, along with the response
+//! status and how long it took to produce the response.
+
+use std::convert::Infallible;
+use std::task::{self, Poll};
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request};
+use axum::response::Response;
+use futures::future::BoxFuture;
+
+use crate::metrics::Metrics;
+
+/// The route label used for requests that did not match any route.
+///
+/// Using the raw path here would let clients create arbitrarily many label
+/// values.
+const UNMATCHED_ROUTE: &str = "<unmatched>";
+
+/// A layer producing the [`RecordMetrics`] middleware.
+#[derive(Debug, Clone)]
+pub struct MetricsLayer
+{
+	/// The registry to record metrics into.
+	metrics: Metrics,
+}
+
+impl MetricsLayer
+{
+	/// Creates a new [`MetricsLayer`].
+	pub fn new(metrics: Metrics) -> Self
+	{
+		Self { metrics }
+	}
+}
+
+impl<S> tower::Layer<S> for MetricsLayer
+{
+	type Service = RecordMetrics<S>;
+
+	fn layer(&self, inner: S) -> Self::Service
+	{
+		RecordMetrics { metrics: self.metrics.clone(), inner }
+	}
+}
+
+/// A middleware that records request counts and latencies.
+///
+/// You can create an instance of this service using [`MetricsLayer`].
+#[derive(Debug, Clone)]
+pub struct RecordMetrics<S>
+{
+	/// The registry to record metrics into.
+	metrics: Metrics,
+
+	/// The inner service.
+	inner: S,
+}
+
+impl<S> tower::Service<Request> for RecordMetrics<S>
+where
+	S: tower::Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+	S::Future: Send,
+{
+	type Response = Response;
+	type Error = Infallible;
+	type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>>
+	{
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, req: Request) -> Self::Future
+	{
+		let metrics = self.metrics.clone();
+		let method = req.method().clone();
+		let route = req
+			.extensions()
+			.get::<MatchedPath>()
+			.map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+			.to_owned();
+
+		let started = Instant::now();
+		let response = self.inner.call(req);
+
+		Box::pin(async move {
+			let response = response.await?;
+
+			metrics.record(&method, &route, response.status(), started.elapsed());
+
+			Ok(response)
+		})
+	}
+}
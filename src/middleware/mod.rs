@@ -12,3 +12,34 @@ pub(crate) mod cors;
 
 pub mod infallible;
 pub use infallible::InfallibleLayer;
+
+pub mod idempotency;
+pub use idempotency::IdempotencyLayer;
+
+pub mod metrics;
+pub use metrics::MetricsLayer;
+
+pub mod problem_format;
+pub use problem_format::ProblemFormatLayer;
+
+pub mod rate_limit;
+pub use rate_limit::RateLimitLayer;
+
+pub mod request_id;
+pub use request_id::RequestIdLayer;
+
+pub mod request_timeout;
+pub use request_timeout::RequestTimeoutLayer;
+
+/// Returns the route group a request path belongs to.
+///
+/// This is the first segment of the path, e.g. `records` for `/records/1`.
+/// Route groups are used by middleware that can be configured differently for
+/// different parts of the API.
+pub(crate) fn route_group(path: &str) -> &str
+{
+	path.trim_start_matches('/')
+		.split('/')
+		.next()
+		.unwrap_or_default()
+}
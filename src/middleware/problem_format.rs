@@ -0,0 +1,145 @@
+//! A middleware for negotiating the format of [`ProblemDetails`] responses.
+//!
+//! The format is picked from the request's `Accept` header (see
+//! [`Format::from_accept()`]) and applies to every [`ProblemDetails`] turned
+//! into a response while the request is being handled, including ones produced
+//! by other middleware further down the stack.
+//!
+//! [`ProblemDetails`]: crate::http::ProblemDetails
+
+use std::convert::Infallible;
+use std::task::{self, Poll};
+
+use axum::extract::Request;
+use axum::response::Response;
+use futures::future::BoxFuture;
+
+use crate::http::problem_details::Format;
+
+/// A layer producing the [`ProblemFormat`] middleware.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemFormatLayer;
+
+impl ProblemFormatLayer
+{
+	/// Creates a new [`ProblemFormatLayer`].
+	pub fn new() -> Self
+	{
+		Self
+	}
+}
+
+impl<S> tower::Layer<S> for ProblemFormatLayer
+{
+	type Service = ProblemFormat<S>;
+
+	fn layer(&self, inner: S) -> Self::Service
+	{
+		ProblemFormat { inner }
+	}
+}
+
+/// A middleware that makes the negotiated format available through
+/// [`Format::current()`].
+///
+/// You can create an instance of this service using [`ProblemFormatLayer`].
+#[derive(Debug, Clone)]
+pub struct ProblemFormat<S>
+{
+	/// The inner service.
+	inner: S,
+}
+
+impl<S> tower::Service<Request> for ProblemFormat<S>
+where
+	S: tower::Service<Request, Response = Response, Error = Infallible> + Send + 'static,
+	S::Future: Send,
+{
+	type Response = Response;
+	type Error = Infallible;
+	type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>>
+	{
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, req: Request) -> Self::Future
+	{
+		let format = Format::from_accept(req.headers());
+
+		Box::pin(format.scope(self.inner.call(req)))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use axum::body::Body;
+	use axum::{routing, Router};
+	use tower::ServiceExt;
+
+	use super::*;
+	use crate::http::problem_details::ProblemType;
+	use crate::http::ProblemDetails;
+	use crate::testing;
+
+	/// Returns a router that fails every request.
+	fn router() -> Router
+	{
+		Router::new()
+			.route(
+				"/",
+				routing::get(|| async { ProblemDetails::new(ProblemType::ResourceNotFound) }),
+			)
+			.layer(ProblemFormatLayer::new())
+	}
+
+	/// Sends a request with the given `Accept` header and returns the
+	/// response's `Content-Type` and body.
+	async fn send(accept: Option<&str>) -> color_eyre::Result<(String, String)>
+	{
+		let mut req = http::Request::get("/");
+
+		if let Some(accept) = accept {
+			req = req.header(http::header::ACCEPT, accept);
+		}
+
+		let res = router().oneshot(req.body(Body::empty())?).await?;
+
+		testing::assert_eq!(res.status(), http::StatusCode::NOT_FOUND);
+
+		let content_type = res.headers()[http::header::CONTENT_TYPE]
+			.to_str()?
+			.to_owned();
+		let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+
+		Ok((content_type, String::from_utf8(body.to_vec())?))
+	}
+
+	#[tokio::test]
+	async fn xml_is_returned_if_requested() -> color_eyre::Result<()>
+	{
+		let (content_type, body) = send(Some("application/problem+xml")).await?;
+
+		testing::assert_eq!(content_type, "application/problem+xml");
+		testing::assert!(body.contains(r#"<problem xmlns="urn:ietf:rfc:7807">"#));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn json_is_the_default() -> color_eyre::Result<()>
+	{
+		let (content_type, body) = send(None).await?;
+
+		testing::assert_eq!(content_type, "application/problem+json");
+		testing::assert!(serde_json::from_str::<serde_json::Value>(&body).is_ok());
+
+		let (content_type, _) = send(Some("application/json;q=0.9, application/xml;q=0.5")).await?;
+
+		testing::assert_eq!(content_type, "application/problem+json");
+
+		Ok(())
+	}
+}
@@ -0,0 +1,549 @@
+//! A middleware for rate limiting requests.
+//!
+//! Every client gets a [token bucket] per route, which is refilled at a
+//! constant rate. Each request takes one token out of the bucket, and once the
+//! bucket is empty, requests are rejected with a `429 Too Many Requests`
+//! response until enough time has passed.
+//!
+//! CS2 servers are identified by the JWT they send along with their requests,
+//! as many of them might share a single IP address. Everyone else is
+//! identified by their IP address.
+//!
+//! Only a limited amount of buckets is kept around; once that limit is
+//! reached, the least recently used bucket is dropped to make room.
+//!
+//! [token bucket]: https://en.wikipedia.org/wiki/Token_bucket
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::task::{self, Poll};
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwap;
+use axum::extract::{ConnectInfo, MatchedPath, Request};
+use axum::response::{IntoResponse, Response};
+use axum_extra::headers::authorization::Bearer;
+use axum_extra::headers::{Authorization, HeaderMapExt};
+use futures::future::BoxFuture;
+use hashlink::LruCache;
+use thiserror::Error;
+
+use crate::http::problem_details::{self, IntoProblemDetails, ProblemType};
+use crate::http::ProblemDetails;
+use crate::net::IpAddr;
+use crate::runtime::config::{RateLimitConfig, RateLimitQuota};
+use crate::services::auth::jwt;
+use crate::services::servers::ServerID;
+use crate::services::AuthService;
+
+/// The amount of buckets we keep around before we start evicting the least
+/// recently used ones.
+const MAX_TRACKED_BUCKETS: usize = 16_384;
+
+/// A layer producing the [`RateLimit`] middleware.
+#[derive(Clone)]
+pub struct RateLimitLayer
+{
+	/// State shared by all the services produced by this layer.
+	state: Arc<State>,
+}
+
+impl RateLimitLayer
+{
+	/// Creates a new [`RateLimitLayer`].
+	///
+	/// `auth_svc` is used for decoding JWTs to identify CS2 servers.
+	pub fn new(config: RateLimitConfig, auth_svc: AuthService) -> Self
+	{
+		Self::with_capacity(config, auth_svc, MAX_TRACKED_BUCKETS)
+	}
+
+	/// Creates a new [`RateLimitLayer`] that keeps at most `capacity` buckets
+	/// around.
+	fn with_capacity(config: RateLimitConfig, auth_svc: AuthService, capacity: usize) -> Self
+	{
+		let state = State {
+			config: ArcSwap::from_pointee(config),
+			auth_svc,
+			buckets: Mutex::new(LruCache::new(capacity)),
+		};
+
+		Self { state: Arc::new(state) }
+	}
+
+	/// Replaces the configured quotas.
+	///
+	/// This affects all services produced by this layer, including ones that
+	/// already exist.
+	pub fn reload(&self, config: RateLimitConfig)
+	{
+		self.state.config.store(Arc::new(config));
+	}
+}
+
+impl<S> tower::Layer<S> for RateLimitLayer
+{
+	type Service = RateLimit<S>;
+
+	fn layer(&self, inner: S) -> Self::Service
+	{
+		RateLimit { state: Arc::clone(&self.state), inner }
+	}
+}
+
+/// A middleware that rejects requests from clients who exceeded their quota.
+///
+/// You can create an instance of this service using [`RateLimitLayer`].
+#[derive(Clone)]
+pub struct RateLimit<S>
+{
+	/// State shared by all instances of this service.
+	state: Arc<State>,
+
+	/// The inner service.
+	inner: S,
+}
+
+impl<S> tower::Service<Request> for RateLimit<S>
+where
+	S: tower::Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+	S::Future: Send,
+{
+	type Response = Response;
+	type Error = Infallible;
+	type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>>
+	{
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, req: Request) -> Self::Future
+	{
+		if let Err(error) = self.state.check(&req, Instant::now()) {
+			return Box::pin(async move { Ok(error.into_response()) });
+		}
+
+		Box::pin(self.inner.call(req))
+	}
+}
+
+/// State shared between [`RateLimit`] services.
+struct State
+{
+	/// The configured quotas.
+	config: ArcSwap<RateLimitConfig>,
+
+	/// For decoding JWTs.
+	auth_svc: AuthService,
+
+	/// The bucket for every client and route.
+	///
+	/// Routes are identified by their [`MatchedPath`].
+	buckets: Mutex<LruCache<(Arc<str>, ClientKey), Bucket>>,
+}
+
+impl State
+{
+	/// Takes a token out of the bucket responsible for `req`.
+	fn check(&self, req: &Request, now: Instant) -> Result<(), RateLimited>
+	{
+		let config = self.config.load();
+
+		if !config.enable {
+			return Ok(());
+		}
+
+		let Some(client) = self.client_key(req) else {
+			tracing::warn!("could not identify client; skipping rate limit");
+			return Ok(());
+		};
+
+		// Requests that didn't match any route all share a single bucket, so
+		// clients can't create new ones by making up paths.
+		let route = req
+			.extensions()
+			.get::<MatchedPath>()
+			.map_or("", MatchedPath::as_str);
+
+		let quota = config
+			.routes
+			.get(route)
+			.or_else(|| config.routes.get(super::route_group(route)))
+			.copied()
+			.unwrap_or(config.default);
+
+		let mut buckets = self.buckets.lock().expect("poisoned");
+		let key = (Arc::<str>::from(route), client);
+
+		let result = match buckets.get_mut(&key) {
+			Some(bucket) => bucket.take(quota, now),
+			None => {
+				let mut bucket = Bucket::new(quota, now);
+				let result = bucket.take(quota, now);
+
+				// evicts the least recently used bucket if we're at capacity
+				buckets.insert(key, bucket);
+
+				result
+			}
+		};
+
+		drop(buckets);
+
+		result.map_err(|retry_after| {
+			tracing::debug!(route, ?client, ?retry_after, "client is being rate limited");
+			RateLimited::new(retry_after)
+		})
+	}
+
+	/// Figures out who sent `req`.
+	fn client_key(&self, req: &Request) -> Option<ClientKey>
+	{
+		let server_id = req
+			.headers()
+			.typed_get::<Authorization<Bearer>>()
+			.and_then(|header| {
+				self.auth_svc
+					.decode_jwt::<jwt::ServerInfo>(header.token())
+					.ok()
+			})
+			.map(|jwt| jwt.id());
+
+		if let Some(server_id) = server_id {
+			return Some(ClientKey::Server(server_id));
+		}
+
+		req.extensions()
+			.get::<ConnectInfo<SocketAddr>>()
+			.map(|ConnectInfo(addr)| ClientKey::Ip(addr.ip().into()))
+	}
+}
+
+/// Identifies a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ClientKey
+{
+	/// A CS2 server, identified by its JWT.
+	Server(ServerID),
+
+	/// Anybody else.
+	Ip(IpAddr),
+}
+
+/// A token bucket.
+#[derive(Debug, Clone, Copy)]
+struct Bucket
+{
+	/// How many tokens are left.
+	tokens: f64,
+
+	/// When `tokens` was last updated.
+	updated_at: Instant,
+}
+
+impl Bucket
+{
+	/// Creates a new, full bucket.
+	fn new(quota: RateLimitQuota, now: Instant) -> Self
+	{
+		Self { tokens: f64::from(quota.burst.get()), updated_at: now }
+	}
+
+	/// Returns the amount of tokens that would be in the bucket at `now`.
+	fn tokens_at(&self, quota: RateLimitQuota, now: Instant) -> f64
+	{
+		let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+		let refilled = self.tokens + elapsed * f64::from(quota.per_second.get());
+
+		refilled.min(f64::from(quota.burst.get()))
+	}
+
+	/// Checks whether the bucket would be full at `now`.
+	#[cfg(test)]
+	fn is_full(&self, quota: RateLimitQuota, now: Instant) -> bool
+	{
+		self.tokens_at(quota, now) >= f64::from(quota.burst.get())
+	}
+
+	/// Takes a single token out of the bucket.
+	///
+	/// If the bucket is empty, returns how long it will take until the next
+	/// token is available.
+	fn take(&mut self, quota: RateLimitQuota, now: Instant) -> Result<(), Duration>
+	{
+		self.tokens = self.tokens_at(quota, now);
+		self.updated_at = now;
+
+		if self.tokens >= 1.0 {
+			self.tokens -= 1.0;
+			return Ok(());
+		}
+
+		let missing = 1.0 - self.tokens;
+
+		Err(Duration::from_secs_f64(missing / f64::from(quota.per_second.get())))
+	}
+}
+
+/// The error returned to clients who exceeded their quota.
+#[derive(Debug, Error)]
+#[error("too many requests; try again in {retry_after} second(s)")]
+struct RateLimited
+{
+	/// How many seconds the client should wait before trying again.
+	retry_after: u64,
+}
+
+impl RateLimited
+{
+	/// Creates a new [`RateLimited`], rounding `retry_after` up to whole
+	/// seconds.
+	fn new(retry_after: Duration) -> Self
+	{
+		Self { retry_after: retry_after.as_secs_f64().ceil() as u64 }
+	}
+}
+
+impl IntoProblemDetails for RateLimited
+{
+	fn problem_type(&self) -> ProblemType
+	{
+		ProblemType::TooManyRequests
+	}
+
+	fn add_extension_members(&self, ext: &mut problem_details::ExtensionMembers)
+	{
+		ext.add("retry_after", &self.retry_after);
+	}
+}
+
+impl IntoResponse for RateLimited
+{
+	fn into_response(self) -> Response
+	{
+		let retry_after = http::HeaderValue::from(self.retry_after);
+		let mut response = ProblemDetails::from(self).into_response();
+
+		response
+			.headers_mut()
+			.insert(http::header::RETRY_AFTER, retry_after);
+
+		response
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::collections::HashMap;
+	use std::num::NonZero;
+
+	use axum::routing::get;
+	use axum::Router;
+	use tower::ServiceExt;
+
+	use super::*;
+	use crate::testing;
+
+	/// Creates a quota.
+	fn quota(burst: u32, per_second: u32) -> RateLimitQuota
+	{
+		RateLimitQuota {
+			burst: NonZero::new(burst).unwrap(),
+			per_second: NonZero::new(per_second).unwrap(),
+		}
+	}
+
+	/// Creates a router with a few route groups, limited by `config`.
+	fn router(config: RateLimitConfig, auth_svc: AuthService) -> Router
+	{
+		router_with_capacity(config, auth_svc, MAX_TRACKED_BUCKETS)
+	}
+
+	/// Creates a router with a few route groups, limited by `config`, that
+	/// keeps at most `capacity` buckets around.
+	fn router_with_capacity(
+		config: RateLimitConfig,
+		auth_svc: AuthService,
+		capacity: usize,
+	) -> Router
+	{
+		let router = Router::new()
+			.route("/records", get(|| async { "records" }))
+			.route("/records/:record", get(|| async { "record" }))
+			.route("/maps/:map", get(|| async { "map" }));
+
+		router.layer(RateLimitLayer::with_capacity(config, auth_svc, capacity))
+	}
+
+	/// Sends a request to `path` from `ip`, optionally authenticated with
+	/// `jwt`, and returns the response.
+	async fn send(
+		router: &Router,
+		path: &str,
+		ip: [u8; 4],
+		jwt: Option<&str>,
+	) -> color_eyre::Result<Response>
+	{
+		let mut req = http::Request::get(path);
+
+		if let Some(jwt) = jwt {
+			req = req.header(http::header::AUTHORIZATION, format!("Bearer {jwt}"));
+		}
+
+		let mut req = req.body(axum::body::Body::empty())?;
+
+		req.extensions_mut()
+			.insert(ConnectInfo(SocketAddr::from((ip, 1337))));
+
+		Ok(router.clone().oneshot(req).await?)
+	}
+
+	#[tokio::test]
+	async fn rejects_after_burst() -> color_eyre::Result<()>
+	{
+		let config = RateLimitConfig { default: quota(3, 1), ..Default::default() };
+		let router = router(config, testing::auth_svc(testing::lazy_pool()));
+
+		for _ in 0..3 {
+			let res = send(&router, "/records", [127, 0, 0, 1], None).await?;
+
+			testing::assert_eq!(res.status(), http::StatusCode::OK);
+		}
+
+		let res = send(&router, "/records", [127, 0, 0, 1], None).await?;
+		let retry_after = res.headers().get(http::header::RETRY_AFTER).cloned();
+
+		testing::assert_eq!(res.status(), http::StatusCode::TOO_MANY_REQUESTS);
+		testing::assert_eq!(retry_after, Some(http::HeaderValue::from_static("1")));
+
+		let problem = testing::parse_body::<ProblemDetails>(res.into_body()).await?;
+
+		testing::assert_eq!(problem.problem_type(), ProblemType::TooManyRequests);
+
+		let other_client = send(&router, "/records", [127, 0, 0, 2], None).await?;
+
+		testing::assert_eq!(other_client.status(), http::StatusCode::OK);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn route_groups_have_separate_quotas() -> color_eyre::Result<()>
+	{
+		let config = RateLimitConfig {
+			default: quota(5, 1),
+			routes: HashMap::from_iter([(String::from("records"), quota(1, 1))]),
+			..Default::default()
+		};
+		let router = router(config, testing::auth_svc(testing::lazy_pool()));
+
+		let first = send(&router, "/records", [127, 0, 0, 1], None).await?;
+		let second = send(&router, "/records", [127, 0, 0, 1], None).await?;
+		let map = send(&router, "/maps/kz_checkmate", [127, 0, 0, 1], None).await?;
+
+		testing::assert_eq!(first.status(), http::StatusCode::OK);
+		testing::assert_eq!(second.status(), http::StatusCode::TOO_MANY_REQUESTS);
+		testing::assert_eq!(map.status(), http::StatusCode::OK);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn routes_have_separate_buckets() -> color_eyre::Result<()>
+	{
+		let config = RateLimitConfig {
+			default: quota(5, 1),
+			routes: HashMap::from_iter([(String::from("/maps/:map"), quota(1, 1))]),
+			..Default::default()
+		};
+		let router = router(config, testing::auth_svc(testing::lazy_pool()));
+
+		let list = send(&router, "/records", [127, 0, 0, 1], None).await?;
+		let single = send(&router, "/records/1", [127, 0, 0, 1], None).await?;
+		let first_map = send(&router, "/maps/kz_checkmate", [127, 0, 0, 1], None).await?;
+		let other_map = send(&router, "/maps/kz_grotto", [127, 0, 0, 1], None).await?;
+
+		testing::assert_eq!(list.status(), http::StatusCode::OK);
+		testing::assert_eq!(single.status(), http::StatusCode::OK);
+		testing::assert_eq!(first_map.status(), http::StatusCode::OK);
+
+		// path parameters don't get their own bucket
+		testing::assert_eq!(other_map.status(), http::StatusCode::TOO_MANY_REQUESTS);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn least_recently_used_buckets_are_evicted() -> color_eyre::Result<()>
+	{
+		let config = RateLimitConfig { default: quota(1, 1), ..Default::default() };
+		let router = router_with_capacity(config, testing::auth_svc(testing::lazy_pool()), 2);
+
+		let first = send(&router, "/records", [127, 0, 0, 1], None).await?;
+		let second = send(&router, "/records", [127, 0, 0, 2], None).await?;
+
+		testing::assert_eq!(first.status(), http::StatusCode::OK);
+		testing::assert_eq!(second.status(), http::StatusCode::OK);
+
+		// touch the first client's bucket so the second one is evicted next
+		let first_again = send(&router, "/records", [127, 0, 0, 1], None).await?;
+		let third = send(&router, "/records", [127, 0, 0, 3], None).await?;
+
+		testing::assert_eq!(first_again.status(), http::StatusCode::TOO_MANY_REQUESTS);
+		testing::assert_eq!(third.status(), http::StatusCode::OK);
+
+		let first_still_limited = send(&router, "/records", [127, 0, 0, 1], None).await?;
+		let second_evicted = send(&router, "/records", [127, 0, 0, 2], None).await?;
+
+		testing::assert_eq!(first_still_limited.status(), http::StatusCode::TOO_MANY_REQUESTS);
+		testing::assert_eq!(second_evicted.status(), http::StatusCode::OK);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn servers_are_identified_by_jwt() -> color_eyre::Result<()>
+	{
+		let auth_svc = testing::auth_svc(testing::lazy_pool());
+		let server_info = jwt::ServerInfo::new(1.into(), 1.into());
+		let jwt = auth_svc.encode_jwt(jwt::Jwt::new(server_info, Duration::from_secs(60)))?;
+		let config = RateLimitConfig { default: quota(1, 1), ..Default::default() };
+		let router = router(config, auth_svc);
+
+		let first = send(&router, "/records", [127, 0, 0, 1], Some(&jwt)).await?;
+		let other_ip = send(&router, "/records", [127, 0, 0, 2], Some(&jwt)).await?;
+		let no_jwt = send(&router, "/records", [127, 0, 0, 1], None).await?;
+
+		testing::assert_eq!(first.status(), http::StatusCode::OK);
+		testing::assert_eq!(other_ip.status(), http::StatusCode::TOO_MANY_REQUESTS);
+		testing::assert_eq!(no_jwt.status(), http::StatusCode::OK);
+
+		Ok(())
+	}
+
+	#[test]
+	fn bucket_refills() -> color_eyre::Result<()>
+	{
+		let quota = quota(2, 4);
+		let start = Instant::now();
+		let mut bucket = Bucket::new(quota, start);
+
+		testing::assert!(bucket.take(quota, start).is_ok());
+		testing::assert!(bucket.take(quota, start).is_ok());
+
+		let retry_after = bucket.take(quota, start);
+
+		testing::assert_eq!(retry_after, Err::<(), _>(Duration::from_millis(250)));
+
+		let later = start + Duration::from_millis(250);
+
+		testing::assert!(bucket.take(quota, later).is_ok());
+		testing::assert!(bucket.take(quota, later).is_err());
+		testing::assert!(bucket.is_full(quota, later + Duration::from_secs(1)));
+
+		Ok(())
+	}
+}
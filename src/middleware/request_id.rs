@@ -0,0 +1,233 @@
+//! A middleware for tagging every request with an ID.
+//!
+//! Clients may send their own `x-request-id` header; otherwise we generate one.
+//! Either way, the ID is echoed back in the response headers, recorded on the
+//! request's tracing span, and included in any [`ProblemDetails`] bodies as the
+//! `request_id` extension member. This way, an error reported by a client can
+//! be matched to the logs it produced.
+//!
+//! [`ProblemDetails`]: crate::http::ProblemDetails
+
+use std::convert::Infallible;
+use std::fmt;
+use std::task::{self, Poll};
+
+use axum::extract::Request;
+use axum::response::Response;
+use futures::future::BoxFuture;
+use http::{HeaderName, HeaderValue};
+use uuid::Uuid;
+
+/// The header carrying the request ID.
+pub const HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The maximum length we accept for client-provided request IDs.
+const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+	/// The ID of the request currently being handled.
+	static CURRENT: RequestId;
+}
+
+/// A unique ID for an HTTP request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(HeaderValue);
+
+impl RequestId
+{
+	/// Generates a new random ID.
+	pub fn generate() -> Self
+	{
+		let id = Uuid::now_v7().to_string();
+
+		Self(HeaderValue::try_from(id).expect("uuids are valid header values"))
+	}
+
+	/// Uses a client-provided ID, if it is reasonable.
+	fn from_header(value: &HeaderValue) -> Option<Self>
+	{
+		let is_valid = !value.is_empty()
+			&& value.len() <= MAX_LEN
+			&& value.as_bytes().iter().all(u8::is_ascii_graphic);
+
+		is_valid.then(|| Self(value.clone()))
+	}
+
+	/// Returns the ID of the request currently being handled, if any.
+	pub fn current() -> Option<Self>
+	{
+		CURRENT.try_with(Clone::clone).ok()
+	}
+
+	/// Returns the ID as a string.
+	pub fn as_str(&self) -> &str
+	{
+		self.0.to_str().expect("only contains visible ascii")
+	}
+}
+
+impl fmt::Display for RequestId
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		f.write_str(self.as_str())
+	}
+}
+
+/// A layer producing the [`RequestIdService`] middleware.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestIdLayer;
+
+impl RequestIdLayer
+{
+	/// Creates a new [`RequestIdLayer`].
+	pub fn new() -> Self
+	{
+		Self
+	}
+}
+
+impl<S> tower::Layer<S> for RequestIdLayer
+{
+	type Service = RequestIdService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service
+	{
+		RequestIdService { inner }
+	}
+}
+
+/// A middleware that assigns every request a [`RequestId`].
+///
+/// The ID is available to inner services as a request extension, in the
+/// `x-request-id` header, and through [`RequestId::current()`].
+///
+/// You can create an instance of this service using [`RequestIdLayer`].
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S>
+{
+	/// The inner service.
+	inner: S,
+}
+
+impl<S> tower::Service<Request> for RequestIdService<S>
+where
+	S: tower::Service<Request, Response = Response, Error = Infallible> + Send + 'static,
+	S::Future: Send,
+{
+	type Response = Response;
+	type Error = Infallible;
+	type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>>
+	{
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, mut req: Request) -> Self::Future
+	{
+		let id = req
+			.headers()
+			.get(HEADER)
+			.and_then(RequestId::from_header)
+			.unwrap_or_else(RequestId::generate);
+
+		req.headers_mut().insert(HEADER, id.0.clone());
+		req.extensions_mut().insert(id.clone());
+
+		let response = CURRENT.scope(id.clone(), self.inner.call(req));
+
+		Box::pin(async move {
+			let mut response = response.await?;
+
+			response.headers_mut().insert(HEADER, id.0);
+
+			Ok(response)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use axum::body::Body;
+	use axum::{routing, Router};
+	use tower::ServiceExt;
+	use tracing_subscriber::layer::SubscriberExt;
+
+	use super::*;
+	use crate::http::problem_details::ProblemType;
+	use crate::http::ProblemDetails;
+	use crate::testing;
+
+	/// Returns a router that fails every request.
+	fn router() -> Router
+	{
+		Router::new()
+			.route(
+				"/",
+				routing::get(|| async {
+					tracing::warn!("something went wrong");
+					ProblemDetails::new(ProblemType::ResourceNotFound)
+				}),
+			)
+			.layer(crate::middleware::logging::layer!())
+			.layer(RequestIdLayer::new())
+	}
+
+	#[tokio::test]
+	async fn id_is_logged_and_returned() -> color_eyre::Result<()>
+	{
+		let buffer = testing::LogBuffer::default();
+		let subscriber = tracing_subscriber::registry().with(
+			tracing_subscriber::fmt::layer()
+				.with_ansi(false)
+				.with_writer(buffer.clone()),
+		);
+
+		let _guard = tracing::subscriber::set_default(subscriber);
+
+		let req = http::Request::builder()
+			.uri("/")
+			.header(HEADER, "kz-0192")
+			.body(Body::empty())?;
+
+		let res = router().oneshot(req).await?;
+
+		testing::assert_eq!(res.headers()[HEADER], "kz-0192");
+
+		let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+		let body = serde_json::from_slice::<serde_json::Value>(&body)?;
+
+		testing::assert_eq!(body["request_id"], "kz-0192");
+
+		let logs = buffer.contents();
+
+		testing::assert!(logs
+			.lines()
+			.any(|line| line.contains("something went wrong") && line.contains("kz-0192")));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn id_is_generated() -> color_eyre::Result<()>
+	{
+		let req = http::Request::builder()
+			.uri("/")
+			.header(HEADER, "not a valid id")
+			.body(Body::empty())?;
+
+		let res = router().oneshot(req).await?;
+		let id = res.headers()[HEADER].to_str()?.to_owned();
+
+		testing::assert!(id.parse::<Uuid>().is_ok());
+
+		let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+		let body = serde_json::from_slice::<serde_json::Value>(&body)?;
+
+		testing::assert_eq!(body["request_id"], id.as_str());
+
+		Ok(())
+	}
+}
@@ -0,0 +1,273 @@
+//! A middleware for aborting requests that take too long.
+//!
+//! Some handlers depend on external services, like Steam, which might stall
+//! indefinitely. Instead of tying up a connection forever, we give up after a
+//! configurable amount of time and respond with `504 Gateway Timeout`.
+//!
+//! Aborting a request means dropping the handler's future, so any database
+//! transactions it has open are rolled back.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{self, Poll};
+use std::time::Duration;
+
+use axum::extract::Request;
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use thiserror::Error;
+
+use crate::http::problem_details::{self, IntoProblemDetails, ProblemType};
+use crate::http::ProblemDetails;
+use crate::runtime::config::RequestTimeoutConfig;
+
+/// A layer producing the [`RequestTimeout`] middleware.
+#[derive(Clone)]
+pub struct RequestTimeoutLayer
+{
+	/// The configured timeouts.
+	timeouts: Arc<Timeouts>,
+}
+
+impl RequestTimeoutLayer
+{
+	/// Creates a new [`RequestTimeoutLayer`].
+	pub fn new(config: RequestTimeoutConfig) -> Self
+	{
+		let timeouts = Timeouts {
+			default: Duration::from_secs(config.default.get()),
+			routes: config
+				.routes
+				.into_iter()
+				.map(|(group, secs)| (group, Duration::from_secs(secs.get())))
+				.collect(),
+		};
+
+		Self { timeouts: Arc::new(timeouts) }
+	}
+}
+
+impl<S> tower::Layer<S> for RequestTimeoutLayer
+{
+	type Service = RequestTimeout<S>;
+
+	fn layer(&self, inner: S) -> Self::Service
+	{
+		RequestTimeout { timeouts: Arc::clone(&self.timeouts), inner }
+	}
+}
+
+/// A middleware that aborts requests taking longer than their timeout.
+///
+/// You can create an instance of this service using [`RequestTimeoutLayer`].
+#[derive(Clone)]
+pub struct RequestTimeout<S>
+{
+	/// The configured timeouts.
+	timeouts: Arc<Timeouts>,
+
+	/// The inner service.
+	inner: S,
+}
+
+impl<S> tower::Service<Request> for RequestTimeout<S>
+where
+	S: tower::Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+	S::Future: Send,
+{
+	type Response = Response;
+	type Error = Infallible;
+	type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>>
+	{
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, req: Request) -> Self::Future
+	{
+		let timeout = self.timeouts.get(req.uri().path());
+		let response = self.inner.call(req);
+
+		Box::pin(async move {
+			match tokio::time::timeout(timeout, response).await {
+				Ok(response) => response,
+				Err(_) => {
+					tracing::warn!(?timeout, "request timed out");
+					Ok(TimedOut { timeout }.into_response())
+				}
+			}
+		})
+	}
+}
+
+/// The configured timeouts.
+#[derive(Debug)]
+struct Timeouts
+{
+	/// The timeout for route groups without an entry in `routes`.
+	default: Duration,
+
+	/// Timeouts for specific route groups.
+	routes: HashMap<String, Duration>,
+}
+
+impl Timeouts
+{
+	/// Returns the timeout for a request to `path`.
+	fn get(&self, path: &str) -> Duration
+	{
+		self.routes
+			.get(super::route_group(path))
+			.copied()
+			.unwrap_or(self.default)
+	}
+}
+
+/// The error returned when a request took too long.
+#[derive(Debug, Error)]
+#[error("request took longer than {timeout:?} and was aborted")]
+struct TimedOut
+{
+	/// The timeout that was exceeded.
+	timeout: Duration,
+}
+
+impl IntoProblemDetails for TimedOut
+{
+	fn problem_type(&self) -> ProblemType
+	{
+		ProblemType::Timeout
+	}
+
+	fn add_extension_members(&self, ext: &mut problem_details::ExtensionMembers)
+	{
+		ext.add("timeout_secs", &self.timeout.as_secs());
+	}
+}
+
+impl IntoResponse for TimedOut
+{
+	fn into_response(self) -> Response
+	{
+		ProblemDetails::from(self).into_response()
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::num::NonZero;
+
+	use axum::extract::State;
+	use axum::routing::{get, post};
+	use axum::Router;
+	use sqlx::{MySql, Pool};
+	use tower::ServiceExt;
+
+	use super::*;
+	use crate::testing;
+
+	/// Creates a config with a 1 second default timeout.
+	fn config() -> RequestTimeoutConfig
+	{
+		RequestTimeoutConfig { default: NonZero::new(1).unwrap(), routes: HashMap::new() }
+	}
+
+	/// Creates a layer that aborts every request after `timeout`.
+	fn layer(timeout: Duration) -> RequestTimeoutLayer
+	{
+		RequestTimeoutLayer {
+			timeouts: Arc::new(Timeouts { default: timeout, routes: HashMap::new() }),
+		}
+	}
+
+	/// Sends a request to `router` and returns the response.
+	async fn send(router: Router, method: http::Method, path: &str)
+		-> color_eyre::Result<Response>
+	{
+		let req = http::Request::builder()
+			.method(method)
+			.uri(path)
+			.body(axum::body::Body::empty())?;
+
+		Ok(router.oneshot(req).await?)
+	}
+
+	/// A handler that inserts a player, but takes way too long to commit.
+	async fn slow_insert(State(database): State<Pool<MySql>>) -> http::StatusCode
+	{
+		let mut txn = database.begin().await.unwrap();
+
+		sqlx::query("INSERT INTO Players (id, name, ip_address) VALUES (?, ?, ?)")
+			.bind(76561197960265729_u64)
+			.bind("slowpoke")
+			.bind("::1".parse::<crate::net::IpAddr>().unwrap())
+			.execute(txn.as_mut())
+			.await
+			.unwrap();
+
+		tokio::time::sleep(Duration::from_secs(60)).await;
+		txn.commit().await.unwrap();
+
+		http::StatusCode::CREATED
+	}
+
+	#[test]
+	fn route_groups_have_separate_timeouts() -> color_eyre::Result<()>
+	{
+		let config = RequestTimeoutConfig {
+			routes: HashMap::from_iter([(String::from("maps"), NonZero::new(300).unwrap())]),
+			..config()
+		};
+		let timeouts = RequestTimeoutLayer::new(config).timeouts;
+
+		testing::assert_eq!(timeouts.get("/maps"), Duration::from_secs(300));
+		testing::assert_eq!(timeouts.get("/maps/kz_checkmate"), Duration::from_secs(300));
+		testing::assert_eq!(timeouts.get("/records/1"), Duration::from_secs(1));
+		testing::assert_eq!(timeouts.get("/"), Duration::from_secs(1));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn fast_requests_pass() -> color_eyre::Result<()>
+	{
+		let router = Router::new()
+			.route("/", get(|| async { "hello" }))
+			.layer(layer(Duration::from_secs(1)));
+
+		let res = send(router, http::Method::GET, "/").await?;
+
+		testing::assert_eq!(res.status(), http::StatusCode::OK);
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn slow_requests_time_out(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let router = Router::new()
+			.route("/players", post(slow_insert))
+			.layer(layer(Duration::from_millis(500)))
+			.with_state(database.clone());
+
+		let res = send(router, http::Method::POST, "/players").await?;
+
+		testing::assert_eq!(res.status(), http::StatusCode::GATEWAY_TIMEOUT);
+
+		let problem = testing::parse_body::<ProblemDetails>(res.into_body()).await?;
+
+		testing::assert_eq!(problem.problem_type(), ProblemType::Timeout);
+
+		let inserted = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM Players WHERE name = ?")
+			.bind("slowpoke")
+			.fetch_one(&database)
+			.await?;
+
+		testing::assert_eq!(inserted, 0);
+
+		Ok(())
+	}
+}
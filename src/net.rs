@@ -10,7 +10,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// A wrapper around [`std::net::Ipv6Addr`] that correctly takes care of mapped
 /// IPv4 addresses when encoding/decoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, utoipa::ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, utoipa::ToSchema)]
 #[schema(value_type = str)]
 pub struct IpAddr(Ipv6Addr);
 
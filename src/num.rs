@@ -1,8 +1,9 @@
 //! This module contains extensions for [`std::num`].
 
-use std::{cmp, ops};
+use std::{cmp, fmt, ops};
 
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::de::{self, Deserialize, Deserializer};
+use serde::Serialize;
 
 /// A u64 with custom default & max value.
 #[repr(transparent)]
@@ -73,6 +74,106 @@ impl<'de, const DEFAULT: u64, const MAX: u64> Deserialize<'de> for ClampedU64<DE
 	}
 }
 
+impl<const DEFAULT: u64, const MAX: u64> ClampedU64<DEFAULT, MAX>
+{
+	/// Deserializes a `limit` parameter, rejecting out-of-range values
+	/// instead of clamping them.
+	///
+	/// Use this with `#[serde(default, deserialize_with = "...")]`.
+	pub fn deserialize_strict_limit<'de, D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		Self::deserialize_strict("limit", deserializer)
+	}
+
+	/// Deserializes an `offset` parameter, rejecting out-of-range values
+	/// instead of clamping them.
+	///
+	/// Use this with `#[serde(default, deserialize_with = "...")]`.
+	pub fn deserialize_strict_offset<'de, D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		Self::deserialize_strict("offset", deserializer)
+	}
+
+	/// Deserializes a value that must be in `0..=MAX`.
+	///
+	/// `name` is included in the error message, so clients know which
+	/// parameter was wrong.
+	fn deserialize_strict<'de, D>(name: &str, deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		const { assert!(DEFAULT <= MAX, "`DEFAULT` cannot exceed `MAX`") };
+
+		let Some(AnyInt(value)) = Option::<AnyInt>::deserialize(deserializer)? else {
+			return Ok(Self::default());
+		};
+
+		match u64::try_from(value) {
+			Ok(value) if value <= MAX => Ok(Self(value)),
+			_ => Err(de::Error::custom(format_args!(
+				"`{name}` must be between 0 and {MAX}, but got {value}"
+			))),
+		}
+	}
+}
+
+/// Any integer, as long as it fits into an [`i128`].
+///
+/// Query strings don't carry type information, so this accepts strings
+/// containing integers as well.
+struct AnyInt(i128);
+
+impl<'de> Deserialize<'de> for AnyInt
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		/// Visitor for [`AnyInt`].
+		struct AnyIntVisitor;
+
+		impl de::Visitor<'_> for AnyIntVisitor
+		{
+			type Value = AnyInt;
+
+			fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+			{
+				f.write_str("an integer")
+			}
+
+			fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+			where
+				E: de::Error,
+			{
+				Ok(AnyInt(value.into()))
+			}
+
+			fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+			where
+				E: de::Error,
+			{
+				Ok(AnyInt(value.into()))
+			}
+
+			fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+			where
+				E: de::Error,
+			{
+				value
+					.parse::<i128>()
+					.map(AnyInt)
+					.map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+			}
+		}
+
+		deserializer.deserialize_any(AnyIntVisitor)
+	}
+}
+
 #[cfg(test)]
 mod tests
 {
@@ -91,4 +192,53 @@ mod tests
 		assert_eq!(ClampedU64::<0, 10>::new(10).0, 10);
 		assert_eq!(ClampedU64::<0, 10>::new(11).0, 10);
 	}
+
+	#[derive(Debug, serde::Deserialize)]
+	struct Strict
+	{
+		#[serde(default, deserialize_with = "ClampedU64::deserialize_strict_limit")]
+		limit: ClampedU64<100, 1000>,
+
+		#[serde(default, deserialize_with = "ClampedU64::deserialize_strict_offset")]
+		offset: ClampedU64,
+	}
+
+	#[test]
+	fn strict_accepts_values_in_range()
+	{
+		let strict = serde_urlencoded::from_str::<Strict>("limit=1000&offset=5").unwrap();
+
+		assert_eq!(*strict.limit, 1000);
+		assert_eq!(*strict.offset, 5);
+
+		let strict = serde_urlencoded::from_str::<Strict>("").unwrap();
+
+		assert_eq!(*strict.limit, 100);
+		assert_eq!(*strict.offset, 0);
+	}
+
+	#[test]
+	fn strict_rejects_limit_over_max()
+	{
+		let error = serde_urlencoded::from_str::<Strict>("limit=1001").unwrap_err();
+
+		assert_eq!(error.to_string(), "`limit` must be between 0 and 1000, but got 1001");
+
+		let error = serde_json::from_str::<Strict>(r#"{ "limit": 5000 }"#).unwrap_err();
+
+		assert!(error
+			.to_string()
+			.starts_with("`limit` must be between 0 and 1000"));
+	}
+
+	#[test]
+	fn strict_rejects_negative_offset()
+	{
+		let error = serde_urlencoded::from_str::<Strict>("offset=-1").unwrap_err();
+
+		assert_eq!(
+			error.to_string(),
+			format!("`offset` must be between 0 and {}, but got -1", u64::MAX)
+		);
+	}
 }
@@ -2,7 +2,10 @@
 //!
 //! [OpenAPI]: https://www.openapis.org
 
-use utoipa::OpenApi;
+#![allow(clippy::needless_for_each, reason = "generated by `#[derive(OpenApi)]`")]
+
+use url::Url;
+use utoipa::{Modify, OpenApi};
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::services;
@@ -10,6 +13,12 @@ use crate::services;
 mod security;
 pub use security::Security;
 
+mod problems;
+pub use problems::Problems;
+
+mod servers;
+pub use servers::{Servers, DEFAULT_PUBLIC_URL, LOCAL_URL};
+
 pub mod responses;
 
 /// The API's OpenAPI schema.
@@ -45,6 +54,7 @@ pub mod responses;
       services::bans::BanID,
       services::bans::UnbanID,
       services::plugin::PluginVersionID,
+      services::plugin::GitRevision,
     )
   ),
   info(
@@ -66,17 +76,28 @@ The source code is available on [GitHub].
     url = "https://docs.cs2kz.org",
     description = "CS2KZ documentation",
   ),
-  modifiers(&Security),
+  modifiers(&Security, &Problems),
 )]
 pub struct Schema;
 
 impl Schema
 {
+	/// Returns the schema with `public_url` (and a local development URL) as
+	/// its `servers`.
+	pub fn spec(public_url: &Url) -> utoipa::openapi::OpenApi
+	{
+		let mut spec = Self::openapi();
+
+		Servers { public_url: public_url.clone() }.modify(&mut spec);
+
+		spec
+	}
+
 	/// Returns a [`SwaggerUi`], which can be turned into an [`axum::Router`] to
 	/// serve the API's SwaggerUI documentation.
-	pub fn swagger_ui() -> SwaggerUi
+	pub fn swagger_ui(public_url: &Url) -> SwaggerUi
 	{
-		SwaggerUi::new("/docs/swagger-ui").url("/docs/openapi.json", Self::openapi())
+		SwaggerUi::new("/docs/swagger-ui").url("/docs/openapi.json", Self::spec(public_url))
 	}
 
 	/// Generates a JSON representation of the schema.
@@ -84,8 +105,90 @@ impl Schema
 	/// # Panics
 	///
 	/// This function will panic if the schema cannot be serialized as JSON.
-	pub fn json() -> String
+	pub fn json(public_url: &Url) -> String
 	{
-		Self::openapi().to_pretty_json().expect("valid schema")
+		Self::spec(public_url)
+			.to_pretty_json()
+			.expect("valid schema")
+	}
+
+	/// Generates a YAML representation of the schema.
+	///
+	/// # Panics
+	///
+	/// This function will panic if the schema cannot be serialized as YAML.
+	pub fn yaml(public_url: &Url) -> String
+	{
+		Self::spec(public_url).to_yaml().expect("valid schema")
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::collections::HashSet;
+
+	use super::*;
+	use crate::http::problem_details::ProblemType;
+	use crate::testing;
+
+	#[test]
+	fn operation_ids_are_unique() -> color_eyre::Result<()>
+	{
+		let spec = Schema::openapi();
+		let mut seen = HashSet::new();
+
+		for (path, item) in &spec.paths.paths {
+			for operation in item.operations.values() {
+				let operation_id = operation.operation_id.as_deref().unwrap_or_default();
+
+				testing::assert!(
+					!operation_id.is_empty(),
+					"an operation on `{path}` has no operationId"
+				);
+				testing::assert!(
+					seen.insert(operation_id),
+					"an operation on `{path}` reuses operationId `{operation_id}`",
+				);
+			}
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn yaml_matches_json() -> color_eyre::Result<()>
+	{
+		// `utoipa::openapi::OpenApi` can't deserialize every schema it
+		// serializes (e.g. untyped `serde_json::Value` fields), so we compare
+		// the documents structurally instead.
+		let public_url = Url::parse(DEFAULT_PUBLIC_URL)?;
+		let from_json = serde_json::from_str::<serde_json::Value>(&Schema::json(&public_url))?;
+		let from_yaml = serde_yaml::from_str::<serde_json::Value>(&Schema::yaml(&public_url))?;
+
+		testing::assert!(from_json == from_yaml);
+
+		Ok(())
+	}
+
+	#[test]
+	fn error_responses_refer_to_problem_details() -> color_eyre::Result<()>
+	{
+		let spec = serde_json::to_value(Schema::openapi())?;
+		let components = &spec["components"];
+
+		testing::assert!(components["schemas"]["ProblemDetails"].is_object());
+
+		let not_found = &components["responses"]["NotFound"]["content"]["application/problem+json"];
+
+		testing::assert_eq!(not_found["schema"]["$ref"], "#/components/schemas/ProblemDetails");
+		testing::assert_eq!(not_found["example"]["title"], ProblemType::ResourceNotFound.title());
+
+		testing::assert_eq!(
+			spec["paths"]["/maps/{map}"]["get"]["responses"]["404"]["$ref"],
+			"#/components/responses/NotFound"
+		);
+
+		Ok(())
 	}
 }
@@ -0,0 +1,27 @@
+//! Problem Details modifier for the OpenAPI spec.
+
+use utoipa::openapi::OpenApi;
+use utoipa::ToSchema;
+
+use crate::http::problem_details::utoipa_impls::common_responses;
+use crate::http::ProblemDetails;
+
+/// Problem Details modifier for the OpenAPI spec.
+///
+/// Registers the [`ProblemDetails`] schema and the response components for
+/// common problems, which the error responses of every operation refer to.
+pub struct Problems;
+
+impl utoipa::Modify for Problems
+{
+	fn modify(&self, openapi: &mut OpenApi)
+	{
+		let components = openapi.components.get_or_insert_with(Default::default);
+		let (name, schema) = ProblemDetails::schema();
+
+		components.schemas.insert(name.to_owned(), schema);
+		components
+			.responses
+			.extend(common_responses().map(|(name, response)| (name.to_owned(), response.into())));
+	}
+}
@@ -0,0 +1,106 @@
+//! `servers` modifier for the OpenAPI spec.
+
+use url::Url;
+use utoipa::openapi::server::Server;
+use utoipa::openapi::OpenApi;
+
+/// The public URL of the production API.
+///
+/// This is used if no other URL is specified when generating the spec.
+pub const DEFAULT_PUBLIC_URL: &str = "https://api.cs2kz.org";
+
+/// The URL the API listens on when running locally with the example config.
+pub const LOCAL_URL: &str = "http://127.0.0.1:42069";
+
+/// `servers` modifier for the OpenAPI spec.
+///
+/// Adds the public URL and a local development URL to the top-level `servers`
+/// list.
+#[derive(Debug, Clone)]
+pub struct Servers
+{
+	/// The public URL of the API.
+	pub public_url: Url,
+}
+
+impl utoipa::Modify for Servers
+{
+	fn modify(&self, openapi: &mut OpenApi)
+	{
+		let public_url = self.public_url.as_str().trim_end_matches('/');
+		let mut servers = Vec::with_capacity(2);
+
+		if public_url != LOCAL_URL {
+			let mut public = Server::new(public_url);
+			public.description = Some(String::from(describe(public_url)));
+			servers.push(public);
+		}
+
+		let mut local = Server::new(LOCAL_URL);
+		local.description = Some(String::from("local"));
+		servers.push(local);
+
+		openapi.servers = Some(servers);
+	}
+}
+
+/// Returns a description for the server listening on `public_url`.
+fn describe(public_url: &str) -> &'static str
+{
+	match public_url {
+		DEFAULT_PUBLIC_URL => "production",
+		_ => "public",
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use serde_json::json;
+
+	use super::*;
+	use crate::openapi::Schema;
+	use crate::testing;
+
+	#[test]
+	fn spec_contains_servers() -> color_eyre::Result<()>
+	{
+		let public_url = Url::parse(DEFAULT_PUBLIC_URL)?;
+		let spec = serde_json::from_str::<serde_json::Value>(&Schema::json(&public_url))?;
+
+		testing::assert_eq!(
+			spec["servers"],
+			json!([
+				{ "url": DEFAULT_PUBLIC_URL, "description": "production" },
+				{ "url": LOCAL_URL, "description": "local" },
+			])
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn other_deployments_are_not_production() -> color_eyre::Result<()>
+	{
+		let public_url = Url::parse("https://staging.cs2kz.org/")?;
+		let spec = serde_json::from_str::<serde_json::Value>(&Schema::json(&public_url))?;
+
+		testing::assert_eq!(
+			spec["servers"][0],
+			json!({ "url": "https://staging.cs2kz.org", "description": "public" })
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn local_url_is_listed_once() -> color_eyre::Result<()>
+	{
+		let public_url = Url::parse(LOCAL_URL)?;
+		let spec = serde_json::from_str::<serde_json::Value>(&Schema::json(&public_url))?;
+
+		testing::assert_eq!(spec["servers"], json!([{ "url": LOCAL_URL, "description": "local" }]));
+
+		Ok(())
+	}
+}
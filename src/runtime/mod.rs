@@ -6,3 +6,6 @@ pub mod panic_hook;
 
 pub mod config;
 pub use config::Config;
+
+pub mod reload;
+pub use reload::Reloader;
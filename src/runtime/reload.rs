@@ -0,0 +1,317 @@
+//! Reloading the config file at runtime.
+//!
+//! When the process receives `SIGHUP`, [`Reloader::reload()`] re-reads the
+//! config file and applies the parts of it that can safely change while the
+//! server is running:
+//!
+//! - `tracing.filter`
+//! - `http.rate-limit`
+//! - `servers.heartbeat-window`
+//!
+//! Changes to anything else are logged, but only take effect after a restart.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::{env, fs};
+
+use tracing_subscriber::EnvFilter;
+
+use super::config::{Config, LoadConfigError};
+use crate::middleware::RateLimitLayer;
+use crate::services::ServerService;
+
+/// Config keys that can be applied without a restart.
+///
+/// Any key nested inside one of these counts as well.
+const RELOADABLE: [&str; 3] = ["tracing.filter", "http.rate-limit", "servers.heartbeat-window"];
+
+/// Callback for applying a new global tracing filter.
+type ApplyFilter = Box<dyn Fn(EnvFilter) -> Result<(), String> + Send + Sync>;
+
+/// Handles to the parts of the running server that can be reconfigured.
+///
+/// This is returned by [`server()`](crate::server()).
+#[derive(Clone)]
+pub struct Handle
+{
+	/// The rate limiting middleware.
+	rate_limit: RateLimitLayer,
+
+	/// The server service, which owns the heartbeat window.
+	server_svc: ServerService,
+}
+
+impl std::fmt::Debug for Handle
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		f.debug_struct("Handle").finish_non_exhaustive()
+	}
+}
+
+impl Handle
+{
+	/// Creates a new [`Handle`].
+	pub(crate) fn new(rate_limit: RateLimitLayer, server_svc: ServerService) -> Self
+	{
+		Self { rate_limit, server_svc }
+	}
+}
+
+/// Re-reads the config file and applies its reloadable subset.
+pub struct Reloader
+{
+	/// The config file.
+	path: PathBuf,
+
+	/// Handles to the running server.
+	handle: Handle,
+
+	/// Applies a new global tracing filter, if tracing is enabled.
+	apply_filter: Option<ApplyFilter>,
+
+	/// The config file as it was last loaded.
+	current: Mutex<toml::Table>,
+
+	/// Used for looking up environment variables.
+	env: fn(&str) -> Option<String>,
+}
+
+impl std::fmt::Debug for Reloader
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		f.debug_struct("Reloader")
+			.field("path", &self.path)
+			.finish_non_exhaustive()
+	}
+}
+
+/// The outcome of a successful [`Reloader::reload()`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReloadReport
+{
+	/// Changed keys that have been applied.
+	pub applied: Vec<String>,
+
+	/// Changed keys that only take effect after a restart.
+	pub requires_restart: Vec<String>,
+}
+
+impl Reloader
+{
+	/// Creates a new [`Reloader`] for the config file at `path`.
+	///
+	/// The file is read immediately, so later reloads can tell what changed.
+	pub fn new(path: impl Into<PathBuf>, handle: Handle) -> Result<Self, LoadConfigError>
+	{
+		let path = path.into();
+		let file = fs::read_to_string(&path).map_err(LoadConfigError::ReadFile)?;
+		let current = toml::from_str(&file).map_err(LoadConfigError::ParseFile)?;
+
+		Ok(Self {
+			path,
+			handle,
+			apply_filter: None,
+			current: Mutex::new(current),
+			env: |name| env::var(name).ok(),
+		})
+	}
+
+	/// Sets the callback used for applying a new global tracing filter.
+	pub fn apply_filter<F>(mut self, apply_filter: F) -> Self
+	where
+		F: Fn(EnvFilter) -> Result<(), String> + Send + Sync + 'static,
+	{
+		self.apply_filter = Some(Box::new(apply_filter));
+		self
+	}
+
+	/// Re-reads the config file and applies every reloadable setting that
+	/// changed.
+	///
+	/// Nothing is applied if the new file is invalid.
+	#[tracing::instrument(target = "cs2kz_api::runtime", skip(self), fields(path = ?self.path))]
+	pub fn reload(&self) -> Result<ReloadReport, LoadConfigError>
+	{
+		let file = fs::read_to_string(&self.path).map_err(LoadConfigError::ReadFile)?;
+		let new_table = toml::from_str::<toml::Table>(&file).map_err(LoadConfigError::ParseFile)?;
+		let config = Config::parse(&file, self.env)?;
+
+		let mut current = self.current.lock().expect("mutex is not poisoned");
+		let mut changed = Vec::new();
+
+		diff(&mut changed, "", &current, &new_table);
+
+		let (applied, requires_restart) = changed
+			.into_iter()
+			.partition::<Vec<_>, _>(|key| is_reloadable(key));
+
+		let is_changed = |prefix: &str| applied.iter().any(|key| key.starts_with(prefix));
+
+		if is_changed("tracing.filter") {
+			if let Some(apply_filter) = &self.apply_filter {
+				if let Err(error) = apply_filter(config.tracing.filter) {
+					tracing::error!(%error, "failed to apply new tracing filter");
+				}
+			}
+		}
+
+		if is_changed("http.rate-limit") {
+			self.handle.rate_limit.reload(config.http.rate_limit);
+		}
+
+		if is_changed("servers.heartbeat-window") {
+			let window = Duration::from_secs(config.servers.heartbeat_window.get());
+			self.handle.server_svc.set_heartbeat_window(window);
+		}
+
+		*current = new_table;
+		drop(current);
+
+		if !applied.is_empty() {
+			tracing::info!(?applied, "applied config changes");
+		}
+
+		if !requires_restart.is_empty() {
+			tracing::warn!(?requires_restart, "some config changes require a restart");
+		}
+
+		Ok(ReloadReport { applied, requires_restart })
+	}
+}
+
+/// Returns whether changes to `key` can be applied without a restart.
+fn is_reloadable(key: &str) -> bool
+{
+	RELOADABLE.iter().any(|reloadable| {
+		key.strip_prefix(reloadable)
+			.is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+	})
+}
+
+/// Collects the dotted paths of all keys that differ between `old` and `new`.
+///
+/// Tables are compared key by key, anything else is compared as a whole.
+fn diff(changed: &mut Vec<String>, prefix: &str, old: &toml::Table, new: &toml::Table)
+{
+	let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+
+	keys.sort_unstable();
+	keys.dedup();
+
+	for key in keys {
+		let path = if prefix.is_empty() {
+			key.clone()
+		} else {
+			format!("{prefix}.{key}")
+		};
+
+		match (old.get(key), new.get(key)) {
+			(Some(toml::Value::Table(old)), Some(toml::Value::Table(new))) => {
+				diff(changed, &path, old, new);
+			}
+			(old, new) if old != new => changed.push(path),
+			_ => {}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use tracing_subscriber::layer::SubscriberExt;
+	use tracing_subscriber::{reload, Layer};
+
+	use super::*;
+	use crate::testing;
+
+	/// The example config shipped with the repository.
+	const EXAMPLE: &str = include_str!("../../.config/config.example.toml");
+
+	/// Creates a [`Handle`] for services that never touch the database.
+	fn handle() -> Handle
+	{
+		let database = testing::lazy_pool();
+		let auth_svc = testing::auth_svc(database.clone());
+		let server_svc = testing::server_svc(database);
+		let rate_limit = RateLimitLayer::new(Default::default(), auth_svc);
+
+		Handle::new(rate_limit, server_svc)
+	}
+
+	#[tokio::test]
+	async fn reload_applies_new_filter() -> color_eyre::Result<()>
+	{
+		let path = env::temp_dir().join(format!("cs2kz-api-{}-reload.toml", std::process::id()));
+		let original = EXAMPLE.replace(
+			r#"filter = "cs2kz_api=trace,cs2kz_api::runtime=info,sqlx=debug,warn""#,
+			r#"filter = "warn""#,
+		);
+
+		fs::write(&path, &original)?;
+
+		let (filter, filter_handle) = reload::Layer::new(EnvFilter::new("warn"));
+		let buffer = testing::LogBuffer::default();
+		let subscriber = tracing_subscriber::registry().with(
+			tracing_subscriber::fmt::layer()
+				.with_ansi(false)
+				.with_writer(buffer.clone())
+				.with_filter(filter),
+		);
+
+		let mut reloader = Reloader::new(&path, handle())?.apply_filter(move |filter| {
+			filter_handle
+				.reload(filter)
+				.map_err(|error| error.to_string())
+		});
+
+		reloader.env = |name| (name == "DATABASE_URL").then(|| String::from("mysql://localhost"));
+
+		let report = tracing::subscriber::with_default(subscriber, || {
+			tracing::info!("before reload");
+
+			fs::write(&path, original.replace(r#"filter = "warn""#, r#"filter = "info""#))?;
+
+			let report = reloader.reload()?;
+
+			tracing::info!("after reload");
+
+			color_eyre::Result::<_>::Ok(report)
+		})?;
+
+		let logs = buffer.contents();
+
+		testing::assert_eq!(report.applied, [String::from("tracing.filter")]);
+		testing::assert!(report.requires_restart.is_empty());
+		testing::assert!(!logs.contains("before reload"));
+		testing::assert!(logs.contains("after reload"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn diff_classifies_keys() -> color_eyre::Result<()>
+	{
+		let old = toml::from_str::<toml::Table>(
+			"[http]\nlisten-port = 1\n[http.rate-limit]\nenable = \
+			 true\n[servers]\nheartbeat-window = 1",
+		)?;
+		let new = toml::from_str::<toml::Table>(
+			"[http]\nlisten-port = 2\n[http.rate-limit]\nenable = \
+			 false\n[servers]\nheartbeat-window = 1",
+		)?;
+
+		let mut changed = Vec::new();
+
+		diff(&mut changed, "", &old, &new);
+
+		testing::assert_eq!(changed, ["http.listen-port", "http.rate-limit.enable"]);
+		testing::assert!(!is_reloadable("http.listen-port"));
+		testing::assert!(is_reloadable("http.rate-limit.enable"));
+		testing::assert!(!is_reloadable("http.rate-limiter"));
+
+		Ok(())
+	}
+}
@@ -1,6 +1,7 @@
 //! This module contains OS signal handlers.
 //!
-//! This is currently only used for graceful shutdown on SIGINT.
+//! `SIGINT` triggers a graceful shutdown, `SIGHUP` reloads the config file (see
+//! [`Reloader`](super::Reloader)).
 
 use tokio::signal;
 
@@ -14,3 +15,31 @@ pub async fn sigint()
 		Err(error) => tracing::error!(%error, "failed to receive SIGINT"),
 	}
 }
+
+/// Calls [`Reloader::reload()`] every time the program receives a `SIGHUP`
+/// signal from the OS.
+///
+/// The returned future only resolves if the signal handler could not be
+/// installed.
+///
+/// [`Reloader::reload()`]: super::Reloader::reload
+#[cfg(unix)]
+#[tracing::instrument(skip(reloader))]
+pub async fn reload_on_sighup(reloader: super::Reloader)
+{
+	let mut sighup = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+		Ok(sighup) => sighup,
+		Err(error) => {
+			tracing::error!(%error, "failed to install SIGHUP handler");
+			return;
+		}
+	};
+
+	while sighup.recv().await.is_some() {
+		tracing::info!("received SIGHUP, reloading config");
+
+		if let Err(error) = reloader.reload() {
+			tracing::error!(%error, "failed to reload config; keeping the old one");
+		}
+	}
+}
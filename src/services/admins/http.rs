@@ -15,6 +15,8 @@ use super::{
 	FetchAdminsResponse,
 	SetPermissionsRequest,
 	SetPermissionsResponse,
+	UpdatePermissionsRequest,
+	UpdatePermissionsResponse,
 };
 use crate::http::extract::{Json, Path, Query};
 use crate::http::ProblemDetails;
@@ -42,8 +44,17 @@ impl From<AdminService> for Router
 			.with_state(svc.clone());
 
 		let protected = Router::new()
-			.route("/:steam_id", routing::put(set_permissions).route_layer(auth))
-			.route_layer(middleware::cors::dashboard([http::Method::OPTIONS, http::Method::PUT]))
+			.route(
+				"/:steam_id",
+				routing::put(set_permissions)
+					.patch(update_permissions)
+					.route_layer(auth),
+			)
+			.route_layer(middleware::cors::dashboard([
+				http::Method::OPTIONS,
+				http::Method::PUT,
+				http::Method::PATCH,
+			]))
 			.with_state(svc.clone());
 
 		public.merge(protected)
@@ -124,3 +135,87 @@ async fn set_permissions(
 
 	Ok(res)
 }
+
+/// Request payload for the `update_permissions` handler.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[schema(title = "UpdatePermissionsRequest")]
+#[doc(hidden)]
+pub(crate) struct UpdatePermissionsPayload
+{
+	/// Permissions to add to the user's existing permissions.
+	#[serde(default)]
+	grant: Permissions,
+
+	/// Permissions to take away from the user.
+	///
+	/// If a permission is both granted and revoked, it is revoked.
+	#[serde(default)]
+	revoke: Permissions,
+}
+
+/// Grant and revoke individual permissions.
+#[tracing::instrument(level = "trace", err(Debug, level = "debug"))]
+#[utoipa::path(
+  patch,
+  path = "/admins/{admin_id}",
+  tag = "Admins",
+  operation_id = "update_admin_permissions",
+  params(("admin_id" = SteamID, Path, description = "an admin's SteamID")),
+  security(("Browser Session" = ["admin"])),
+)]
+async fn update_permissions(
+	session: Session,
+	State(svc): State<AdminService>,
+	Path(user_id): Path<SteamID>,
+	Json(UpdatePermissionsPayload { grant, revoke }): Json<UpdatePermissionsPayload>,
+) -> Result<UpdatePermissionsResponse, ProblemDetails>
+{
+	let req = UpdatePermissionsRequest { user_id, grant, revoke };
+	let res = svc.update_permissions(req).await?;
+
+	Ok(res)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use axum::extract::{FromRequest, Request};
+	use axum::response::IntoResponse;
+
+	use super::*;
+	use crate::testing;
+
+	/// Runs the [`Json`] extractor on a request with the given body, returning
+	/// the status code of the rejection if it fails.
+	async fn extract(body: &'static str) -> Result<UpdatePermissionsPayload, http::StatusCode>
+	{
+		let req = Request::builder()
+			.method(http::Method::PATCH)
+			.header(http::header::CONTENT_TYPE, "application/json")
+			.body(body.into())
+			.expect("valid request");
+
+		Json::<UpdatePermissionsPayload>::from_request(req, &())
+			.await
+			.map(|Json(payload)| payload)
+			.map_err(|rejection| rejection.into_response().status())
+	}
+
+	#[tokio::test]
+	async fn rejects_unknown_permission() -> color_eyre::Result<()>
+	{
+		let valid = extract(r#"{ "grant": ["bans", "maps"], "revoke": ["admin"] }"#).await;
+
+		testing::assert_matches!(
+			valid,
+			Ok(UpdatePermissionsPayload { grant, revoke })
+				if grant == Permissions::BANS | Permissions::MAPS && revoke == Permissions::ADMIN
+		);
+
+		let unknown = extract(r#"{ "grant": ["bans", "superuser"] }"#).await;
+
+		testing::assert_matches!(unknown, Err(http::StatusCode::UNPROCESSABLE_ENTITY));
+
+		Ok(())
+	}
+}
@@ -23,6 +23,8 @@ pub use models::{
 	FetchAdminsResponse,
 	SetPermissionsRequest,
 	SetPermissionsResponse,
+	UpdatePermissionsRequest,
+	UpdatePermissionsResponse,
 };
 
 /// A service for managing KZ admins.
@@ -147,4 +149,135 @@ impl AdminService
 
 		Ok(SetPermissionsResponse { _priv: () })
 	}
+
+	/// Grant and revoke individual permissions, keeping all others as-is.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn update_permissions(
+		&self,
+		req: UpdatePermissionsRequest,
+	) -> Result<UpdatePermissionsResponse>
+	{
+		let mut txn = self.database.begin().await?;
+
+		let current = sqlx::query_scalar! {
+			r"
+			SELECT
+			  permissions `permissions: user::Permissions`
+			FROM
+			  Players
+			WHERE
+			  id = ?
+			FOR UPDATE
+			",
+			req.user_id,
+		}
+		.fetch_optional(txn.as_mut())
+		.await?
+		.ok_or(Error::UserDoesNotExist { user_id: req.user_id })?;
+
+		let permissions = (current | req.grant) & !req.revoke;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  Players
+			SET
+			  permissions = ?
+			WHERE
+			  id = ?
+			",
+			permissions,
+			req.user_id,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		txn.commit().await?;
+
+		tracing::info! {
+			user_id = %req.user_id,
+			granted = %req.grant,
+			revoked = %req.revoke,
+			%permissions,
+			"updated permissions for user",
+		};
+
+		Ok(UpdatePermissionsResponse { permissions })
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use sqlx::{MySql, Pool};
+
+	use super::*;
+	use crate::services::auth::session::user::Permissions;
+	use crate::testing::{self, ALPHAKEKS_ID};
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/session.sql")
+	)]
+	async fn grant_permissions(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::admin_svc(database);
+		let req = UpdatePermissionsRequest {
+			user_id: ALPHAKEKS_ID,
+			grant: Permissions::SERVERS,
+			revoke: Permissions::NONE,
+		};
+
+		let res = svc.update_permissions(req).await?;
+
+		testing::assert_eq!(res.permissions, Permissions::ALL);
+
+		let stored = svc
+			.fetch_admin(FetchAdminRequest { user_id: ALPHAKEKS_ID })
+			.await?
+			.map(|admin| admin.permissions);
+
+		testing::assert_eq!(stored, Some(Permissions::ALL));
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/session.sql")
+	)]
+	async fn revoke_permissions(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::admin_svc(database);
+		let req = UpdatePermissionsRequest {
+			user_id: ALPHAKEKS_ID,
+			grant: Permissions::BANS,
+			revoke: Permissions::BANS | Permissions::MAPS,
+		};
+
+		let res = svc.update_permissions(req).await?;
+		let expected = Permissions::RECORDS | Permissions::ADMIN;
+
+		testing::assert_eq!(res.permissions, expected);
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn update_permissions_of_unknown_user(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::admin_svc(database);
+		let user_id = SteamID::new(76561197960265729_u64).unwrap();
+		let req = UpdatePermissionsRequest {
+			user_id,
+			grant: Permissions::BANS,
+			revoke: Permissions::NONE,
+		};
+
+		let res = svc.update_permissions(req).await;
+
+		testing::assert_matches!(res, Err(Error::UserDoesNotExist { .. }));
+
+		Ok(())
+	}
 }
@@ -105,3 +105,36 @@ impl IntoResponse for SetPermissionsResponse
 }
 
 crate::openapi::responses::no_content!(SetPermissionsResponse);
+
+/// Request payload for granting and revoking individual permissions.
+#[derive(Debug)]
+pub struct UpdatePermissionsRequest
+{
+	/// The user's SteamID.
+	pub user_id: SteamID,
+
+	/// Permissions to add to the user's existing permissions.
+	pub grant: Permissions,
+
+	/// Permissions to take away from the user.
+	///
+	/// If a permission is both granted and revoked, it is revoked.
+	pub revoke: Permissions,
+}
+
+/// Response payload for granting and revoking individual permissions.
+#[derive(Debug, Serialize, utoipa::ToSchema, utoipa::IntoResponses)]
+#[response(status = OK)]
+pub struct UpdatePermissionsResponse
+{
+	/// The user's permissions after the update.
+	pub permissions: Permissions,
+}
+
+impl IntoResponse for UpdatePermissionsResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
@@ -0,0 +1,57 @@
+//! HTTP handlers for this service.
+
+use axum::extract::State;
+use axum::{routing, Router};
+use tower::ServiceBuilder;
+
+use super::{AuditService, Error, FetchAuditLogRequest, FetchAuditLogResponse};
+use crate::http::extract::Query;
+use crate::http::ProblemDetails;
+use crate::middleware;
+use crate::services::auth::session::authorization::RequiredPermissions;
+use crate::services::auth::session::user::Permissions;
+use crate::services::auth::session::SessionManagerLayer;
+use crate::services::auth::Session;
+
+impl From<AuditService> for Router
+{
+	fn from(svc: AuditService) -> Self
+	{
+		let auth = ServiceBuilder::new()
+			.layer(middleware::InfallibleLayer::new())
+			.layer(SessionManagerLayer::with_strategy(
+				svc.auth_svc.clone(),
+				RequiredPermissions(Permissions::ADMIN),
+			));
+
+		Router::new()
+			.route("/", routing::get(get_many).route_layer(auth))
+			.route_layer(middleware::cors::dashboard([http::Method::OPTIONS, http::Method::GET]))
+			.with_state(svc.clone())
+	}
+}
+
+/// Fetch audit log entries.
+#[tracing::instrument(level = "trace", err(Debug, level = "debug"))]
+#[utoipa::path(
+  get,
+  path = "/audit",
+  tag = "Audit Log",
+  operation_id = "get_audit_log",
+  params(FetchAuditLogRequest),
+  security(("Browser Session" = ["admin"])),
+)]
+async fn get_many(
+	session: Session,
+	State(svc): State<AuditService>,
+	Query(req): Query<FetchAuditLogRequest>,
+) -> Result<FetchAuditLogResponse, ProblemDetails>
+{
+	let res = svc.fetch_entries(req).await?;
+
+	if res.entries.is_empty() {
+		return Err(Error::NoData.into());
+	}
+
+	Ok(res)
+}
@@ -0,0 +1,248 @@
+//! A service for recording and querying changes made through the API.
+//!
+//! Other services write entries into the audit log as part of the same
+//! transaction that performs the change, using [`record()`]. This service only
+//! exposes them for reading.
+
+use std::fmt;
+
+use axum::extract::FromRef;
+use cs2kz::SteamID;
+use sqlx::types::Json;
+use sqlx::{MySql, Pool, Transaction};
+use time::OffsetDateTime;
+
+use crate::database::TransactionExt;
+use crate::services::AuthService;
+
+pub(crate) mod http;
+
+mod error;
+pub use error::{Error, Result};
+
+pub(crate) mod models;
+pub use models::{
+	AuditAction,
+	AuditLogEntry,
+	AuditLogID,
+	EntityType,
+	FetchAuditLogRequest,
+	FetchAuditLogResponse,
+};
+
+/// A service for recording and querying changes made through the API.
+#[derive(Clone, FromRef)]
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct AuditService
+{
+	database: Pool<MySql>,
+	auth_svc: AuthService,
+}
+
+impl fmt::Debug for AuditService
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		f.debug_struct("AuditService").finish_non_exhaustive()
+	}
+}
+
+impl AuditService
+{
+	/// Create a new [`AuditService`].
+	#[tracing::instrument]
+	pub fn new(database: Pool<MySql>, auth_svc: AuthService) -> Self
+	{
+		Self { database, auth_svc }
+	}
+
+	/// Fetches audit log entries, newest first.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn fetch_entries(&self, req: FetchAuditLogRequest) -> Result<FetchAuditLogResponse>
+	{
+		let mut txn = self.database.begin().await?;
+
+		let entries = sqlx::query_as! {
+			AuditLogEntry,
+			r"
+			SELECT
+			  SQL_CALC_FOUND_ROWS id `id: AuditLogID`,
+			  actor_id `actor_id: SteamID`,
+			  entity_type `entity_type: EntityType`,
+			  entity_id,
+			  action `action: AuditAction`,
+			  details `details: Json<serde_json::Value>`,
+			  created_on `created_on: OffsetDateTime`
+			FROM
+			  AuditLog
+			WHERE
+			  (? IS NULL OR actor_id = ?)
+			  AND entity_type = COALESCE(?, entity_type)
+			  AND entity_id = COALESCE(?, entity_id)
+			  AND created_on > COALESCE(?, '1970-01-01 00:00:01')
+			  AND created_on < COALESCE(?, '2038-01-19 03:14:07')
+			ORDER BY
+			  created_on DESC,
+			  id DESC
+			LIMIT
+			  ? OFFSET ?
+			",
+			req.actor_id,
+			req.actor_id,
+			req.entity_type,
+			req.entity_id,
+			req.created_after,
+			req.created_before,
+			*req.limit,
+			*req.offset,
+		}
+		.fetch_all(txn.as_mut())
+		.await?;
+
+		let total = txn.total_rows().await?;
+
+		txn.commit().await?;
+
+		Ok(FetchAuditLogResponse { entries, total })
+	}
+}
+
+/// Records a change in the audit log.
+///
+/// This should be called inside the same transaction that performs the change,
+/// so the entry only persists if the change does.
+#[tracing::instrument(level = "debug", skip(txn), err(Debug, level = "debug"))]
+pub(crate) async fn record(
+	txn: &mut Transaction<'_, MySql>,
+	actor_id: Option<SteamID>,
+	action: AuditAction,
+	entity_id: u64,
+	details: serde_json::Value,
+) -> sqlx::Result<()>
+{
+	sqlx::query! {
+		r"
+		INSERT INTO
+		  AuditLog (actor_id, entity_type, entity_id, action, details)
+		VALUES
+		  (?, ?, ?, ?, ?)
+		",
+		actor_id,
+		action.entity_type(),
+		entity_id,
+		action,
+		Json(details),
+	}
+	.execute(txn.as_mut())
+	.await?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests
+{
+	use sqlx::{MySql, Pool};
+	use time::OffsetDateTime;
+
+	use super::*;
+	use crate::services::maps::{MapID, UpdateMapRequest};
+	use crate::testing::{self, ALPHAKEKS_ID};
+
+	/// Updates kz_checkmate's description as [`ALPHAKEKS_ID`] and returns its
+	/// ID.
+	async fn update_checkmate(database: &Pool<MySql>) -> color_eyre::Result<MapID>
+	{
+		let map_svc = testing::map_svc(database.clone());
+		let map_id =
+			sqlx::query_scalar::<_, MapID>("SELECT id FROM Maps WHERE name = 'kz_checkmate'")
+				.fetch_one(database)
+				.await?;
+
+		let req = UpdateMapRequest {
+			map_id,
+			description: Some(String::from("a new description")),
+			workshop_id: None,
+			global_status: None,
+			check_steam: false,
+			added_mappers: None,
+			removed_mappers: None,
+			course_updates: None,
+			expected_checksum: None,
+			actor_id: Some(ALPHAKEKS_ID),
+		};
+
+		map_svc.update_map(req).await?;
+
+		Ok(map_id)
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/checkmate.sql")
+	)]
+	async fn updating_a_map_is_recorded(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let map_id = update_checkmate(&database).await?;
+		let svc = testing::audit_svc(database);
+		let req = FetchAuditLogRequest {
+			entity_type: Some(EntityType::Map),
+			entity_id: Some(u64::from(map_id.0)),
+			..Default::default()
+		};
+
+		let res = svc.fetch_entries(req).await?;
+
+		testing::assert_eq!(res.total, 1);
+
+		let entry = &res.entries[0];
+
+		testing::assert_eq!(entry.action, AuditAction::MapUpdated);
+		testing::assert_eq!(entry.entity_id, u64::from(map_id.0));
+		testing::assert_eq!(entry.actor_id, Some(ALPHAKEKS_ID));
+		testing::assert_eq!(entry.details["description"], "a new description");
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/checkmate.sql")
+	)]
+	async fn fetch_entries_filters(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		update_checkmate(&database).await?;
+
+		let svc = testing::audit_svc(database);
+		let now = OffsetDateTime::now_utc();
+
+		let by_actor = svc
+			.fetch_entries(FetchAuditLogRequest {
+				actor_id: Some(ALPHAKEKS_ID),
+				..Default::default()
+			})
+			.await?;
+
+		testing::assert_eq!(by_actor.total, 1);
+
+		let by_other_type = svc
+			.fetch_entries(FetchAuditLogRequest {
+				entity_type: Some(EntityType::Server),
+				..Default::default()
+			})
+			.await?;
+
+		testing::assert!(by_other_type.entries.is_empty());
+
+		let in_the_future = svc
+			.fetch_entries(FetchAuditLogRequest {
+				created_after: Some(now + time::Duration::HOUR),
+				..Default::default()
+			})
+			.await?;
+
+		testing::assert!(in_the_future.entries.is_empty());
+
+		Ok(())
+	}
+}
@@ -0,0 +1,226 @@
+//! Request / Response types for this service.
+
+use std::str::FromStr;
+
+use axum::response::{IntoResponse, Response};
+use cs2kz::SteamID;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use thiserror::Error;
+use time::OffsetDateTime;
+
+use crate::num::ClampedU64;
+
+crate::macros::make_id! {
+	/// A unique identifier for an audit log entry.
+	AuditLogID as u64
+}
+
+/// The kinds of entities changes are recorded for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType
+{
+	/// A map.
+	Map,
+
+	/// A server.
+	Server,
+}
+
+impl EntityType
+{
+	/// Returns a string representation of this entity type.
+	pub fn as_str(&self) -> &'static str
+	{
+		match self {
+			Self::Map => "map",
+			Self::Server => "server",
+		}
+	}
+}
+
+impl FromStr for EntityType
+{
+	type Err = UnknownVariant;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err>
+	{
+		match s {
+			"map" => Ok(Self::Map),
+			"server" => Ok(Self::Server),
+			unknown => Err(UnknownVariant(unknown.to_owned())),
+		}
+	}
+}
+
+crate::macros::sqlx_scalar_forward!(EntityType as String => {
+	encode: |self| { self.as_str().to_owned() },
+	decode: |value| { value.parse()? },
+});
+
+/// The changes that get recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction
+{
+	/// A new map was approved.
+	MapCreated,
+
+	/// A map's metadata (description, global status, etc.) was updated.
+	MapUpdated,
+
+	/// Mappers were added to a map.
+	MappersAdded,
+
+	/// Mappers were removed from a map.
+	MappersRemoved,
+
+	/// A map's courses or their filters were updated.
+	CoursesUpdated,
+
+	/// A server was updated.
+	ServerUpdated,
+}
+
+impl AuditAction
+{
+	/// Returns a string representation of this action.
+	pub fn as_str(&self) -> &'static str
+	{
+		match self {
+			Self::MapCreated => "map_created",
+			Self::MapUpdated => "map_updated",
+			Self::MappersAdded => "mappers_added",
+			Self::MappersRemoved => "mappers_removed",
+			Self::CoursesUpdated => "courses_updated",
+			Self::ServerUpdated => "server_updated",
+		}
+	}
+
+	/// Returns the kind of entity this action is performed on.
+	pub fn entity_type(&self) -> EntityType
+	{
+		match self {
+			Self::MapCreated
+			| Self::MapUpdated
+			| Self::MappersAdded
+			| Self::MappersRemoved
+			| Self::CoursesUpdated => EntityType::Map,
+			Self::ServerUpdated => EntityType::Server,
+		}
+	}
+}
+
+impl FromStr for AuditAction
+{
+	type Err = UnknownVariant;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err>
+	{
+		match s {
+			"map_created" => Ok(Self::MapCreated),
+			"map_updated" => Ok(Self::MapUpdated),
+			"mappers_added" => Ok(Self::MappersAdded),
+			"mappers_removed" => Ok(Self::MappersRemoved),
+			"courses_updated" => Ok(Self::CoursesUpdated),
+			"server_updated" => Ok(Self::ServerUpdated),
+			unknown => Err(UnknownVariant(unknown.to_owned())),
+		}
+	}
+}
+
+crate::macros::sqlx_scalar_forward!(AuditAction as String => {
+	encode: |self| { self.as_str().to_owned() },
+	decode: |value| { value.parse()? },
+});
+
+/// The error returned when decoding an unknown [`EntityType`] or
+/// [`AuditAction`] from the database.
+#[derive(Debug, Error)]
+#[error("unknown variant `{0}`")]
+pub struct UnknownVariant(String);
+
+/// An entry in the audit log.
+#[derive(Debug, Serialize, sqlx::FromRow, utoipa::ToSchema)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct AuditLogEntry
+{
+	/// The entry's ID.
+	pub id: AuditLogID,
+
+	/// The user who made the change.
+	///
+	/// This is absent for changes made by the API itself.
+	pub actor_id: Option<SteamID>,
+
+	/// The kind of entity that was changed.
+	pub entity_type: EntityType,
+
+	/// The ID of the entity that was changed.
+	pub entity_id: u64,
+
+	/// What was changed.
+	pub action: AuditAction,
+
+	/// Additional information about the change.
+	#[schema(value_type = Object)]
+	pub details: Json<serde_json::Value>,
+
+	/// When the change was made.
+	#[serde(with = "time::serde::rfc3339")]
+	pub created_on: OffsetDateTime,
+}
+
+/// Request payload for fetching audit log entries.
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct FetchAuditLogRequest
+{
+	/// Filter by the user who made the change.
+	pub actor_id: Option<SteamID>,
+
+	/// Filter by the kind of entity that was changed.
+	pub entity_type: Option<EntityType>,
+
+	/// Filter by the ID of the entity that was changed.
+	pub entity_id: Option<u64>,
+
+	/// Only include changes made after this date.
+	#[serde(default, with = "time::serde::rfc3339::option")]
+	pub created_after: Option<OffsetDateTime>,
+
+	/// Only include changes made before this date.
+	#[serde(default, with = "time::serde::rfc3339::option")]
+	pub created_before: Option<OffsetDateTime>,
+
+	/// Maximum number of results to return.
+	#[serde(default)]
+	#[param(value_type = u64)]
+	pub limit: ClampedU64<100, 500>,
+
+	/// Pagination offset.
+	#[serde(default)]
+	#[param(value_type = u64)]
+	pub offset: ClampedU64,
+}
+
+/// Response payload for fetching audit log entries.
+#[derive(Debug, Serialize, utoipa::IntoResponses)]
+#[cfg_attr(test, derive(Deserialize))]
+#[response(status = OK)]
+pub struct FetchAuditLogResponse
+{
+	/// The entries, newest first.
+	pub entries: Vec<AuditLogEntry>,
+
+	/// How many entries **could have been** fetched, if there was no limit.
+	pub total: u64,
+}
+
+impl IntoResponse for FetchAuditLogResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
@@ -132,6 +132,14 @@ where
 		}
 	}
 
+	fn detail(&self) -> String
+	{
+		match self {
+			Self::Service(source) => source.detail(),
+			_ => self.to_string(),
+		}
+	}
+
 	fn add_extension_members(&self, ext: &mut problem_details::ExtensionMembers)
 	{
 		if let Self::Service(source) = self {
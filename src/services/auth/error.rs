@@ -35,6 +35,16 @@ pub enum Error
 		source: jsonwebtoken::errors::Error,
 	},
 
+	/// The refresh token is missing, unknown, or expired.
+	#[error("invalid refresh token")]
+	InvalidRefreshToken,
+
+	/// A refresh token was presented that has already been used.
+	///
+	/// Its entire family has been revoked in response.
+	#[error("refresh token has already been used")]
+	RefreshTokenReused,
+
 	/// An operation using the steam service failed.
 	#[error(transparent)]
 	Steam(#[from] steam::Error),
@@ -52,6 +62,7 @@ impl IntoProblemDetails for Error
 			Self::EncodeJwt { .. } => ProblemType::Internal,
 			Self::Database(source) => source.problem_type(),
 			Self::DecodeJwt { .. } => ProblemType::InvalidHeader,
+			Self::InvalidRefreshToken | Self::RefreshTokenReused => ProblemType::Unauthorized,
 			Self::Steam(source) => source.problem_type(),
 		}
 	}
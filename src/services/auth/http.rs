@@ -11,12 +11,16 @@ use serde::Deserialize;
 use time::OffsetDateTime;
 
 use super::{
+	refresh_token,
 	session,
 	AuthService,
+	Error,
 	LoginRequest,
 	LoginResponse,
 	LogoutRequest,
 	LogoutResponse,
+	RefreshResponse,
+	RefreshToken,
 	Session,
 };
 use crate::http::ProblemDetails;
@@ -34,8 +38,13 @@ impl From<AuthService> for Router
 
 		let cors = Router::new()
 			.route("/logout", routing::get(logout))
+			.route("/refresh", routing::post(refresh))
 			.route("/verify-session", routing::get(verify_session))
-			.route_layer(middleware::cors::dashboard([http::Method::OPTIONS, http::Method::GET]))
+			.route_layer(middleware::cors::dashboard([
+				http::Method::OPTIONS,
+				http::Method::GET,
+				http::Method::POST,
+			]))
 			.with_state(svc);
 
 		no_cors.merge(cors)
@@ -44,10 +53,21 @@ impl From<AuthService> for Router
 
 /// Login with Steam.
 #[tracing::instrument(ret(level = "Debug"))]
-#[utoipa::path(get, path = "/auth/login", tag = "Auth", params(LoginRequest))]
-async fn login(State(svc): State<AuthService>, Query(req): Query<LoginRequest>) -> LoginResponse
+#[utoipa::path(
+	get,
+	path = "/auth/login",
+	tag = "Auth",
+	operation_id = "login",
+	params(LoginRequest)
+)]
+async fn login(
+	State(svc): State<AuthService>,
+	Query(req): Query<LoginRequest>,
+) -> Result<LoginResponse, ProblemDetails>
 {
-	svc.login_url(req)
+	let res = svc.login_url(req)?;
+
+	Ok(res)
 }
 
 /// Query parameters for the [`logout`] handler.
@@ -62,7 +82,13 @@ struct LogoutQuery
 
 /// Invalidate your existing session(s).
 #[tracing::instrument(skip(cookies), err(Debug, level = "debug"))]
-#[utoipa::path(get, path = "/auth/logout", tag = "Auth", params(LogoutQuery))]
+#[utoipa::path(
+	get,
+	path = "/auth/logout",
+	tag = "Auth",
+	operation_id = "logout",
+	params(LogoutQuery)
+)]
 async fn logout(
 	session: Session,
 	State(svc): State<AuthService>,
@@ -82,26 +108,64 @@ async fn logout(
 		.expires(OffsetDateTime::now_utc())
 		.build();
 
-	let session_cookie = Cookie::build((session::COOKIE_NAME, ""))
+	let mut session_cookie = svc.cookie_config.apply(
+		Cookie::build((session::COOKIE_NAME, ""))
+			.domain((*svc.cookie_domain).to_owned())
+			.path("/")
+			.build(),
+	);
+
+	session_cookie.set_max_age(time::Duration::ZERO);
+	session_cookie.set_expires(OffsetDateTime::now_utc());
+
+	let refresh_cookie = Cookie::build((refresh_token::COOKIE_NAME, ""))
 		.domain((*svc.cookie_domain).to_owned())
-		.path("/")
-		.secure(cfg!(feature = "production"))
-		.same_site(SameSite::Lax)
+		.path("/auth")
+		.secure(svc.cookie_config.secure)
+		.same_site(SameSite::Strict)
 		.http_only(true)
 		.expires(OffsetDateTime::now_utc())
 		.build();
 
-	let cookies = cookies.add(user_cookie).add(session_cookie);
+	let cookies = cookies
+		.add(user_cookie)
+		.add(session_cookie)
+		.add(refresh_cookie);
 
 	Ok(LogoutResponse { cookies })
 }
 
+/// Exchange your refresh token for a new session.
+///
+/// Refresh tokens can only be used once; every call returns a new one along
+/// with the new session. If a refresh token is used a second time, every
+/// session created from the same login is invalidated.
+#[tracing::instrument(skip(cookies), err(Debug, level = "debug"))]
+#[utoipa::path(post, path = "/auth/refresh", tag = "Auth", operation_id = "refresh_session")]
+async fn refresh(
+	State(svc): State<AuthService>,
+	cookies: CookieJar,
+) -> Result<RefreshResponse, ProblemDetails>
+{
+	let refresh_token = cookies
+		.get(refresh_token::COOKIE_NAME)
+		.and_then(|cookie| cookie.value().parse::<RefreshToken>().ok())
+		.ok_or(Error::InvalidRefreshToken)?;
+
+	let (session, refresh_token) = svc.refresh(refresh_token).await?;
+	let cookies = cookies
+		.add(session.into_cookie(&*svc.cookie_domain, &svc.cookie_config))
+		.add(refresh_token.into_cookie(&*svc.cookie_domain, &svc.cookie_config));
+
+	Ok(RefreshResponse { cookies })
+}
+
 /// Verify whether a session token is still valid.
 ///
 /// This endpoint will return a status in the 4xx range if you do not have a
 /// valid session token, and a `200 OK` if you do.
 #[tracing::instrument]
-#[utoipa::path(get, path = "/auth/verify-session", tag = "Auth")]
+#[utoipa::path(get, path = "/auth/verify-session", tag = "Auth", operation_id = "verify_session")]
 async fn verify_session(session: Session)
 {
 	// We don't actually need to do anything here; the middleware will reject
@@ -120,12 +184,14 @@ async fn callback(
 {
 	let user = svc.steam_svc.fetch_user(openid_payload.steam_id()).await?;
 	let user_cookie = user.to_cookie(&*svc.cookie_domain);
-	let session_cookie = svc
+	let (session, refresh_token) = svc
 		.login(user.steam_id, user.username, req_addr.ip().into())
-		.await?
-		.into_cookie(&*svc.cookie_domain);
+		.await?;
 
-	let cookies = cookies.add(user_cookie).add(session_cookie);
+	let cookies = cookies
+		.add(user_cookie)
+		.add(session.into_cookie(&*svc.cookie_domain, &svc.cookie_config))
+		.add(refresh_token.into_cookie(&*svc.cookie_domain, &svc.cookie_config));
 	let redirect = Redirect::to(openid_payload.redirect_to.as_str());
 
 	Ok((cookies, redirect))
@@ -0,0 +1,222 @@
+//! This module contains the [`JwtKeys`] - the set of secrets used for signing
+//! and verifying JWTs.
+//!
+//! Every key has an optional ID, which is embedded in the `kid` header of every
+//! token signed with it. New tokens are always signed with the primary key,
+//! but any key in the set can still be used for verifying tokens. This allows
+//! rotating the primary key without invalidating every outstanding token at
+//! once: add a new primary, keep the old key around until its tokens have
+//! expired, then remove it.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use jsonwebtoken::errors::{Error, ErrorKind};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::Jwt;
+use crate::runtime::config::Secrets;
+
+/// A set of keys for signing and verifying JWTs.
+pub struct JwtKeys
+{
+	/// The ID of the key used for signing new tokens.
+	primary: Option<String>,
+
+	/// All the keys, including the primary one.
+	///
+	/// Tokens without a `kid` header are verified against the key without an
+	/// ID if there is one, and against the primary key otherwise.
+	keys: HashMap<Option<String>, Key>,
+
+	/// Validation rules for decoding.
+	validation: Validation,
+}
+
+/// A single key.
+#[allow(clippy::missing_docs_in_private_items)]
+struct Key
+{
+	encoding_key: EncodingKey,
+	decoding_key: DecodingKey,
+}
+
+impl Key
+{
+	/// Creates a new [`Key`] from a shared secret.
+	fn new(secret: &str) -> Self
+	{
+		Self {
+			encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+			decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+		}
+	}
+}
+
+impl fmt::Debug for JwtKeys
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		f.debug_struct("JwtKeys")
+			.field("primary", &self.primary)
+			.field("ids", &self.keys.keys().collect::<Vec<_>>())
+			.finish_non_exhaustive()
+	}
+}
+
+impl JwtKeys
+{
+	/// Creates a new [`JwtKeys`] with `secret` as the primary key.
+	pub fn new(id: Option<String>, secret: &str) -> Self
+	{
+		Self {
+			keys: HashMap::from_iter([(id.clone(), Key::new(secret))]),
+			primary: id,
+			validation: Validation::default(),
+		}
+	}
+
+	/// Adds a key that is only used for verifying tokens.
+	///
+	/// A key with the same ID as the primary key is ignored; any other key with
+	/// the same ID is replaced.
+	pub fn with_key(mut self, id: Option<String>, secret: &str) -> Self
+	{
+		if id != self.primary {
+			self.keys.insert(id, Key::new(secret));
+		}
+
+		self
+	}
+
+	/// Builds the key set described by the `[secrets]` section of the config
+	/// file.
+	///
+	/// [`Config::load()`] already made sure there is exactly one primary key,
+	/// and that no key is empty.
+	///
+	/// [`Config::load()`]: crate::runtime::Config::load
+	pub fn from_config(secrets: &Secrets) -> Self
+	{
+		let unnamed = (!secrets.jwt_key.is_empty()).then_some((None, secrets.jwt_key.as_str()));
+
+		let named = secrets
+			.jwt_keys
+			.iter()
+			.map(|key| (Some(key.id.clone()), key.key.as_str()));
+
+		let (primary_id, primary_secret) = match secrets.jwt_keys.iter().find(|key| key.primary) {
+			Some(primary) => (Some(primary.id.clone()), primary.key.as_str()),
+			None => (None, secrets.jwt_key.as_str()),
+		};
+
+		unnamed
+			.into_iter()
+			.chain(named)
+			.fold(Self::new(primary_id, primary_secret), |keys, (id, secret)| {
+				keys.with_key(id, secret)
+			})
+	}
+
+	/// Returns the ID of the primary key.
+	pub fn primary_id(&self) -> Option<&str>
+	{
+		self.primary.as_deref()
+	}
+
+	/// Encodes a JWT into a string using the primary key.
+	pub(crate) fn encode<T>(&self, jwt: Jwt<T>) -> Result<String, Error>
+	where
+		T: Serialize + fmt::Debug,
+	{
+		let header = Header { kid: self.primary.clone(), ..Default::default() };
+		let key = &self.keys[&self.primary];
+
+		jsonwebtoken::encode(&header, &jwt, &key.encoding_key)
+	}
+
+	/// Decodes a string as a JWT, verifying it with the key named in its `kid`
+	/// header.
+	pub(crate) fn decode<T>(&self, jwt: &str) -> Result<Jwt<T>, Error>
+	where
+		T: DeserializeOwned,
+	{
+		let kid = jsonwebtoken::decode_header(jwt)?.kid;
+		let key = match kid {
+			kid @ Some(_) => self.keys.get(&kid),
+			None => self
+				.keys
+				.get(&None)
+				.or_else(|| self.keys.get(&self.primary)),
+		}
+		.ok_or_else(|| Error::from(ErrorKind::InvalidToken))?;
+
+		jsonwebtoken::decode(jwt, &key.decoding_key, &self.validation).map(|data| data.claims)
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::time::Duration;
+
+	use serde::{Deserialize, Serialize};
+
+	use super::*;
+	use crate::testing;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Data
+	{
+		foo: i32,
+	}
+
+	/// Returns the `kid` header of an encoded token.
+	fn kid(jwt: &str) -> color_eyre::Result<Option<String>>
+	{
+		Ok(jsonwebtoken::decode_header(jwt)?.kid)
+	}
+
+	#[test]
+	fn old_keys_still_verify() -> color_eyre::Result<()>
+	{
+		let old = JwtKeys::new(Some(String::from("2024")), "b2xkLXNlY3JldA");
+		let old_token = old.encode(Jwt::new(Data { foo: 1 }, Duration::from_secs(69)))?;
+
+		let rotated = JwtKeys::new(Some(String::from("2025")), "bmV3LXNlY3JldA")
+			.with_key(Some(String::from("2024")), "b2xkLXNlY3JldA");
+
+		let new_token = rotated.encode(Jwt::new(Data { foo: 2 }, Duration::from_secs(69)))?;
+
+		testing::assert_eq!(kid(&old_token)?.as_deref(), Some("2024"));
+		testing::assert_eq!(kid(&new_token)?.as_deref(), Some("2025"));
+		testing::assert_eq!(rotated.decode::<Data>(&old_token)?.payload(), &Data { foo: 1 });
+		testing::assert_eq!(rotated.decode::<Data>(&new_token)?.payload(), &Data { foo: 2 });
+
+		// Once the old key is removed, its tokens are rejected.
+		let removed = JwtKeys::new(Some(String::from("2025")), "bmV3LXNlY3JldA");
+
+		testing::assert!(removed.decode::<Data>(&old_token).is_err());
+		testing::assert!(old.decode::<Data>(&new_token).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn tokens_without_kid_use_unnamed_key() -> color_eyre::Result<()>
+	{
+		let legacy = JwtKeys::new(None, "Zm9vYmFyYmF6");
+		let legacy_token = legacy.encode(Jwt::new(Data { foo: 69 }, Duration::from_secs(69)))?;
+
+		testing::assert!(kid(&legacy_token)?.is_none());
+
+		let rotated = JwtKeys::new(Some(String::from("2025")), "bmV3LXNlY3JldA")
+			.with_key(None, "Zm9vYmFyYmF6");
+
+		testing::assert_eq!(rotated.decode::<Data>(&legacy_token)?.payload(), &Data { foo: 69 });
+
+		Ok(())
+	}
+}
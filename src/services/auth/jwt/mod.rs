@@ -22,6 +22,9 @@ use crate::services::AuthService;
 mod rejection;
 pub use rejection::JwtRejection;
 
+mod keys;
+pub use keys::JwtKeys;
+
 mod service;
 pub use service::{JwtLayer, JwtService};
 
@@ -40,6 +40,14 @@ impl IntoProblemDetails for JwtRejection
 		}
 	}
 
+	fn detail(&self) -> String
+	{
+		match self {
+			Self::Auth(source) => source.detail(),
+			_ => self.to_string(),
+		}
+	}
+
 	fn add_extension_members(&self, ext: &mut problem_details::ExtensionMembers)
 	{
 		if let Self::Auth(source) = self {
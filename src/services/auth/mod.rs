@@ -14,7 +14,11 @@
 //! can be used for encoding/decoding JWTs respectively. Generating new tokens
 //! is left to the caller.
 //!
+//! Sessions are renewed using single-use [refresh tokens]; see
+//! [`AuthService::refresh()`].
+//!
 //! [`AuthorizeSession`]: session::AuthorizeSession
+//! [refresh tokens]: refresh_token
 
 use std::fmt;
 use std::sync::Arc;
@@ -23,12 +27,13 @@ use axum::extract::FromRef;
 use cs2kz::SteamID;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use sqlx::{MySql, Pool};
+use sqlx::{MySql, Pool, Transaction};
 use time::OffsetDateTime;
 
 use self::session::User;
 use crate::net::IpAddr;
-use crate::services::SteamService;
+use crate::runtime::config::CookieConfig;
+use crate::services::{steam, SteamService};
 
 pub(crate) mod http;
 
@@ -36,17 +41,21 @@ mod error;
 pub use error::{Error, Result};
 
 pub(crate) mod models;
-pub use models::{LoginRequest, LoginResponse, LogoutRequest, LogoutResponse};
+pub use models::{LoginRequest, LoginResponse, LogoutRequest, LogoutResponse, RefreshResponse};
 
 pub mod session;
 pub use session::{Session, SessionID};
 
 pub mod jwt;
-pub use jwt::Jwt;
+pub use jwt::{Jwt, JwtKeys};
 
 pub mod api_key;
 pub use api_key::ApiKey;
 
+pub mod refresh_token;
+use refresh_token::FamilyID;
+pub use refresh_token::RefreshToken;
+
 /// A service for managing user authentication.
 #[derive(Clone, FromRef)]
 #[allow(clippy::missing_docs_in_private_items)]
@@ -54,9 +63,10 @@ pub struct AuthService
 {
 	database: Pool<MySql>,
 	http_client: reqwest::Client,
-	jwt_state: Arc<JwtState>,
+	jwt_keys: Arc<JwtKeys>,
 	steam_svc: SteamService,
 	cookie_domain: Arc<str>,
+	cookie_config: Arc<CookieConfig>,
 }
 
 impl fmt::Debug for AuthService
@@ -65,6 +75,7 @@ impl fmt::Debug for AuthService
 	{
 		f.debug_struct("AuthService")
 			.field("cookie_domain", &self.cookie_domain)
+			.field("cookie_config", &self.cookie_config)
 			.finish_non_exhaustive()
 	}
 }
@@ -77,35 +88,41 @@ impl AuthService
 		database: Pool<MySql>,
 		http_client: reqwest::Client,
 		steam_svc: SteamService,
-		jwt_secret: String,
+		jwt_keys: JwtKeys,
 		cookie_domain: String,
+		cookie_config: CookieConfig,
 	) -> Self
 	{
 		Self {
 			database,
 			http_client,
-			jwt_state: Arc::new(JwtState::new(&jwt_secret)),
+			jwt_keys: Arc::new(jwt_keys),
 			steam_svc,
 			cookie_domain: cookie_domain.into(),
+			cookie_config: Arc::new(cookie_config),
 		}
 	}
 
 	/// Generates a URL that allows a user to login with Steam.
 	#[tracing::instrument(level = "debug")]
-	pub fn login_url(&self, req: LoginRequest) -> LoginResponse
+	pub fn login_url(&self, req: LoginRequest) -> Result<LoginResponse>
 	{
-		LoginResponse {
-			openid_url: self
-				.steam_svc
-				.openid_login_form()
-				.redirect_to(&req.redirect_to),
-		}
+		let openid_url = self
+			.steam_svc
+			.openid_login_form()
+			.redirect_to(&req.redirect_to)
+			.map_err(steam::Error::from)?;
+
+		Ok(LoginResponse { openid_url })
 	}
 
-	/// Invalidates a user's login session(s).
+	/// Invalidates a user's login session(s), and the refresh tokens belonging
+	/// to them.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn logout(&self, req: LogoutRequest) -> Result<()>
 	{
+		let mut txn = self.database.begin().await?;
+
 		sqlx::query! {
 			r"
 			UPDATE
@@ -124,9 +141,32 @@ impl AuthService
 			req.session.id(),
 			req.invalidate_all_sessions,
 		}
-		.execute(&self.database)
+		.execute(txn.as_mut())
 		.await?;
 
+		sqlx::query! {
+			r"
+			UPDATE
+			  RefreshTokens
+			SET
+			  expires_on = NOW()
+			WHERE
+			  player_id = ?
+			  AND expires_on > NOW()
+			  AND (
+			    session_id = ?
+			    OR ?
+			  )
+			",
+			req.session.user().steam_id(),
+			req.session.id(),
+			req.invalidate_all_sessions,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		txn.commit().await?;
+
 		tracing::trace! {
 			session.id = %req.session.id(),
 			session.user.id = %req.session.user().steam_id(),
@@ -138,6 +178,9 @@ impl AuthService
 	}
 
 	/// Creates a new session for the given user.
+	///
+	/// This also starts a new refresh token family, and returns the first token
+	/// in it.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"), skip_all, fields(
 		user.id = %user_id,
 		user.name = %user_name,
@@ -150,7 +193,7 @@ impl AuthService
 		user_id: SteamID,
 		user_name: String,
 		user_ip: IpAddr,
-	) -> Result<Session>
+	) -> Result<(Session, RefreshToken)>
 	{
 		let session_id = SessionID::new();
 		let expires_on = generate_session_expiration_date();
@@ -197,6 +240,8 @@ impl AuthService
 
 		tracing::debug!("created session");
 
+		let refresh_token =
+			insert_refresh_token(&mut txn, FamilyID::new(), session_id, user_id).await?;
 		let user_permissions = sqlx::query_scalar! {
 			r"
 			SELECT
@@ -213,77 +258,244 @@ impl AuthService
 
 		txn.commit().await?;
 
-		Ok(Session::new(session_id, User::new(user_id, user_permissions)))
+		Ok((Session::new(session_id, User::new(user_id, user_permissions)), refresh_token))
+	}
+
+	/// Exchanges a refresh token for a new session and a new refresh token.
+	///
+	/// The presented token and the session it belongs to are invalidated. If
+	/// the token has already been used before, its entire family is revoked,
+	/// and [`Error::RefreshTokenReused`] is returned.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"), fields(
+		session.id = tracing::field::Empty,
+		family.id = tracing::field::Empty,
+	))]
+	pub async fn refresh(&self, refresh_token: RefreshToken) -> Result<(Session, RefreshToken)>
+	{
+		let mut txn = self.database.begin().await?;
+
+		let token = sqlx::query! {
+			r"
+			SELECT
+			  family_id `family_id: FamilyID`,
+			  session_id `session_id: SessionID`,
+			  player_id `player_id: SteamID`,
+			  used_on IS NOT NULL `is_used: bool`,
+			  expires_on <= NOW() `is_expired: bool`
+			FROM
+			  RefreshTokens
+			WHERE
+			  id = ?
+			FOR UPDATE
+			",
+			refresh_token,
+		}
+		.fetch_optional(txn.as_mut())
+		.await?
+		.ok_or(Error::InvalidRefreshToken)?;
+
+		let (family_id, old_session_id, user_id) =
+			(token.family_id, token.session_id, token.player_id);
+
+		tracing::Span::current().record("family.id", format_args!("{family_id}"));
+
+		if token.is_used {
+			revoke_family(&mut txn, family_id).await?;
+			txn.commit().await?;
+
+			tracing::warn!(%family_id, user.id = %user_id, "refresh token reused; revoked family");
+
+			return Err(Error::RefreshTokenReused);
+		}
+
+		if token.is_expired {
+			return Err(Error::InvalidRefreshToken);
+		}
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  RefreshTokens
+			SET
+			  used_on = NOW()
+			WHERE
+			  id = ?
+			",
+			refresh_token,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  LoginSessions
+			SET
+			  expires_on = NOW()
+			WHERE
+			  id = ?
+			  AND expires_on > NOW()
+			",
+			old_session_id,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		let session_id = SessionID::new();
+
+		tracing::Span::current().record("session.id", format_args!("{session_id}"));
+
+		sqlx::query! {
+			r"
+			INSERT INTO
+			  LoginSessions (id, player_id, expires_on)
+			VALUES
+			  (?, ?, ?)
+			",
+			session_id,
+			user_id,
+			generate_session_expiration_date(),
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		let new_refresh_token =
+			insert_refresh_token(&mut txn, family_id, session_id, user_id).await?;
+		let user_permissions = sqlx::query_scalar! {
+			r"
+			SELECT
+			  permissions `permissions: session::user::Permissions`
+			FROM
+			  Players
+			WHERE
+			  id = ?
+			",
+			user_id,
+		}
+		.fetch_one(txn.as_mut())
+		.await?;
+
+		txn.commit().await?;
+
+		tracing::debug!(%old_session_id, "rotated refresh token");
+
+		Ok((Session::new(session_id, User::new(user_id, user_permissions)), new_refresh_token))
 	}
 
 	/// Encode a JWT into a string.
+	///
+	/// The token is signed with the primary key, and its ID is embedded in the
+	/// `kid` header.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub fn encode_jwt<T>(&self, jwt: Jwt<T>) -> Result<String>
 	where
 		T: Serialize + fmt::Debug,
 	{
-		self.jwt_state
+		self.jwt_keys
 			.encode(jwt)
 			.map_err(|source| Error::EncodeJwt { source })
 	}
 
 	/// Decode a string as a JWT.
+	///
+	/// The token is verified with the key named in its `kid` header.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub fn decode_jwt<T>(&self, jwt: &str) -> Result<Jwt<T>>
 	where
 		T: DeserializeOwned,
 	{
-		self.jwt_state
+		self.jwt_keys
 			.decode(jwt)
 			.map_err(|source| Error::DecodeJwt { source })
 	}
 }
 
-/// State for encoding/decoding JWTs.
-#[allow(clippy::missing_docs_in_private_items)]
-struct JwtState
+/// Generates a new expiration date for any given session.
+fn generate_session_expiration_date() -> OffsetDateTime
 {
-	header: jsonwebtoken::Header,
-	encoding_key: jsonwebtoken::EncodingKey,
-	decoding_key: jsonwebtoken::DecodingKey,
-	validation: jsonwebtoken::Validation,
+	OffsetDateTime::now_utc() + (time::Duration::WEEK * 2)
 }
 
-impl JwtState
+/// Generates a new expiration date for any given refresh token.
+///
+/// This is longer than a session's lifetime, so an expired session can still
+/// be renewed.
+fn generate_refresh_token_expiration_date() -> OffsetDateTime
 {
-	/// Creates a new [`JwtState`] using the provided `secret` for
-	/// encoding/decoding.
-	fn new(secret: &str) -> Self
-	{
-		Self {
-			header: jsonwebtoken::Header::default(),
-			encoding_key: jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
-			decoding_key: jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()),
-			validation: jsonwebtoken::Validation::default(),
-		}
-	}
+	OffsetDateTime::now_utc() + (time::Duration::WEEK * 4)
+}
 
-	/// Encode a JWT into a string.
-	fn encode<T>(&self, jwt: Jwt<T>) -> Result<String, jsonwebtoken::errors::Error>
-	where
-		T: Serialize + fmt::Debug,
-	{
-		jsonwebtoken::encode(&self.header, &jwt, &self.encoding_key)
+/// Creates a new refresh token for `session_id` in the given `family_id`.
+async fn insert_refresh_token(
+	txn: &mut Transaction<'_, MySql>,
+	family_id: FamilyID,
+	session_id: SessionID,
+	user_id: SteamID,
+) -> sqlx::Result<RefreshToken>
+{
+	let refresh_token = RefreshToken::new();
+
+	sqlx::query! {
+		r"
+		INSERT INTO
+		  RefreshTokens (id, family_id, session_id, player_id, expires_on)
+		VALUES
+		  (?, ?, ?, ?, ?)
+		",
+		refresh_token,
+		family_id,
+		session_id,
+		user_id,
+		generate_refresh_token_expiration_date(),
 	}
+	.execute(txn.as_mut())
+	.await?;
 
-	/// Decode a string as a JWT.
-	fn decode<T>(&self, jwt: &str) -> Result<Jwt<T>, jsonwebtoken::errors::Error>
-	where
-		T: DeserializeOwned,
-	{
-		jsonwebtoken::decode(jwt, &self.decoding_key, &self.validation).map(|data| data.claims)
-	}
+	Ok(refresh_token)
 }
 
-/// Generates a new expiration date for any given session.
-fn generate_session_expiration_date() -> OffsetDateTime
+/// Invalidates every refresh token in `family_id`, and every session created
+/// from one of them.
+async fn revoke_family(txn: &mut Transaction<'_, MySql>, family_id: FamilyID) -> sqlx::Result<()>
 {
-	OffsetDateTime::now_utc() + (time::Duration::WEEK * 2)
+	sqlx::query! {
+		r"
+		UPDATE
+		  LoginSessions
+		SET
+		  expires_on = NOW()
+		WHERE
+		  expires_on > NOW()
+		  AND id IN (
+		    SELECT
+		      session_id
+		    FROM
+		      RefreshTokens
+		    WHERE
+		      family_id = ?
+		  )
+		",
+		family_id,
+	}
+	.execute(txn.as_mut())
+	.await?;
+
+	sqlx::query! {
+		r"
+		UPDATE
+		  RefreshTokens
+		SET
+		  expires_on = NOW()
+		WHERE
+		  family_id = ?
+		  AND expires_on > NOW()
+		",
+		family_id,
+	}
+	.execute(txn.as_mut())
+	.await?;
+
+	Ok(())
 }
 
 #[cfg(test)]
@@ -294,7 +506,7 @@ mod tests
 	use serde::{Deserialize, Serialize};
 
 	use super::*;
-	use crate::testing;
+	use crate::testing::{self, ALPHAKEKS_ID};
 
 	#[derive(Debug, PartialEq, Serialize, Deserialize)]
 	struct Data
@@ -305,13 +517,88 @@ mod tests
 	#[test]
 	fn jwt_ping_pong() -> color_eyre::Result<()>
 	{
-		let state = JwtState::new("Zm9vYmFyYmF6");
+		let keys = JwtKeys::new(None, "Zm9vYmFyYmF6");
 		let data = Data { foo: 69 };
-		let encoded = state.encode(Jwt::new(&data, Duration::from_secs(69)))?;
-		let decoded = state.decode::<Data>(&encoded)?;
+		let encoded = keys.encode(Jwt::new(&data, Duration::from_secs(69)))?;
+		let decoded = keys.decode::<Data>(&encoded)?;
 
 		testing::assert_eq!(decoded.payload(), &data);
 
 		Ok(())
 	}
+
+	/// Returns whether the session with the given ID is still valid.
+	async fn is_valid(database: &Pool<MySql>, session_id: SessionID) -> color_eyre::Result<bool>
+	{
+		let is_valid = sqlx::query_scalar! {
+			r"
+			SELECT
+			  COUNT(*) > 0 `is_valid: bool`
+			FROM
+			  LoginSessions
+			WHERE
+			  id = ?
+			  AND expires_on > NOW()
+			",
+			session_id,
+		}
+		.fetch_one(database)
+		.await?;
+
+		Ok(is_valid)
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/session.sql")
+	)]
+	async fn refresh_rotates_tokens(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::auth_svc(database.clone());
+		let (session, refresh_token) = svc
+			.login(ALPHAKEKS_ID, String::from("AlphaKeks"), "::1".parse::<IpAddr>()?)
+			.await?;
+
+		let (new_session, new_refresh_token) = svc.refresh(refresh_token).await?;
+
+		testing::assert_ne!(new_session.id(), session.id());
+		testing::assert_ne!(new_refresh_token, refresh_token);
+		testing::assert_eq!(new_session.user().steam_id(), ALPHAKEKS_ID);
+		testing::assert!(!is_valid(&database, session.id()).await?);
+		testing::assert!(is_valid(&database, new_session.id()).await?);
+
+		let (newest_session, _) = svc.refresh(new_refresh_token).await?;
+
+		testing::assert!(is_valid(&database, newest_session.id()).await?);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/session.sql")
+	)]
+	async fn reused_refresh_token_revokes_family(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::auth_svc(database.clone());
+		let (_, stolen_token) = svc
+			.login(ALPHAKEKS_ID, String::from("AlphaKeks"), "::1".parse::<IpAddr>()?)
+			.await?;
+
+		let (legit_session, legit_token) = svc.refresh(stolen_token).await?;
+
+		let reused = svc.refresh(stolen_token).await;
+
+		testing::assert_matches!(reused, Err(Error::RefreshTokenReused));
+		testing::assert!(!is_valid(&database, legit_session.id()).await?);
+
+		let after_revocation = svc.refresh(legit_token).await;
+
+		testing::assert_matches!(after_revocation, Err(Error::InvalidRefreshToken));
+
+		// Other sessions are unaffected.
+		testing::assert!(is_valid(&database, SessionID::TESTING).await?);
+
+		Ok(())
+	}
 }
@@ -68,20 +68,48 @@ impl utoipa::IntoResponses for LogoutResponse
 {
 	fn responses() -> BTreeMap<String, utoipa::openapi::RefOr<utoipa::openapi::response::Response>>
 	{
-		use utoipa::openapi::header::HeaderBuilder;
-		use utoipa::openapi::response::{ResponseBuilder, ResponsesBuilder};
-
-		ResponsesBuilder::new()
-			.response(
-				"200",
-				ResponseBuilder::new().header(
-					"Set-Cookies",
-					HeaderBuilder::new()
-						.description(Some("your cleared `kz-*` cookies"))
-						.into(),
-				),
-			)
-			.build()
-			.into()
+		cookie_responses("your cleared `kz-*` cookies")
 	}
 }
+
+/// Response for `/auth/refresh`.
+#[derive(Debug)]
+pub struct RefreshResponse
+{
+	/// The cookie jar that contains the new session and refresh token cookies.
+	pub(super) cookies: CookieJar,
+}
+
+impl IntoResponse for RefreshResponse
+{
+	fn into_response(self) -> Response
+	{
+		self.cookies.into_response()
+	}
+}
+
+impl utoipa::IntoResponses for RefreshResponse
+{
+	fn responses() -> BTreeMap<String, utoipa::openapi::RefOr<utoipa::openapi::response::Response>>
+	{
+		cookie_responses("your new `kz-auth` and `kz-refresh` cookies")
+	}
+}
+
+/// Builds the responses for an endpoint that only sets cookies.
+fn cookie_responses(
+	description: &str,
+) -> BTreeMap<String, utoipa::openapi::RefOr<utoipa::openapi::response::Response>>
+{
+	use utoipa::openapi::header::HeaderBuilder;
+	use utoipa::openapi::response::{ResponseBuilder, ResponsesBuilder};
+
+	ResponsesBuilder::new()
+		.response(
+			"200",
+			ResponseBuilder::new()
+				.header("Set-Cookies", HeaderBuilder::new().description(Some(description)).into()),
+		)
+		.build()
+		.into()
+}
@@ -0,0 +1,113 @@
+//! Refresh tokens for renewing user sessions.
+//!
+//! Every login creates a new token *family*. Whenever a refresh token is used,
+//! it is marked as used and replaced by a new session and a new token in the
+//! same family. Refresh tokens are therefore single-use; if one that has
+//! already been used is presented again, someone other than the user must have
+//! gotten hold of it, and the whole family (including every session created
+//! from it) is revoked.
+
+use std::fmt;
+use std::str::FromStr;
+
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use serde::{Deserialize, Serialize};
+use uuid::fmt::Hyphenated;
+use uuid::Uuid;
+
+use crate::runtime::config::CookieConfig;
+
+/// The name of the HTTP cookie that will store the user's refresh token.
+pub const COOKIE_NAME: &str = "kz-refresh";
+
+/// A single-use token for renewing a session.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RefreshToken(Uuid);
+
+impl RefreshToken
+{
+	/// Generates a new random token.
+	pub fn new() -> Self
+	{
+		Self(Uuid::new_v4())
+	}
+
+	/// Creates an HTTP cookie from this token.
+	///
+	/// The cookie is only sent to `/auth` routes. Only the `Secure` attribute
+	/// is taken from `config`; the token is never exposed to JavaScript or sent
+	/// along with cross-site requests.
+	pub fn into_cookie(self, domain: impl Into<String>, config: &CookieConfig) -> Cookie<'static>
+	{
+		Cookie::build((COOKIE_NAME, self.to_string()))
+			.domain(domain.into())
+			.path("/auth")
+			.secure(config.secure)
+			.same_site(SameSite::Strict)
+			.http_only(true)
+			.expires(super::generate_refresh_token_expiration_date())
+			.build()
+	}
+}
+
+impl fmt::Display for RefreshToken
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		fmt::Display::fmt(self.0.as_hyphenated(), f)
+	}
+}
+
+impl fmt::Debug for RefreshToken
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		f.debug_tuple("RefreshToken")
+			.field(self.0.as_hyphenated())
+			.finish()
+	}
+}
+
+impl FromStr for RefreshToken
+{
+	type Err = uuid::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err>
+	{
+		s.parse::<Uuid>().map(Self)
+	}
+}
+
+crate::macros::sqlx_scalar_forward!(RefreshToken as Hyphenated => {
+	encode: |self| { *self.0.as_hyphenated() },
+	decode: |uuid| { Self(Uuid::from(uuid)) },
+});
+
+/// The ID of a refresh token family.
+///
+/// All tokens descending from the same login share a family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FamilyID(Uuid);
+
+impl FamilyID
+{
+	/// Generates a new random ID.
+	pub(crate) fn new() -> Self
+	{
+		Self(Uuid::new_v4())
+	}
+}
+
+impl fmt::Display for FamilyID
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		fmt::Display::fmt(self.0.as_hyphenated(), f)
+	}
+}
+
+crate::macros::sqlx_scalar_forward!(FamilyID as Hyphenated => {
+	encode: |self| { *self.0.as_hyphenated() },
+	decode: |uuid| { Self(Uuid::from(uuid)) },
+});
@@ -14,6 +14,7 @@ pub struct SessionID(Uuid);
 
 impl SessionID
 {
+	/// A fixed ID used in tests.
 	#[cfg(test)]
 	pub const TESTING: Self = Self(uuid::uuid!("331c9a7e-2536-4149-9aee-774de29f368e"));
 
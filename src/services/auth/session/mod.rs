@@ -6,11 +6,13 @@
 
 use axum::async_trait;
 use axum::extract::{FromRef, FromRequestParts};
-use axum_extra::extract::cookie::{Cookie, SameSite};
+use axum_extra::extract::cookie::Cookie;
 use cs2kz::SteamID;
 use http::{header, request};
 use sqlx::{MySql, Pool};
 
+use crate::runtime::config::CookieConfig;
+
 mod id;
 pub use id::SessionID;
 
@@ -67,16 +69,16 @@ impl Session
 	}
 
 	/// Creates an HTTP cookie from this session.
-	pub fn into_cookie(self, domain: impl Into<String>) -> Cookie<'static>
+	///
+	/// Its attributes are taken from `config`.
+	pub fn into_cookie(self, domain: impl Into<String>, config: &CookieConfig) -> Cookie<'static>
 	{
-		Cookie::build((COOKIE_NAME, self.id().to_string()))
+		let cookie = Cookie::build((COOKIE_NAME, self.id().to_string()))
 			.domain(domain.into())
 			.path("/")
-			.secure(cfg!(feature = "production"))
-			.same_site(SameSite::Lax)
-			.http_only(true)
-			.expires(super::generate_session_expiration_date())
-			.build()
+			.build();
+
+		config.apply(cookie)
 	}
 }
 
@@ -159,6 +161,7 @@ mod tests
 	use sqlx::{MySql, Pool};
 
 	use super::*;
+	use crate::runtime::config::CookieSameSite;
 	use crate::testing::{self, ALPHAKEKS_ID};
 
 	#[sqlx::test(
@@ -236,4 +239,45 @@ mod tests
 
 		Ok(())
 	}
+
+	#[test]
+	fn cookie_flags_depend_on_environment() -> color_eyre::Result<()>
+	{
+		let session =
+			Session::new(SessionID::TESTING, User::new(ALPHAKEKS_ID, user::Permissions::NONE));
+		let set_cookie = |config: &CookieConfig| {
+			session
+				.clone()
+				.into_cookie("cs2kz.org", config)
+				.encoded()
+				.to_string()
+		};
+
+		let production = set_cookie(&CookieConfig::production());
+
+		testing::assert!(production.contains("Secure"), "{production}");
+		testing::assert!(production.contains("HttpOnly"), "{production}");
+		testing::assert!(production.contains("SameSite=Lax"), "{production}");
+		testing::assert!(production.contains("Max-Age=1209600"), "{production}");
+		testing::assert!(production.contains("Domain=cs2kz.org"), "{production}");
+
+		let local = set_cookie(&CookieConfig::local());
+
+		testing::assert!(!local.contains("Secure"), "{local}");
+		testing::assert!(local.contains("HttpOnly"), "{local}");
+		testing::assert!(local.contains("SameSite=Lax"), "{local}");
+
+		let custom = set_cookie(&CookieConfig {
+			same_site: CookieSameSite::Strict,
+			http_only: false,
+			max_age: 60,
+			..CookieConfig::local()
+		});
+
+		testing::assert!(!custom.contains("HttpOnly"), "{custom}");
+		testing::assert!(custom.contains("SameSite=Strict"), "{custom}");
+		testing::assert!(custom.contains("Max-Age=60"), "{custom}");
+
+		Ok(())
+	}
 }
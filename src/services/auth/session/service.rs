@@ -187,7 +187,7 @@ where
 		.map_err(SessionManagerError::Service)?;
 
 	let session_cookie = session
-		.into_cookie(&*auth_svc.cookie_domain)
+		.into_cookie(&*auth_svc.cookie_domain, &auth_svc.cookie_config)
 		.encoded()
 		.to_string()
 		.parse::<http::HeaderValue>()
@@ -212,6 +212,7 @@ mod tests
 
 	use super::*;
 	use crate::http::problem_details::{IntoProblemDetails, ProblemType};
+	use crate::services::auth::session::user::Permissions;
 	use crate::services::auth::session::{AuthorizeSession, SessionID};
 	use crate::testing;
 
@@ -288,4 +289,31 @@ mod tests
 
 		Ok(())
 	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../../database/fixtures/session.sql")
+	)]
+	async fn reject_partial_permissions(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let auth_svc = testing::auth_svc(database);
+		let strategy =
+			authorization::RequiredPermissions(Permissions::ADMIN | Permissions::SERVERS);
+
+		let req = Request::builder()
+			.method(http::Method::GET)
+			.uri("/")
+			.header("Cookie", format!("kz-auth={}", SessionID::TESTING))
+			.body(Default::default())?;
+
+		let res = SessionManagerLayer::with_strategy(auth_svc, strategy)
+			.layer(service_fn(|_| async { Result::<_, Infallible>::Ok(Default::default()) }))
+			.oneshot(req)
+			.await
+			.unwrap_err();
+
+		testing::assert_matches!(res, SessionManagerError::Authorize(_));
+
+		Ok(())
+	}
 }
@@ -16,6 +16,9 @@ mod sqlx;
 mod serde;
 mod utoipa;
 
+#[cfg(test)]
+mod tests;
+
 /// User permissions.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Permissions(u64);
@@ -88,11 +91,19 @@ impl Permissions
 	}
 
 	/// Checks if `other` is a subset of `self`.
+	///
+	/// That is, `self` has to contain **all** of the permissions in `other`.
 	pub const fn contains(self, other: Self) -> bool
 	{
 		(self.0 & other.0) == other.0
 	}
 
+	/// Checks if `self` and `other` have at least one permission in common.
+	pub const fn contains_any(self, other: Self) -> bool
+	{
+		(self.0 & other.0) != 0
+	}
+
 	/// Creates an iterator over the permission bits.
 	#[allow(private_interfaces)]
 	pub const fn iter_bits(self) -> Iter<u64>
@@ -158,6 +169,16 @@ impl ops::Deref for Permissions
 	}
 }
 
+impl ops::Not for Permissions
+{
+	type Output = Self;
+
+	fn not(self) -> Self::Output
+	{
+		Self::new(!self.0 & Self::ALL.0)
+	}
+}
+
 impl ops::BitOr for Permissions
 {
 	type Output = Self;
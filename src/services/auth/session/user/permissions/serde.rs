@@ -40,10 +40,10 @@ impl<'de> Deserialize<'de> for Permissions
 		Helper::deserialize(deserializer).and_then(|value| match value {
 			Helper::Int(flags) => Ok(Self::new(flags)),
 			Helper::Word(word) => word.parse::<Self>().map_err(serde::de::Error::custom),
-			Helper::Words(words) => Ok(words
+			Helper::Words(words) => words
 				.into_iter()
-				.flat_map(|word| word.parse::<Self>())
-				.fold(Self::NONE, |acc, curr| (acc | curr))),
+				.try_fold(Self::NONE, |acc, word| word.parse::<Self>().map(|curr| acc | curr))
+				.map_err(serde::de::Error::custom),
 		})
 	}
 }
@@ -0,0 +1,47 @@
+//! Unit tests.
+
+use super::Permissions;
+use crate::testing;
+
+#[test]
+fn contains_requires_all_bits() -> color_eyre::Result<()>
+{
+	let user = Permissions::BANS | Permissions::MAPS;
+
+	testing::assert!(user.contains(Permissions::NONE));
+	testing::assert!(user.contains(Permissions::BANS));
+	testing::assert!(user.contains(Permissions::BANS | Permissions::MAPS));
+	testing::assert!(!user.contains(Permissions::MAPS | Permissions::SERVERS));
+	testing::assert!(!user.contains(Permissions::ALL));
+
+	Ok(())
+}
+
+#[test]
+fn contains_any_requires_one_bit() -> color_eyre::Result<()>
+{
+	let user = Permissions::BANS | Permissions::MAPS;
+
+	testing::assert!(user.contains_any(Permissions::MAPS | Permissions::SERVERS));
+	testing::assert!(user.contains_any(Permissions::ALL));
+	testing::assert!(!user.contains_any(Permissions::SERVERS | Permissions::ADMIN));
+	testing::assert!(!user.contains_any(Permissions::NONE));
+
+	Ok(())
+}
+
+#[test]
+fn names_roundtrip() -> color_eyre::Result<()>
+{
+	let permissions = Permissions::RECORDS | Permissions::ADMIN;
+	let json = serde_json::to_string(&permissions)?;
+
+	testing::assert_eq!(json, r#"["records","admin"]"#);
+
+	let parsed = serde_json::from_str::<Permissions>(&json)?;
+
+	testing::assert_eq!(parsed, permissions);
+	testing::assert!(serde_json::from_str::<Permissions>(r#"["records","root"]"#).is_err());
+
+	Ok(())
+}
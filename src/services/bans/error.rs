@@ -26,6 +26,9 @@ pub enum Error
 	{
 		/// The player's SteamID.
 		steam_id: SteamID,
+
+		/// The ID of the player's active ban.
+		existing_id: BanID,
 	},
 
 	/// A request dedicated to a specific player was made, but the player could
@@ -102,7 +105,11 @@ impl IntoProblemDetails for Error
 	fn add_extension_members(&self, ext: &mut problem_details::ExtensionMembers)
 	{
 		match self {
-			Self::PlayerAlreadyBanned { steam_id } | Self::PlayerDoesNotExist { steam_id } => {
+			Self::PlayerAlreadyBanned { steam_id, existing_id } => {
+				ext.add("steam_id", steam_id);
+				ext.add("ban_id", existing_id);
+			}
+			Self::PlayerDoesNotExist { steam_id } => {
 				ext.add("steam_id", steam_id);
 			}
 			Self::BanDoesNotExist { ban_id } => {
@@ -103,6 +103,17 @@ pub(crate) struct BanRequestPayload
 
 	/// The reason for the ban.
 	pub reason: BanReason,
+
+	/// Whether the ban should never expire.
+	///
+	/// Only admins can issue permanent bans.
+	#[serde(default)]
+	pub permanent: bool,
+
+	/// Whether to extend the player's active ban, if they have one, instead
+	/// of rejecting the request.
+	#[serde(default)]
+	pub stack: bool,
 }
 
 /// Ban a player.
@@ -115,7 +126,9 @@ async fn create(
 	server: Option<Jwt<jwt::ServerInfo>>,
 	session: Option<Session>,
 	State(svc): State<BanService>,
-	Json(BanRequestPayload { player_id, player_ip, reason }): Json<BanRequestPayload>,
+	Json(BanRequestPayload { player_id, player_ip, reason, permanent, stack }): Json<
+		BanRequestPayload,
+	>,
 ) -> Result<BanResponse, ProblemDetails>
 {
 	let banned_by = match (server, session) {
@@ -130,6 +143,9 @@ async fn create(
 				admin_id: session.user().steam_id(),
 			})?;
 		}
+		(Some(_), None) if permanent => {
+			return Err(Error::Unauthorized)?;
+		}
 		(Some(server), None) => {
 			BannedBy::Server { id: server.id(), plugin_version_id: server.plugin_version_id() }
 		}
@@ -142,7 +158,7 @@ async fn create(
 		}
 	};
 
-	let req = BanRequest { player_id, player_ip, reason, banned_by };
+	let req = BanRequest { player_id, player_ip, reason, banned_by, permanent, stack };
 	let res = svc.ban_player(req).await?;
 
 	Ok(res)
@@ -15,10 +15,12 @@ use sqlx::{MySql, Pool, Row, Transaction};
 use time::OffsetDateTime;
 
 use crate::database::{SqlErrorExt, TransactionExt};
+use crate::events::{Event, EventBus};
 use crate::net::IpAddr;
 use crate::services::plugin::PluginVersionID;
 use crate::services::servers::ServerID;
 use crate::services::AuthService;
+use crate::time::Seconds;
 
 pub(crate) mod http;
 mod queries;
@@ -53,6 +55,7 @@ pub struct BanService
 {
 	database: Pool<MySql>,
 	auth_svc: AuthService,
+	events: EventBus,
 }
 
 impl fmt::Debug for BanService
@@ -67,9 +70,9 @@ impl BanService
 {
 	/// Create a new [`BanService`].
 	#[tracing::instrument]
-	pub fn new(database: Pool<MySql>, auth_svc: AuthService) -> Self
+	pub fn new(database: Pool<MySql>, auth_svc: AuthService, events: EventBus) -> Self
 	{
-		Self { database, auth_svc }
+		Self { database, auth_svc, events }
 	}
 
 	/// Fetch a ban.
@@ -109,6 +112,16 @@ impl BanService
 			Some(ident) => ident.resolve_id(txn.as_mut()).await?,
 		};
 
+		let admin_id = match req.banned_by {
+			None => None,
+			Some(ident) => ident.resolve_id(txn.as_mut()).await?,
+		};
+
+		let unban_admin_id = match req.unbanned_by {
+			None => None,
+			Some(ident) => ident.resolve_id(txn.as_mut()).await?,
+		};
+
 		let bans = sqlx::query_as::<_, FetchBanResponse>(&format!(
 			r"
 			{}
@@ -119,9 +132,20 @@ impl BanService
 			    OR b.server_id = ?
 			  )
 			  AND b.reason = COALESCE(?, b.reason)
+			  AND (
+			    (? IS NULL)
+			    OR b.admin_id = ?
+			  )
+			  AND (
+			    (? IS NULL)
+			    OR ub.admin_id = ?
+			  )
 			  AND b.created_on > COALESCE(?, '1970-01-01 00:00:01')
 			  AND b.created_on < COALESCE(?, '2038-01-19 03:14:07')
 			  AND ({})
+			  AND ({})
+			ORDER BY
+			  b.id DESC
 			LIMIT
 			  ? OFFSET ?
 			",
@@ -131,11 +155,20 @@ impl BanService
 				Some(false) => "ub.id IS NULL",
 				Some(true) => "ub.id IS NOT NULL",
 			},
+			match req.active {
+				None => "true",
+				Some(true) => "ub.id IS NULL AND (b.expires_on IS NULL OR b.expires_on > NOW())",
+				Some(false) => "ub.id IS NOT NULL OR b.expires_on <= NOW()",
+			},
 		))
 		.bind(player_id)
 		.bind(server_id)
 		.bind(server_id)
 		.bind(req.reason)
+		.bind(admin_id)
+		.bind(admin_id)
+		.bind(unban_admin_id)
+		.bind(unban_admin_id)
 		.bind(req.created_after)
 		.bind(req.created_before)
 		.bind(*req.limit)
@@ -151,12 +184,47 @@ impl BanService
 	}
 
 	/// Ban a player.
+	///
+	/// If the player is already banned, the request is either rejected, or the
+	/// existing ban is extended, depending on [`BanRequest::stack`].
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn ban_player(&self, req: BanRequest) -> Result<BanResponse>
 	{
 		let mut txn = self.database.begin().await?;
 
-		let ban_duration = calculate_ban_duration(req.player_id, req.reason, &mut txn).await?;
+		let active_ban = find_active_ban(req.player_id, &mut txn).await?;
+
+		if let (Some(existing_id), false) = (active_ban, req.stack) {
+			return Err(Error::PlayerAlreadyBanned { steam_id: req.player_id, existing_id });
+		}
+
+		let ban_duration = match req.permanent {
+			true => None,
+			false => Some(calculate_ban_duration(req.player_id, req.reason, &mut txn).await?),
+		};
+
+		let admin_id = match req.banned_by {
+			BannedBy::Server { .. } => None,
+			BannedBy::Admin { steam_id } => Some(steam_id),
+		};
+
+		if let Some(ban_id) = active_ban {
+			extend_ban(ban_id, ban_duration, &mut txn).await?;
+			txn.commit().await?;
+
+			tracing::trace!(%ban_id, player_id = %req.player_id, ?ban_duration, "extended ban");
+
+			self.events.dispatch(Event::PlayerBanned {
+				ban_id,
+				player_id: req.player_id,
+				admin_id,
+				reason: req.reason,
+				duration: ban_duration.map(Seconds::from),
+			});
+
+			return Ok(BanResponse { ban_id });
+		}
+
 		let player_ip = resolve_player_ip(req.player_ip, req.player_id, &mut txn).await?;
 		let banned_by_details = banned_by_details(req.banned_by, &mut txn).await?;
 
@@ -182,6 +250,14 @@ impl BanService
 			"issued ban",
 		};
 
+		self.events.dispatch(Event::PlayerBanned {
+			ban_id,
+			player_id: req.player_id,
+			admin_id,
+			reason: req.reason,
+			duration: ban_duration.map(Seconds::from),
+		});
+
 		Ok(BanResponse { ban_id })
 	}
 
@@ -293,6 +369,20 @@ impl BanService
 			n => assert_eq!(n, 1, "updated more than 1 ban"),
 		}
 
+		let player_id = sqlx::query_scalar! {
+			r"
+			SELECT
+			  player_id `player_id: SteamID`
+			FROM
+			  Bans
+			WHERE
+			  id = ?
+			",
+			req.ban_id,
+		}
+		.fetch_one(txn.as_mut())
+		.await?;
+
 		let unban_id = sqlx::query! {
 			r"
 			INSERT INTO
@@ -313,10 +403,54 @@ impl BanService
 
 		tracing::debug!(ban_id = %req.ban_id, %unban_id, admin_id = %req.admin_id, "reverted ban");
 
+		self.events.dispatch(Event::PlayerUnbanned {
+			ban_id: req.ban_id,
+			unban_id,
+			player_id,
+			admin_id: req.admin_id,
+			reason: req.reason,
+		});
+
 		Ok(UnbanResponse { ban_id: req.ban_id, unban_id })
 	}
 }
 
+/// Returns the ID of the player's currently active ban, if any.
+///
+/// A ban is active if it is permanent or has not expired yet. Reverting a ban
+/// sets its expiration date to the time of the unban, so reverted bans are
+/// never active.
+#[tracing::instrument(level = "trace", err(Debug, level = "debug"))]
+async fn find_active_ban(
+	player_id: SteamID,
+	txn: &mut Transaction<'_, MySql>,
+) -> Result<Option<BanID>>
+{
+	let ban_id = sqlx::query_scalar! {
+		r"
+		SELECT
+		  id `id: BanID`
+		FROM
+		  Bans
+		WHERE
+		  player_id = ?
+		  AND (
+		    expires_on IS NULL
+		    OR expires_on > NOW()
+		  )
+		ORDER BY
+		  created_on DESC
+		LIMIT
+		  1
+		",
+		player_id,
+	}
+	.fetch_optional(txn.as_mut())
+	.await?;
+
+	Ok(ban_id)
+}
+
 /// Calculates the ban duration for a new ban for a given player for a given
 /// reason.
 #[tracing::instrument(level = "trace", err(Debug, level = "debug"))]
@@ -326,11 +460,9 @@ async fn calculate_ban_duration(
 	txn: &mut Transaction<'_, MySql>,
 ) -> Result<Duration>
 {
-	let (currently_banned, has_previous_bans, previous_ban_duration) = sqlx::query! {
+	let previous_ban_duration = sqlx::query_scalar! {
 		r"
 		SELECT
-		  COUNT(active_bans.id) > 0 `currently_banned: bool`,
-		  COUNT(expired_bans.id) > 0 `has_previous_bans: bool`,
 		  TIMESTAMPDIFF(
 		    SECOND,
 		    expired_bans.created_on,
@@ -338,8 +470,6 @@ async fn calculate_ban_duration(
 		  ) `previous_ban_duration: u64`
 		FROM
 		  Players p
-		  LEFT JOIN Bans active_bans ON active_bans.player_id = p.id
-		  AND active_bans.expires_on > NOW()
 		  LEFT JOIN Bans expired_bans ON expired_bans.player_id = p.id
 		  AND expired_bans.expires_on < NOW()
 		  AND expired_bans.id IN (
@@ -357,38 +487,38 @@ async fn calculate_ban_duration(
 	}
 	.fetch_optional(txn.as_mut())
 	.await?
-	.map(|row| (row.currently_banned, row.has_previous_bans, row.previous_ban_duration))
-	.unwrap_or_default();
-
-	match (currently_banned, has_previous_bans, previous_ban_duration) {
-		// This is the player's first ever ban
-		(false, false, previous_ban_duration @ None)
-		// The player isn't currently banned but was banned in the past
-		| (false, true, previous_ban_duration @ Some(_)) => {
-			Ok(reason.duration(previous_ban_duration.map(Duration::from_secs)))
-		}
-
-		// The player isn't currently banned, has never been banned, but has a
-		// total ban duration...?
-		(false, false, Some(_)) => {
-			unreachable!("cannot have ban duration without bans");
-		}
+	.flatten();
 
-		// The player is currently banned, was never banned in the past, but has
-		// a previous ban duration?
-		(true, false, Some(_)) => {
-			unreachable!("cannot be currently banned with 0 previous bans");
-		}
-
-		// The player is not currently banned, was banned in the past, but has no
-		// total ban duration...?
-		(false, true, None) => {
-			unreachable!("cannot be not-banned and have perma ban at the same time");
-		}
+	Ok(reason.duration(previous_ban_duration.map(Duration::from_secs)))
+}
 
-		// Player is currently banned, so we can't ban them again
-		(true, ..) => Err(Error::PlayerAlreadyBanned { steam_id: player_id }),
+/// Extends an existing ban by `ban_duration`.
+///
+/// If either the existing ban or the extension is permanent, the result is a
+/// permanent ban.
+#[tracing::instrument(level = "trace", err(Debug, level = "debug"))]
+async fn extend_ban(
+	ban_id: BanID,
+	ban_duration: Option<Duration>,
+	txn: &mut Transaction<'_, MySql>,
+) -> Result<()>
+{
+	sqlx::query! {
+		r"
+		UPDATE
+		  Bans
+		SET
+		  expires_on = TIMESTAMPADD(SECOND, ?, expires_on)
+		WHERE
+		  id = ?
+		",
+		ban_duration.map(|duration| duration.as_secs()),
+		ban_id,
 	}
+	.execute(txn.as_mut())
+	.await?;
+
+	Ok(())
 }
 
 /// Resolves a player's IP address by mapping IPv4 to IPv6 or fetching the
@@ -464,13 +594,15 @@ async fn banned_by_details(
 }
 
 /// Creates a new ban in the database and returns its ID.
+///
+/// A `ban_duration` of `None` creates a permanent ban.
 #[tracing::instrument(level = "trace", err(Debug, level = "debug"))]
 async fn create_ban(
 	player_id: SteamID,
 	player_ip: IpAddr,
 	reason: BanReason,
 	banned_by_details: &BannedByDetails,
-	ban_duration: Duration,
+	ban_duration: Option<Duration>,
 	txn: &mut Transaction<'_, MySql>,
 ) -> Result<BanID>
 {
@@ -496,7 +628,7 @@ async fn create_ban(
 		reason,
 		banned_by_details.admin_id,
 		banned_by_details.plugin_version_id,
-		OffsetDateTime::now_utc() + ban_duration,
+		ban_duration.map(|duration| OffsetDateTime::now_utc() + duration),
 	}
 	.fetch_one(txn.as_mut())
 	.await
@@ -522,7 +654,13 @@ mod tests
 	use sqlx::{MySql, Pool};
 
 	use super::*;
-	use crate::testing;
+	use crate::testing::{self, ALPHAKEKS_ID};
+
+	/// GameChaos' SteamID, as inserted by the `players.sql` fixture.
+	const GAMECHAOS_ID: SteamID = match SteamID::new(76561198165203332_u64) {
+		Some(id) => id,
+		None => unreachable!(),
+	};
 
 	#[sqlx::test(
 		migrations = "database/migrations",
@@ -584,6 +722,77 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/bans.sql",
+			"../../../database/fixtures/expired-bans.sql",
+		)
+	)]
+	async fn fetch_bans_paginates(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::ban_svc(database);
+		let req = FetchBansRequest { limit: 3.into(), ..Default::default() };
+		let first_page = svc.fetch_bans(req).await?;
+
+		testing::assert_eq!(first_page.bans.len(), 3);
+		testing::assert_eq!(first_page.total, 4);
+
+		let req = FetchBansRequest { limit: 3.into(), offset: 3.into(), ..Default::default() };
+		let second_page = svc.fetch_bans(req).await?;
+
+		testing::assert_eq!(second_page.bans.len(), 1);
+		testing::assert_eq!(second_page.total, 4);
+		testing::assert!(first_page
+			.bans
+			.iter()
+			.all(|ban| ban.id != second_page.bans[0].id));
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/bans.sql",
+			"../../../database/fixtures/expired-bans.sql",
+		)
+	)]
+	async fn fetch_bans_filters(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::ban_svc(database);
+		let req = FetchBansRequest { active: Some(true), ..Default::default() };
+		let active = svc.fetch_bans(req).await?;
+
+		testing::assert_eq!(active.total, 3);
+		testing::assert!(active.bans.iter().all(|ban| ban.is_active));
+
+		let req = FetchBansRequest { active: Some(false), ..Default::default() };
+		let inactive = svc.fetch_bans(req).await?;
+
+		testing::assert_eq!(inactive.total, 1);
+		testing::assert!(!inactive.bans[0].is_active);
+		testing::assert_eq!(inactive.bans[0].player.name, "zer0.k");
+
+		let req = FetchBansRequest {
+			player: Some("zer0.k".parse()?),
+			active: Some(true),
+			..Default::default()
+		};
+		let res = svc.fetch_bans(req).await?;
+
+		testing::assert_eq!(res.total, 1);
+		testing::assert_eq!(res.bans[0].reason, BanReason::AutoStrafe);
+
+		let req = FetchBansRequest { banned_by: Some("alphakeks".parse()?), ..Default::default() };
+		let res = svc.fetch_bans(req).await?;
+
+		testing::assert_eq!(res.total, 3);
+		testing::assert!(res.bans.iter().all(|ban| ban.admin.is_some()));
+
+		Ok(())
+	}
+
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn fetch_bans_no_content(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
@@ -596,4 +805,225 @@ mod tests
 
 		Ok(())
 	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/players.sql")
+	)]
+	async fn ban_player_works(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::ban_svc(database);
+		let req = BanRequest {
+			player_id: GAMECHAOS_ID,
+			player_ip: None,
+			reason: BanReason::AutoBhop,
+			banned_by: BannedBy::Admin { steam_id: ALPHAKEKS_ID },
+			permanent: false,
+			stack: false,
+		};
+
+		let res = svc.ban_player(req).await?;
+		let ban = svc
+			.fetch_ban(FetchBanRequest { ban_id: res.ban_id })
+			.await?
+			.expect("ban should exist");
+
+		testing::assert_eq!(ban.player.steam_id, GAMECHAOS_ID);
+		testing::assert_eq!(ban.admin.as_ref().map(|admin| admin.steam_id), Some(ALPHAKEKS_ID));
+		testing::assert_eq!(ban.reason, BanReason::AutoBhop);
+		testing::assert!(ban.server.is_none());
+		testing::assert!(ban.unban.is_none());
+
+		let req = BanRequest {
+			player_id: GAMECHAOS_ID,
+			player_ip: None,
+			reason: BanReason::AutoStrafe,
+			banned_by: BannedBy::Admin { steam_id: ALPHAKEKS_ID },
+			permanent: false,
+			stack: false,
+		};
+
+		let res = svc.ban_player(req).await.unwrap_err();
+
+		testing::assert_matches!(
+			res,
+			Error::PlayerAlreadyBanned { steam_id, .. } if steam_id == GAMECHAOS_ID
+		);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/players.sql")
+	)]
+	async fn unban_player_works(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::ban_svc(database);
+		let req = BanRequest {
+			player_id: GAMECHAOS_ID,
+			player_ip: None,
+			reason: BanReason::AutoBhop,
+			banned_by: BannedBy::Admin { steam_id: ALPHAKEKS_ID },
+			permanent: false,
+			stack: false,
+		};
+
+		let ban_id = svc.ban_player(req).await?.ban_id;
+		let req = UnbanRequest { ban_id, reason: UnbanReason::FalseBan, admin_id: ALPHAKEKS_ID };
+		let res = svc.unban_player(req).await?;
+		let ban = svc
+			.fetch_ban(FetchBanRequest { ban_id })
+			.await?
+			.expect("ban should exist");
+
+		testing::assert_eq!(ban.unban.as_ref().map(|unban| unban.id), Some(res.unban_id));
+		testing::assert!(ban.expires_on.is_some());
+
+		let req = UnbanRequest { ban_id, reason: UnbanReason::FalseBan, admin_id: ALPHAKEKS_ID };
+		let err = svc.unban_player(req).await.unwrap_err();
+
+		testing::assert_matches!(err, Error::BanAlreadyReverted { unban_id } if unban_id == res.unban_id);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/players.sql")
+	)]
+	async fn ban_player_stacks_onto_active_ban(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::ban_svc(database);
+		let req = |stack| BanRequest {
+			player_id: GAMECHAOS_ID,
+			player_ip: None,
+			reason: BanReason::Macro,
+			banned_by: BannedBy::Admin { steam_id: ALPHAKEKS_ID },
+			permanent: false,
+			stack,
+		};
+
+		let ban_id = svc.ban_player(req(false)).await?.ban_id;
+		let expires_on = svc
+			.fetch_ban(FetchBanRequest { ban_id })
+			.await?
+			.and_then(|ban| ban.expires_on)
+			.expect("ban should exist and expire");
+
+		let err = svc.ban_player(req(false)).await.unwrap_err();
+
+		testing::assert_matches!(
+			err,
+			Error::PlayerAlreadyBanned { existing_id, .. } if existing_id == ban_id
+		);
+
+		let res = svc.ban_player(req(true)).await?;
+
+		testing::assert_eq!(res.ban_id, ban_id);
+
+		let ban = svc
+			.fetch_ban(FetchBanRequest { ban_id })
+			.await?
+			.expect("ban should exist");
+
+		testing::assert!(ban.expires_on.is_some_and(|extended| extended > expires_on));
+		testing::assert!(ban.is_active);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/players.sql")
+	)]
+	async fn permanent_ban_is_active(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::ban_svc(database);
+		let req = BanRequest {
+			player_id: GAMECHAOS_ID,
+			player_ip: None,
+			reason: BanReason::AutoBhop,
+			banned_by: BannedBy::Admin { steam_id: ALPHAKEKS_ID },
+			permanent: true,
+			stack: false,
+		};
+
+		let ban_id = svc.ban_player(req).await?.ban_id;
+		let ban = svc
+			.fetch_ban(FetchBanRequest { ban_id })
+			.await?
+			.expect("ban should exist");
+
+		testing::assert!(ban.expires_on.is_none());
+		testing::assert!(ban.is_active);
+
+		let req = UnbanRequest { ban_id, reason: UnbanReason::FalseBan, admin_id: ALPHAKEKS_ID };
+
+		svc.unban_player(req).await?;
+
+		let ban = svc
+			.fetch_ban(FetchBanRequest { ban_id })
+			.await?
+			.expect("ban should exist");
+
+		testing::assert!(!ban.is_active);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/players.sql")
+	)]
+	async fn ban_player_dispatches_events(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::ban_svc(database);
+		let mut events = svc.events.subscribe();
+		let req = BanRequest {
+			player_id: GAMECHAOS_ID,
+			player_ip: None,
+			reason: BanReason::AutoStrafe,
+			banned_by: BannedBy::Admin { steam_id: ALPHAKEKS_ID },
+			permanent: true,
+			stack: false,
+		};
+
+		let ban_id = svc.ban_player(req).await?.ban_id;
+
+		let event = events.try_recv()?;
+
+		testing::assert_eq!(
+			event,
+			Some(Event::PlayerBanned {
+				ban_id,
+				player_id: GAMECHAOS_ID,
+				admin_id: Some(ALPHAKEKS_ID),
+				reason: BanReason::AutoStrafe,
+				duration: None,
+			})
+		);
+
+		testing::assert!(events.is_empty());
+
+		let req = UnbanRequest { ban_id, reason: UnbanReason::FalseBan, admin_id: ALPHAKEKS_ID };
+		let unban_id = svc.unban_player(req).await?.unban_id;
+
+		let event = events.try_recv()?;
+
+		testing::assert_eq!(
+			event,
+			Some(Event::PlayerUnbanned {
+				ban_id,
+				unban_id,
+				player_id: GAMECHAOS_ID,
+				admin_id: ALPHAKEKS_ID,
+				reason: UnbanReason::FalseBan,
+			})
+		);
+
+		testing::assert!(events.is_empty());
+
+		Ok(())
+	}
 }
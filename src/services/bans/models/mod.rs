@@ -72,6 +72,12 @@ pub struct FetchBanResponse
 	/// The corresponding unban for this ban.
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub unban: Option<Unban>,
+
+	/// Whether this ban is currently in effect.
+	///
+	/// A ban is active if it has not been reverted and either has not expired
+	/// yet or is permanent.
+	pub is_active: bool,
 }
 
 // We can't derive this because `#[sqlx(flatten)]` does not support `Option<T>`.
@@ -106,7 +112,7 @@ where
 		};
 		let reason = row.try_get("reason")?;
 		let created_on = row.try_get("created_on")?;
-		let expires_on = row.try_get("expires_on")?;
+		let expires_on: Option<OffsetDateTime> = row.try_get("expires_on")?;
 		let unban = {
 			let id: Option<UnbanID> = row.try_get("unban_id")?;
 			let reason: Option<UnbanReason> = row.try_get("unban_reason")?;
@@ -123,7 +129,10 @@ where
 				.map(|((id, reason), created_on)| Unban { id, reason, admin, created_on })
 		};
 
-		Ok(Self { id, player, server, admin, reason, created_on, expires_on, unban })
+		let is_active = unban.is_none()
+			&& expires_on.is_none_or(|expires_on| expires_on > OffsetDateTime::now_utc());
+
+		Ok(Self { id, player, server, admin, reason, created_on, expires_on, unban, is_active })
 	}
 }
 
@@ -170,6 +179,11 @@ pub struct FetchBansRequest
 	/// Only include bans that have (not) already expired / have been reverted.
 	pub unbanned: Option<bool>,
 
+	/// Only include bans that are (not) currently in effect.
+	///
+	/// A ban is in effect if it has neither expired nor been reverted.
+	pub active: Option<bool>,
+
 	/// Filter by admin who created the ban.
 	pub banned_by: Option<PlayerIdentifier>,
 
@@ -230,6 +244,21 @@ pub struct BanRequest
 
 	/// Who issued this ban?
 	pub banned_by: BannedBy,
+
+	/// Whether the ban should never expire.
+	///
+	/// If this is `false`, the duration is determined by the ban reason and
+	/// the player's previous bans.
+	pub permanent: bool,
+
+	/// What to do if the player is already banned.
+	///
+	/// If this is `true`, the existing ban is extended by the new ban's
+	/// duration. Otherwise the request is rejected with
+	/// [`Error::PlayerAlreadyBanned`].
+	///
+	/// [`Error::PlayerAlreadyBanned`]: super::Error::PlayerAlreadyBanned
+	pub stack: bool,
 }
 
 /// Enum indicating who issued a [`BanRequest`].
@@ -262,6 +291,9 @@ pub enum BannedBy
 pub struct BanResponse
 {
 	/// The ID of the ban that was just created.
+	///
+	/// If the ban was stacked onto an existing ban, this is the ID of the
+	/// existing ban.
 	pub ban_id: BanID,
 }
 
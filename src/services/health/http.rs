@@ -3,13 +3,16 @@
 use axum::extract::State;
 use axum::{routing, Router};
 
-use super::HealthService;
+use super::{HealthReport, HealthService};
 
 impl From<HealthService> for Router
 {
 	fn from(svc: HealthService) -> Self
 	{
-		Router::new().route("/", routing::get(get)).with_state(svc)
+		Router::new()
+			.route("/", routing::get(get))
+			.route("/health", routing::get(get_report))
+			.with_state(svc)
 	}
 }
 
@@ -22,3 +25,89 @@ async fn get(State(svc): State<HealthService>) -> &'static str
 {
 	svc.hello().await
 }
+
+/// Reports on the health of the API and the services it depends on.
+///
+/// Dependencies are probed at most once every few seconds, so the report may be
+/// slightly out of date.
+#[tracing::instrument]
+#[utoipa::path(get, path = "/health", tag = "Health", operation_id = "health_report")]
+async fn get_report(State(svc): State<HealthService>) -> HealthReport
+{
+	svc.report().await
+}
+
+#[cfg(test)]
+mod tests
+{
+	use axum::body::Body;
+	use sqlx::{MySql, Pool};
+	use tower::ServiceExt;
+	use url::Url;
+
+	use super::*;
+	use crate::services::health::OverallStatus;
+	use crate::testing;
+
+	/// Returns a health service whose Steam probe always fails quickly.
+	fn health_svc(database: Pool<MySql>) -> color_eyre::Result<HealthService>
+	{
+		let steam_svc = testing::steam_svc().with_status_url(Url::parse("http://127.0.0.1:1")?);
+
+		Ok(HealthService::new(database, steam_svc))
+	}
+
+	/// Sends `GET /health` and returns the status code and report.
+	async fn get_health(
+		svc: HealthService,
+	) -> color_eyre::Result<(http::StatusCode, serde_json::Value)>
+	{
+		let req = http::Request::builder()
+			.uri("/health")
+			.body(Body::empty())?;
+
+		let res = Router::from(svc).oneshot(req).await?;
+		let status = res.status();
+		let body = testing::parse_body::<serde_json::Value>(res.into_body()).await?;
+
+		Ok((status, body))
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn report_shape(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let (status, body) = get_health(health_svc(database)?).await?;
+
+		testing::assert_eq!(status, http::StatusCode::OK);
+		testing::assert_eq!(body["status"], "degraded");
+		testing::assert_eq!(body["database"]["reachable"], true);
+		testing::assert!(body["database"]["latency"].is_f64());
+		testing::assert_eq!(body["steam"]["reachable"], false);
+		testing::assert!(body["plugin_versions"].is_u64());
+		testing::assert!(body["uptime"].is_f64());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn closed_database_is_unhealthy() -> color_eyre::Result<()>
+	{
+		let database = testing::lazy_pool();
+
+		database.close().await;
+
+		let svc = health_svc(database)?;
+		let (status, body) = get_health(svc.clone()).await?;
+
+		testing::assert_eq!(status, http::StatusCode::SERVICE_UNAVAILABLE);
+		testing::assert_eq!(body["status"], "unhealthy");
+		testing::assert_eq!(body["database"]["reachable"], false);
+		testing::assert!(body["plugin_versions"].is_null());
+
+		let report = svc.report().await;
+
+		testing::assert_eq!(report.status, OverallStatus::Unhealthy);
+
+		Ok(())
+	}
+}
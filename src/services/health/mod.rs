@@ -1,18 +1,40 @@
-//! A very basic service that acts as a healthcheck.
+//! A service that reports on the health of the API.
 //!
-//! This doesn't really need to be a service, but it's the simplest example of
-//! one, and can be used as a reference for writing new services.
+//! `GET /` is the simplest possible healthcheck and can be used as a reference
+//! for writing new services. `GET /health` reports on the API's dependencies.
 
 use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use axum::extract::FromRef;
+use sqlx::{MySql, Pool};
+use tokio::sync::Mutex;
+
+use crate::services::SteamService;
 
 pub(crate) mod http;
 
-/// A service that simply responds if the API is healthy.
-#[derive(Clone, Copy, FromRef)]
+pub(crate) mod models;
+pub use models::{ComponentHealth, HealthReport, OverallStatus};
+
+/// How long a [`HealthReport`] is reused before dependencies are probed again.
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long we wait for a dependency to respond before considering it
+/// unreachable.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A service that reports on the health of the API.
+#[derive(Clone, FromRef)]
 #[allow(clippy::missing_docs_in_private_items)]
-pub struct HealthService {}
+pub struct HealthService
+{
+	database: Pool<MySql>,
+	steam_svc: SteamService,
+	started_at: Instant,
+	last_report: Arc<Mutex<Option<(Instant, HealthReport)>>>,
+}
 
 impl fmt::Debug for HealthService
 {
@@ -26,9 +48,14 @@ impl HealthService
 {
 	/// Create a new [`HealthService`].
 	#[tracing::instrument]
-	pub fn new() -> Self
+	pub fn new(database: Pool<MySql>, steam_svc: SteamService) -> Self
 	{
-		Self {}
+		Self {
+			database,
+			steam_svc,
+			started_at: Instant::now(),
+			last_report: Arc::new(Mutex::new(None)),
+		}
 	}
 
 	/// Says hello to the world.
@@ -37,4 +64,80 @@ impl HealthService
 	{
 		"(͡ ͡° ͜ つ ͡͡°)"
 	}
+
+	/// Reports on the health of the API.
+	///
+	/// Dependencies are probed at most once every [`PROBE_INTERVAL`]; requests
+	/// in between get the previous report. Concurrent requests wait for the
+	/// same probe instead of starting their own.
+	#[tracing::instrument(level = "debug")]
+	pub async fn report(&self) -> HealthReport
+	{
+		let mut last_report = self.last_report.lock().await;
+
+		if let Some((probed_at, report)) = &*last_report {
+			if probed_at.elapsed() < PROBE_INTERVAL {
+				return report.clone();
+			}
+		}
+
+		let report = self.probe().await;
+
+		*last_report = Some((Instant::now(), report.clone()));
+
+		report
+	}
+
+	/// Probes all dependencies.
+	async fn probe(&self) -> HealthReport
+	{
+		let ((database, plugin_versions), steam) = tokio::join!(self.probe_database(), async {
+			let started = Instant::now();
+			let reachable = self.steam_svc.is_reachable(PROBE_TIMEOUT).await;
+
+			ComponentHealth { reachable, latency: started.elapsed().into() }
+		});
+
+		let status = match (database.reachable, steam.reachable) {
+			(false, _) => OverallStatus::Unhealthy,
+			(true, false) => OverallStatus::Degraded,
+			(true, true) => OverallStatus::Healthy,
+		};
+
+		HealthReport {
+			status,
+			uptime: self.started_at.elapsed().into(),
+			database,
+			steam,
+			plugin_versions,
+		}
+	}
+
+	/// Checks if the database is reachable, and counts plugin versions while
+	/// we're at it.
+	async fn probe_database(&self) -> (ComponentHealth, Option<u64>)
+	{
+		let started = Instant::now();
+		let query =
+			sqlx::query_scalar!("SELECT COUNT(*) FROM PluginVersions").fetch_one(&self.database);
+
+		let plugin_versions = match tokio::time::timeout(PROBE_TIMEOUT, query).await {
+			Ok(Ok(count)) => u64::try_from(count).ok(),
+			Ok(Err(error)) => {
+				tracing::error!(%error, "database is unreachable");
+				None
+			}
+			Err(_) => {
+				tracing::error!(timeout = ?PROBE_TIMEOUT, "database did not respond in time");
+				None
+			}
+		};
+
+		let database = ComponentHealth {
+			reachable: plugin_versions.is_some(),
+			latency: started.elapsed().into(),
+		};
+
+		(database, plugin_versions)
+	}
 }
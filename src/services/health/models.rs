@@ -0,0 +1,96 @@
+//! Request / Response types for this service.
+
+use std::collections::BTreeMap;
+
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use utoipa::openapi::{self, ContentBuilder, Ref, RefOr, ResponseBuilder};
+
+use crate::time::Seconds;
+
+/// A report about the health of the API and the services it depends on.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct HealthReport
+{
+	/// The overall status.
+	pub status: OverallStatus,
+
+	/// How long the API has been running.
+	#[schema(value_type = f64)]
+	pub uptime: Seconds,
+
+	/// The status of the database.
+	pub database: ComponentHealth,
+
+	/// The status of the Steam Web API.
+	pub steam: ComponentHealth,
+
+	/// How many plugin versions are currently known.
+	///
+	/// This is absent if the database is unreachable.
+	pub plugin_versions: Option<u64>,
+}
+
+/// The overall status of the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OverallStatus
+{
+	/// Everything is working.
+	Healthy,
+
+	/// The API is working, but some non-essential dependency is unreachable.
+	Degraded,
+
+	/// The API cannot serve requests.
+	Unhealthy,
+}
+
+/// The status of a single dependency.
+#[derive(Debug, Clone, Copy, Serialize, utoipa::ToSchema)]
+pub struct ComponentHealth
+{
+	/// Whether the dependency is reachable.
+	pub reachable: bool,
+
+	/// How long it took to check (in seconds).
+	#[schema(value_type = f64)]
+	pub latency: Seconds,
+}
+
+impl IntoResponse for HealthReport
+{
+	fn into_response(self) -> Response
+	{
+		let status = match self.status {
+			OverallStatus::Healthy | OverallStatus::Degraded => http::StatusCode::OK,
+			OverallStatus::Unhealthy => http::StatusCode::SERVICE_UNAVAILABLE,
+		};
+
+		(status, crate::http::extract::Json(self)).into_response()
+	}
+}
+
+impl utoipa::IntoResponses for HealthReport
+{
+	fn responses() -> BTreeMap<String, RefOr<openapi::response::Response>>
+	{
+		let response = |description| {
+			ResponseBuilder::new()
+				.description(description)
+				.content(
+					"application/json",
+					ContentBuilder::new()
+						.schema(Ref::from_schema_name("HealthReport"))
+						.build(),
+				)
+				.build()
+				.into()
+		};
+
+		BTreeMap::from_iter([
+			(String::from("200"), response("The API is healthy, or degraded.")),
+			(String::from("503"), response("The database is unreachable.")),
+		])
+	}
+}
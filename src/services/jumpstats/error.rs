@@ -1,6 +1,6 @@
 //! The errors that can occur when interacting with this service.
 
-use cs2kz::SteamID;
+use cs2kz::{JumpType, SteamID};
 use thiserror::Error;
 
 use super::JumpstatID;
@@ -37,6 +37,18 @@ pub enum Error
 		jumpstat_id: JumpstatID,
 	},
 
+	/// A jumpstat was submitted with a distance that is impossible for its
+	/// jump type.
+	#[error("`{distance}` is not a valid distance for a {jump_type}")]
+	InvalidDistance
+	{
+		/// The jump type.
+		jump_type: JumpType,
+
+		/// The submitted distance.
+		distance: f32,
+	},
+
 	/// Something went wrong communicating with the database.
 	#[error("something went wrong")]
 	Database(#[from] sqlx::Error),
@@ -51,6 +63,7 @@ impl IntoProblemDetails for Error
 			Self::PlayerDoesNotExist { .. } | Self::JumpstatDoesNotExist { .. } => {
 				ProblemType::ResourceNotFound
 			}
+			Self::InvalidDistance { .. } => ProblemType::InvalidRequestBody,
 			Self::Database(source) => source.problem_type(),
 		}
 	}
@@ -64,6 +77,10 @@ impl IntoProblemDetails for Error
 			Self::JumpstatDoesNotExist { jumpstat_id } => {
 				ext.add("jumpstat_id", jumpstat_id);
 			}
+			Self::InvalidDistance { jump_type, distance } => {
+				ext.add("jump_type", jump_type);
+				ext.add("distance", distance);
+			}
 			_ => {}
 		}
 	}
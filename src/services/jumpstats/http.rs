@@ -11,6 +11,9 @@ use super::{
 	FetchJumpstatResponse,
 	FetchJumpstatsRequest,
 	FetchJumpstatsResponse,
+	FetchLeaderboardRequest,
+	FetchLeaderboardResponse,
+	FetchPersonalBestRequest,
 	JumpstatID,
 	JumpstatService,
 	SubmitJumpstatRequest,
@@ -38,6 +41,8 @@ impl From<JumpstatService> for Router
 
 		let public = Router::new()
 			.route("/", routing::get(get_many))
+			.route("/leaderboard", routing::get(get_leaderboard))
+			.route("/personal-best", routing::get(get_personal_best))
 			.route("/:id", routing::get(get_single))
 			.route_layer(middleware::cors::permissive())
 			.with_state(svc.clone());
@@ -194,3 +199,45 @@ async fn get_single(
 
 	Ok(res)
 }
+
+/// Fetch every player's best jump of a specific type.
+#[tracing::instrument(level = "trace", err(Debug, level = "debug"))]
+#[utoipa::path(
+	get,
+	path = "/jumpstats/leaderboard",
+	tag = "Jumpstats",
+	operation_id = "get_jumpstat_leaderboard",
+	params(FetchLeaderboardRequest)
+)]
+async fn get_leaderboard(
+	State(svc): State<JumpstatService>,
+	Query(req): Query<FetchLeaderboardRequest>,
+) -> Result<FetchLeaderboardResponse, ProblemDetails>
+{
+	let res = svc.fetch_leaderboard(req).await?;
+
+	if res.leaderboard.is_empty() {
+		Err(Error::NoData)?;
+	}
+
+	Ok(res)
+}
+
+/// Fetch a player's best jump of a specific type.
+#[tracing::instrument(level = "trace", err(Debug, level = "debug"))]
+#[utoipa::path(
+	get,
+	path = "/jumpstats/personal-best",
+	tag = "Jumpstats",
+	operation_id = "get_jumpstat_personal_best",
+	params(FetchPersonalBestRequest)
+)]
+async fn get_personal_best(
+	State(svc): State<JumpstatService>,
+	Query(req): Query<FetchPersonalBestRequest>,
+) -> Result<FetchJumpstatResponse, ProblemDetails>
+{
+	let res = svc.fetch_personal_best(req).await?.ok_or(Error::NoData)?;
+
+	Ok(res)
+}
@@ -7,8 +7,10 @@
  */
 
 use std::fmt;
+use std::ops::RangeInclusive;
 
 use axum::extract::FromRef;
+use cs2kz::JumpType;
 use sqlx::{MySql, Pool, Row};
 
 pub(crate) mod http;
@@ -23,7 +25,11 @@ pub use models::{
 	FetchJumpstatResponse,
 	FetchJumpstatsRequest,
 	FetchJumpstatsResponse,
+	FetchLeaderboardRequest,
+	FetchLeaderboardResponse,
+	FetchPersonalBestRequest,
 	JumpstatID,
+	LeaderboardEntry,
 	SubmitJumpstatRequest,
 	SubmitJumpstatResponse,
 };
@@ -135,6 +141,96 @@ impl JumpstatService
 		Ok(FetchJumpstatsResponse { jumpstats, total })
 	}
 
+	/// Fetch every player's best jump of a specific type, ordered by distance.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn fetch_leaderboard(
+		&self,
+		req: FetchLeaderboardRequest,
+	) -> Result<FetchLeaderboardResponse>
+	{
+		let jumpstats = sqlx::query_as::<_, FetchJumpstatResponse>(&format!(
+			r"
+			{}
+			  JOIN (
+			    SELECT
+			      id,
+			      ROW_NUMBER() OVER (
+			        PARTITION BY player_id
+			        ORDER BY
+			          distance DESC,
+			          created_on ASC
+			      ) n
+			    FROM
+			      Jumpstats
+			    WHERE
+			      type = ?
+			      AND mode = ?
+			  ) best ON best.id = j.id
+			WHERE
+			  best.n = 1
+			ORDER BY
+			  j.distance DESC,
+			  j.created_on ASC
+			LIMIT
+			  ?
+			",
+			queries::SELECT,
+		))
+		.bind(req.jump_type)
+		.bind(req.mode)
+		.bind(*req.limit)
+		.fetch_all(&self.database)
+		.await?;
+
+		let mut leaderboard = Vec::<LeaderboardEntry>::with_capacity(jumpstats.len());
+
+		for (idx, jumpstat) in jumpstats.into_iter().enumerate() {
+			let rank = match leaderboard.last() {
+				Some(prev) if prev.jumpstat.distance == jumpstat.distance => prev.rank,
+				_ => idx as u64 + 1,
+			};
+
+			leaderboard.push(LeaderboardEntry { rank, jumpstat });
+		}
+
+		Ok(FetchLeaderboardResponse { leaderboard })
+	}
+
+	/// Fetch a player's best jump of a specific type.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn fetch_personal_best(
+		&self,
+		req: FetchPersonalBestRequest,
+	) -> Result<Option<FetchJumpstatResponse>>
+	{
+		let Some(player_id) = req.player.resolve_id(&self.database).await? else {
+			return Ok(None);
+		};
+
+		let jumpstat = sqlx::query_as::<_, FetchJumpstatResponse>(&format!(
+			r"
+			{}
+			WHERE
+			  j.player_id = ?
+			  AND j.type = ?
+			  AND j.mode = ?
+			ORDER BY
+			  j.distance DESC,
+			  j.created_on ASC
+			LIMIT
+			  1
+			",
+			queries::SELECT,
+		))
+		.bind(player_id)
+		.bind(req.jump_type)
+		.bind(req.mode)
+		.fetch_optional(&self.database)
+		.await?;
+
+		Ok(jumpstat)
+	}
+
 	/// Submit a new jumpstat.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn submit_jumpstat(
@@ -142,6 +238,13 @@ impl JumpstatService
 		req: SubmitJumpstatRequest,
 	) -> Result<SubmitJumpstatResponse>
 	{
+		if !distance_bounds(req.jump_type).contains(&req.distance) {
+			return Err(Error::InvalidDistance {
+				jump_type: req.jump_type,
+				distance: req.distance,
+			});
+		}
+
 		let mut txn = self.database.begin().await?;
 
 		let jumpstat_id = sqlx::query! {
@@ -227,3 +330,140 @@ impl JumpstatService
 		Ok(SubmitJumpstatResponse { jumpstat_id })
 	}
 }
+
+/// Returns the range of distances that are possible for a given jump type.
+///
+/// These are deliberately generous; anything outside of them is a bug in the
+/// plugin rather than an exceptionally good (or bad) jump.
+fn distance_bounds(jump_type: JumpType) -> RangeInclusive<f32>
+{
+	match jump_type {
+		JumpType::LadderJump => 1.0..=250.0,
+		_ => 1.0..=400.0,
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::time::Duration;
+
+	use color_eyre::eyre::ContextCompat;
+	use cs2kz::Mode;
+
+	use super::*;
+	use crate::testing::{self, ALPHAKEKS_ID};
+
+	/// Returns a plausible jumpstat submission.
+	fn longjump(distance: f32) -> SubmitJumpstatRequest
+	{
+		SubmitJumpstatRequest {
+			jump_type: JumpType::LongJump,
+			mode: Mode::Vanilla,
+			player_id: ALPHAKEKS_ID,
+			strafes: 5,
+			distance,
+			sync: 0.8,
+			pre: 276.3,
+			max: 310.9,
+			overlap: Duration::from_millis(30).into(),
+			bad_angles: Duration::from_millis(10).into(),
+			dead_air: Duration::from_millis(20).into(),
+			height: 63.2,
+			airpath: 0.95,
+			deviation: 0.4,
+			average_width: 12.5,
+			airtime: Duration::from_millis(730).into(),
+			server_id: 1.into(),
+			server_plugin_version_id: 1.into(),
+		}
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn submit_and_fetch_jumpstat(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::jumpstat_svc(database);
+		let res = svc.submit_jumpstat(longjump(265.5)).await?;
+
+		let req = FetchJumpstatRequest { jumpstat_id: res.jumpstat_id };
+		let jumpstat = svc.fetch_jumpstat(req).await?.context("got `None`")?;
+
+		testing::assert_eq!(jumpstat.id, res.jumpstat_id);
+		testing::assert_eq!(jumpstat.jump_type, JumpType::LongJump);
+		testing::assert_eq!(jumpstat.player.steam_id, ALPHAKEKS_ID);
+		testing::assert_eq!(jumpstat.distance, 265.5);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/players.sql",
+			"../../../database/fixtures/jumpstats.sql",
+		)
+	)]
+	async fn leaderboards_are_ranked_per_jump_type(database: Pool<MySql>)
+		-> color_eyre::Result<()>
+	{
+		let svc = testing::jumpstat_svc(database);
+
+		for (jump_type, expected) in [
+			(
+				JumpType::LongJump,
+				&[("zer0.k", 280.2), ("AlphaKeks", 270.1), ("iBrahizy", 260.7)][..],
+			),
+			(JumpType::Bhop, &[("iBrahizy", 310.9), ("AlphaKeks", 300.3)][..]),
+		] {
+			let req = FetchLeaderboardRequest {
+				jump_type,
+				mode: Mode::Vanilla,
+				limit: Default::default(),
+			};
+
+			let res = svc.fetch_leaderboard(req).await?;
+			let entries = res
+				.leaderboard
+				.iter()
+				.map(|entry| {
+					(entry.rank, entry.jumpstat.player.name.as_str(), entry.jumpstat.distance)
+				})
+				.collect::<Vec<_>>();
+
+			let expected = expected
+				.iter()
+				.enumerate()
+				.map(|(idx, &(name, distance))| (idx as u64 + 1, name, distance))
+				.collect::<Vec<_>>();
+
+			testing::assert_eq!(entries, expected);
+		}
+
+		let req = FetchPersonalBestRequest {
+			player: ALPHAKEKS_ID.into(),
+			jump_type: JumpType::LongJump,
+			mode: Mode::Vanilla,
+		};
+
+		let pb = svc.fetch_personal_best(req).await?.context("got `None`")?;
+
+		testing::assert_eq!(pb.distance, 270.1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn submit_jumpstat_rejects_impossible_distance() -> color_eyre::Result<()>
+	{
+		let database = testing::lazy_pool();
+		let svc = testing::jumpstat_svc(database);
+
+		for distance in [0.0, -20.0, 1337.0, f32::NAN] {
+			let res = svc.submit_jumpstat(longjump(distance)).await.unwrap_err();
+
+			testing::assert!(matches!(res, Error::InvalidDistance { .. }), "{distance}");
+		}
+
+		Ok(())
+	}
+}
@@ -246,3 +246,66 @@ impl IntoResponse for SubmitJumpstatResponse
 		(status, headers, body).into_response()
 	}
 }
+
+/// Request payload for fetching a jump type's leaderboard.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct FetchLeaderboardRequest
+{
+	/// The jump type.
+	#[serde(rename = "type")]
+	pub jump_type: JumpType,
+
+	/// The mode.
+	pub mode: Mode,
+
+	/// Maximum number of results to return.
+	#[serde(default)]
+	#[param(value_type = u64, default = 100, maximum = 1000)]
+	pub limit: ClampedU64<100, 1000>,
+}
+
+/// A single entry on a jumpstat leaderboard.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct LeaderboardEntry
+{
+	/// The jump's rank on the leaderboard.
+	///
+	/// Jumps with the same distance share a rank.
+	pub rank: u64,
+
+	/// The jump itself.
+	#[serde(flatten)]
+	pub jumpstat: FetchJumpstatResponse,
+}
+
+/// Response payload for fetching a jump type's leaderboard.
+#[derive(Debug, Serialize, utoipa::IntoResponses)]
+#[response(status = OK)]
+pub struct FetchLeaderboardResponse
+{
+	/// Every player's best jump, ordered by distance.
+	pub leaderboard: Vec<LeaderboardEntry>,
+}
+
+impl IntoResponse for FetchLeaderboardResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
+
+/// Request payload for fetching a player's personal best.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct FetchPersonalBestRequest
+{
+	/// The player.
+	pub player: PlayerIdentifier,
+
+	/// The jump type.
+	#[serde(rename = "type")]
+	pub jump_type: JumpType,
+
+	/// The mode.
+	pub mode: Mode,
+}
@@ -2,9 +2,10 @@
 
 use std::io;
 
+use cs2kz::GlobalStatus;
 use thiserror::Error;
 
-use super::{CourseID, FilterID, MapID};
+use super::{Checksum, CourseID, FilterID, MapID};
 use crate::http::problem_details::{self, IntoProblemDetails, ProblemType};
 use crate::services::steam;
 
@@ -64,6 +65,11 @@ pub enum Error
 		course_id: Option<CourseID>,
 	},
 
+	/// A request wanted to create a course or rename an existing one, but the
+	/// map already has a course with that name.
+	#[error("course name is already taken")]
+	CourseNameAlreadyTaken,
+
 	/// A request wanted to update a map's courses, but specified a course ID
 	/// that does not belong to the map ID it made the request for.
 	#[error("course is not part of map")]
@@ -90,6 +96,37 @@ pub enum Error
 		filter_id: FilterID,
 	},
 
+	/// A request specified the checksum a map was expected to have, but the map
+	/// downloaded from the workshop has a different one.
+	#[error("map checksum does not match the expected checksum")]
+	ChecksumMismatch
+	{
+		/// The checksum the request expected.
+		expected: Checksum,
+
+		/// The checksum of the downloaded map.
+		actual: Checksum,
+	},
+
+	/// A request specified the checksum a map was expected to have, but didn't
+	/// ask us to check the workshop, so there is nothing to compare it to.
+	#[error("`expected_checksum` requires `check_steam` or a new `workshop_id`")]
+	ChecksumWithoutSteamCheck,
+
+	/// A request wanted to change a map's global status, but the map's current
+	/// status cannot be changed to the requested one.
+	///
+	/// See [`GlobalStatus::can_transition_to()`].
+	#[error("map cannot go from {from} to {to}")]
+	InvalidStateTransition
+	{
+		/// The map's current status.
+		from: GlobalStatus,
+
+		/// The requested status.
+		to: GlobalStatus,
+	},
+
 	/// An operation using the steam service failed.
 	#[error(transparent)]
 	Steam(#[from] steam::Error),
@@ -114,10 +151,14 @@ impl IntoProblemDetails for Error
 				ProblemType::MustHaveMappers
 			}
 			Self::MapMustHaveCourses => ProblemType::MapMustHaveCourses,
+			Self::CourseNameAlreadyTaken => ProblemType::ResourceAlreadyExists,
+			Self::ChecksumMismatch { .. } => ProblemType::ChecksumMismatch,
+			Self::ChecksumWithoutSteamCheck => ProblemType::InvalidRequestBody,
 			Self::MismatchingCourseID { .. } | Self::MismatchingFilterID { .. } => {
 				ProblemType::UnrelatedUpdate
 			}
 			Self::MapperDoesNotExist => ProblemType::ResourceNotFound,
+			Self::InvalidStateTransition { .. } => ProblemType::InvalidStatusTransition,
 			Self::Steam(source) => source.problem_type(),
 			Self::CalculateMapChecksum(_) => ProblemType::Internal,
 			Self::Database(source) => source.problem_type(),
@@ -138,6 +179,14 @@ impl IntoProblemDetails for Error
 				ext.add("course_id", course_id);
 				ext.add("filter_id", filter_id);
 			}
+			Self::ChecksumMismatch { expected, actual } => {
+				ext.add("expected", expected);
+				ext.add("actual", actual);
+			}
+			Self::InvalidStateTransition { from, to } => {
+				ext.add("from", from);
+				ext.add("to", to);
+			}
 			Self::Steam(source) => {
 				source.add_extension_members(ext);
 			}
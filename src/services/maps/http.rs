@@ -2,30 +2,41 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 
-use axum::extract::State;
+use axum::extract::{OriginalUri, State};
 use axum::{routing, Router};
+use axum_extra::headers::IfNoneMatch;
+use axum_extra::TypedHeader;
 use cs2kz::{GlobalStatus, SteamID};
 use serde::Deserialize;
 use tower::ServiceBuilder;
 
 use super::{
 	Error,
+	FetchFiltersRequest,
+	FetchFiltersResponse,
 	FetchMapRequest,
 	FetchMapResponse,
+	FetchMapsByWorkshopIDRequest,
+	FetchMapsByWorkshopIDResponse,
 	FetchMapsRequest,
 	FetchMapsResponse,
 	MapService,
+	SearchCoursesRequest,
+	SearchCoursesResponse,
 	SubmitMapRequest,
 	SubmitMapResponse,
+	SubmitMapsRequest,
+	SubmitMapsResponse,
 	UpdateMapRequest,
 	UpdateMapResponse,
 };
 use crate::http::extract::{Json, Path, Query};
-use crate::http::ProblemDetails;
+use crate::http::pagination::Page;
+use crate::http::{ETagged, Paginated, ProblemDetails};
 use crate::middleware;
 use crate::services::auth::session::{authorization, user, SessionManagerLayer};
 use crate::services::auth::Session;
-use crate::services::maps::{CourseID, CourseUpdate, MapID};
+use crate::services::maps::{Checksum, CourseID, CourseUpdate, MapID};
 use crate::services::steam::WorkshopID;
 use crate::util::MapIdentifier;
 
@@ -43,11 +54,25 @@ impl From<MapService> for Router
 		let public = Router::new()
 			.route("/", routing::get(get_many))
 			.route("/:map", routing::get(get_single))
+			.route("/workshop/:workshop_id", routing::get(get_by_workshop_id))
+			.route("/courses/search", routing::get(search_courses))
+			.route("/filters", routing::get(get_filters))
 			.route_layer(middleware::cors::permissive())
 			.with_state(svc.clone());
 
 		let protected = Router::new()
-			.route("/", routing::put(submit_map).route_layer(auth.clone()))
+			.route(
+				"/",
+				routing::put(submit_map)
+					.layer(middleware::IdempotencyLayer::new(svc.database.clone()))
+					.route_layer(auth.clone()),
+			)
+			.route(
+				"/bulk",
+				routing::put(submit_maps)
+					.layer(middleware::IdempotencyLayer::new(svc.database.clone()))
+					.route_layer(auth.clone()),
+			)
 			.route("/:map", routing::patch(update_map).route_layer(auth.clone()))
 			.route_layer(middleware::cors::dashboard([
 				http::Method::OPTIONS,
@@ -70,43 +95,191 @@ impl From<MapService> for Router
 )]
 async fn get_many(
 	State(svc): State<MapService>,
+	if_none_match: Option<TypedHeader<IfNoneMatch>>,
+	OriginalUri(uri): OriginalUri,
 	Query(req): Query<FetchMapsRequest>,
-) -> Result<FetchMapsResponse, ProblemDetails>
+) -> Result<Paginated<ETagged<FetchMapsResponse>>, ProblemDetails>
 {
+	let (limit, offset) = (*req.limit, *req.offset);
 	let res = svc.fetch_maps(req).await?;
 
 	if res.maps.is_empty() {
 		Err(Error::NoData)?;
 	}
 
-	Ok(res)
+	let page = Page { uri: &uri, limit, offset, total: res.total };
+
+	Ok(Paginated::new(res, page).map(|res| ETagged::new(res, if_none_match)))
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(put, path = "/maps", tag = "Maps")]
+#[utoipa::path(put, path = "/maps", tag = "Maps", operation_id = "submit_map")]
 async fn submit_map(
 	session: Session,
 	State(svc): State<MapService>,
-	Json(req): Json<SubmitMapRequest>,
+	Json(mut req): Json<SubmitMapRequest>,
 ) -> Result<SubmitMapResponse, ProblemDetails>
 {
+	req.actor_id = Some(session.user().steam_id());
+
 	let res = svc.submit_map(req).await?;
 
 	Ok(res)
 }
 
+#[tracing::instrument(skip(maps), fields(maps = maps.len()), err(Debug, level = "debug"))]
+#[utoipa::path(
+	put,
+	path = "/maps/bulk",
+	tag = "Maps",
+	operation_id = "submit_maps",
+	request_body = Vec<SubmitMapRequest>
+)]
+async fn submit_maps(
+	session: Session,
+	State(svc): State<MapService>,
+	Json(SubmitMapsRequestPayload(mut maps)): Json<SubmitMapsRequestPayload>,
+) -> Result<SubmitMapsResponse, ProblemDetails>
+{
+	for map in &mut maps {
+		map.actor_id = Some(session.user().steam_id());
+	}
+
+	let res = svc.submit_maps(SubmitMapsRequest { maps }).await?;
+
+	Ok(res)
+}
+
+/// The maximum amount of maps that can be submitted in a single batch.
+pub const MAX_BATCH_SIZE: usize = 25;
+
+/// Request payload for `PUT /maps/bulk`.
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+struct SubmitMapsRequestPayload(
+	#[serde(deserialize_with = "deserialize_batch")] Vec<SubmitMapRequest>,
+);
+
+/// Deserializes the maps of a batch and makes sure there are at most
+/// [`MAX_BATCH_SIZE`] of them.
+fn deserialize_batch<'de, D>(deserializer: D) -> Result<Vec<SubmitMapRequest>, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	let maps = Vec::<SubmitMapRequest>::deserialize(deserializer)?;
+
+	if maps.len() > MAX_BATCH_SIZE {
+		let expected = format!("at most {MAX_BATCH_SIZE} maps");
+
+		return Err(serde::de::Error::invalid_length(maps.len(), &expected.as_str()));
+	}
+
+	Ok(maps)
+}
+
+/// Query parameters for `GET /maps/{map}`.
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[doc(hidden)]
+pub(crate) struct GetMapQuery
+{
+	/// Additional data to include in the response.
+	#[serde(default)]
+	pub include: Vec<MapInclude>,
+}
+
+/// Additional data that can be included when fetching a single map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[doc(hidden)]
+pub(crate) enum MapInclude
+{
+	/// Record counts and world record times for every filter.
+	Stats,
+}
+
 #[tracing::instrument(err(Debug, level = "debug"))]
 #[utoipa::path(get, path = "/maps/{map}", tag = "Maps", operation_id = "get_map", params(
   ("map" = MapIdentifier, Path, description = "a map's ID or name"),
+  GetMapQuery,
 ))]
 async fn get_single(
 	State(svc): State<MapService>,
+	if_none_match: Option<TypedHeader<IfNoneMatch>>,
 	Path(ident): Path<MapIdentifier>,
-) -> Result<FetchMapResponse, ProblemDetails>
+	Query(query): Query<GetMapQuery>,
+) -> Result<ETagged<FetchMapResponse>, ProblemDetails>
 {
-	let req = FetchMapRequest { ident };
+	let include_stats = query.include.contains(&MapInclude::Stats);
+	let req = FetchMapRequest { ident, include_stats };
 	let res = svc.fetch_map(req).await?.ok_or(Error::MapDoesNotExist)?;
 
+	Ok(ETagged::new(res, if_none_match))
+}
+
+#[tracing::instrument(err(Debug, level = "debug"))]
+#[utoipa::path(
+  get,
+  path = "/maps/workshop/{workshop_id}",
+  tag = "Maps",
+  operation_id = "get_map_by_workshop_id",
+  params(("workshop_id" = WorkshopID, Path, description = "a map's Workshop ID")),
+)]
+async fn get_by_workshop_id(
+	State(svc): State<MapService>,
+	Path(workshop_id): Path<WorkshopID>,
+) -> Result<FetchMapsByWorkshopIDResponse, ProblemDetails>
+{
+	let req = FetchMapsByWorkshopIDRequest { workshop_id };
+	let res = svc.fetch_maps_by_workshop_id(req).await?;
+
+	if res.maps.is_empty() {
+		Err(Error::NoData)?;
+	}
+
+	Ok(res)
+}
+
+#[tracing::instrument(err(Debug, level = "debug"))]
+#[utoipa::path(
+	get,
+	path = "/maps/courses/search",
+	tag = "Maps",
+	operation_id = "search_courses",
+	params(SearchCoursesRequest)
+)]
+async fn search_courses(
+	State(svc): State<MapService>,
+	Query(req): Query<SearchCoursesRequest>,
+) -> Result<SearchCoursesResponse, ProblemDetails>
+{
+	let res = svc.search_courses(req).await?;
+
+	if res.courses.is_empty() {
+		Err(Error::NoData)?;
+	}
+
+	Ok(res)
+}
+
+#[tracing::instrument(err(Debug, level = "debug"))]
+#[utoipa::path(
+	get,
+	path = "/maps/filters",
+	tag = "Maps",
+	operation_id = "get_filters",
+	params(FetchFiltersRequest)
+)]
+async fn get_filters(
+	State(svc): State<MapService>,
+	Query(req): Query<FetchFiltersRequest>,
+) -> Result<FetchFiltersResponse, ProblemDetails>
+{
+	let res = svc.fetch_filters(req).await?;
+
+	if res.filters.is_empty() {
+		Err(Error::NoData)?;
+	}
+
 	Ok(res)
 }
 
@@ -150,6 +323,13 @@ pub(crate) struct UpdateMapRequestPayload
 
 	/// Updates to this map's courses.
 	pub course_updates: Option<BTreeMap<CourseID, CourseUpdate>>,
+
+	/// The checksum the map is expected to have after checking the workshop.
+	///
+	/// If the downloaded map has a different checksum, the update is rejected.
+	/// This requires `check_steam` to be set or `workshop_id` to be changed.
+	#[serde(default)]
+	pub expected_checksum: Option<Checksum>,
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
@@ -157,6 +337,7 @@ pub(crate) struct UpdateMapRequestPayload
   patch,
   path = "/maps/{map_id}",
   tag = "Maps",
+  operation_id = "update_map",
   params(("map_id" = MapID, Path, description = "a map's ID")),
   security(("Browser Session" = ["maps"])),
 )]
@@ -172,6 +353,7 @@ async fn update_map(
 		added_mappers,
 		removed_mappers,
 		course_updates,
+		expected_checksum,
 	}): Json<UpdateMapRequestPayload>,
 ) -> Result<UpdateMapResponse, ProblemDetails>
 {
@@ -184,6 +366,8 @@ async fn update_map(
 		added_mappers,
 		removed_mappers,
 		course_updates,
+		expected_checksum,
+		actor_id: Some(session.user().steam_id()),
 	};
 
 	let res = svc.update_map(req).await?;
@@ -202,6 +386,46 @@ mod tests
 	use super::*;
 	use crate::testing;
 
+	#[test]
+	fn rejects_oversized_batch() -> color_eyre::Result<()>
+	{
+		let filter = |mode: &str, teleports: bool| {
+			serde_json::json!({
+				"mode": mode,
+				"teleports": teleports,
+				"tier": "easy",
+				"ranked_status": "ranked",
+			})
+		};
+
+		let map = serde_json::json!({
+			"workshop_id": 3070194623_u32,
+			"global_status": "global",
+			"mappers": [testing::ALPHAKEKS_ID],
+			"courses": [{
+				"mappers": [testing::ALPHAKEKS_ID],
+				"filters": [
+					filter("vanilla", true),
+					filter("vanilla", false),
+					filter("classic", true),
+					filter("classic", false),
+				],
+			}],
+		});
+
+		let batch = |count: usize| {
+			serde_json::from_value::<SubmitMapsRequestPayload>(serde_json::json!(vec![
+				map.clone();
+				count
+			]))
+		};
+
+		testing::assert_matches!(batch(MAX_BATCH_SIZE), Ok(SubmitMapsRequestPayload(maps)) if maps.len() == MAX_BATCH_SIZE);
+		testing::assert!(batch(MAX_BATCH_SIZE + 1).is_err());
+
+		Ok(())
+	}
+
 	#[sqlx::test(
 		migrations = "database/migrations",
 		fixtures(
@@ -257,4 +481,46 @@ mod tests
 
 		Ok(())
 	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/grotto.sql",
+		)
+	)]
+	async fn unchanged_maps_are_not_modified(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let mut router = Router::new()
+			.route("/", routing::get(get_many))
+			.route("/:map", routing::get(get_single))
+			.with_state(testing::map_svc(database));
+
+		for uri in ["/", "/checkmate"] {
+			let req = Request::get(uri).body(axum::body::Body::default())?;
+			let res = router.call(req).await?;
+
+			testing::assert_eq!(res.status(), http::StatusCode::OK);
+
+			let etag = res
+				.headers()
+				.get(http::header::ETAG)
+				.cloned()
+				.expect("response has an etag");
+
+			let req = Request::get(uri)
+				.header(http::header::IF_NONE_MATCH, etag)
+				.body(axum::body::Body::default())?;
+
+			let res = router.call(req).await?;
+
+			testing::assert_eq!(res.status(), http::StatusCode::NOT_MODIFIED);
+
+			let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+
+			testing::assert!(body.is_empty());
+		}
+
+		Ok(())
+	}
 }
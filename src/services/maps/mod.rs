@@ -1,18 +1,22 @@
 //! A service for managing KZ maps.
 
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
 use std::{fmt, iter};
 
 use axum::extract::FromRef;
-use cs2kz::{GlobalStatus, SteamID};
-use futures::{TryFutureExt, TryStreamExt};
+use cs2kz::{GlobalStatus, Mode, RankedStatus, SteamID, Tier};
+use futures::{stream, FutureExt, StreamExt, TryFutureExt, TryStreamExt};
 use itertools::Itertools;
-use sqlx::{MySql, Pool, QueryBuilder, Row, Transaction};
+use sqlx::{Acquire, MySql, Pool, QueryBuilder, Row, Transaction};
 use tap::{Pipe, Tap, TryConv};
 
-use crate::database::SqlErrorExt;
+use crate::database::{self, SqlErrorExt, TransactionExt};
+use crate::services::audit::{self, AuditAction};
 use crate::services::steam::WorkshopID;
 use crate::services::{AuthService, SteamService};
+use crate::time::Seconds;
+use crate::util::Cursor;
 
 pub(crate) mod http;
 mod queries;
@@ -25,25 +29,40 @@ pub use models::{
 	Checksum,
 	Course,
 	CourseID,
+	CourseSearchResult,
 	CourseUpdate,
 	CreatedCourse,
+	FetchFiltersRequest,
+	FetchFiltersResponse,
 	FetchMapRequest,
 	FetchMapResponse,
+	FetchMapsByWorkshopIDRequest,
+	FetchMapsByWorkshopIDResponse,
 	FetchMapsRequest,
 	FetchMapsResponse,
 	Filter,
 	FilterID,
+	FilterInfo,
+	FilterStats,
 	FilterUpdate,
 	MapID,
 	NewCourse,
 	NewFilter,
+	SearchCoursesRequest,
+	SearchCoursesResponse,
 	SubmitMapRequest,
 	SubmitMapResponse,
+	SubmitMapsRequest,
+	SubmitMapsResponse,
 	UpdateMapRequest,
 	UpdateMapResponse,
 	UpdatedCourse,
 };
 
+/// How many Workshop items are downloaded at the same time when submitting
+/// many maps at once.
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
 /// A service for managing KZ maps.
 #[derive(Clone, FromRef)]
 #[allow(clippy::missing_docs_in_private_items)]
@@ -93,15 +112,64 @@ impl MapService
 			return Ok(None);
 		};
 
-		let map = raw_maps
+		let mut map = raw_maps
 			.into_iter()
 			.filter(|m| m.id == map_id)
 			.reduce(reduce_chunk)
 			.expect("we got the id we're filtering by from the original list");
 
+		if req.include_stats {
+			let mut stats = self.fetch_filter_stats(map_id).await?;
+
+			for filter in map.courses.iter_mut().flat_map(|c| &mut c.filters) {
+				filter.stats = Some(stats.remove(&filter.id).unwrap_or(FilterStats {
+					record_count: 0,
+					pro_record_count: 0,
+					wr_time: None,
+				}));
+			}
+		}
+
 		Ok(Some(map))
 	}
 
+	/// Fetches record statistics for every filter of a map that has records.
+	async fn fetch_filter_stats(&self, map_id: MapID) -> Result<HashMap<FilterID, FilterStats>>
+	{
+		let stats = sqlx::query! {
+			r"
+			SELECT
+			  r.filter_id `filter_id: FilterID`,
+			  CAST(COUNT(*) AS UNSIGNED) `record_count!: u64`,
+			  CAST(COUNT(CASE WHEN r.teleports = 0 THEN 1 END) AS UNSIGNED) `pro_record_count!: u64`,
+			  MIN(r.time) `wr_time: Seconds`
+			FROM
+			  Records r
+			  JOIN CourseFilters f ON f.id = r.filter_id
+			  JOIN Courses c ON c.id = f.course_id
+			WHERE
+			  c.map_id = ?
+			GROUP BY
+			  r.filter_id
+			",
+			map_id,
+		}
+		.fetch(&self.database)
+		.map_ok(|row| {
+			let stats = FilterStats {
+				record_count: row.record_count,
+				pro_record_count: row.pro_record_count,
+				wr_time: row.wr_time,
+			};
+
+			(row.filter_id, stats)
+		})
+		.try_collect::<HashMap<_, _>>()
+		.await?;
+
+		Ok(stats)
+	}
+
 	/// Fetch maps.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn fetch_maps(&self, req: FetchMapsRequest) -> Result<FetchMapsResponse>
@@ -115,6 +183,7 @@ impl MapService
 			  AND m.global_status = COALESCE(?, m.global_status)
 			  AND m.created_on > COALESCE(?, '1970-01-01 00:00:01')
 			  AND m.created_on < COALESCE(?, '2038-01-19 03:14:07')
+			  AND m.id < COALESCE(?, ~0)
 			ORDER BY
 			  m.id DESC
 			",
@@ -125,98 +194,387 @@ impl MapService
 		.bind(req.global_status)
 		.bind(req.created_after)
 		.bind(req.created_before)
+		.bind(req.after.map(|Cursor(map_id)| map_id))
 		.fetch_all(&self.database)
 		.await?
 		.into_iter()
 		.chunk_by(|m| m.id);
 
-		// Take into account how many maps we're gonna skip over
-		let mut total = *req.offset;
-
 		let maps = map_chunks
 			.into_iter()
 			.map(|(_, chunk)| chunk.reduce(reduce_chunk).expect("chunk can't be empty"))
+			.collect_vec();
+
+		// Every map in `maps` matched the filters, so this is also the total
+		// regardless of `limit` and `offset`.
+		let total = maps.len() as u64;
+
+		let maps = maps
+			.into_iter()
 			.skip(*req.offset as usize)
 			.take(*req.limit as usize)
 			.collect_vec();
 
-		// Add all the maps we actually return
-		total += maps.len() as u64;
+		let next_cursor = Cursor::after_last(&maps, *req.limit, |map| map.id);
+
+		Ok(FetchMapsResponse { maps, total, next_cursor })
+	}
+
+	/// Fetch every version of a map that was uploaded to the Workshop.
+	///
+	/// One Workshop item can be approved multiple times, e.g. after the mapper
+	/// uploaded a new version.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn fetch_maps_by_workshop_id(
+		&self,
+		req: FetchMapsByWorkshopIDRequest,
+	) -> Result<FetchMapsByWorkshopIDResponse>
+	{
+		let maps = sqlx::query_as::<_, FetchMapResponse>(&format!(
+			r"
+			{}
+			WHERE
+			  m.workshop_id = ?
+			ORDER BY
+			  m.created_on DESC,
+			  m.id DESC
+			",
+			queries::SELECT,
+		))
+		.bind(req.workshop_id)
+		.fetch_all(&self.database)
+		.await?
+		.into_iter()
+		.chunk_by(|m| m.id)
+		.into_iter()
+		.map(|(_, chunk)| chunk.reduce(reduce_chunk).expect("chunk can't be empty"))
+		.collect_vec();
+
+		Ok(FetchMapsByWorkshopIDResponse { maps })
+	}
+
+	/// Fetch course filters across all maps.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn fetch_filters(&self, req: FetchFiltersRequest) -> Result<FetchFiltersResponse>
+	{
+		let mut txn = self.database.begin().await?;
+
+		let filters = sqlx::query! {
+			r"
+			SELECT SQL_CALC_FOUND_ROWS
+			  m.id `map_id: MapID`,
+			  m.name map_name,
+			  c.id `course_id: CourseID`,
+			  c.name course_name,
+			  f.id `filter_id: FilterID`,
+			  f.mode `filter_mode: Mode`,
+			  f.teleports `filter_teleports: bool`,
+			  f.tier `filter_tier: Tier`,
+			  f.ranked_status `filter_ranked_status: RankedStatus`,
+			  f.notes filter_notes,
+			  f.needs_retiering `filter_needs_retiering: bool`
+			FROM
+			  CourseFilters f
+			  JOIN Courses c ON c.id = f.course_id
+			  JOIN Maps m ON m.id = c.map_id
+			WHERE
+			  f.mode = COALESCE(?, f.mode)
+			  AND f.ranked_status = COALESCE(?, f.ranked_status)
+			  AND f.needs_retiering = COALESCE(?, f.needs_retiering)
+			ORDER BY
+			  f.id ASC
+			LIMIT
+			  ? OFFSET ?
+			",
+			req.mode,
+			req.ranked_status,
+			req.needs_retiering,
+			*req.limit,
+			*req.offset,
+		}
+		.fetch_all(txn.as_mut())
+		.await?
+		.into_iter()
+		.map(|row| FilterInfo {
+			map_id: row.map_id,
+			map_name: row.map_name,
+			course_id: row.course_id,
+			course_name: row.course_name,
+			filter: Filter {
+				id: row.filter_id,
+				mode: row.filter_mode,
+				teleports: row.filter_teleports,
+				tier: row.filter_tier,
+				ranked_status: row.filter_ranked_status,
+				notes: row.filter_notes,
+				needs_retiering: row.filter_needs_retiering,
+				stats: None,
+			},
+		})
+		.collect_vec();
+
+		let total = txn.total_rows().await?;
+
+		txn.commit().await?;
+
+		Ok(FetchFiltersResponse { filters, total })
+	}
 
-		// And everything else that we would have ignored otherwise
-		total += map_chunks.into_iter().count() as u64;
+	/// Search courses across all maps by name.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn search_courses(&self, req: SearchCoursesRequest) -> Result<SearchCoursesResponse>
+	{
+		let query = database::escape_like(&req.query);
+		let courses = sqlx::query_as::<_, CourseSearchResult>(
+			r"
+			SELECT
+			  m.id map_id,
+			  m.name map_name,
+			  c.id course_id,
+			  c.name course_name,
+			  f.id filter_id,
+			  f.mode filter_mode,
+			  f.teleports filter_teleports,
+			  f.tier filter_tier,
+			  f.ranked_status filter_ranked_status,
+			  f.notes filter_notes,
+			  f.needs_retiering filter_needs_retiering
+			FROM
+			  (
+			    SELECT
+			      id,
+			      name,
+			      map_id,
+			      name LIKE CONCAT(?, '%') is_prefix
+			    FROM
+			      Courses
+			    WHERE
+			      name LIKE CONCAT('%', ?, '%')
+			    ORDER BY
+			      is_prefix DESC,
+			      name ASC,
+			      id ASC
+			    LIMIT
+			      ?
+			  ) c
+			  JOIN Maps m ON m.id = c.map_id
+			  JOIN CourseFilters f ON f.course_id = c.id
+			ORDER BY
+			  c.is_prefix DESC,
+			  c.name ASC,
+			  c.id ASC,
+			  f.id ASC
+			",
+		)
+		.bind(&query)
+		.bind(&query)
+		.bind(*req.limit)
+		.fetch_all(&self.database)
+		.await?
+		.into_iter()
+		.chunk_by(|c| c.course_id)
+		.into_iter()
+		.map(|(_, chunk)| {
+			chunk
+				.reduce(|mut acc, curr| {
+					acc.filters.extend(curr.filters);
+					acc
+				})
+				.expect("chunk can't be empty")
+		})
+		.collect_vec();
 
-		Ok(FetchMapsResponse { maps, total })
+		Ok(SearchCoursesResponse { courses })
 	}
 
 	/// Submit a new map.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn submit_map(&self, req: SubmitMapRequest) -> Result<SubmitMapResponse>
 	{
-		if req.mappers.is_empty() {
-			return Err(Error::MapMustHaveMappers);
-		}
+		validate_submission(&req)?;
 
-		if req.courses.is_empty() {
-			return Err(Error::MapMustHaveCourses);
-		}
+		let mut txn = self.database.begin().await?;
+		let (map_name, checksum) = download_workshop_map(&self.steam_svc, req.workshop_id).await?;
+		let res = insert_submission(&req, &map_name, checksum, &mut txn).await?;
 
-		if req.courses.iter().any(|c| c.mappers.is_empty()) {
-			return Err(Error::CourseMustHaveMappers { course_id: None });
-		}
+		txn.commit().await?;
+
+		Ok(res)
+	}
+
+	/// Submit many new maps at once.
+	///
+	/// Every Workshop item is only downloaded once, even if it is part of
+	/// multiple submissions. All maps are inserted in the same transaction,
+	/// but a failing submission does not affect the others; the response
+	/// contains one result per submission, in the same order.
+	#[tracing::instrument(level = "debug", skip(req), fields(maps = req.maps.len()), err(Debug, level = "debug"))]
+	pub async fn submit_maps(&self, req: SubmitMapsRequest) -> Result<SubmitMapsResponse>
+	{
+		self.submit_maps_with(req, |workshop_id| {
+			download_workshop_map(&self.steam_svc, workshop_id)
+		})
+		.await
+	}
+
+	/// Submit many new maps at once, using `download` to fetch them from the
+	/// Workshop.
+	///
+	/// At most [`MAX_CONCURRENT_DOWNLOADS`] downloads run at the same time.
+	async fn submit_maps_with<F, Fut>(
+		&self,
+		req: SubmitMapsRequest,
+		download: F,
+	) -> Result<SubmitMapsResponse>
+	where
+		F: Fn(WorkshopID) -> Fut + Sync,
+		Fut: Future<Output = Result<(String, Checksum)>> + Send,
+	{
+		let workshop_ids = req
+			.maps
+			.iter()
+			.filter(|map| validate_submission(map).is_ok())
+			.map(|map| map.workshop_id)
+			.collect::<BTreeSet<_>>();
+
+		let mut downloads = stream::iter(workshop_ids)
+			.map(|workshop_id| download(workshop_id).map(move |result| (workshop_id, result)))
+			.buffer_unordered(MAX_CONCURRENT_DOWNLOADS)
+			.collect::<HashMap<_, _>>()
+			.await;
 
 		let mut txn = self.database.begin().await?;
+		let mut results = Vec::with_capacity(req.maps.len());
 
-		let (map_name, checksum) = tokio::try_join! {
-			self.steam_svc.fetch_map_name(req.workshop_id).map_err(Error::Steam),
+		for map in &req.maps {
+			if let Err(error) = validate_submission(map) {
+				results.push(Err(error));
+				continue;
+			}
 
-			// TODO: put this in background task and return `202`?
-			self.steam_svc.download_map(req.workshop_id)
-				.map_err(Error::Steam)
-				.and_then(|map_file| {
-					map_file.checksum()
-						.map_ok(Checksum::from)
-						.map_err(Error::CalculateMapChecksum)
-				}),
-		}?;
+			// Failed downloads are only reported for the first submission that
+			// needed them; any later ones try again.
+			let download = match downloads.remove(&map.workshop_id) {
+				Some(Ok(download)) => {
+					downloads.insert(map.workshop_id, Ok(download.clone()));
+					Ok(download)
+				}
+				Some(Err(error)) => Err(error),
+				None => download(map.workshop_id).await,
+			};
+
+			let (map_name, checksum) = match download {
+				Ok(download) => download,
+				Err(error) => {
+					results.push(Err(error));
+					continue;
+				}
+			};
 
-		let map_id = create_map(&map_name, checksum, &req, &mut txn).await?;
-		create_mappers(map_id, &req.mappers, &mut txn).await?;
-		let courses = create_courses(map_id, &req.courses, &mut txn).await?;
+			let mut savepoint = txn.begin().await?;
+
+			match insert_submission(map, &map_name, checksum, &mut savepoint).await {
+				Ok(res) => {
+					savepoint.commit().await?;
+					results.push(Ok(res));
+				}
+				Err(error) => {
+					savepoint.rollback().await?;
+					results.push(Err(error));
+				}
+			}
+		}
 
 		txn.commit().await?;
 
-		Ok(SubmitMapResponse { map_id, courses })
+		tracing::info! {
+			submitted = results.iter().filter(|result| result.is_ok()).count(),
+			failed = results.iter().filter(|result| result.is_err()).count(),
+			"submitted maps",
+		};
+
+		Ok(SubmitMapsResponse { results })
 	}
 
 	/// Update an existing map.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn update_map(&self, req: UpdateMapRequest) -> Result<UpdateMapResponse>
 	{
-		let mut response = UpdateMapResponse::default();
+		if req.expected_checksum.is_some() && !req.check_steam && req.workshop_id.is_none() {
+			return Err(Error::ChecksumWithoutSteamCheck);
+		}
 
 		if req.is_empty() {
-			return Ok(response);
+			return Ok(UpdateMapResponse::default());
 		}
 
+		// Downloading the map is slow, so we only want to do it once, and not
+		// while holding any locks.
+		let steam_metadata = check_steam(&req, &self.database, &self.steam_svc).await?;
+
+		database::with_retry(|| self.try_update_map(req.clone(), steam_metadata.as_ref())).await
+	}
+
+	/// Performs a single attempt at [`MapService::update_map()`].
+	async fn try_update_map(
+		&self,
+		req: UpdateMapRequest,
+		steam_metadata: Option<&SteamMetadata>,
+	) -> Result<UpdateMapResponse>
+	{
+		let mut response = UpdateMapResponse::default();
 		let mut txn = self.database.begin().await?;
 
+		let entity_id = u64::from(req.map_id.0);
+
 		update_metadata(&req, &mut txn).await?;
 
-		if req.check_steam || req.workshop_id.is_some() {
-			check_steam(&req, &mut txn, &self.steam_svc).await?;
+		if let Some(steam_metadata) = steam_metadata {
+			update_steam_metadata(req.map_id, steam_metadata, &mut txn).await?;
+		}
+
+		if req.description.is_some()
+			|| req.workshop_id.is_some()
+			|| req.global_status.is_some()
+			|| req.check_steam
+		{
+			audit::record(
+				&mut txn,
+				req.actor_id,
+				AuditAction::MapUpdated,
+				entity_id,
+				serde_json::json!({
+					"description": req.description,
+					"workshop_id": req.workshop_id,
+					"global_status": req.global_status,
+					"check_steam": req.check_steam,
+				}),
+			)
+			.await?;
 		}
 
 		if let Some(mappers) = req.added_mappers {
 			create_mappers(req.map_id, &mappers, &mut txn).await?;
+
+			let details = serde_json::json!({ "mappers": mappers });
+			audit::record(&mut txn, req.actor_id, AuditAction::MappersAdded, entity_id, details)
+				.await?;
 		}
 
 		if let Some(mappers) = req.removed_mappers {
 			remove_mappers(req.map_id, &mappers, &mut txn).await?;
+
+			let details = serde_json::json!({ "mappers": mappers });
+			audit::record(&mut txn, req.actor_id, AuditAction::MappersRemoved, entity_id, details)
+				.await?;
 		}
 
 		if let Some(updates) = req.course_updates {
 			response.updated_courses = update_courses(req.map_id, updates, &mut txn).await?;
+
+			let details = serde_json::json!({ "courses": response.updated_courses });
+			audit::record(&mut txn, req.actor_id, AuditAction::CoursesUpdated, entity_id, details)
+				.await?;
 		}
 
 		txn.commit().await?;
@@ -267,6 +625,57 @@ fn reduce_chunk(mut acc: FetchMapResponse, curr: FetchMapResponse) -> FetchMapRe
 	acc
 }
 
+/// Checks the parts of a map submission that don't require any I/O.
+fn validate_submission(req: &SubmitMapRequest) -> Result<()>
+{
+	if req.mappers.is_empty() {
+		return Err(Error::MapMustHaveMappers);
+	}
+
+	if req.courses.is_empty() {
+		return Err(Error::MapMustHaveCourses);
+	}
+
+	if req.courses.iter().any(|c| c.mappers.is_empty()) {
+		return Err(Error::CourseMustHaveMappers { course_id: None });
+	}
+
+	Ok(())
+}
+
+/// Inserts a new map, including its mappers and courses, into the database.
+async fn insert_submission(
+	req: &SubmitMapRequest,
+	map_name: &str,
+	checksum: Checksum,
+	txn: &mut Transaction<'_, MySql>,
+) -> Result<SubmitMapResponse>
+{
+	verify_checksum(req.expected_checksum, checksum)?;
+
+	let map_id = create_map(map_name, checksum, req, txn).await?;
+	create_mappers(map_id, &req.mappers, txn).await?;
+	let courses = create_courses(map_id, &req.courses, txn).await?;
+
+	audit::record(
+		txn,
+		req.actor_id,
+		AuditAction::MapCreated,
+		u64::from(map_id.0),
+		serde_json::json!({
+			"name": map_name,
+			"workshop_id": req.workshop_id,
+			"checksum": checksum,
+			"global_status": req.global_status,
+			"mappers": req.mappers,
+			"courses": courses,
+		}),
+	)
+	.await?;
+
+	Ok(SubmitMapResponse { map_id, courses })
+}
+
 /// Creates a new map in the database and returns the generated ID.
 #[tracing::instrument(level = "trace", err(Debug, level = "debug"), skip(txn))]
 async fn create_map(
@@ -393,7 +802,14 @@ async fn create_courses(
 		})
 		.build_query_scalar::<CourseID>()
 		.fetch_all(txn.as_mut())
-		.await?;
+		.await
+		.map_err(|error| {
+			if error.is_unique_violation("unique_course_name") {
+				Error::CourseNameAlreadyTaken
+			} else {
+				Error::Database(error)
+			}
+		})?;
 
 	let mut created_courses = Vec::with_capacity(courses.len());
 
@@ -480,6 +896,28 @@ async fn update_metadata(req: &UpdateMapRequest, txn: &mut Transaction<'_, MySql
 		return Ok(());
 	}
 
+	if let Some(to) = req.global_status {
+		let from = sqlx::query_scalar! {
+			r"
+			SELECT
+			  global_status `global_status: GlobalStatus`
+			FROM
+			  Maps
+			WHERE
+			  id = ?
+			FOR UPDATE
+			",
+			req.map_id,
+		}
+		.fetch_optional(txn.as_mut())
+		.await?
+		.ok_or(Error::MapDoesNotExist)?;
+
+		if !from.can_transition_to(to) {
+			return Err(Error::InvalidStateTransition { from, to });
+		}
+	}
+
 	let update_query_result = sqlx::query! {
 		r"
 		UPDATE
@@ -509,23 +947,55 @@ async fn update_metadata(req: &UpdateMapRequest, txn: &mut Transaction<'_, MySql
 	Ok(())
 }
 
+/// Fetches a map's name from the Workshop and calculates its checksum.
+async fn download_workshop_map(
+	steam_svc: &SteamService,
+	workshop_id: WorkshopID,
+) -> Result<(String, Checksum)>
+{
+	tokio::try_join! {
+		steam_svc.fetch_map_name(workshop_id).map_err(Error::Steam),
+
+		// TODO: put this in background task and return `202`?
+		steam_svc.download_map(workshop_id)
+			.map_err(Error::Steam)
+			.and_then(|map_file| {
+				map_file.checksum()
+					.map_ok(Checksum::from)
+					.map_err(Error::CalculateMapChecksum)
+			}),
+	}
+}
+
+/// A map's name and checksum, as currently found on Steam.
+#[derive(Debug)]
+struct SteamMetadata
+{
+	/// The map's name.
+	map_name: String,
+
+	/// The checksum of the map file.
+	checksum: Checksum,
+}
+
 /// Checks Steam to see if a map's name or checksum have changed.
-#[tracing::instrument(level = "trace", err(Debug, level = "debug"), skip(txn))]
+///
+/// Returns `None` if `req` didn't ask for a check.
+#[tracing::instrument(level = "trace", err(Debug, level = "debug"), skip(database))]
 async fn check_steam(
 	req: &UpdateMapRequest,
-	txn: &mut Transaction<'_, MySql>,
+	database: &Pool<MySql>,
 	steam_svc: &SteamService,
-) -> Result<()>
+) -> Result<Option<SteamMetadata>>
 {
 	if !req.check_steam && req.workshop_id.is_none() {
-		return Ok(());
+		return Ok(None);
 	}
 
 	let workshop_id = match req.workshop_id {
 		Some(id) => id,
-		None => {
-			sqlx::query_scalar! {
-				r"
+		None => sqlx::query_scalar! {
+			r"
 				SELECT
 				  workshop_id `workshop_id: WorkshopID`
 				FROM
@@ -533,26 +1003,32 @@ async fn check_steam(
 				WHERE
 				  id = ?
 				",
-				req.map_id,
-			}
-			.fetch_one(txn.as_mut())
-			.await?
+			req.map_id,
 		}
+		.fetch_optional(database)
+		.await?
+		.ok_or(Error::MapDoesNotExist)?,
 	};
 
-	let (map_name, checksum) = tokio::try_join! {
-		steam_svc.fetch_map_name(workshop_id).map_err(Error::Steam),
+	// We were asked to look for a new name (or the workshop ID changed), so
+	// don't trust whatever we cached earlier.
+	steam_svc.invalidate_map_name(workshop_id);
 
-		// TODO: put this in background task and return `202`?
-		steam_svc.download_map(workshop_id)
-			.map_err(Error::Steam)
-			.and_then(|map_file| {
-				map_file.checksum()
-					.map_ok(Checksum::from)
-					.map_err(Error::CalculateMapChecksum)
-			}),
-	}?;
+	let (map_name, checksum) = download_workshop_map(steam_svc, workshop_id).await?;
+
+	verify_checksum(req.expected_checksum, checksum)?;
+
+	Ok(Some(SteamMetadata { map_name, checksum }))
+}
 
+/// Updates a map's name and checksum with the results of [`check_steam()`].
+#[tracing::instrument(level = "trace", err(Debug, level = "debug"), skip(txn))]
+async fn update_steam_metadata(
+	map_id: MapID,
+	SteamMetadata { map_name, checksum }: &SteamMetadata,
+	txn: &mut Transaction<'_, MySql>,
+) -> Result<()>
+{
 	let query_result = sqlx::query! {
 		r"
 		UPDATE
@@ -565,7 +1041,7 @@ async fn check_steam(
 		",
 		map_name,
 		checksum,
-		req.map_id,
+		map_id,
 	}
 	.execute(txn.as_mut())
 	.await?;
@@ -575,11 +1051,21 @@ async fn check_steam(
 		n => assert_eq!(n, 1, "updated more than 1 map"),
 	}
 
-	tracing::info!(map_id = %req.map_id, %map_name, %checksum, "updated map name and checksum");
+	tracing::info!(%map_id, %map_name, %checksum, "updated map name and checksum");
 
 	Ok(())
 }
 
+/// Makes sure a downloaded map's checksum matches what the request expected,
+/// if it expected anything.
+fn verify_checksum(expected: Option<Checksum>, actual: Checksum) -> Result<()>
+{
+	match expected {
+		Some(expected) if expected != actual => Err(Error::ChecksumMismatch { expected, actual }),
+		_ => Ok(()),
+	}
+}
+
 /// Deletes mappers for a map from the database.
 ///
 /// If `mapper_ids` contains **all** the mappers associated with this map, this
@@ -723,7 +1209,14 @@ async fn update_course(
 			course_id,
 		}
 		.execute(txn.as_mut())
-		.await?;
+		.await
+		.map_err(|error| {
+			if error.is_unique_violation("unique_course_name") {
+				Error::CourseNameAlreadyTaken
+			} else {
+				Error::Database(error)
+			}
+		})?;
 	}
 
 	if let Some(mappers) = update.added_mappers {
@@ -877,13 +1370,15 @@ async fn update_filter(
 		SET
 		  tier = COALESCE(?, tier),
 		  ranked_status = COALESCE(?, ranked_status),
-		  notes = COALESCE(?, notes)
+		  notes = COALESCE(?, notes),
+		  needs_retiering = COALESCE(?, needs_retiering)
 		WHERE
 		  id = ?
 		",
 		update.tier,
 		update.ranked_status,
 		update.notes,
+		update.needs_retiering,
 		filter_id,
 	}
 	.execute(txn.as_mut())
@@ -896,11 +1391,15 @@ async fn update_filter(
 mod tests
 {
 	use std::collections::BTreeMap;
+	use std::io;
 
+	use axum::response::IntoResponse;
 	use cs2kz::{GlobalStatus, Mode, RankedStatus, Tier};
 	use sqlx::{MySql, Pool};
 
 	use super::*;
+	use crate::http::ProblemDetails;
+	use crate::services::audit::{EntityType, FetchAuditLogRequest};
 	use crate::testing::{self, ALPHAKEKS_ID};
 
 	#[sqlx::test(
@@ -910,7 +1409,7 @@ mod tests
 	async fn fetch_map_works(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
 		let svc = testing::map_svc(database);
-		let req = FetchMapRequest { ident: "checkmate".parse()? };
+		let req = FetchMapRequest { ident: "checkmate".parse()?, include_stats: false };
 		let res = svc.fetch_map(req).await?;
 
 		testing::assert!(res.is_some());
@@ -919,11 +1418,45 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/records.sql",
+		)
+	)]
+	async fn fetch_map_includes_filter_stats(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database);
+		let req = FetchMapRequest { ident: "checkmate".parse()?, include_stats: true };
+		let map = svc.fetch_map(req).await?.expect("checkmate fixture");
+		let stats = |mode, teleports| {
+			map.courses[0]
+				.filters
+				.iter()
+				.find(|f| f.mode == mode && f.teleports == teleports)
+				.and_then(|f| f.stats)
+				.map(|s| (s.record_count, s.pro_record_count, s.wr_time.map(|t| t.as_secs_f64())))
+		};
+
+		testing::assert_eq!(stats(Mode::Vanilla, false), Some((3, 3, Some(59.0))));
+		testing::assert_eq!(stats(Mode::Vanilla, true), Some((2, 0, Some(58.1))));
+		testing::assert_eq!(stats(Mode::Classic, false), Some((2, 2, Some(69.3))));
+		testing::assert_eq!(stats(Mode::Classic, true), Some((1, 0, Some(75.8))));
+
+		let req = FetchMapRequest { ident: "checkmate".parse()?, include_stats: false };
+		let map = svc.fetch_map(req).await?.expect("checkmate fixture");
+
+		testing::assert!(map.courses[0].filters.iter().all(|f| f.stats.is_none()));
+
+		Ok(())
+	}
+
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn fetch_map_not_found(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
 		let svc = testing::map_svc(database);
-		let req = FetchMapRequest { ident: "foobar".parse()? };
+		let req = FetchMapRequest { ident: "foobar".parse()?, include_stats: false };
 		let res = svc.fetch_map(req).await?;
 
 		testing::assert!(res.is_none());
@@ -1008,16 +1541,125 @@ mod tests
 			"../../../database/fixtures/grotto.sql",
 		)
 	)]
-	async fn fetch_maps_works_with_global_status(database: Pool<MySql>) -> color_eyre::Result<()>
+	async fn fetch_maps_by_workshop_id_returns_all_versions(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
 	{
-		let svc = testing::map_svc(database);
-		let req =
-			FetchMapsRequest { global_status: Some(GlobalStatus::Global), ..Default::default() };
-		let res = svc.fetch_maps(req).await?;
+		// Pretend grotto is a newer version of checkmate that was approved
+		// after the old one got invalidated.
+		sqlx::query(
+			"UPDATE Maps SET global_status = -1, created_on = '2024-01-01 00:00:00' WHERE name = ?",
+		)
+		.bind("kz_checkmate")
+		.execute(&database)
+		.await?;
 
-		testing::assert_eq!(res.maps.len(), 1);
-		testing::assert_eq!(res.total, 1);
-		testing::assert_eq!(res.maps[0].name, "kz_checkmate");
+		sqlx::query(
+			"UPDATE Maps SET workshop_id = ?, created_on = '2024-06-01 00:00:00' WHERE name = ?",
+		)
+		.bind(3070194623_u32)
+		.bind("kz_grotto")
+		.execute(&database)
+		.await?;
+
+		let svc = testing::map_svc(database);
+		let req = FetchMapsByWorkshopIDRequest { workshop_id: 3070194623.into() };
+		let res = svc.fetch_maps_by_workshop_id(req).await?;
+		let names = res.maps.iter().map(|m| &*m.name).collect_vec();
+		let statuses = res.maps.iter().map(|m| m.global_status).collect_vec();
+
+		testing::assert_eq!(names, ["kz_grotto", "kz_checkmate"]);
+		testing::assert_eq!(statuses, [GlobalStatus::InTesting, GlobalStatus::NotGlobal]);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/grotto.sql",
+		)
+	)]
+	async fn search_courses_ranks_prefix_matches_first(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		sqlx::query(
+			"UPDATE Courses SET name = ? WHERE map_id = (SELECT id FROM Maps WHERE name = ?)",
+		)
+		.bind("Domain")
+		.bind("kz_grotto")
+		.execute(&database)
+		.await?;
+
+		let svc = testing::map_svc(database);
+		let req = SearchCoursesRequest { query: String::from("main"), limit: Default::default() };
+		let res = svc.search_courses(req).await?;
+		let found = res
+			.courses
+			.iter()
+			.map(|c| (&*c.map_name, &*c.course_name))
+			.collect_vec();
+
+		testing::assert_eq!(found, [("kz_checkmate", "Main"), ("kz_grotto", "Domain")]);
+		testing::assert!(res.courses.iter().all(|c| !c.filters.is_empty()));
+
+		let req = SearchCoursesRequest { query: String::from("main"), limit: 1.into() };
+		let res = svc.search_courses(req).await?;
+
+		testing::assert_eq!(res.courses.len(), 1);
+		testing::assert_eq!(res.courses[0].map_name, "kz_checkmate");
+
+		let req = SearchCoursesRequest { query: String::from("bonus"), limit: Default::default() };
+		let res = svc.search_courses(req).await?;
+
+		testing::assert!(res.courses.is_empty());
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/grotto.sql",
+		)
+	)]
+	async fn search_courses_treats_wildcards_literally(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database);
+
+		for query in ["%", "_", "\\"] {
+			let req =
+				SearchCoursesRequest { query: String::from(query), limit: Default::default() };
+			let res = svc.search_courses(req).await?;
+
+			testing::assert!(res.courses.is_empty(), "`{query}` matched {:?}", res.courses);
+		}
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/grotto.sql",
+		)
+	)]
+	async fn fetch_maps_works_with_global_status(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database);
+		let req =
+			FetchMapsRequest { global_status: Some(GlobalStatus::Global), ..Default::default() };
+		let res = svc.fetch_maps(req).await?;
+
+		testing::assert_eq!(res.maps.len(), 1);
+		testing::assert_eq!(res.total, 1);
+		testing::assert_eq!(res.maps[0].name, "kz_checkmate");
 
 		let req =
 			FetchMapsRequest { global_status: Some(GlobalStatus::InTesting), ..Default::default() };
@@ -1100,6 +1742,36 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/grotto.sql",
+		)
+	)]
+	async fn fetch_maps_total_matches_filters(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database);
+
+		for (global_status, expected) in [
+			(None, 2),
+			(Some(GlobalStatus::Global), 1),
+			(Some(GlobalStatus::InTesting), 1),
+			(Some(GlobalStatus::NotGlobal), 0),
+		] {
+			for offset in [0, 1, 5] {
+				let req =
+					FetchMapsRequest { global_status, offset: offset.into(), ..Default::default() };
+				let res = svc.fetch_maps(req).await?;
+
+				testing::assert_eq!(res.total, expected);
+				testing::assert_eq!(res.maps.len() as u64, expected.saturating_sub(offset));
+			}
+		}
+
+		Ok(())
+	}
+
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn create_map_works(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
@@ -1145,6 +1817,8 @@ mod tests
 					},
 				],
 			}],
+			expected_checksum: None,
+			actor_id: None,
 		};
 
 		let map_id = create_map("kz_foobar", Checksum::new(b"foobar"), &req, &mut txn).await?;
@@ -1184,6 +1858,50 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn create_courses_rejects_duplicate_names(database: Pool<MySql>)
+		-> color_eyre::Result<()>
+	{
+		let mut txn = database.begin().await?;
+		let course = || NewCourse {
+			name: Some(String::from("Main")),
+			description: None,
+			mappers: FromIterator::from_iter([ALPHAKEKS_ID]),
+			filters: [Mode::Vanilla, Mode::Classic]
+				.into_iter()
+				.flat_map(|mode| [(mode, true), (mode, false)])
+				.map(|(mode, teleports)| NewFilter {
+					mode,
+					teleports,
+					tier: Tier::Easy,
+					ranked_status: RankedStatus::Ranked,
+					notes: None,
+				})
+				.collect::<Vec<_>>()
+				.try_into()
+				.expect("4 filters"),
+		};
+
+		let req = SubmitMapRequest {
+			workshop_id: 69.into(),
+			description: None,
+			global_status: GlobalStatus::InTesting,
+			mappers: FromIterator::from_iter([ALPHAKEKS_ID]),
+			courses: vec![course(), course()],
+			expected_checksum: None,
+			actor_id: None,
+		};
+
+		let map_id = create_map("kz_foobar", Checksum::new(b"foobar"), &req, &mut txn).await?;
+		let res = create_courses(map_id, &req.courses, &mut txn)
+			.await
+			.unwrap_err();
+
+		testing::assert_matches!(res, Error::CourseNameAlreadyTaken);
+
+		Ok(())
+	}
+
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn create_map_rejects_no_mappers(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
@@ -1229,6 +1947,8 @@ mod tests
 					},
 				],
 			}],
+			expected_checksum: None,
+			actor_id: None,
 		};
 
 		let res = svc.submit_map(req).await.unwrap_err();
@@ -1249,6 +1969,8 @@ mod tests
 			global_status: GlobalStatus::InTesting,
 			mappers: FromIterator::from_iter([ALPHAKEKS_ID]),
 			courses: Vec::new(),
+			expected_checksum: None,
+			actor_id: None,
 		};
 
 		let res = svc.submit_map(req).await.unwrap_err();
@@ -1303,6 +2025,8 @@ mod tests
 					},
 				],
 			}],
+			expected_checksum: None,
+			actor_id: None,
 		};
 
 		let res = svc.submit_map(req).await.unwrap_err();
@@ -1312,6 +2036,56 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/checkmate.sql")
+	)]
+	async fn update_map_rejects_invalid_status_transition(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database);
+
+		sqlx::query!("UPDATE Maps SET global_status = -1 WHERE name = 'kz_checkmate'")
+			.execute(&svc.database)
+			.await?;
+
+		let map_id = sqlx::query_scalar! {
+			r#"
+			SELECT
+			  id `id: MapID`
+			FROM
+			  Maps
+			WHERE
+			  name = "kz_checkmate"
+			"#,
+		}
+		.fetch_one(&svc.database)
+		.await?;
+
+		let req = UpdateMapRequest {
+			map_id,
+			description: None,
+			workshop_id: None,
+			global_status: Some(GlobalStatus::Global),
+			check_steam: false,
+			added_mappers: None,
+			removed_mappers: None,
+			course_updates: None,
+			expected_checksum: None,
+			actor_id: None,
+		};
+
+		let res = svc.update_map(req).await.unwrap_err();
+
+		testing::assert_matches!(res, Error::InvalidStateTransition {
+			from: GlobalStatus::NotGlobal,
+			to: GlobalStatus::Global,
+		});
+
+		Ok(())
+	}
+
 	#[sqlx::test(
 		migrations = "database/migrations",
 		fixtures("../../../database/fixtures/checkmate.sql")
@@ -1343,6 +2117,8 @@ mod tests
 			added_mappers: None,
 			removed_mappers: None,
 			course_updates: None,
+			expected_checksum: None,
+			actor_id: None,
 		};
 
 		let res = svc.update_map(req).await?;
@@ -1370,6 +2146,71 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/grotto.sql",
+		)
+	)]
+	async fn flagged_filters_can_be_queried(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database);
+		let map = svc
+			.fetch_map(FetchMapRequest { ident: "checkmate".parse()?, include_stats: false })
+			.await?
+			.expect("checkmate fixture");
+
+		let course = &map.courses[0];
+		let filter = &course.filters[0];
+
+		testing::assert!(!filter.needs_retiering);
+
+		let flag = |needs_retiering| UpdateMapRequest {
+			map_id: map.id,
+			description: None,
+			workshop_id: None,
+			global_status: None,
+			check_steam: false,
+			added_mappers: None,
+			removed_mappers: None,
+			course_updates: Some(BTreeMap::from_iter([(course.id, CourseUpdate {
+				filter_updates: Some(BTreeMap::from_iter([(filter.id, FilterUpdate {
+					needs_retiering: Some(needs_retiering),
+					..Default::default()
+				})])),
+				..Default::default()
+			})])),
+			expected_checksum: None,
+			actor_id: None,
+		};
+
+		svc.update_map(flag(true)).await?;
+
+		let req = FetchFiltersRequest { needs_retiering: Some(true), ..Default::default() };
+		let res = svc.fetch_filters(req).await?;
+
+		testing::assert_eq!(res.total, 1);
+		testing::assert_eq!(res.filters[0].map_name, "kz_checkmate");
+		testing::assert_eq!(res.filters[0].filter.id, filter.id);
+		testing::assert_eq!(res.filters[0].filter.tier, filter.tier);
+		testing::assert!(res.filters[0].filter.needs_retiering);
+
+		let req = FetchFiltersRequest { needs_retiering: Some(false), ..Default::default() };
+		let res = svc.fetch_filters(req).await?;
+
+		testing::assert!(res.filters.iter().all(|f| f.filter.id != filter.id));
+
+		svc.update_map(flag(false)).await?;
+
+		let req = FetchFiltersRequest { needs_retiering: Some(true), ..Default::default() };
+		let res = svc.fetch_filters(req).await?;
+
+		testing::assert_eq!(res.total, 0);
+
+		Ok(())
+	}
+
 	#[sqlx::test(
 		migrations = "database/migrations",
 		fixtures(
@@ -1420,6 +2261,8 @@ mod tests
 				name: Some(String::from("this won't work!")),
 				..Default::default()
 			})])),
+			expected_checksum: None,
+			actor_id: None,
 		};
 
 		let res = svc.update_map(req).await.unwrap_err();
@@ -1495,6 +2338,8 @@ mod tests
 				})])),
 				..Default::default()
 			})])),
+			expected_checksum: None,
+			actor_id: None,
 		};
 
 		let res = svc.update_map(req).await.unwrap_err();
@@ -1507,4 +2352,400 @@ mod tests
 
 		Ok(())
 	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/grotto.sql",
+		)
+	)]
+	async fn fetch_maps_works_with_cursor(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database);
+		let mut seen = Vec::new();
+		let mut after = None;
+
+		loop {
+			let req = FetchMapsRequest { limit: 1.into(), after, ..Default::default() };
+			let res = svc.fetch_maps(req).await?;
+
+			testing::assert!(res.maps.len() <= 1);
+
+			seen.extend(res.maps.iter().map(|map| map.name.clone()));
+
+			match res.next_cursor {
+				None => break,
+				Some(cursor) => after = Some(cursor),
+			}
+		}
+
+		testing::assert_eq!(seen, ["kz_grotto", "kz_checkmate"]);
+
+		Ok(())
+	}
+
+	/// Creates a [`MapService`] whose workshop serves a single map called
+	/// `name`, containing `contents`.
+	///
+	/// Downloads are written to `dir`, which is created (or emptied) first.
+	#[cfg(target_os = "linux")]
+	async fn map_svc_with_workshop(
+		database: Pool<MySql>,
+		dir: &std::path::Path,
+		name: &'static str,
+		contents: &str,
+	) -> color_eyre::Result<MapService>
+	{
+		use std::fs;
+		use std::os::unix::fs::PermissionsExt;
+
+		if dir.exists() {
+			fs::remove_dir_all(dir)?;
+		}
+
+		fs::create_dir_all(dir)?;
+
+		// Called as `DepotDownloader -app 730 -pubfile <id> -dir <dir>`.
+		let downloader = dir.join("DepotDownloader");
+		fs::write(&downloader, format!("#!/bin/sh\nprintf '{contents}' > \"$6/$4.vpk\"\n"))?;
+		fs::set_permissions(&downloader, fs::Permissions::from_mode(0o755))?;
+
+		let router = axum::Router::new().route(
+			"/details",
+			axum::routing::post(move || async move {
+				axum::Json(serde_json::json!({
+					"response": { "publishedfiledetails": [{ "title": name }] },
+				}))
+			}),
+		);
+
+		let map_url = testing::spawn_mock_server(router).await?.join("details")?;
+		let steam_svc = SteamService::new(
+			"http://127.0.0.1".parse()?,
+			String::new(),
+			dir.to_owned(),
+			downloader,
+			reqwest::Client::new(),
+		)
+		.with_map_url(map_url);
+
+		Ok(MapService::new(database.clone(), testing::auth_svc(database), steam_svc))
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/checkmate.sql")
+	)]
+	async fn update_map_requires_steam_check_for_checksum(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database);
+		let req = UpdateMapRequest {
+			map_id: 1.into(),
+			description: Some(String::from("new description")),
+			workshop_id: None,
+			global_status: None,
+			check_steam: false,
+			added_mappers: None,
+			removed_mappers: None,
+			course_updates: None,
+			expected_checksum: Some(Checksum::new(b"reviewed")),
+			actor_id: None,
+		};
+
+		let res = svc.update_map(req).await.unwrap_err();
+
+		testing::assert_matches!(res, Error::ChecksumWithoutSteamCheck);
+
+		Ok(())
+	}
+
+	#[cfg(target_os = "linux")]
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/checkmate.sql")
+	)]
+	async fn update_map_rejects_checksum_mismatch(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let dir = std::env::temp_dir()
+			.join(format!("cs2kz-api-checksum-mismatch-{}", std::process::id()));
+		let svc = map_svc_with_workshop(database, &dir, "kz_checkmate", "changed").await?;
+		let map_id =
+			sqlx::query_scalar::<_, MapID>("SELECT id FROM Maps WHERE name = 'kz_checkmate'")
+				.fetch_one(&svc.database)
+				.await?;
+		let checksum = || {
+			sqlx::query_scalar::<_, Checksum>("SELECT checksum FROM Maps WHERE id = ?")
+				.bind(map_id)
+				.fetch_one(&svc.database)
+		};
+
+		let old_checksum = checksum().await?;
+		let expected = Checksum::new(b"reviewed");
+		let actual = Checksum::new(b"changed");
+		let req = |expected_checksum| UpdateMapRequest {
+			map_id,
+			description: None,
+			workshop_id: None,
+			global_status: None,
+			check_steam: true,
+			added_mappers: None,
+			removed_mappers: None,
+			course_updates: None,
+			expected_checksum: Some(expected_checksum),
+			actor_id: None,
+		};
+
+		let res = svc.update_map(req(expected)).await.unwrap_err();
+
+		testing::assert_matches!(
+			res,
+			Error::ChecksumMismatch { expected: e, actual: a } if e == expected && a == actual
+		);
+		testing::assert_eq!(checksum().await?, old_checksum);
+
+		svc.update_map(req(actual)).await?;
+
+		testing::assert_eq!(checksum().await?, actual);
+
+		std::fs::remove_dir_all(&dir)?;
+
+		Ok(())
+	}
+
+	#[test]
+	fn verify_checksum_rejects_mismatch() -> color_eyre::Result<()>
+	{
+		let expected = Checksum::new(b"reviewed");
+		let actual = Checksum::new(b"changed on the workshop");
+
+		testing::assert!(verify_checksum(None, actual).is_ok());
+		testing::assert!(verify_checksum(Some(actual), actual).is_ok());
+
+		let error = verify_checksum(Some(expected), actual).unwrap_err();
+
+		testing::assert_matches!(
+			error,
+			Error::ChecksumMismatch { expected: e, actual: a } if e == expected && a == actual
+		);
+
+		let problem = ProblemDetails::from(error);
+
+		testing::assert_eq!(problem.status(), ::http::StatusCode::CONFLICT);
+
+		let json = serde_json::to_value(&problem)?;
+
+		testing::assert_eq!(json["expected"], expected.to_string());
+		testing::assert_eq!(json["actual"], actual.to_string());
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn submit_maps_reports_every_result(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database.clone());
+		let course = || NewCourse {
+			name: None,
+			description: None,
+			mappers: FromIterator::from_iter([ALPHAKEKS_ID]),
+			filters: [Mode::Vanilla, Mode::Classic]
+				.into_iter()
+				.flat_map(|mode| [(mode, true), (mode, false)])
+				.map(|(mode, teleports)| NewFilter {
+					mode,
+					teleports,
+					tier: Tier::Easy,
+					ranked_status: RankedStatus::Ranked,
+					notes: None,
+				})
+				.collect::<Vec<_>>()
+				.try_into()
+				.expect("4 filters"),
+		};
+		let map = |workshop_id: u32| SubmitMapRequest {
+			workshop_id: workshop_id.into(),
+			description: None,
+			global_status: GlobalStatus::InTesting,
+			mappers: FromIterator::from_iter([ALPHAKEKS_ID]),
+			courses: vec![course()],
+			expected_checksum: None,
+			actor_id: None,
+		};
+
+		let maps = vec![
+			SubmitMapRequest { mappers: BTreeSet::new(), ..map(1) },
+			SubmitMapRequest { courses: Vec::new(), ..map(2) },
+			// The test Steam service can't reach the Workshop, so these fail
+			// after validation.
+			map(3),
+			map(3),
+		];
+
+		let res = svc.submit_maps(SubmitMapsRequest { maps }).await?;
+
+		testing::assert_eq!(res.results.len(), 4);
+		testing::assert_matches!(res.results[0], Err(Error::MapMustHaveMappers));
+		testing::assert_matches!(res.results[1], Err(Error::MapMustHaveCourses));
+		testing::assert_matches!(res.results[2], Err(Error::Steam(_)));
+		testing::assert_matches!(res.results[3], Err(Error::Steam(_)));
+
+		let maps = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM Maps")
+			.fetch_one(&database)
+			.await?;
+
+		testing::assert_eq!(maps, 0);
+
+		let body = res.into_response();
+
+		testing::assert_eq!(body.status(), ::http::StatusCode::MULTI_STATUS);
+
+		let body = axum::body::to_bytes(body.into_body(), usize::MAX).await?;
+		let body = serde_json::from_slice::<Vec<serde_json::Value>>(&body)?;
+		let statuses = body
+			.iter()
+			.map(|result| result["status"].as_u64())
+			.collect::<Vec<_>>();
+
+		testing::assert_eq!(body.len(), 4);
+		testing::assert_eq!(statuses[0], Some(409));
+		testing::assert_eq!(statuses[1], Some(409));
+		testing::assert!(body.iter().all(|result| result.get("error").is_some()));
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn submit_maps_commits_successful_submissions(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database.clone());
+		let course = || NewCourse {
+			name: None,
+			description: None,
+			mappers: FromIterator::from_iter([ALPHAKEKS_ID]),
+			filters: [Mode::Vanilla, Mode::Classic]
+				.into_iter()
+				.flat_map(|mode| [(mode, true), (mode, false)])
+				.map(|(mode, teleports)| NewFilter {
+					mode,
+					teleports,
+					tier: Tier::Easy,
+					ranked_status: RankedStatus::Ranked,
+					notes: None,
+				})
+				.collect::<Vec<_>>()
+				.try_into()
+				.expect("4 filters"),
+		};
+		let map = |workshop_id: u32| SubmitMapRequest {
+			workshop_id: workshop_id.into(),
+			description: None,
+			global_status: GlobalStatus::InTesting,
+			mappers: FromIterator::from_iter([ALPHAKEKS_ID]),
+			courses: vec![course()],
+			expected_checksum: None,
+			actor_id: None,
+		};
+
+		let maps = vec![SubmitMapRequest { mappers: BTreeSet::new(), ..map(1) }, map(2), map(3)];
+
+		// Only the second map can be "downloaded".
+		let download = |workshop_id: WorkshopID| async move {
+			match *workshop_id {
+				2 => Ok((String::from("kz_batch"), Checksum::new(b"batch"))),
+				_ => Err(Error::CalculateMapChecksum(io::Error::other("download failed"))),
+			}
+		};
+
+		let res = svc
+			.submit_maps_with(SubmitMapsRequest { maps }, download)
+			.await?;
+
+		testing::assert_eq!(res.results.len(), 3);
+		testing::assert_matches!(res.results[0], Err(Error::MapMustHaveMappers));
+		testing::assert!(res.results[1].is_ok());
+		testing::assert_matches!(res.results[2], Err(Error::CalculateMapChecksum(_)));
+
+		let names = sqlx::query_scalar!("SELECT name FROM Maps")
+			.fetch_all(&database)
+			.await?;
+
+		testing::assert_eq!(names, ["kz_batch"]);
+
+		let body = res.into_response();
+		let body = axum::body::to_bytes(body.into_body(), usize::MAX).await?;
+		let body = serde_json::from_slice::<Vec<serde_json::Value>>(&body)?;
+		let statuses = body
+			.iter()
+			.map(|result| result["status"].as_u64())
+			.collect::<Vec<_>>();
+
+		testing::assert_eq!(statuses, [Some(409), Some(201), Some(500)]);
+		testing::assert!(body[1]["map_id"].is_u64());
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn submitting_a_map_is_audited(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::map_svc(database.clone());
+		let course = NewCourse {
+			name: None,
+			description: None,
+			mappers: FromIterator::from_iter([ALPHAKEKS_ID]),
+			filters: [Mode::Vanilla, Mode::Classic]
+				.into_iter()
+				.flat_map(|mode| [(mode, true), (mode, false)])
+				.map(|(mode, teleports)| NewFilter {
+					mode,
+					teleports,
+					tier: Tier::Easy,
+					ranked_status: RankedStatus::Ranked,
+					notes: None,
+				})
+				.collect::<Vec<_>>()
+				.try_into()
+				.expect("4 filters"),
+		};
+		let map = SubmitMapRequest {
+			workshop_id: 1.into(),
+			description: None,
+			global_status: GlobalStatus::Global,
+			mappers: FromIterator::from_iter([ALPHAKEKS_ID]),
+			courses: vec![course],
+			expected_checksum: None,
+			actor_id: Some(ALPHAKEKS_ID),
+		};
+
+		let download = |_| async { Ok((String::from("kz_audited"), Checksum::new(b"audited"))) };
+		let mut res = svc
+			.submit_maps_with(SubmitMapsRequest { maps: vec![map] }, download)
+			.await?;
+
+		let map_id = res.results.remove(0)?.map_id;
+		let audit_svc = testing::audit_svc(database);
+		let req = FetchAuditLogRequest {
+			entity_type: Some(EntityType::Map),
+			entity_id: Some(u64::from(map_id.0)),
+			..Default::default()
+		};
+
+		let res = audit_svc.fetch_entries(req).await?;
+
+		testing::assert_eq!(res.total, 1);
+
+		let entry = &res.entries[0];
+
+		testing::assert_eq!(entry.action, AuditAction::MapCreated);
+		testing::assert_eq!(entry.entity_id, u64::from(map_id.0));
+		testing::assert_eq!(entry.actor_id, Some(ALPHAKEKS_ID));
+		testing::assert_eq!(entry.details["name"], "kz_audited");
+
+		Ok(())
+	}
 }
@@ -2,17 +2,38 @@
 //! `.vpk` map file.
 
 use std::slice::SliceIndex;
+use std::str::FromStr;
 use std::{fmt, ops};
 
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// The length of a [`Checksum`] in bytes.
+const LEN: usize = 16;
 
 /// A map file's checksum.
 ///
 /// Currently this uses the MD5 hashing algorithm.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, utoipa::ToSchema)]
-#[schema(value_type = str)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Checksum(md5::Digest);
 
+/// The error returned when parsing a [`Checksum`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseChecksumError
+{
+	/// The string has the wrong length.
+	#[error("checksum must be {expected} hex digits, got {actual}", expected = LEN * 2)]
+	InvalidLength
+	{
+		/// The length of the string.
+		actual: usize,
+	},
+
+	/// The string contains something other than hex digits.
+	#[error("checksum must only contain hex digits")]
+	InvalidHex,
+}
+
 impl Checksum
 {
 	/// Computes a new [`Checksum`] from the given bytes.
@@ -107,25 +128,100 @@ impl Serialize for Checksum
 	}
 }
 
+impl FromStr for Checksum
+{
+	type Err = ParseChecksumError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err>
+	{
+		if s.len() != LEN * 2 {
+			return Err(ParseChecksumError::InvalidLength { actual: s.len() });
+		}
+
+		let mut bytes = [0; LEN];
+
+		hex::decode_to_slice(s, &mut bytes).map_err(|_| ParseChecksumError::InvalidHex)?;
+
+		Ok(Self(md5::Digest(bytes)))
+	}
+}
+
 impl<'de> Deserialize<'de> for Checksum
 {
 	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
 	where
 		D: Deserializer<'de>,
 	{
-		#[derive(Deserialize)]
-		#[serde(untagged)]
-		enum Helper
+		/// Accepts either the hex representation or the raw bytes.
+		struct ChecksumVisitor;
+
+		impl<'de> de::Visitor<'de> for ChecksumVisitor
 		{
-			Bytes([u8; 16]),
+			type Value = Checksum;
+
+			fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+			{
+				write!(f, "a checksum ({} hex digits or {LEN} bytes)", LEN * 2)
+			}
+
+			fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+			where
+				E: de::Error,
+			{
+				value.parse().map_err(E::custom)
+			}
 
-			#[serde(deserialize_with = "hex::serde::deserialize")]
-			Hex([u8; 16]),
+			fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+			where
+				E: de::Error,
+			{
+				<[u8; LEN]>::try_from(value)
+					.map(|bytes| Checksum(md5::Digest(bytes)))
+					.map_err(|_| E::invalid_length(value.len(), &self))
+			}
+
+			fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+			where
+				A: de::SeqAccess<'de>,
+			{
+				let mut bytes = [0; LEN];
+
+				for (idx, byte) in bytes.iter_mut().enumerate() {
+					*byte = seq
+						.next_element()?
+						.ok_or_else(|| de::Error::invalid_length(idx, &self))?;
+				}
+
+				if seq.next_element::<de::IgnoredAny>()?.is_some() {
+					return Err(de::Error::invalid_length(LEN + 1, &self));
+				}
+
+				Ok(Checksum(md5::Digest(bytes)))
+			}
 		}
 
-		Helper::deserialize(deserializer).map(|v| match v {
-			Helper::Bytes(bytes) | Helper::Hex(bytes) => Self(md5::Digest(bytes)),
-		})
+		deserializer.deserialize_any(ChecksumVisitor)
+	}
+}
+
+impl<'s> utoipa::ToSchema<'s> for Checksum
+{
+	fn schema() -> (&'s str, utoipa::openapi::RefOr<utoipa::openapi::Schema>)
+	{
+		use utoipa::openapi::{ObjectBuilder, SchemaType};
+
+		(
+			"Checksum",
+			ObjectBuilder::new()
+				.schema_type(SchemaType::String)
+				.description(Some("An MD5 checksum, encoded as hex"))
+				.min_length(Some(LEN * 2))
+				.max_length(Some(LEN * 2))
+				.pattern(Some("^[0-9a-fA-F]{32}$"))
+				.example(Some("a7b8c0b0a1a1e1ef9fb2a47dcecf4d32".into()))
+				.build()
+				.into(),
+		)
 	}
 }
 
@@ -174,3 +270,53 @@ where
 			.map_err(Into::into)
 	}
 }
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn parse_valid() -> color_eyre::Result<()>
+	{
+		let checksum = Checksum::new(b"kz_checkmate");
+		let lower = checksum.to_string();
+		let upper = format!("{checksum:X}");
+
+		testing::assert_eq!(lower.parse::<Checksum>()?, checksum);
+		testing::assert_eq!(upper.parse::<Checksum>()?, checksum);
+
+		let json = serde_json::to_string(&checksum)?;
+
+		testing::assert_eq!(serde_json::from_str::<Checksum>(&json)?, checksum);
+
+		Ok(())
+	}
+
+	#[test]
+	fn parse_too_short() -> color_eyre::Result<()>
+	{
+		testing::assert_matches!(
+			"a7b8c0b0".parse::<Checksum>(),
+			Err(ParseChecksumError::InvalidLength { actual: 8 }),
+		);
+
+		let error = serde_json::from_str::<Checksum>(r#""a7b8c0b0""#).unwrap_err();
+
+		testing::assert!(error.to_string().contains("32 hex digits"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn parse_non_hex() -> color_eyre::Result<()>
+	{
+		testing::assert_matches!(
+			"zzb8c0b0a1a1e1ef9fb2a47dcecf4d32".parse::<Checksum>(),
+			Err(ParseChecksumError::InvalidHex),
+		);
+
+		Ok(())
+	}
+}
@@ -8,11 +8,14 @@ use cs2kz::{GlobalStatus, Mode, RankedStatus, SteamID, Tier};
 use serde::{Deserialize, Deserializer, Serialize};
 use tap::{Conv, Tap};
 use time::OffsetDateTime;
+use utoipa::openapi::{self, ArrayBuilder, ContentBuilder, Ref, RefOr, ResponseBuilder};
 
+use crate::http::ProblemDetails;
 use crate::num::ClampedU64;
 use crate::services::players::PlayerInfo;
 use crate::services::steam::WorkshopID;
-use crate::util::MapIdentifier;
+use crate::time::{Seconds, Timestamp};
+use crate::util::{Cursor, MapIdentifier};
 
 #[doc(hidden)]
 pub(crate) mod checksum;
@@ -39,6 +42,9 @@ pub struct FetchMapRequest
 {
 	/// An identifier specifying which map you want to fetch.
 	pub ident: MapIdentifier,
+
+	/// Whether to include [`FilterStats`] for every filter.
+	pub include_stats: bool,
 }
 
 /// Response payload for fetching a map.
@@ -208,6 +214,31 @@ pub struct Filter
 	#[serde(skip_serializing_if = "Option::is_none")]
 	#[sqlx(rename = "filter_notes")]
 	pub notes: Option<String>,
+
+	/// Whether this filter has been flagged for review of its tier.
+	#[sqlx(rename = "filter_needs_retiering")]
+	pub needs_retiering: bool,
+
+	/// Record statistics, if they were requested.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[sqlx(skip)]
+	pub stats: Option<FilterStats>,
+}
+
+/// Record statistics for a course filter.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, utoipa::ToSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct FilterStats
+{
+	/// How many records have been submitted on this filter.
+	pub record_count: u64,
+
+	/// How many of those records have 0 teleports.
+	pub pro_record_count: u64,
+
+	/// The fastest time on this filter.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub wr_time: Option<Seconds>,
 }
 
 /// Request payload for fetching maps.
@@ -224,12 +255,10 @@ pub struct FetchMapsRequest
 	pub global_status: Option<GlobalStatus>,
 
 	/// Only include maps approved after this date.
-	#[serde(default, with = "time::serde::rfc3339::option")]
-	pub created_after: Option<OffsetDateTime>,
+	pub created_after: Option<Timestamp>,
 
 	/// Only include maps approved before this date.
-	#[serde(default, with = "time::serde::rfc3339::option")]
-	pub created_before: Option<OffsetDateTime>,
+	pub created_before: Option<Timestamp>,
 
 	/// Maximum number of results to return.
 	#[serde(default)]
@@ -240,6 +269,13 @@ pub struct FetchMapsRequest
 	#[serde(default)]
 	#[param(value_type = u64)]
 	pub offset: ClampedU64,
+
+	/// Only include maps older than the one this cursor points at.
+	///
+	/// This is the `next_cursor` returned by a previous request, and is a more
+	/// efficient alternative to `offset`.
+	#[param(value_type = Option<String>)]
+	pub after: Option<Cursor<MapID>>,
 }
 
 /// Response payload for fetching maps.
@@ -253,6 +289,13 @@ pub struct FetchMapsResponse
 
 	/// How many maps **could have been** fetched, if there was no limit.
 	pub total: u64,
+
+	/// A cursor pointing at the last map in this response.
+	///
+	/// Pass this as `after` to fetch the next page. If this is absent, there
+	/// are no more maps.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub next_cursor: Option<Cursor<MapID>>,
 }
 
 impl IntoResponse for FetchMapsResponse
@@ -263,6 +306,180 @@ impl IntoResponse for FetchMapsResponse
 	}
 }
 
+/// Request payload for fetching every version of a Workshop map.
+#[derive(Debug)]
+pub struct FetchMapsByWorkshopIDRequest
+{
+	/// The Workshop ID of the map.
+	pub workshop_id: WorkshopID,
+}
+
+/// Response payload for fetching every version of a Workshop map.
+#[derive(Debug, Serialize, utoipa::IntoResponses)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[response(status = OK)]
+pub struct FetchMapsByWorkshopIDResponse
+{
+	/// The versions of the map, newest first.
+	///
+	/// Versions that have been invalidated are included, but have a global
+	/// status of `not_global`.
+	pub maps: Vec<FetchMapResponse>,
+}
+
+impl IntoResponse for FetchMapsByWorkshopIDResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
+
+/// Request payload for fetching course filters.
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct FetchFiltersRequest
+{
+	/// Filter by mode.
+	pub mode: Option<Mode>,
+
+	/// Filter by ranked status.
+	pub ranked_status: Option<RankedStatus>,
+
+	/// Filter by whether the filter has been flagged for review of its tier.
+	pub needs_retiering: Option<bool>,
+
+	/// Maximum number of results to return.
+	///
+	/// Values above 1000 are rejected.
+	#[serde(default, deserialize_with = "ClampedU64::deserialize_strict_limit")]
+	#[param(value_type = u64, maximum = 1000)]
+	pub limit: ClampedU64<100, 1000>,
+
+	/// Pagination offset.
+	#[serde(default, deserialize_with = "ClampedU64::deserialize_strict_offset")]
+	#[param(value_type = u64)]
+	pub offset: ClampedU64,
+}
+
+/// A course filter, along with the course and map it belongs to.
+#[derive(Debug, PartialEq, Serialize, sqlx::FromRow, utoipa::ToSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct FilterInfo
+{
+	/// The ID of the map the filter belongs to.
+	pub map_id: MapID,
+
+	/// The name of the map the filter belongs to.
+	pub map_name: String,
+
+	/// The ID of the course the filter belongs to.
+	pub course_id: CourseID,
+
+	/// The name of the course the filter belongs to.
+	pub course_name: String,
+
+	/// The filter itself.
+	#[serde(flatten)]
+	#[sqlx(flatten)]
+	pub filter: Filter,
+}
+
+/// Response payload for fetching course filters.
+#[derive(Debug, Serialize, utoipa::IntoResponses)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[response(status = OK)]
+pub struct FetchFiltersResponse
+{
+	/// The filters.
+	pub filters: Vec<FilterInfo>,
+
+	/// How many filters **could have been** fetched, if there was no limit.
+	pub total: u64,
+}
+
+impl IntoResponse for FetchFiltersResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
+
+/// Request payload for searching courses by name.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct SearchCoursesRequest
+{
+	/// (Part of) the name of the course.
+	pub query: String,
+
+	/// Maximum number of results to return.
+	#[serde(default)]
+	#[param(value_type = u64)]
+	pub limit: ClampedU64<10, 100>,
+}
+
+/// A course found by [`SearchCoursesRequest`].
+#[derive(Debug, PartialEq, Serialize, utoipa::ToSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct CourseSearchResult
+{
+	/// The ID of the map the course belongs to.
+	pub map_id: MapID,
+
+	/// The name of the map the course belongs to.
+	pub map_name: String,
+
+	/// The course's ID.
+	pub course_id: CourseID,
+
+	/// The course's name.
+	pub course_name: String,
+
+	/// The course's filters, including their tiers.
+	pub filters: Vec<Filter>,
+}
+
+impl<'r, R> sqlx::FromRow<'r, R> for CourseSearchResult
+where
+	R: sqlx::Row,
+	for<'a> &'a str: sqlx::ColumnIndex<R>,
+	MapID: sqlx::Type<R::Database> + sqlx::Decode<'r, R::Database>,
+	CourseID: sqlx::Type<R::Database> + sqlx::Decode<'r, R::Database>,
+	String: sqlx::Type<R::Database> + sqlx::Decode<'r, R::Database>,
+	Filter: sqlx::FromRow<'r, R>,
+{
+	fn from_row(row: &'r R) -> sqlx::Result<Self>
+	{
+		Ok(Self {
+			map_id: row.try_get("map_id")?,
+			map_name: row.try_get("map_name")?,
+			course_id: row.try_get("course_id")?,
+			course_name: row.try_get("course_name")?,
+			filters: vec![Filter::from_row(row)?],
+		})
+	}
+}
+
+/// Response payload for searching courses by name.
+#[derive(Debug, Serialize, utoipa::IntoResponses)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[response(status = OK)]
+pub struct SearchCoursesResponse
+{
+	/// The matching courses.
+	///
+	/// Courses whose name starts with the query come first.
+	pub courses: Vec<CourseSearchResult>,
+}
+
+impl IntoResponse for SearchCoursesResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
+
 /// Request payload for submitting a new map.
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 #[schema(example = json!({
@@ -324,6 +541,18 @@ pub struct SubmitMapRequest
 	/// The map's courses.
 	#[serde(deserialize_with = "SubmitMapRequest::deserialize_courses")]
 	pub courses: Vec<NewCourse>,
+
+	/// The checksum the map is expected to have.
+	///
+	/// If this is set, and the map downloaded from the workshop has a
+	/// different checksum, the submission is rejected. This guards against the
+	/// workshop item changing between review and approval.
+	#[serde(default)]
+	pub expected_checksum: Option<Checksum>,
+
+	/// The user who is submitting the map.
+	#[serde(skip)]
+	pub actor_id: Option<SteamID>,
 }
 
 impl SubmitMapRequest
@@ -438,7 +667,7 @@ impl NewCourse
 				)));
 			}
 
-			if actual.tier > Tier::Death && actual.ranked_status.is_ranked() {
+			if !actual.tier.is_humanly_possible() && actual.ranked_status.is_ranked() {
 				return Err(serde::de::Error::custom(format!(
 					"tier {} is too high for a ranked filter",
 					actual.tier.conv::<u8>(),
@@ -505,6 +734,101 @@ impl IntoResponse for SubmitMapResponse
 	}
 }
 
+/// Request payload for submitting many new maps at once.
+#[derive(Debug)]
+pub struct SubmitMapsRequest
+{
+	/// The maps to submit.
+	pub maps: Vec<SubmitMapRequest>,
+}
+
+/// Response payload for submitting many new maps at once.
+///
+/// Contains one result for every submitted map, in the order they were
+/// submitted in.
+#[derive(Debug)]
+pub struct SubmitMapsResponse
+{
+	/// The result of each submission.
+	pub results: Vec<Result<SubmitMapResponse, super::Error>>,
+}
+
+impl IntoResponse for SubmitMapsResponse
+{
+	fn into_response(self) -> Response
+	{
+		let results = self
+			.results
+			.into_iter()
+			.map(MapSubmissionResult::from)
+			.collect::<Vec<_>>();
+
+		(http::StatusCode::MULTI_STATUS, crate::http::extract::Json(results)).into_response()
+	}
+}
+
+impl utoipa::IntoResponses for SubmitMapsResponse
+{
+	fn responses() -> BTreeMap<String, RefOr<openapi::response::Response>>
+	{
+		let content = ContentBuilder::new()
+			.schema(ArrayBuilder::new().items(Ref::from_schema_name("MapSubmissionResult")))
+			.build();
+
+		let response = ResponseBuilder::new()
+			.description("The result of each submission, in the same order as the request.")
+			.content("application/json", content)
+			.build();
+
+		BTreeMap::from_iter([(String::from("207"), response.into())])
+	}
+}
+
+/// The result of a single submission in a [`SubmitMapsResponse`].
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct MapSubmissionResult
+{
+	/// The HTTP status code this submission would have gotten on its own.
+	pub status: u16,
+
+	/// The created map's ID, if the submission succeeded.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub map_id: Option<MapID>,
+
+	/// IDs related to the created courses, if the submission succeeded.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub courses: Option<Vec<CreatedCourse>>,
+
+	/// The error, if the submission failed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<ProblemDetails>,
+}
+
+impl From<Result<SubmitMapResponse, super::Error>> for MapSubmissionResult
+{
+	fn from(result: Result<SubmitMapResponse, super::Error>) -> Self
+	{
+		match result {
+			Ok(SubmitMapResponse { map_id, courses }) => Self {
+				status: http::StatusCode::CREATED.as_u16(),
+				map_id: Some(map_id),
+				courses: Some(courses),
+				error: None,
+			},
+			Err(error) => {
+				let error = ProblemDetails::from(error);
+
+				Self {
+					status: error.status().as_u16(),
+					map_id: None,
+					courses: None,
+					error: Some(error),
+				}
+			}
+		}
+	}
+}
+
 /// Response payload for created courses when submitting a new map.
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct CreatedCourse
@@ -517,7 +841,7 @@ pub struct CreatedCourse
 }
 
 /// Request payload for updating an existing map.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UpdateMapRequest
 {
 	/// The ID of the map to update.
@@ -543,6 +867,15 @@ pub struct UpdateMapRequest
 
 	/// Updates to this map's courses.
 	pub course_updates: Option<BTreeMap<CourseID, CourseUpdate>>,
+
+	/// The checksum the map is expected to have after checking the workshop.
+	///
+	/// This requires `check_steam` to be set or `workshop_id` to be changed;
+	/// otherwise the update is rejected.
+	pub expected_checksum: Option<Checksum>,
+
+	/// The user who is making the update.
+	pub actor_id: Option<SteamID>,
 }
 
 impl UpdateMapRequest
@@ -559,6 +892,8 @@ impl UpdateMapRequest
 			added_mappers,
 			removed_mappers,
 			course_updates,
+			expected_checksum: _,
+			actor_id: _,
 		} = self;
 
 		description.is_none()
@@ -620,7 +955,7 @@ impl Ord for UpdatedCourse
 }
 
 /// An update to a map course.
-#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+#[derive(Debug, Default, Clone, Deserialize, utoipa::ToSchema)]
 pub struct CourseUpdate
 {
 	/// A new name.
@@ -657,12 +992,12 @@ impl CourseUpdate
 			&& removed_mappers.is_none()
 			&& filter_updates
 				.as_ref()
-				.map_or(true, |updates| updates.values().all(FilterUpdate::is_empty))
+				.is_none_or(|updates| updates.values().all(FilterUpdate::is_empty))
 	}
 }
 
 /// An update to a course filter.
-#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+#[derive(Debug, Default, Clone, Deserialize, utoipa::ToSchema)]
 pub struct FilterUpdate
 {
 	/// A new tier.
@@ -674,6 +1009,9 @@ pub struct FilterUpdate
 	/// New notes.
 	#[serde(default, deserialize_with = "crate::serde::deserialize_empty_as_none")]
 	pub notes: Option<String>,
+
+	/// Flag (or unflag) the filter for review of its tier.
+	pub needs_retiering: Option<bool>,
 }
 
 impl FilterUpdate
@@ -681,8 +1019,8 @@ impl FilterUpdate
 	/// Checks if this update is empty (contains no changes).
 	pub fn is_empty(&self) -> bool
 	{
-		let Self { tier, ranked_status, notes } = self;
+		let Self { tier, ranked_status, notes, needs_retiering } = self;
 
-		tier.is_none() && ranked_status.is_none() && notes.is_none()
+		tier.is_none() && ranked_status.is_none() && notes.is_none() && needs_retiering.is_none()
 	}
 }
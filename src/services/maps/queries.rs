@@ -22,6 +22,7 @@ pub const SELECT: &str = r#"
 	  f.tier filter_tier,
 	  f.ranked_status filter_ranked_status,
 	  f.notes filter_notes,
+	  f.needs_retiering filter_needs_retiering,
 	  m.created_on
 	FROM
 	  Maps m
@@ -40,5 +40,8 @@ pub use bans::BanService;
 pub mod admins;
 pub use admins::AdminService;
 
+pub mod audit;
+pub use audit::AuditService;
+
 pub mod plugin;
 pub use plugin::PluginService;
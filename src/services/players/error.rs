@@ -2,6 +2,7 @@
 
 use thiserror::Error;
 
+use super::PreferencesError;
 use crate::http::problem_details::{IntoProblemDetails, ProblemType};
 
 /// Type alias with a default `Err` type of [`Error`].
@@ -27,6 +28,15 @@ pub enum Error
 	#[error("player does not exist")]
 	PlayerDoesNotExist,
 
+	/// A request for merging two players was made, but both players were the
+	/// same.
+	#[error("cannot merge a player into themselves")]
+	MergeIntoSelf,
+
+	/// A player's preferences were invalid.
+	#[error(transparent)]
+	InvalidPreferences(#[from] PreferencesError),
+
 	/// Something went wrong communicating with the database.
 	#[error("something went wrong")]
 	Database(#[from] sqlx::Error),
@@ -40,6 +50,8 @@ impl IntoProblemDetails for Error
 			Self::NoData => ProblemType::NoContent,
 			Self::PlayerAlreadyExists => ProblemType::ResourceAlreadyExists,
 			Self::PlayerDoesNotExist => ProblemType::ResourceNotFound,
+			Self::MergeIntoSelf => ProblemType::InvalidRequestBody,
+			Self::InvalidPreferences(_) => ProblemType::InvalidRequestBody,
 			Self::Database(source) => source.problem_type(),
 		}
 	}
@@ -19,6 +19,8 @@ use super::{
 	PlayerService,
 	RegisterPlayerRequest,
 	RegisterPlayerResponse,
+	UpdatePlayerPreferencesRequest,
+	UpdatePlayerPreferencesResponse,
 	UpdatePlayerRequest,
 	UpdatePlayerResponse,
 };
@@ -43,8 +45,9 @@ impl From<PlayerService> for Router
 			.route("/", routing::get(get_many))
 			.route("/", routing::post(register_player).route_layer(auth.clone()))
 			.route("/:player", routing::get(get_single))
-			.route("/:player", routing::patch(update_player).route_layer(auth))
+			.route("/:player", routing::patch(update_player).route_layer(auth.clone()))
 			.route("/:player/preferences", routing::get(get_preferences))
+			.route("/:player/preferences", routing::patch(update_preferences).route_layer(auth))
 			.route("/:player/steam", routing::get(get_steam_profile))
 			.with_state(svc)
 	}
@@ -83,7 +86,7 @@ async fn get_many(
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(post, path = "/players", tag = "Players", security(
+#[utoipa::path(post, path = "/players", tag = "Players", operation_id = "register_player", security(
   ("CS2 Server" = []),
 ))]
 async fn register_player(
@@ -97,21 +100,45 @@ async fn register_player(
 	Ok(res)
 }
 
+/// Query parameters for `GET /players/{player}`.
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[doc(hidden)]
+pub(crate) struct GetPlayerQuery
+{
+	/// Additional data to include in the response.
+	#[serde(default)]
+	pub include: Vec<PlayerInclude>,
+}
+
+/// Additional data that can be included when fetching a single player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[doc(hidden)]
+pub(crate) enum PlayerInclude
+{
+	/// Every name the player has been seen with.
+	NameHistory,
+}
+
 #[tracing::instrument(err(Debug, level = "debug"))]
 #[utoipa::path(get, path = "/players/{player}", tag = "Players", operation_id = "get_player", params(
   ("player" = PlayerIdentifier, Path, description = "a player's SteamID or name"),
+  GetPlayerQuery,
 ))]
 async fn get_single(
 	session: Option<Session>,
 	State(svc): State<PlayerService>,
 	Path(identifier): Path<PlayerIdentifier>,
+	Query(query): Query<GetPlayerQuery>,
 ) -> Result<FetchPlayerResponse, ProblemDetails>
 {
 	let may_view_ips =
 		session.is_some_and(|session| session.user().permissions().contains(Permissions::BANS));
 
+	let include_name_history = query.include.contains(&PlayerInclude::NameHistory);
+
 	let mut player = svc
-		.fetch_player(FetchPlayerRequest { identifier })
+		.fetch_player(FetchPlayerRequest { identifier, include_name_history })
 		.await?
 		.ok_or(Error::PlayerDoesNotExist)?;
 
@@ -149,6 +176,7 @@ pub(crate) struct UpdatePlayerPayload
   patch,
   path = "/players/{player_id}",
   tag = "Players",
+  operation_id = "update_player",
   params(("player_id" = SteamID, Path, description = "a player's SteamID")),
   security(("CS2 Server" = [])),
 )]
@@ -174,7 +202,7 @@ async fn update_player(
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(get, path = "/players/{player}/preferences", tag = "Players", params(
+#[utoipa::path(get, path = "/players/{player}/preferences", tag = "Players", operation_id = "get_player_preferences", params(
   ("player" = PlayerIdentifier, Path, description = "a player's SteamID or name"),
 ))]
 async fn get_preferences(
@@ -191,8 +219,31 @@ async fn get_preferences(
 	Ok(res)
 }
 
+#[tracing::instrument(skip(server), fields(server.id = %server.id()), err(Debug, level = "debug"))]
+#[utoipa::path(
+  patch,
+  path = "/players/{player_id}/preferences",
+  tag = "Players",
+  operation_id = "update_player_preferences",
+  params(("player_id" = SteamID, Path, description = "a player's SteamID")),
+  request_body = Object,
+  security(("CS2 Server" = [])),
+)]
+async fn update_preferences(
+	server: Jwt<jwt::ServerInfo>,
+	State(svc): State<PlayerService>,
+	Path(player_id): Path<SteamID>,
+	Json(patch): Json<serde_json::Map<String, serde_json::Value>>,
+) -> Result<UpdatePlayerPreferencesResponse, ProblemDetails>
+{
+	let req = UpdatePlayerPreferencesRequest { player_id, patch };
+	let res = svc.update_player_preferences(req).await?;
+
+	Ok(res)
+}
+
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(get, path = "/players/{player_id}/steam", tag = "Players", params(
+#[utoipa::path(get, path = "/players/{player_id}/steam", tag = "Players", operation_id = "get_player_steam_profile", params(
   ("player_id" = SteamID, Path, description = "a player's SteamID"),
 ))]
 async fn get_steam_profile(
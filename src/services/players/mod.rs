@@ -3,9 +3,12 @@
 use std::{fmt, iter};
 
 use axum::extract::FromRef;
-use sqlx::{MySql, Pool, Row};
+use cs2kz::SteamID;
+use sqlx::{MySql, Pool, Row, Transaction};
+use time::OffsetDateTime;
 
-use crate::database::{SqlErrorExt, TransactionExt};
+use crate::database::{self, SqlErrorExt, TransactionExt};
+use crate::net::IpAddr;
 use crate::services::{AuthService, SteamService};
 
 pub(crate) mod http;
@@ -14,6 +17,9 @@ mod queries;
 mod error;
 pub use error::{Error, Result};
 
+mod preferences;
+pub use preferences::{Preferences, PreferencesError};
+
 pub(crate) mod models;
 pub use models::{
 	CourseSession,
@@ -27,11 +33,16 @@ pub use models::{
 	FetchPlayersRequest,
 	FetchPlayersResponse,
 	FetchSteamProfileResponse,
+	MergePlayersRequest,
+	MergePlayersResponse,
+	NameHistoryEntry,
 	PlayerInfo,
 	RegisterPlayerRequest,
 	RegisterPlayerResponse,
 	Session,
 	SessionID,
+	UpdatePlayerPreferencesRequest,
+	UpdatePlayerPreferencesResponse,
 	UpdatePlayerRequest,
 	UpdatePlayerResponse,
 };
@@ -71,21 +82,21 @@ impl PlayerService
 	pub async fn fetch_player(&self, req: FetchPlayerRequest)
 		-> Result<Option<FetchPlayerResponse>>
 	{
-		let res = sqlx::query_as(
+		let res = sqlx::query! {
 			r"
 			SELECT
-			  SQL_CALC_FOUND_ROWS p.id player_id,
+			  p.id `player_id: SteamID`,
 			  p.name player_name,
-			  p.ip_address,
+			  p.ip_address `ip_address: IpAddr`,
 			  (
 			    SELECT
-			      COUNT(b.id)
+			      COUNT(b.id) > 0
 			    FROM
 			      Bans b
 			    WHERE
 			      b.player_id = p.id
 			      AND b.expires_on > NOW()
-			  ) is_banned
+			  ) `is_banned!: bool`
 			FROM
 			  Players p
 			WHERE
@@ -94,29 +105,97 @@ impl PlayerService
 			LIMIT
 			  1
 			",
-		)
-		.bind(req.identifier.as_id())
-		.bind(req.identifier.as_name().map(|name| format!("%{name}%")))
+			req.identifier.as_id(),
+			req.identifier.as_name().map(|name| format!("%{name}%")),
+		}
 		.fetch_optional(&self.database)
+		.await?
+		.map(|row| FetchPlayerResponse {
+			info: PlayerInfo { name: row.player_name, steam_id: row.player_id },
+			is_banned: row.is_banned,
+			ip_address: Some(row.ip_address),
+			name_history: None,
+		});
+
+		let Some(mut player) = res else {
+			return Ok(None);
+		};
+
+		if req.include_name_history {
+			player.name_history = Some(self.fetch_name_history(player.info.steam_id).await?);
+		}
+
+		Ok(Some(player))
+	}
+
+	/// Fetches every name a player has been seen with, most recent first.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn fetch_name_history(&self, player_id: SteamID) -> Result<Vec<NameHistoryEntry>>
+	{
+		let history = sqlx::query_as! {
+			NameHistoryEntry,
+			r"
+			SELECT
+			  name,
+			  first_seen_on `first_seen_on: OffsetDateTime`,
+			  last_seen_on `last_seen_on: OffsetDateTime`
+			FROM
+			  PlayerNameHistory
+			WHERE
+			  player_id = ?
+			ORDER BY
+			  last_seen_on DESC,
+			  first_seen_on DESC
+			",
+			player_id,
+		}
+		.fetch_all(&self.database)
 		.await?;
 
-		Ok(res)
+		Ok(history)
 	}
 
 	/// Fetches potentially many players.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn fetch_players(&self, req: FetchPlayersRequest) -> Result<FetchPlayersResponse>
 	{
+		let pattern = req.search.as_deref().map(database::escape_like);
 		let mut txn = self.database.begin().await?;
 
 		let players = sqlx::query_as::<_, FetchPlayerResponse>(&format!(
 			r"
 			{}
+			WHERE
+			  p.name LIKE COALESCE(?, p.name)
+			ORDER BY
+			  CASE
+			    WHEN ? IS NULL THEN 0
+			    WHEN p.name = ? THEN 0
+			    WHEN p.name LIKE ? THEN 1
+			    ELSE 2
+			  END,
+			  CASE
+			    WHEN ? IS NULL THEN 0
+			    ELSE (
+			      SELECT
+			        COUNT(r.id)
+			      FROM
+			        Records r
+			      WHERE
+			        r.player_id = p.id
+			    )
+			  END DESC,
+			  p.id ASC
 			LIMIT
 			  ? OFFSET ?
 			",
 			queries::SELECT,
 		))
+		.bind(pattern.as_deref().map(|pattern| format!("%{pattern}%")))
+		.bind(req.search.as_deref())
+		.bind(req.search.as_deref())
+		.bind(pattern.as_deref().map(|pattern| format!("{pattern}%")))
+		.bind(req.search.as_deref())
 		.bind(*req.limit)
 		.bind(*req.offset)
 		.fetch_all(txn.as_mut())
@@ -165,6 +244,8 @@ impl PlayerService
 		req: RegisterPlayerRequest,
 	) -> Result<RegisterPlayerResponse>
 	{
+		let mut txn = self.database.begin().await?;
+
 		sqlx::query! {
 			r"
 			INSERT INTO
@@ -176,13 +257,17 @@ impl PlayerService
 			req.name,
 			req.ip_address,
 		}
-		.execute(&self.database)
+		.execute(txn.as_mut())
 		.await
 		.map_err(|error| match error.is_duplicate_entry() {
 			true => Error::PlayerAlreadyExists,
 			false => Error::Database(error),
 		})?;
 
+		record_name(&mut txn, req.steam_id, &req.name).await?;
+
+		txn.commit().await?;
+
 		tracing::info!("registered new player");
 
 		Ok(RegisterPlayerResponse { player_id: req.steam_id })
@@ -192,6 +277,7 @@ impl PlayerService
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn update_player(&self, req: UpdatePlayerRequest) -> Result<UpdatePlayerResponse>
 	{
+		let preferences = serde_json::Value::from(Preferences::try_from(req.preferences)?);
 		let mut txn = self.database.begin().await?;
 
 		let update_result = sqlx::query! {
@@ -208,7 +294,7 @@ impl PlayerService
 			",
 			req.name,
 			req.ip_address,
-			req.preferences,
+			preferences,
 			req.player_id,
 		}
 		.execute(txn.as_mut())
@@ -219,6 +305,8 @@ impl PlayerService
 			n => assert_eq!(n, 1, "updated more than one player"),
 		}
 
+		record_name(&mut txn, req.player_id, &req.name).await?;
+
 		tracing::info!("updated player");
 
 		let session_id = sqlx::query! {
@@ -301,6 +389,459 @@ impl PlayerService
 
 		Ok(UpdatePlayerResponse { session_id, course_session_ids })
 	}
+
+	/// Applies a partial update to a player's in-game preferences.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn update_player_preferences(
+		&self,
+		req: UpdatePlayerPreferencesRequest,
+	) -> Result<UpdatePlayerPreferencesResponse>
+	{
+		let mut txn = self.database.begin().await?;
+
+		let current = sqlx::query_scalar! {
+			r"
+			SELECT
+			  preferences `preferences: serde_json::Value`
+			FROM
+			  Players
+			WHERE
+			  id = ?
+			FOR UPDATE
+			",
+			req.player_id,
+		}
+		.fetch_optional(txn.as_mut())
+		.await?
+		.ok_or(Error::PlayerDoesNotExist)?;
+
+		let preferences = Preferences::merge(current, req.patch)?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  Players
+			SET
+			  preferences = ?
+			WHERE
+			  id = ?
+			",
+			serde_json::Value::from(preferences.clone()),
+			req.player_id,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		txn.commit().await?;
+
+		tracing::info!("updated player preferences");
+
+		Ok(UpdatePlayerPreferencesResponse { preferences })
+	}
+
+	/// Merges two records of the same player.
+	///
+	/// Everything that belongs to `req.remove` is transferred to `req.keep`,
+	/// and `req.remove` is deleted afterwards. This happens inside a single
+	/// transaction, so either everything is transferred, or nothing is.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn merge_players(&self, req: MergePlayersRequest) -> Result<MergePlayersResponse>
+	{
+		if req.keep == req.remove {
+			return Err(Error::MergeIntoSelf);
+		}
+
+		let mut txn = self.database.begin().await?;
+
+		let existing = sqlx::query_scalar! {
+			r"
+			SELECT
+			  COUNT(*)
+			FROM
+			  Players
+			WHERE
+			  id IN (?, ?)
+			FOR UPDATE
+			",
+			req.keep,
+			req.remove,
+		}
+		.fetch_one(txn.as_mut())
+		.await?;
+
+		if existing != 2 {
+			return Err(Error::PlayerDoesNotExist);
+		}
+
+		// Both players may e.g. be mappers of the same map, in which case the
+		// duplicate row is simply dropped.
+		sqlx::query! {
+			r"
+			UPDATE IGNORE
+			  Mappers
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			DELETE FROM
+			  Mappers
+			WHERE
+			  player_id = ?
+			",
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE IGNORE
+			  CourseMappers
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			DELETE FROM
+			  CourseMappers
+			WHERE
+			  player_id = ?
+			",
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE IGNORE
+			  PlayerNameHistory
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			DELETE FROM
+			  PlayerNameHistory
+			WHERE
+			  player_id = ?
+			",
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		let mut records = 0;
+
+		records += sqlx::query! {
+			r"
+			UPDATE
+			  Records
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?
+		.rows_affected();
+
+		records += sqlx::query! {
+			r"
+			UPDATE
+			  SuspiciousRecords
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?
+		.rows_affected();
+
+		records += sqlx::query! {
+			r"
+			UPDATE
+			  CheatedRecords
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?
+		.rows_affected();
+
+		records += sqlx::query! {
+			r"
+			UPDATE
+			  WipedRecords
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?
+		.rows_affected();
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  Jumpstats
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  SuspiciousJumpstats
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  CheatedJumpstats
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  Bans
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  Bans
+			SET
+			  admin_id = ?
+			WHERE
+			  admin_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  Unbans
+			SET
+			  admin_id = ?
+			WHERE
+			  admin_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  Servers
+			SET
+			  owner_id = ?
+			WHERE
+			  owner_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  GameSessions
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  CourseSessions
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  LoginSessions
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  RefreshTokens
+			SET
+			  player_id = ?
+			WHERE
+			  player_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  AuditLog
+			SET
+			  actor_id = ?
+			WHERE
+			  actor_id = ?
+			",
+			req.keep,
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		sqlx::query! {
+			r"
+			DELETE FROM
+			  Players
+			WHERE
+			  id = ?
+			",
+			req.remove,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		txn.commit().await?;
+
+		tracing::info!(records, "merged players");
+
+		Ok(MergePlayersResponse { records })
+	}
+}
+
+/// Records that a player has been seen with a specific name.
+async fn record_name(txn: &mut Transaction<'_, MySql>, player_id: SteamID, name: &str)
+	-> Result<()>
+{
+	sqlx::query! {
+		r"
+		INSERT INTO
+		  PlayerNameHistory (player_id, name)
+		VALUES
+		  (?, ?)
+		ON DUPLICATE KEY UPDATE
+		  last_seen_on = NOW()
+		",
+		player_id,
+		name,
+	}
+	.execute(txn.as_mut())
+	.await?;
+
+	Ok(())
 }
 
 #[cfg(test)]
@@ -315,13 +856,15 @@ mod tests
 	use sqlx::{MySql, Pool};
 
 	use super::*;
+	use crate::http::problem_details::{IntoProblemDetails, ProblemType};
 	use crate::testing::{self, ALPHAKEKS_ID};
 
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn fetch_player_works(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
 		let svc = testing::player_svc(database);
-		let req = FetchPlayerRequest { identifier: ALPHAKEKS_ID.into() };
+		let req =
+			FetchPlayerRequest { identifier: ALPHAKEKS_ID.into(), include_name_history: false };
 		let res = svc.fetch_player(req).await?.context("got `None`")?;
 
 		testing::assert_eq!(res.info.name, "AlphaKeks");
@@ -335,7 +878,7 @@ mod tests
 	async fn fetch_player_not_found(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
 		let svc = testing::player_svc(database);
-		let req = FetchPlayerRequest { identifier: "foobar".parse()? };
+		let req = FetchPlayerRequest { identifier: "foobar".parse()?, include_name_history: false };
 		let res = svc.fetch_player(req).await?;
 
 		testing::assert!(res.is_none());
@@ -350,7 +893,11 @@ mod tests
 	async fn fetch_players_works(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
 		let svc = testing::player_svc(database);
-		let req = FetchPlayersRequest { limit: Default::default(), offset: Default::default() };
+		let req = FetchPlayersRequest {
+			search: None,
+			limit: Default::default(),
+			offset: Default::default(),
+		};
 		let res = svc.fetch_players(req).await?;
 
 		testing::assert_eq!(res.players.len(), 4);
@@ -373,7 +920,7 @@ mod tests
 	async fn fetch_players_works_with_limit(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
 		let svc = testing::player_svc(database);
-		let req = FetchPlayersRequest { limit: 2.into(), offset: Default::default() };
+		let req = FetchPlayersRequest { search: None, limit: 2.into(), offset: Default::default() };
 		let res = svc.fetch_players(req).await?;
 
 		testing::assert_eq!(res.players.len(), 2);
@@ -396,18 +943,22 @@ mod tests
 	async fn fetch_players_works_with_offset(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
 		let svc = testing::player_svc(database);
-		let req = FetchPlayersRequest { limit: Default::default(), offset: Default::default() };
+		let req = FetchPlayersRequest {
+			search: None,
+			limit: Default::default(),
+			offset: Default::default(),
+		};
 		let all = svc.fetch_players(req).await?;
 
 		testing::assert_eq!(all.players.len() as u64, all.total);
 
-		let req = FetchPlayersRequest { limit: 2.into(), offset: 0.into() };
+		let req = FetchPlayersRequest { search: None, limit: 2.into(), offset: 0.into() };
 		let first_two = svc.fetch_players(req).await?;
 
 		testing::assert_eq!(first_two.players.len(), 2);
 		testing::assert_eq!(first_two.total, 4);
 
-		let req = FetchPlayersRequest { limit: 2.into(), offset: 2.into() };
+		let req = FetchPlayersRequest { search: None, limit: 2.into(), offset: 2.into() };
 		let last_two = svc.fetch_players(req).await?;
 
 		testing::assert_eq!(first_two.players.len(), 2);
@@ -423,6 +974,66 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/similar-players.sql",
+		)
+	)]
+	async fn fetch_players_ranks_search_results(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::player_svc(database);
+		let req = FetchPlayersRequest {
+			search: Some(String::from("Kiwi")),
+			limit: Default::default(),
+			offset: Default::default(),
+		};
+
+		let res = svc.fetch_players(req).await?;
+		let names = res
+			.players
+			.iter()
+			.map(|player| player.info.name.as_str())
+			.collect::<Vec<_>>();
+
+		testing::assert_eq!(names, ["kiwi", "kiwinator", "kiwi_fan", "ikiwi", "xkiwix"]);
+		testing::assert_eq!(res.total, 5);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/similar-players.sql",
+		)
+	)]
+	async fn fetch_players_treats_wildcards_literally(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::player_svc(database);
+		let req = FetchPlayersRequest {
+			search: Some(String::from("_")),
+			limit: Default::default(),
+			offset: Default::default(),
+		};
+
+		let res = svc.fetch_players(req).await?;
+		let names = res
+			.players
+			.iter()
+			.map(|player| player.info.name.as_str())
+			.collect::<Vec<_>>();
+
+		testing::assert_eq!(names, ["kiwi_fan"]);
+		testing::assert_eq!(res.total, 1);
+
+		Ok(())
+	}
+
 	#[sqlx::test(
 		migrations = "database/migrations",
 		fixtures("../../../database/fixtures/player-preferences.sql")
@@ -517,6 +1128,46 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn name_changes_are_recorded(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::player_svc(database);
+		let steam_id = Faker.fake::<SteamID>();
+
+		let req = RegisterPlayerRequest {
+			name: String::from("iBrahizy"),
+			steam_id,
+			ip_address: "::1".parse()?,
+		};
+
+		svc.register_player(req).await?;
+
+		let req = UpdatePlayerRequest {
+			player_id: steam_id,
+			server_id: 1.into(),
+			name: String::from("(͡ ͡° ͜ つ ͡͡°)"),
+			ip_address: "::1".parse()?,
+			preferences: json!({}),
+			session: Faker.fake(),
+		};
+
+		svc.update_player(req).await?;
+
+		let req = FetchPlayerRequest { identifier: steam_id.into(), include_name_history: true };
+		let res = svc.fetch_player(req).await?.context("got `None`")?;
+		let history = res.name_history.context("got no name history")?;
+		let names = history
+			.iter()
+			.map(|entry| entry.name.as_str())
+			.collect::<Vec<_>>();
+
+		testing::assert_eq!(names.len(), 2);
+		testing::assert!(names.contains(&"iBrahizy"));
+		testing::assert!(names.contains(&"(͡ ͡° ͜ つ ͡͡°)"));
+
+		Ok(())
+	}
+
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn update_player_fails_player_does_not_exist(
 		database: Pool<MySql>,
@@ -546,4 +1197,64 @@ mod tests
 
 		Ok(())
 	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/players.sql",
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/records.sql",
+		)
+	)]
+	async fn merge_players_transfers_records(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::player_svc(database.clone());
+
+		let keep = const {
+			match SteamID::new(76561198264939817) {
+				Some(id) => id,
+				None => unreachable!(),
+			}
+		};
+
+		let count_records = |player_id: SteamID| {
+			sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM Records WHERE player_id = ?")
+				.bind(player_id)
+				.fetch_one(&database)
+		};
+
+		let before = count_records(ALPHAKEKS_ID).await?;
+
+		testing::assert_ne!(before, 0);
+
+		let req = MergePlayersRequest { keep, remove: ALPHAKEKS_ID };
+		let res = svc.merge_players(req).await?;
+
+		testing::assert_eq!(res.records as i64, before);
+		testing::assert_eq!(count_records(keep).await?, before);
+		testing::assert_eq!(count_records(ALPHAKEKS_ID).await?, 0);
+
+		let req =
+			FetchPlayerRequest { identifier: ALPHAKEKS_ID.into(), include_name_history: false };
+		let removed = svc.fetch_player(req).await?;
+
+		testing::assert!(removed.is_none());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn merge_players_rejects_self_merge() -> color_eyre::Result<()>
+	{
+		let database = testing::lazy_pool();
+		let svc = testing::player_svc(database);
+
+		let req = MergePlayersRequest { keep: ALPHAKEKS_ID, remove: ALPHAKEKS_ID };
+		let res = svc.merge_players(req).await.unwrap_err();
+
+		testing::assert_matches!(res, Error::MergeIntoSelf);
+		testing::assert_eq!(res.problem_type(), ProblemType::InvalidRequestBody);
+
+		Ok(())
+	}
 }
@@ -5,7 +5,9 @@ use std::collections::BTreeMap;
 use axum::response::{AppendHeaders, IntoResponse, Response};
 use cs2kz::{Mode, SteamID};
 use serde::{Deserialize, Deserializer, Serialize};
+use time::OffsetDateTime;
 
+use super::Preferences;
 use crate::net::IpAddr;
 use crate::num::ClampedU64;
 use crate::services::maps::CourseID;
@@ -44,6 +46,11 @@ pub struct FetchPlayerRequest
 {
 	/// An identifier specifying which player you want to fetch.
 	pub identifier: PlayerIdentifier,
+
+	/// Whether to include the player's [name history].
+	///
+	/// [name history]: FetchPlayerResponse::name_history
+	pub include_name_history: bool,
 }
 
 /// Response payload for fetching a player.
@@ -63,6 +70,29 @@ pub struct FetchPlayerResponse
 	/// The player's IP address.
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub ip_address: Option<IpAddr>,
+
+	/// Every name the player has been seen with.
+	///
+	/// This is only included if it was explicitly requested.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[sqlx(skip)]
+	pub name_history: Option<Vec<NameHistoryEntry>>,
+}
+
+/// A name a player has been seen with.
+#[derive(Debug, PartialEq, Serialize, Deserialize, sqlx::FromRow, utoipa::ToSchema)]
+pub struct NameHistoryEntry
+{
+	/// The name.
+	pub name: String,
+
+	/// When the player was first seen with this name.
+	#[serde(with = "time::serde::rfc3339")]
+	pub first_seen_on: OffsetDateTime,
+
+	/// When the player was last seen with this name.
+	#[serde(with = "time::serde::rfc3339")]
+	pub last_seen_on: OffsetDateTime,
 }
 
 impl IntoResponse for FetchPlayerResponse
@@ -77,6 +107,13 @@ impl IntoResponse for FetchPlayerResponse
 #[derive(Debug, Deserialize, utoipa::IntoParams)]
 pub struct FetchPlayersRequest
 {
+	/// Only include players whose name contains this string.
+	///
+	/// Results are ranked by how well they match: exact matches first, then
+	/// names starting with the query, then everything else. Players with more
+	/// records come first within each group.
+	pub search: Option<String>,
+
 	/// The maximum amount of players to return.
 	#[serde(default)]
 	#[param(value_type = u64, default = 100, maximum = 500)]
@@ -134,6 +171,38 @@ impl IntoResponse for FetchPlayerPreferencesResponse
 	}
 }
 
+/// Request payload for partially updating a player's in-game preferences.
+#[derive(Debug)]
+pub struct UpdatePlayerPreferencesRequest
+{
+	/// The SteamID of the player whose preferences you wish to update.
+	pub player_id: SteamID,
+
+	/// The keys to update.
+	///
+	/// See [`Preferences::merge()`] for how this is applied.
+	///
+	/// [`Preferences::merge()`]: super::Preferences::merge
+	pub patch: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Response payload for partially updating a player's in-game preferences.
+#[derive(Debug, Serialize, utoipa::IntoResponses)]
+#[response(status = OK)]
+pub struct UpdatePlayerPreferencesResponse
+{
+	/// The player's preferences after the update.
+	pub preferences: Preferences,
+}
+
+impl IntoResponse for UpdatePlayerPreferencesResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
+
 /// Response payload for fetching a player's Steam profile.
 #[derive(Debug, Serialize, utoipa::IntoResponses)]
 #[serde(transparent)]
@@ -378,3 +447,22 @@ impl IntoResponse for UpdatePlayerResponse
 		(status, body).into_response()
 	}
 }
+
+/// Request payload for merging two player records.
+#[derive(Debug, Clone, Copy)]
+pub struct MergePlayersRequest
+{
+	/// The SteamID of the player that should remain.
+	pub keep: SteamID,
+
+	/// The SteamID of the duplicate player that should be removed.
+	pub remove: SteamID,
+}
+
+/// Response payload for merging two player records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergePlayersResponse
+{
+	/// How many records have been transferred to the remaining player.
+	pub records: u64,
+}
@@ -0,0 +1,329 @@
+//! In-game player preferences.
+//!
+//! Preferences are stored as a JSON object. A few keys are known to the API and
+//! validated whenever preferences are written; everything else is kept as-is
+//! in [`Preferences::extensions`], so the plugin can store new settings without
+//! requiring an API update.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// The current version of the preferences format.
+pub const CURRENT_VERSION: u64 = 1;
+
+/// The allowed range for [`Preferences::hud_scale`].
+pub const HUD_SCALE_RANGE: std::ops::RangeInclusive<f64> = 0.5..=2.0;
+
+/// The maximum length of [`Preferences::language`].
+const MAX_LANGUAGE_LEN: usize = 16;
+
+/// A player's in-game preferences.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Map<String, Value>", into = "Map<String, Value>")]
+pub struct Preferences
+{
+	/// The version of the format these preferences are stored in.
+	pub version: u64,
+
+	/// The player's preferred language, e.g. `en` or `pt-BR`.
+	pub language: Option<String>,
+
+	/// How much the HUD should be scaled.
+	pub hud_scale: Option<f64>,
+
+	/// Any other keys.
+	pub extensions: Map<String, Value>,
+}
+
+/// An error that can occur when validating [`Preferences`].
+#[derive(Debug, Error)]
+pub enum PreferencesError
+{
+	/// The preferences were not a JSON object.
+	#[error("preferences must be an object")]
+	NotAnObject,
+
+	/// The preferences were stored in a version we don't know about.
+	#[error("unsupported preferences version `{0}`")]
+	UnsupportedVersion(u64),
+
+	/// A known key had the wrong type.
+	#[error("`{key}` must be {expected}")]
+	InvalidType
+	{
+		/// The key.
+		key: &'static str,
+
+		/// A description of the expected type.
+		expected: &'static str,
+	},
+
+	/// `hud_scale` was out of range.
+	#[error(
+		"`hud_scale` must be between {} and {}",
+		HUD_SCALE_RANGE.start(),
+		HUD_SCALE_RANGE.end()
+	)]
+	HudScaleOutOfRange,
+
+	/// `language` was not a valid language tag.
+	#[error("`language` must be a language tag like `en` or `pt-BR`")]
+	InvalidLanguage,
+}
+
+impl Default for Preferences
+{
+	fn default() -> Self
+	{
+		Self { version: CURRENT_VERSION, language: None, hud_scale: None, extensions: Map::new() }
+	}
+}
+
+impl Preferences
+{
+	/// Applies a partial update to `current` and validates the result.
+	///
+	/// `patch` is a JSON merge patch ([RFC 7396]): keys set to `null` are
+	/// removed, nested objects are merged recursively, and everything else
+	/// replaces the existing value.
+	///
+	/// [RFC 7396]: https://www.rfc-editor.org/rfc/rfc7396
+	pub fn merge(current: Value, patch: Map<String, Value>) -> Result<Self, PreferencesError>
+	{
+		let mut merged = match current {
+			Value::Object(current) => Value::Object(current),
+			_ => Value::Object(Map::new()),
+		};
+
+		merge_patch(&mut merged, Value::Object(patch));
+
+		Self::try_from(merged)
+	}
+}
+
+/// Merges `patch` into `target` according to [RFC 7396].
+///
+/// [RFC 7396]: https://www.rfc-editor.org/rfc/rfc7396
+fn merge_patch(target: &mut Value, patch: Value)
+{
+	let Value::Object(patch) = patch else {
+		*target = patch;
+		return;
+	};
+
+	if !target.is_object() {
+		*target = Value::Object(Map::new());
+	}
+
+	let target = target
+		.as_object_mut()
+		.expect("we just made sure it's an object");
+
+	for (key, value) in patch {
+		if value.is_null() {
+			target.remove(&key);
+		} else {
+			merge_patch(target.entry(key).or_insert(Value::Null), value);
+		}
+	}
+}
+
+impl TryFrom<Value> for Preferences
+{
+	type Error = PreferencesError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error>
+	{
+		match value {
+			Value::Object(map) => Self::try_from(map),
+			_ => Err(PreferencesError::NotAnObject),
+		}
+	}
+}
+
+impl TryFrom<Map<String, Value>> for Preferences
+{
+	type Error = PreferencesError;
+
+	fn try_from(mut map: Map<String, Value>) -> Result<Self, Self::Error>
+	{
+		let version = match map.remove("version") {
+			None => CURRENT_VERSION,
+			Some(version) => version.as_u64().ok_or(PreferencesError::InvalidType {
+				key: "version",
+				expected: "a positive integer",
+			})?,
+		};
+
+		if !(1..=CURRENT_VERSION).contains(&version) {
+			return Err(PreferencesError::UnsupportedVersion(version));
+		}
+
+		let language = match map.remove("language") {
+			None => None,
+			Some(Value::String(language)) if is_language_tag(&language) => Some(language),
+			Some(Value::String(_)) => return Err(PreferencesError::InvalidLanguage),
+			Some(_) => {
+				return Err(PreferencesError::InvalidType {
+					key: "language",
+					expected: "a string",
+				});
+			}
+		};
+
+		let hud_scale = match map.remove("hud_scale") {
+			None => None,
+			Some(scale) => {
+				let scale = scale.as_f64().ok_or(PreferencesError::InvalidType {
+					key: "hud_scale",
+					expected: "a number",
+				})?;
+
+				if !HUD_SCALE_RANGE.contains(&scale) {
+					return Err(PreferencesError::HudScaleOutOfRange);
+				}
+
+				Some(scale)
+			}
+		};
+
+		Ok(Self { version, language, hud_scale, extensions: map })
+	}
+}
+
+impl From<Preferences> for Map<String, Value>
+{
+	fn from(preferences: Preferences) -> Self
+	{
+		let mut map = preferences.extensions;
+
+		map.insert(String::from("version"), Value::from(preferences.version));
+
+		if let Some(language) = preferences.language {
+			map.insert(String::from("language"), Value::from(language));
+		}
+
+		if let Some(hud_scale) = preferences.hud_scale {
+			map.insert(String::from("hud_scale"), Value::from(hud_scale));
+		}
+
+		map
+	}
+}
+
+impl From<Preferences> for Value
+{
+	fn from(preferences: Preferences) -> Self
+	{
+		Value::Object(preferences.into())
+	}
+}
+
+/// Checks whether `language` looks like a BCP 47 language tag.
+fn is_language_tag(language: &str) -> bool
+{
+	(2..=MAX_LANGUAGE_LEN).contains(&language.len())
+		&& language
+			.split('-')
+			.all(|part| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_alphanumeric()))
+}
+
+/// Trait implementations for the [`utoipa`] crate.
+mod utoipa_impls
+{
+	use utoipa::openapi::{ObjectBuilder, RefOr, Schema, SchemaType};
+	use utoipa::ToSchema as _ToSchema;
+
+	use super::{Preferences, HUD_SCALE_RANGE};
+
+	impl<'s> _ToSchema<'s> for Preferences
+	{
+		fn schema() -> (&'s str, RefOr<Schema>)
+		{
+			(
+				"Preferences",
+				Schema::Object(
+					ObjectBuilder::new()
+						.description(Some("A player's in-game preferences"))
+						.schema_type(SchemaType::Object)
+						.property(
+							"version",
+							ObjectBuilder::new()
+								.schema_type(SchemaType::Integer)
+								.minimum(Some(1.0)),
+						)
+						.property("language", ObjectBuilder::new().schema_type(SchemaType::String))
+						.property(
+							"hud_scale",
+							ObjectBuilder::new()
+								.schema_type(SchemaType::Number)
+								.minimum(Some(*HUD_SCALE_RANGE.start()))
+								.maximum(Some(*HUD_SCALE_RANGE.end())),
+						)
+						.additional_properties(Some(Schema::Object(ObjectBuilder::new().build())))
+						.build(),
+				)
+				.into(),
+			)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use serde_json::json;
+
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn partial_update_keeps_other_keys() -> color_eyre::Result<()>
+	{
+		let current = json!({
+			"language": "en",
+			"hud_scale": 1.0,
+			"hud": { "speed": true, "keys": false },
+		});
+
+		let patch = json!({
+			"hud_scale": 1.5,
+			"hud": { "keys": true },
+			"language": null,
+		});
+
+		let Value::Object(patch) = patch else {
+			unreachable!()
+		};
+		let merged = Preferences::merge(current, patch)?;
+
+		testing::assert_eq!(merged.version, CURRENT_VERSION);
+		testing::assert!(merged.language.is_none());
+		testing::assert_eq!(merged.hud_scale, Some(1.5));
+		testing::assert_eq!(merged.extensions["hud"], json!({ "speed": true, "keys": true }));
+
+		Ok(())
+	}
+
+	#[test]
+	fn out_of_range_values_are_rejected() -> color_eyre::Result<()>
+	{
+		let Value::Object(patch) = json!({ "hud_scale": 5.0 }) else {
+			unreachable!()
+		};
+		let result = Preferences::merge(json!({ "hud_scale": 1.0 }), patch);
+
+		testing::assert_matches!(result, Err(PreferencesError::HudScaleOutOfRange));
+
+		let result = Preferences::try_from(json!({ "language": "not a language" }));
+
+		testing::assert_matches!(result, Err(PreferencesError::InvalidLanguage));
+
+		let result = Preferences::try_from(json!({ "version": CURRENT_VERSION + 1 }));
+
+		testing::assert_matches!(result, Err(PreferencesError::UnsupportedVersion(_)));
+
+		Ok(())
+	}
+}
@@ -14,6 +14,8 @@ use super::{
 	PluginVersionIdentifier,
 	SubmitPluginVersionRequest,
 	SubmitPluginVersionResponse,
+	YankPluginVersionRequest,
+	YankPluginVersionResponse,
 };
 use crate::http::extract::{Json, Path, Query};
 use crate::http::ProblemDetails;
@@ -31,14 +33,21 @@ impl From<PluginService> for Router
 
 		Router::new()
 			.route("/versions", routing::get(get_versions))
-			.route("/versions", routing::post(submit_version).route_layer(auth))
+			.route("/versions", routing::post(submit_version).route_layer(auth.clone()))
 			.route("/versions/:version", routing::get(get_version))
+			.route("/versions/:version/yank", routing::post(yank_version).route_layer(auth))
 			.with_state(svc)
 	}
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(get, path = "/plugin/versions", tag = "Plugin", params(FetchPluginVersionsRequest))]
+#[utoipa::path(
+	get,
+	path = "/plugin/versions",
+	tag = "Plugin",
+	operation_id = "get_plugin_versions",
+	params(FetchPluginVersionsRequest)
+)]
 async fn get_versions(
 	State(svc): State<PluginService>,
 	Query(req): Query<FetchPluginVersionsRequest>,
@@ -54,7 +63,7 @@ async fn get_versions(
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(post, path = "/plugin/versions", tag = "Plugin", security(("API Key" = [])))]
+#[utoipa::path(post, path = "/plugin/versions", tag = "Plugin", operation_id = "submit_plugin_version", security(("API Key" = [])))]
 async fn submit_version(
 	Extension(key): Extension<ApiKey>,
 	State(svc): State<PluginService>,
@@ -67,7 +76,7 @@ async fn submit_version(
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(get, path = "/plugin/versions/{version}", tag = "Plugin", params(
+#[utoipa::path(get, path = "/plugin/versions/{version}", tag = "Plugin", operation_id = "get_plugin_version", params(
   ("version" = str, Path, description = "a plugin version identifier"),
 ))]
 async fn get_version(
@@ -83,3 +92,19 @@ async fn get_version(
 
 	Ok(res)
 }
+
+#[tracing::instrument(err(Debug, level = "debug"))]
+#[utoipa::path(post, path = "/plugin/versions/{version}/yank", tag = "Plugin", operation_id = "yank_plugin_version", params(
+  ("version" = str, Path, description = "a plugin version identifier"),
+), security(("API Key" = [])))]
+async fn yank_version(
+	Extension(key): Extension<ApiKey>,
+	State(svc): State<PluginService>,
+	Path(ident): Path<PluginVersionIdentifier>,
+) -> Result<YankPluginVersionResponse, ProblemDetails>
+{
+	let req = YankPluginVersionRequest { ident };
+	let res = svc.yank_version(req).await?;
+
+	Ok(res)
+}
@@ -18,11 +18,14 @@ pub use models::{
 	FetchPluginVersionResponse,
 	FetchPluginVersionsRequest,
 	FetchPluginVersionsResponse,
+	GitRevision,
 	PluginVersion,
 	PluginVersionID,
 	PluginVersionIdentifier,
 	SubmitPluginVersionRequest,
 	SubmitPluginVersionResponse,
+	YankPluginVersionRequest,
+	YankPluginVersionResponse,
 };
 
 /// A service for managing KZ maps.
@@ -63,6 +66,7 @@ impl PluginService
 			  id,
 			  semver,
 			  git_revision,
+			  yanked,
 			  created_on
 			FROM
 			  PluginVersions
@@ -97,6 +101,7 @@ impl PluginService
 			  SQL_CALC_FOUND_ROWS id,
 			  semver,
 			  git_revision,
+			  yanked,
 			  created_on
 			FROM
 			  PluginVersions
@@ -116,35 +121,79 @@ impl PluginService
 		Ok(FetchPluginVersionsResponse { versions, total })
 	}
 
-	/// Submit a new plugin version.
+	/// Mark a plugin version as yanked.
+	///
+	/// Yanked versions stay in the database, so records and bans referencing
+	/// them are kept intact, but servers can no longer authenticate with them.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
-	pub async fn submit_version(
+	pub async fn yank_version(
 		&self,
-		req: SubmitPluginVersionRequest,
-	) -> Result<SubmitPluginVersionResponse>
+		req: YankPluginVersionRequest,
+	) -> Result<YankPluginVersionResponse>
 	{
-		let mut txn = self.database.begin().await?;
-
-		let latest_version = sqlx::query! {
+		let query_result = sqlx::query! {
 			r"
-			SELECT
-			  semver `semver: PluginVersion`
-			FROM
+			UPDATE
 			  PluginVersions
-			ORDER BY
-			  created_on DESC
+			SET
+			  yanked = TRUE
+			WHERE
+			  id = COALESCE(?, id)
+			  AND semver = COALESCE(?, semver)
+			  AND git_revision = COALESCE(?, git_revision)
 			LIMIT
 			  1
 			",
+			req.ident.as_id(),
+			req.ident.as_semver(),
+			req.ident.as_git_rev(),
+		}
+		.execute(&self.database)
+		.await?;
+
+		if query_result.rows_affected() == 0 {
+			return Err(Error::VersionDoesNotExist);
 		}
-		.fetch_optional(txn.as_mut())
-		.await?
-		.map(|row| row.semver);
 
-		if let Some(latest) = latest_version.filter(|v| v >= &req.semver) {
-			tracing::warn!(%latest, %req.semver, "submitted outdated plugin version");
+		tracing::info!(ident = ?req.ident, "yanked plugin version");
 
-			return Err(Error::OutdatedVersion { latest, actual: req.semver });
+		Ok(YankPluginVersionResponse { _priv: () })
+	}
+
+	/// Submit a new plugin version.
+	///
+	/// Unless [`force`] is set, releases must be newer than the latest release
+	/// that has already been submitted. Prereleases are not checked.
+	///
+	/// [`force`]: SubmitPluginVersionRequest::force
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn submit_version(
+		&self,
+		req: SubmitPluginVersionRequest,
+	) -> Result<SubmitPluginVersionResponse>
+	{
+		let mut txn = self.database.begin().await?;
+
+		if !req.force && req.semver.0.pre.is_empty() {
+			let latest_release = sqlx::query_scalar! {
+				r"
+				SELECT
+				  semver `semver: PluginVersion`
+				FROM
+				  PluginVersions
+				",
+			}
+			.fetch_all(txn.as_mut())
+			.await?
+			.into_iter()
+			.filter(|version| version.0.pre.is_empty())
+			.max();
+
+			if let Some(latest) = latest_release.filter(|v| v >= &req.semver) {
+				tracing::warn!(%latest, %req.semver, "submitted outdated plugin version");
+
+				return Err(Error::OutdatedVersion { latest, actual: req.semver });
+			}
 		}
 
 		let plugin_version_id = sqlx::query! {
@@ -164,8 +213,128 @@ impl PluginService
 
 		txn.commit().await?;
 
-		tracing::info!(version = %req.semver, revision = req.git_revision, "registered new plugin version");
+		tracing::info!(version = %req.semver, revision = %req.git_revision, "registered new plugin version");
 
 		Ok(SubmitPluginVersionResponse { plugin_version_id })
 	}
 }
+
+#[cfg(test)]
+mod tests
+{
+	use sqlx::{MySql, Pool};
+
+	use super::*;
+	use crate::testing;
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn yanked_versions_are_listed(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = PluginService::new(database);
+		let old: PluginVersion = "0.0.1".parse()?;
+		let new: PluginVersion = "0.0.2".parse()?;
+
+		let req = SubmitPluginVersionRequest {
+			semver: new.clone(),
+			git_revision: "2a8c2ab79f3b8fd9f0da5f2e12a3bb2e5b1a7b2c".parse()?,
+			force: false,
+		};
+
+		svc.submit_version(req).await?;
+
+		let req = YankPluginVersionRequest { ident: PluginVersionIdentifier::SemVer(old.clone()) };
+
+		svc.yank_version(req).await?;
+
+		let req =
+			FetchPluginVersionsRequest { limit: Default::default(), offset: Default::default() };
+		let res = svc.fetch_versions(req).await?;
+
+		testing::assert_eq!(res.total, 2);
+
+		for version in &res.versions {
+			testing::assert_eq!(version.yanked, version.semver == old);
+		}
+
+		// Yanking is idempotent.
+		let req = YankPluginVersionRequest { ident: PluginVersionIdentifier::SemVer(old) };
+		let res = svc.yank_version(req).await;
+
+		testing::assert!(res.is_ok());
+
+		let req =
+			YankPluginVersionRequest { ident: PluginVersionIdentifier::SemVer("6.9.0".parse()?) };
+		let res = svc.yank_version(req).await.unwrap_err();
+
+		testing::assert_matches!(res, Error::VersionDoesNotExist);
+
+		Ok(())
+	}
+
+	/// Submits `semver` with a made-up git revision.
+	async fn submit(svc: &PluginService, semver: &str, force: bool) -> Result<PluginVersionID>
+	{
+		let git_revision = semver
+			.bytes()
+			.map(|byte| format!("{byte:02x}"))
+			.collect::<String>();
+
+		let req = SubmitPluginVersionRequest {
+			semver: semver.parse().expect("valid semver"),
+			git_revision: git_revision.parse().expect("valid git revision"),
+			force,
+		};
+
+		svc.submit_version(req)
+			.await
+			.map(|res| res.plugin_version_id)
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn submit_version_accepts_newer_release(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = PluginService::new(database);
+		let res = submit(&svc, "0.1.0", false).await;
+
+		testing::assert!(res.is_ok());
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn submit_version_rejects_downgrade(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = PluginService::new(database);
+
+		submit(&svc, "0.2.0", false).await?;
+
+		// Prereleases are exempt from the check.
+		submit(&svc, "0.1.1-rc.1", false).await?;
+
+		let res = submit(&svc, "0.1.0", false).await.unwrap_err();
+
+		testing::assert_matches!(
+			res,
+			Error::OutdatedVersion { ref latest, ref actual }
+				if latest.to_string() == "0.2.0" && actual.to_string() == "0.1.0"
+		);
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn submit_version_accepts_forced_downgrade(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = PluginService::new(database);
+
+		submit(&svc, "0.2.0", false).await?;
+
+		let res = submit(&svc, "0.1.5", true).await;
+
+		testing::assert!(res.is_ok());
+
+		Ok(())
+	}
+}
@@ -0,0 +1,185 @@
+//! Git revisions associated with plugin versions.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// The shortest abbreviated form of a revision we accept.
+const MIN_LEN: usize = 7;
+
+/// The length of a full SHA-1 revision.
+const MAX_LEN: usize = 40;
+
+/// A git commit hash.
+///
+/// Both full and abbreviated forms are accepted, but they are always stored
+/// in lowercase.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GitRevision(String);
+
+/// An error that can occur when parsing a [`GitRevision`].
+#[derive(Debug, Error)]
+pub enum InvalidGitRevision
+{
+	/// The revision was too short or too long.
+	#[error("git revision must be between {MIN_LEN} and {MAX_LEN} characters long")]
+	InvalidLength,
+
+	/// The revision contained non-hex characters.
+	#[error("git revision must only contain hex digits")]
+	NotHex,
+}
+
+impl GitRevision
+{
+	/// Returns the revision as a string.
+	pub fn as_str(&self) -> &str
+	{
+		&self.0
+	}
+
+	/// Returns the abbreviated form of this revision, as displayed by
+	/// `git log --oneline`.
+	pub fn short(&self) -> &str
+	{
+		self.0.get(..MIN_LEN).unwrap_or(&self.0)
+	}
+}
+
+impl fmt::Display for GitRevision
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		f.write_str(&self.0)
+	}
+}
+
+impl FromStr for GitRevision
+{
+	type Err = InvalidGitRevision;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err>
+	{
+		if !(MIN_LEN..=MAX_LEN).contains(&s.len()) {
+			return Err(InvalidGitRevision::InvalidLength);
+		}
+
+		if !s.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+			return Err(InvalidGitRevision::NotHex);
+		}
+
+		Ok(Self(s.to_ascii_lowercase()))
+	}
+}
+
+impl Serialize for GitRevision
+{
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		self.0.serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for GitRevision
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(serde::de::Error::custom)
+	}
+}
+
+// Revisions are validated when they come in through the API, so we trust
+// whatever is already in the database rather than failing to load old rows.
+crate::macros::sqlx_scalar_forward!(GitRevision as String => {
+	encode: |self| { self.0.clone() },
+	decode: |value| { Self(value) },
+});
+
+/// Trait implementations for the [`utoipa`] crate.
+mod utoipa_impls
+{
+	use utoipa::openapi::{ObjectBuilder, RefOr, Schema, SchemaType};
+	use utoipa::ToSchema as _ToSchema;
+
+	use super::{GitRevision, MAX_LEN, MIN_LEN};
+
+	impl<'s> _ToSchema<'s> for GitRevision
+	{
+		fn schema() -> (&'s str, RefOr<Schema>)
+		{
+			(
+				"GitRevision",
+				Schema::Object(
+					ObjectBuilder::new()
+						.description(Some("A git commit hash"))
+						.schema_type(SchemaType::String)
+						.pattern(Some(format!("^[0-9a-fA-F]{{{MIN_LEN},{MAX_LEN}}}$")))
+						.example(Some("c7521668a25a207abad2cc2cca2e955c29827645".into()))
+						.build(),
+				)
+				.into(),
+			)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn full_revisions_are_accepted() -> color_eyre::Result<()>
+	{
+		let rev = "C7521668A25A207ABAD2CC2CCA2E955C29827645".parse::<GitRevision>()?;
+
+		testing::assert_eq!(rev.as_str(), "c7521668a25a207abad2cc2cca2e955c29827645");
+		testing::assert_eq!(rev.short(), "c752166");
+
+		Ok(())
+	}
+
+	#[test]
+	fn abbreviated_revisions_are_accepted() -> color_eyre::Result<()>
+	{
+		let rev = "c752166".parse::<GitRevision>()?;
+
+		testing::assert_eq!(rev.as_str(), "c752166");
+		testing::assert_eq!(rev.short(), "c752166");
+
+		Ok(())
+	}
+
+	#[test]
+	fn invalid_revisions_are_rejected() -> color_eyre::Result<()>
+	{
+		let too_short = "c75216".parse::<GitRevision>();
+		let too_long = "c7521668a25a207abad2cc2cca2e955c298276450".parse::<GitRevision>();
+		let not_hex = "c7521668a25a207abad2cc2cca2e955c2982764g".parse::<GitRevision>();
+
+		testing::assert_matches!(too_short, Err(InvalidGitRevision::InvalidLength));
+		testing::assert_matches!(too_long, Err(InvalidGitRevision::InvalidLength));
+		testing::assert_matches!(not_hex, Err(InvalidGitRevision::NotHex));
+
+		Ok(())
+	}
+
+	#[test]
+	fn stored_revisions_are_not_revalidated() -> color_eyre::Result<()>
+	{
+		let rev = GitRevision(String::from("abc"));
+
+		testing::assert_eq!(rev.short(), "abc");
+
+		Ok(())
+	}
+}
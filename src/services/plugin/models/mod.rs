@@ -10,6 +10,10 @@ use crate::num::ClampedU64;
 pub(crate) mod version;
 pub use version::PluginVersion;
 
+#[doc(hidden)]
+pub(crate) mod git_revision;
+pub use git_revision::GitRevision;
+
 crate::macros::make_id! {
 	/// A unique identifier for CS2KZ versions.
 	PluginVersionID as u16
@@ -27,7 +31,7 @@ pub enum PluginVersionIdentifier
 	SemVer(PluginVersion),
 
 	/// A git revision.
-	GitRev(String),
+	GitRev(GitRevision),
 }
 
 impl PluginVersionIdentifier
@@ -83,7 +87,12 @@ pub struct FetchPluginVersionResponse
 	pub semver: PluginVersion,
 
 	/// The git revision associated with this version.
-	pub git_revision: String,
+	pub git_revision: GitRevision,
+
+	/// Whether this version has been yanked.
+	///
+	/// Servers running a yanked version can no longer authenticate.
+	pub yanked: bool,
 
 	/// When this version was submitted.
 	#[serde(with = "time::serde::rfc3339")]
@@ -142,7 +151,13 @@ pub struct SubmitPluginVersionRequest
 	pub semver: PluginVersion,
 
 	/// The git revision associated with this version.
-	pub git_revision: String,
+	pub git_revision: GitRevision,
+
+	/// Skip checking that this version is newer than the latest release.
+	///
+	/// This is only necessary for publishing backports.
+	#[serde(default)]
+	pub force: bool,
 }
 
 /// Response payload for submitting a new plugin version.
@@ -168,3 +183,29 @@ impl IntoResponse for SubmitPluginVersionResponse
 		(status, headers, body).into_response()
 	}
 }
+
+/// Request payload for yanking a plugin version.
+#[derive(Debug)]
+pub struct YankPluginVersionRequest
+{
+	/// Identifier specifying which plugin version you want to yank.
+	pub ident: PluginVersionIdentifier,
+}
+
+/// Response payload for yanking a plugin version.
+#[derive(Debug)]
+pub struct YankPluginVersionResponse
+{
+	/// non-exhaustive
+	pub(super) _priv: (),
+}
+
+impl IntoResponse for YankPluginVersionResponse
+{
+	fn into_response(self) -> Response
+	{
+		http::StatusCode::NO_CONTENT.into_response()
+	}
+}
+
+crate::openapi::responses::no_content!(YankPluginVersionResponse);
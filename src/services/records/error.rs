@@ -2,7 +2,7 @@
 
 use thiserror::Error;
 
-use crate::http::problem_details::{IntoProblemDetails, ProblemType};
+use crate::http::problem_details::{self, IntoProblemDetails, ProblemType};
 
 /// Type alias with a default `Err` type of [`Error`].
 ///
@@ -27,6 +27,15 @@ pub enum Error
 	#[error("cannot update record; supplied status is the same as current status")]
 	WouldNotMove,
 
+	/// A record in a batch submission was set on a course / mode / teleports
+	/// combination that has no filter.
+	#[error("record #{index} does not belong to any filter")]
+	FilterDoesNotExist
+	{
+		/// The record's position in the batch.
+		index: usize,
+	},
+
 	/// Something went wrong communicating with the database.
 	#[error("something went wrong")]
 	Database(#[from] sqlx::Error),
@@ -39,8 +48,16 @@ impl IntoProblemDetails for Error
 		match self {
 			Self::NoData => ProblemType::NoContent,
 			Self::RecordDoesNotExist => ProblemType::ResourceNotFound,
+			Self::FilterDoesNotExist { .. } => ProblemType::InvalidRequestBody,
 			Self::WouldNotMove => ProblemType::NoChange,
 			Self::Database(source) => source.problem_type(),
 		}
 	}
+
+	fn add_extension_members(&self, ext: &mut problem_details::ExtensionMembers)
+	{
+		if let Self::FilterDoesNotExist { index } = self {
+			ext.add("index", index);
+		}
+	}
 }
@@ -1,13 +1,19 @@
 //! HTTP handlers for this service.
 
-use axum::extract::State;
+use axum::extract::{OriginalUri, State};
 use axum::{routing, Router};
 use cs2kz::{Mode, SteamID, Styles};
 use serde::Deserialize;
 use tower::ServiceBuilder;
 
 use super::{
+	DeletePlayerRecordsRequest,
+	DeletePlayerRecordsResponse,
 	Error,
+	ExportRecordsRequest,
+	ExportRecordsResponse,
+	FetchPlayerRankRequest,
+	FetchPlayerRankResponse,
 	FetchRecordRequest,
 	FetchRecordResponse,
 	FetchRecordsRequest,
@@ -18,18 +24,21 @@ use super::{
 	RecordService,
 	SubmitRecordRequest,
 	SubmitRecordResponse,
+	SubmitRecordsRequest,
+	SubmitRecordsResponse,
 	UpdateRecordAction,
 	UpdateRecordRequest,
 	UpdateRecordResponse,
 };
 use crate::http::extract::{Json, Path, Query};
-use crate::http::ProblemDetails;
+use crate::http::pagination::Page;
+use crate::http::{Paginated, ProblemDetails};
 use crate::middleware;
 use crate::services::auth::jwt::{self, JwtLayer};
 use crate::services::auth::session::user::Permissions;
 use crate::services::auth::session::{authorization, SessionManagerLayer};
 use crate::services::auth::{Jwt, Session};
-use crate::services::maps::CourseID;
+use crate::services::maps::{CourseID, FilterID};
 use crate::stats::BhopStats;
 use crate::time::Seconds;
 
@@ -48,20 +57,36 @@ impl From<RecordService> for Router
 				authorization::RequiredPermissions(Permissions::RECORDS),
 			));
 
+		let admin_auth = ServiceBuilder::new()
+			.layer(middleware::InfallibleLayer::new())
+			.layer(SessionManagerLayer::with_strategy(
+				svc.auth_svc.clone(),
+				authorization::RequiredPermissions(Permissions::ADMIN),
+			));
+
 		let no_cors = Router::new()
-			.route("/", routing::post(submit_record).layer(jwt_auth))
+			.route("/", routing::post(submit_record).layer(jwt_auth.clone()))
+			.route("/batch", routing::post(submit_records).layer(jwt_auth))
 			.with_state(svc.clone());
 
 		let public = Router::new()
 			.route("/", routing::get(get_many))
+			.route("/rank", routing::get(get_rank))
 			.route("/:record", routing::get(get_single))
 			.route("/:record/replay", routing::get(get_replay))
 			.route_layer(middleware::cors::permissive())
 			.with_state(svc.clone());
 
 		let protected = Router::new()
-			.route("/:record", routing::patch(update_record).layer(session_auth))
-			.route_layer(middleware::cors::dashboard([http::Method::OPTIONS, http::Method::PATCH]))
+			.route("/export", routing::get(export_records).layer(admin_auth))
+			.route("/:record", routing::patch(update_record).layer(session_auth.clone()))
+			.route("/players/:player", routing::delete(delete_player_records).layer(session_auth))
+			.route_layer(middleware::cors::dashboard([
+				http::Method::OPTIONS,
+				http::Method::GET,
+				http::Method::PATCH,
+				http::Method::DELETE,
+			]))
 			.with_state(svc.clone());
 
 		no_cors.merge(public).merge(protected)
@@ -78,16 +103,44 @@ impl From<RecordService> for Router
 )]
 async fn get_many(
 	State(svc): State<RecordService>,
+	OriginalUri(uri): OriginalUri,
 	Query(req): Query<FetchRecordsRequest>,
-) -> Result<FetchRecordsResponse, ProblemDetails>
+) -> Result<Paginated<FetchRecordsResponse>, ProblemDetails>
 {
+	// Cursor-based requests already get `next_cursor`; offset links would
+	// point at the wrong pages.
+	let page = req.after.is_none().then_some((*req.limit, *req.offset));
 	let res = svc.fetch_records(req).await?;
 
 	if res.records.is_empty() {
 		Err(Error::NoData)?;
 	}
 
-	Ok(res)
+	Ok(match page {
+		Some((limit, offset)) => {
+			let total = res.total;
+			Paginated::new(res, Page { uri: &uri, limit, offset, total })
+		}
+		None => Paginated::without_links(res),
+	})
+}
+
+#[tracing::instrument(level = "trace")]
+#[utoipa::path(
+  get,
+  path = "/records/export",
+  tag = "Records",
+  operation_id = "export_records",
+  params(ExportRecordsRequest),
+  security(("Browser Session" = ["admin"])),
+)]
+async fn export_records(
+	session: Session,
+	State(svc): State<RecordService>,
+	Query(req): Query<ExportRecordsRequest>,
+) -> ExportRecordsResponse
+{
+	svc.export_records(req)
 }
 
 /// Request payload for `POST /records`.
@@ -118,7 +171,7 @@ pub struct SubmitRecordRequestPayload
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(post, path = "/records", tag = "Records", security(("CS2 Server" = [])))]
+#[utoipa::path(post, path = "/records", tag = "Records", operation_id = "submit_record", security(("CS2 Server" = [])))]
 async fn submit_record(
 	server: Jwt<jwt::ServerInfo>,
 	State(svc): State<RecordService>,
@@ -150,6 +203,86 @@ async fn submit_record(
 	Ok(res)
 }
 
+/// The maximum amount of records that can be submitted in a single batch.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// Request payload for `POST /records/batch`.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[schema(title = "SubmitRecordsRequest")]
+pub struct SubmitRecordsRequestPayload
+{
+	/// The records to submit.
+	///
+	/// They are submitted atomically; if any of them is invalid, none of them
+	/// are created.
+	#[serde(deserialize_with = "deserialize_batch")]
+	#[schema(min_items = 1, max_items = 100)] // must match `MAX_BATCH_SIZE`
+	pub records: Vec<SubmitRecordRequestPayload>,
+}
+
+/// Deserializes the records of a batch and makes sure there are at least 1
+/// and at most [`MAX_BATCH_SIZE`] of them.
+fn deserialize_batch<'de, D>(deserializer: D) -> Result<Vec<SubmitRecordRequestPayload>, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	let records = crate::serde::deserialize_non_empty::<Vec<_>, _>(deserializer)?;
+
+	if records.len() > MAX_BATCH_SIZE {
+		let expected = format!("at most {MAX_BATCH_SIZE} records");
+
+		return Err(serde::de::Error::invalid_length(records.len(), &expected.as_str()));
+	}
+
+	Ok(records)
+}
+
+#[tracing::instrument(skip(records), err(Debug, level = "debug"))]
+#[utoipa::path(post, path = "/records/batch", tag = "Records", operation_id = "submit_records", security(("CS2 Server" = [])))]
+async fn submit_records(
+	server: Jwt<jwt::ServerInfo>,
+	State(svc): State<RecordService>,
+	Json(SubmitRecordsRequestPayload { records }): Json<SubmitRecordsRequestPayload>,
+) -> Result<SubmitRecordsResponse, ProblemDetails>
+{
+	let records = records
+		.into_iter()
+		.map(|record| SubmitRecordRequest {
+			course_id: record.course_id,
+			mode: record.mode,
+			styles: record.styles,
+			teleports: record.teleports,
+			time: record.time,
+			player_id: record.player_id,
+			server_id: server.id(),
+			bhop_stats: record.bhop_stats,
+			plugin_version_id: server.plugin_version_id(),
+		})
+		.collect();
+
+	let res = svc.submit_records(SubmitRecordsRequest { records }).await?;
+
+	Ok(res)
+}
+
+#[tracing::instrument(err(Debug, level = "debug"))]
+#[utoipa::path(
+	get,
+	path = "/records/rank",
+	tag = "Records",
+	operation_id = "get_player_rank",
+	params(FetchPlayerRankRequest)
+)]
+async fn get_rank(
+	State(svc): State<RecordService>,
+	Query(req): Query<FetchPlayerRankRequest>,
+) -> Result<FetchPlayerRankResponse, ProblemDetails>
+{
+	let res = svc.fetch_player_rank(req).await?.ok_or(Error::NoData)?;
+
+	Ok(res)
+}
+
 #[tracing::instrument(err(Debug, level = "debug"))]
 #[utoipa::path(get, path = "/records/{record_id}", tag = "Records", operation_id = "get_record", params(
   ("record_id" = RecordID, Path, description = "a record ID"),
@@ -182,6 +315,7 @@ pub struct UpdateRecordRequestPayload
   patch,
   path = "/records/{record_id}",
   tag = "Records",
+  operation_id = "update_record",
   params(("record_id" = RecordID, Path, description = "a record ID")),
   security(("Browser Session" = ["records"])),
 )]
@@ -198,8 +332,44 @@ async fn update_record(
 	Ok(res)
 }
 
+/// Query parameters for `DELETE /records/players/{player}`.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[doc(hidden)]
+pub(crate) struct DeletePlayerRecordsQuery
+{
+	/// Only delete records on this filter.
+	pub filter_id: Option<FilterID>,
+}
+
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(get, path = "/records/{record_id}/replay", tag = "Records", params(
+#[utoipa::path(
+  delete,
+  path = "/records/players/{player}",
+  tag = "Records",
+  operation_id = "delete_player_records",
+  params(("player" = SteamID, Path, description = "a player's SteamID"), DeletePlayerRecordsQuery),
+  security(("Browser Session" = ["records"])),
+)]
+async fn delete_player_records(
+	session: Session,
+	State(svc): State<RecordService>,
+	Path(player_id): Path<SteamID>,
+	Query(DeletePlayerRecordsQuery { filter_id }): Query<DeletePlayerRecordsQuery>,
+) -> Result<DeletePlayerRecordsResponse, ProblemDetails>
+{
+	let admin_id = Some(session.user().steam_id());
+	let req = DeletePlayerRecordsRequest { player_id, filter_id, admin_id };
+	let res = svc.delete_player_records(req).await?;
+
+	if res.deleted == 0 {
+		Err(Error::NoData)?;
+	}
+
+	Ok(res)
+}
+
+#[tracing::instrument(err(Debug, level = "debug"))]
+#[utoipa::path(get, path = "/records/{record_id}/replay", tag = "Records", operation_id = "get_record_replay", params(
   ("record_id" = RecordID, Path, description = "a record ID"),
 ))]
 async fn get_replay(
@@ -212,3 +382,70 @@ async fn get_replay(
 
 	Ok(res)
 }
+
+#[cfg(test)]
+mod tests
+{
+	use axum::extract::{FromRequest, Request};
+	use axum::response::IntoResponse;
+
+	use super::*;
+	use crate::testing;
+
+	/// Runs the [`Json`] extractor on a batch of `count` records, returning the
+	/// status code of the rejection if it fails.
+	async fn extract(count: usize) -> Result<SubmitRecordsRequestPayload, http::StatusCode>
+	{
+		let record = serde_json::json!({
+			"course_id": 1,
+			"mode": "vanilla",
+			"styles": [],
+			"teleports": 0,
+			"time": 10.5,
+			"player_id": testing::ALPHAKEKS_ID,
+			"bhop_stats": { "total": 0, "perfs": 0, "perfect_perfs": 0 },
+		});
+
+		let body = serde_json::json!({ "records": vec![record; count] });
+		let req = Request::builder()
+			.method(http::Method::POST)
+			.header(http::header::CONTENT_TYPE, "application/json")
+			.body(body.to_string().into())
+			.expect("valid request");
+
+		Json::<SubmitRecordsRequestPayload>::from_request(req, &())
+			.await
+			.map(|Json(payload)| payload)
+			.map_err(|rejection| rejection.into_response().status())
+	}
+
+	#[tokio::test]
+	async fn rejects_oversized_batch() -> color_eyre::Result<()>
+	{
+		let full = extract(MAX_BATCH_SIZE)
+			.await
+			.map(|payload| payload.records.len());
+
+		testing::assert_matches!(full, Ok(MAX_BATCH_SIZE));
+
+		let oversized = extract(MAX_BATCH_SIZE + 1).await;
+
+		testing::assert_matches!(oversized, Err(http::StatusCode::UNPROCESSABLE_ENTITY));
+
+		let empty = extract(0).await;
+
+		testing::assert_matches!(empty, Err(http::StatusCode::UNPROCESSABLE_ENTITY));
+
+		Ok(())
+	}
+	#[test]
+	fn schema_matches_batch_size() -> color_eyre::Result<()>
+	{
+		let (_, schema) = <SubmitRecordsRequestPayload as utoipa::ToSchema<'_>>::schema();
+		let schema = serde_json::to_value(schema)?;
+
+		testing::assert_eq!(schema["properties"]["records"]["maxItems"], MAX_BATCH_SIZE);
+
+		Ok(())
+	}
+}
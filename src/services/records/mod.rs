@@ -1,13 +1,22 @@
 //! A service for managing records.
 
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
 
 use axum::extract::FromRef;
+use axum::BoxError;
+use cs2kz::Mode;
+use futures::StreamExt;
+use itertools::Itertools;
 use sqlx::{MySql, Pool, QueryBuilder, Row, Transaction};
 use tap::Tap;
+use tokio::sync::mpsc;
 
-use crate::database::TransactionExt;
+use crate::database::{self, TransactionExt};
+use crate::events::{Event, EventBus};
+use crate::services::maps::{CourseID, FilterID};
 use crate::services::AuthService;
+use crate::util::Cursor;
 
 pub(crate) mod http;
 
@@ -16,6 +25,12 @@ pub use error::{Error, Result};
 
 pub(crate) mod models;
 pub use models::{
+	DeletePlayerRecordsRequest,
+	DeletePlayerRecordsResponse,
+	ExportRecordsRequest,
+	ExportRecordsResponse,
+	FetchPlayerRankRequest,
+	FetchPlayerRankResponse,
 	FetchRecordRequest,
 	FetchRecordResponse,
 	FetchRecordsRequest,
@@ -26,11 +41,21 @@ pub use models::{
 	RecordStatus,
 	SubmitRecordRequest,
 	SubmitRecordResponse,
+	SubmitRecordsRequest,
+	SubmitRecordsResponse,
 	UpdateRecordAction,
 	UpdateRecordRequest,
 	UpdateRecordResponse,
 };
 
+/// The maximum amount of records returned by
+/// [`RecordService::export_records()`].
+pub const MAX_EXPORTED_RECORDS: u64 = 100_000;
+
+/// How many exported records may be queued up before we wait for the client
+/// to catch up.
+const EXPORT_BUFFER_SIZE: usize = 64;
+
 /// A service for managing records.
 #[derive(Clone, FromRef)]
 #[allow(clippy::missing_docs_in_private_items)]
@@ -38,6 +63,7 @@ pub struct RecordService
 {
 	database: Pool<MySql>,
 	auth_svc: AuthService,
+	events: EventBus,
 }
 
 impl fmt::Debug for RecordService
@@ -52,9 +78,9 @@ impl RecordService
 {
 	/// Create a new [`RecordService`].
 	#[tracing::instrument]
-	pub fn new(database: Pool<MySql>, auth_svc: AuthService) -> Self
+	pub fn new(database: Pool<MySql>, auth_svc: AuthService, events: EventBus) -> Self
 	{
-		Self { database, auth_svc }
+		Self { database, auth_svc, events }
 	}
 
 	/// Fetch a record by its ID.
@@ -136,7 +162,7 @@ impl RecordService
 
 		// TODO:
 		// - handle `req.top`
-		let records = sqlx::query_as(
+		let records = sqlx::query_as::<_, FetchRecordResponse>(
 			r"
 			SELECT SQL_CALC_FOUND_ROWS
 			  r.id,
@@ -168,6 +194,7 @@ impl RecordService
 			WHERE
 			  f.mode = COALESCE(?, f.mode)
 			  AND r.styles = COALESCE(?, r.styles)
+			  AND (r.styles & COALESCE(?, 0)) = 0
 			  AND (
 			    r.teleports BETWEEN COALESCE(?, 0) AND COALESCE(?, (1 << 31))
 			  )
@@ -175,22 +202,31 @@ impl RecordService
 			  AND m.id = COALESCE(?, m.id)
 			  AND p.id = COALESCE(?, p.id)
 			  AND s.id = COALESCE(?, s.id)
+			  AND r.time >= COALESCE(?, 0)
+			  AND r.time <= COALESCE(?, r.time)
 			  AND r.created_on > COALESCE(?, '1970-01-01 00:00:01')
 			  AND r.created_on < COALESCE(?, '2038-01-19 03:14:07')
+			  AND r.id < COALESCE(?, ~0)
+			ORDER BY
+			  r.id DESC
 			LIMIT
 			  ? OFFSET ?
 			",
 		)
 		.bind(req.mode)
 		.bind(req.styles)
+		.bind(req.exclude_styles)
 		.bind(min_tp)
 		.bind(max_tp)
 		.bind(course_id)
 		.bind(map_id)
 		.bind(player_id)
 		.bind(server_id)
+		.bind(req.min_time)
+		.bind(req.max_time)
 		.bind(req.created_after)
 		.bind(req.created_before)
+		.bind(req.after.map(|Cursor(record_id)| record_id))
 		.bind(*req.limit)
 		.bind(*req.offset)
 		.fetch_all(txn.as_mut())
@@ -200,7 +236,140 @@ impl RecordService
 
 		txn.commit().await?;
 
-		Ok(FetchRecordsResponse { records, total })
+		let next_cursor = Cursor::after_last(&records, *req.limit, |record| record.id);
+
+		Ok(FetchRecordsResponse { records, total, next_cursor })
+	}
+
+	/// Export every record on a filter.
+	///
+	/// The records are streamed from the database by a background task, newest
+	/// first, and at most [`MAX_EXPORTED_RECORDS`] of them are returned.
+	#[tracing::instrument(level = "debug")]
+	pub fn export_records(&self, req: ExportRecordsRequest) -> ExportRecordsResponse
+	{
+		let (tx, lines) = mpsc::channel(EXPORT_BUFFER_SIZE);
+		let database = self.database.clone();
+
+		tokio::spawn(async move {
+			let mut records = sqlx::query_as::<_, FetchRecordResponse>(
+				r"
+				SELECT
+				  r.id,
+				  f.mode,
+				  r.styles,
+				  r.teleports,
+				  r.time,
+				  c.id course_id,
+				  c.name course_name,
+				  m.id course_map_id,
+				  m.name course_map_name,
+				  f.tier course_tier,
+				  f.ranked_status course_ranked_status,
+				  p.id player_id,
+				  p.name player_name,
+				  s.id server_id,
+				  s.name server_name,
+				  r.bhops bhops_total,
+				  r.perfs bhops_perfs,
+				  r.perfect_perfs bhops_perfect_perfs,
+				  r.created_on
+				FROM
+				  Records r
+				  JOIN CourseFilters f ON f.id = r.filter_id
+				  JOIN Courses c ON c.id = f.course_id
+				  JOIN Maps m ON m.id = c.map_id
+				  JOIN Players p ON p.id = r.player_id
+				  JOIN Servers s ON s.id = r.server_id
+				WHERE
+				  f.id = ?
+				ORDER BY
+				  r.id DESC
+				LIMIT
+				  ?
+				",
+			)
+			.bind(req.filter_id)
+			.bind(MAX_EXPORTED_RECORDS)
+			.fetch(&database);
+
+			while let Some(record) = records.next().await {
+				let line = record.map_err(BoxError::from).and_then(|record| {
+					let mut line = serde_json::to_vec(&record)?;
+					line.push(b'\n');
+					Ok(line)
+				});
+
+				if let Err(error) = &line {
+					tracing::error!(%error, "failed to export record");
+				}
+
+				let failed = line.is_err();
+
+				// If the client went away, there's no point in fetching more rows.
+				if tx.send(line).await.is_err() || failed {
+					break;
+				}
+			}
+		});
+
+		ExportRecordsResponse { lines }
+	}
+
+	/// Fetch a player's rank on a filter's leaderboard.
+	///
+	/// Only each player's fastest time counts, and players with the same time
+	/// share a rank (i.e. this is a "dense" rank). If the player has no
+	/// records on the filter, `None` is returned.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn fetch_player_rank(
+		&self,
+		req: FetchPlayerRankRequest,
+	) -> Result<Option<FetchPlayerRankResponse>>
+	{
+		let mut txn = self.database.begin().await?;
+
+		let Some(player_id) = req.player.resolve_id(txn.as_mut()).await? else {
+			return Ok(None);
+		};
+
+		let rank = sqlx::query_scalar! {
+			r"
+			WITH
+			  PersonalBests AS (
+			    SELECT
+			      player_id,
+			      MIN(time) time
+			    FROM
+			      Records
+			    WHERE
+			      filter_id = ?
+			    GROUP BY
+			      player_id
+			  ),
+			  Ranks AS (
+			    SELECT
+			      player_id,
+			      CAST(DENSE_RANK() OVER (ORDER BY time ASC) AS UNSIGNED) player_rank
+			    FROM
+			      PersonalBests
+			  )
+			SELECT
+			  player_rank `player_rank: u64`
+			FROM
+			  Ranks
+			WHERE
+			  player_id = ?
+			",
+			req.filter_id,
+			player_id,
+		}
+		.fetch_optional(txn.as_mut())
+		.await?;
+
+		txn.commit().await?;
+
+		Ok(rank.map(|rank| FetchPlayerRankResponse { rank }))
 	}
 
 	/// Fetch the replay for a record.
@@ -214,56 +383,223 @@ impl RecordService
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn submit_record(&self, req: SubmitRecordRequest) -> Result<SubmitRecordResponse>
 	{
-		let record_id = sqlx::query! {
+		let insert = || async {
+			sqlx::query! {
+				r"
+				INSERT INTO
+				  Records (
+				    filter_id,
+				    styles,
+				    teleports,
+				    time,
+				    player_id,
+				    server_id,
+				    bhops,
+				    perfs,
+				    perfect_perfs,
+				    plugin_version_id
+				  )
+				VALUES
+				  (
+				    (
+				      SELECT
+					id
+				      FROM
+					CourseFilters
+				      WHERE
+					course_id = ?
+					AND mode = ?
+					AND teleports = ?
+				      LIMIT
+					1
+				    ), ?, ?, ?, ?, ?, ?, ?, ?, ?
+				  )
+				RETURNING id, filter_id
+				",
+				req.course_id,
+				req.mode,
+				req.teleports > 0,
+				req.styles,
+				req.teleports,
+				req.time,
+				req.player_id,
+				req.server_id,
+				req.bhop_stats.total,
+				req.bhop_stats.perfs,
+				req.bhop_stats.perfect_perfs,
+				req.plugin_version_id,
+			}
+			.fetch_one(&self.database)
+			.await
+			.and_then(|row| Ok((row.try_get::<RecordID, _>(0)?, row.try_get::<FilterID, _>(1)?)))
+		};
+
+		let (record_id, filter_id) = database::with_retry(insert).await?;
+
+		self.events
+			.dispatch(Event::NewRecord { record_id, filter_id, player_id: req.player_id });
+
+		Ok(SubmitRecordResponse { record_id })
+	}
+
+	/// Submit many records at once.
+	///
+	/// The batch is submitted atomically: if any record is invalid, none of
+	/// them are inserted. An [`Event::NewRecord`] is dispatched for every
+	/// record once the whole batch has been committed.
+	#[tracing::instrument(level = "debug", skip(req), fields(records = req.records.len()), err(Debug, level = "debug"))]
+	pub async fn submit_records(&self, req: SubmitRecordsRequest) -> Result<SubmitRecordsResponse>
+	{
+		if req.records.is_empty() {
+			return Ok(SubmitRecordsResponse { record_ids: Vec::new() });
+		}
+
+		let mut txn = self.database.begin().await?;
+		let filters = resolve_filters(&req.records, &mut txn).await?;
+		let mut record_ids = Vec::with_capacity(req.records.len());
+		let mut new_records = Vec::with_capacity(req.records.len());
+
+		for (index, record) in req.records.iter().enumerate() {
+			let filter_id = filters
+				.get(&(record.course_id, record.mode, record.teleports > 0))
+				.copied()
+				.ok_or(Error::FilterDoesNotExist { index })?;
+
+			let record_id = sqlx::query! {
+				r"
+				INSERT INTO
+				  Records (
+				    filter_id,
+				    styles,
+				    teleports,
+				    time,
+				    player_id,
+				    server_id,
+				    bhops,
+				    perfs,
+				    perfect_perfs,
+				    plugin_version_id
+				  )
+				VALUES
+				  (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+				RETURNING id
+				",
+				filter_id,
+				record.styles,
+				record.teleports,
+				record.time,
+				record.player_id,
+				record.server_id,
+				record.bhop_stats.total,
+				record.bhop_stats.perfs,
+				record.bhop_stats.perfect_perfs,
+				record.plugin_version_id,
+			}
+			.fetch_one(txn.as_mut())
+			.await
+			.and_then(|row| row.try_get::<RecordID, _>(0))?;
+
+			record_ids.push(record_id);
+			new_records.push(Event::NewRecord {
+				record_id,
+				filter_id,
+				player_id: record.player_id,
+			});
+		}
+
+		txn.commit().await?;
+
+		tracing::debug!(?record_ids, "submitted records");
+
+		for event in new_records {
+			self.events.dispatch(event);
+		}
+
+		Ok(SubmitRecordsResponse { record_ids })
+	}
+
+	/// Delete all of a player's records, optionally only on a single filter.
+	///
+	/// Like any other deleted record, they are moved into the `WipedRecords`
+	/// table. A [`Event::RecordsDeleted`] is dispatched for the affected
+	/// filters, so their leaderboards can be recalculated.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn delete_player_records(
+		&self,
+		req: DeletePlayerRecordsRequest,
+	) -> Result<DeletePlayerRecordsResponse>
+	{
+		let mut txn = self.database.begin().await?;
+
+		let affected_filters = sqlx::query_scalar! {
+			r"
+			SELECT
+			  filter_id `filter_id: FilterID`
+			FROM
+			  Records
+			WHERE
+			  player_id = ?
+			  AND filter_id = COALESCE(?, filter_id)
+			FOR UPDATE
+			",
+			req.player_id,
+			req.filter_id,
+		}
+		.fetch_all(txn.as_mut())
+		.await?
+		.into_iter()
+		.collect::<BTreeSet<_>>()
+		.into_iter()
+		.collect_vec();
+
+		if affected_filters.is_empty() {
+			return Ok(DeletePlayerRecordsResponse { deleted: 0, affected_filters });
+		}
+
+		sqlx::query! {
 			r"
 			INSERT INTO
-			  Records (
-			    filter_id,
-			    styles,
-			    teleports,
-			    time,
-			    player_id,
-			    server_id,
-			    bhops,
-			    perfs,
-			    perfect_perfs,
-			    plugin_version_id
-			  )
-			VALUES
-			  (
-			    (
-			      SELECT
-				id
-			      FROM
-				CourseFilters
-			      WHERE
-				course_id = ?
-				AND mode = ?
-				AND teleports = ?
-			      LIMIT
-				1
-			    ), ?, ?, ?, ?, ?, ?, ?, ?, ?
-			  )
-			RETURNING id
+			  WipedRecords
+			SELECT
+			  *
+			FROM
+			  Records
+			WHERE
+			  player_id = ?
+			  AND filter_id = COALESCE(?, filter_id)
+			",
+			req.player_id,
+			req.filter_id,
+		}
+		.execute(txn.as_mut())
+		.await?;
+
+		let deleted = sqlx::query! {
+			r"
+			DELETE FROM
+			  Records
+			WHERE
+			  player_id = ?
+			  AND filter_id = COALESCE(?, filter_id)
 			",
-			req.course_id,
-			req.mode,
-			req.teleports > 0,
-			req.styles,
-			req.teleports,
-			req.time,
 			req.player_id,
-			req.server_id,
-			req.bhop_stats.total,
-			req.bhop_stats.perfs,
-			req.bhop_stats.perfect_perfs,
-			req.plugin_version_id,
+			req.filter_id,
 		}
-		.fetch_one(&self.database)
-		.await
-		.and_then(|row| row.try_get(0))?;
+		.execute(txn.as_mut())
+		.await?
+		.rows_affected();
 
-		Ok(SubmitRecordResponse { record_id })
+		txn.commit().await?;
+
+		tracing::info!(player_id = %req.player_id, deleted, ?affected_filters, "deleted records");
+
+		self.events.dispatch(Event::RecordsDeleted {
+			player_id: req.player_id,
+			admin_id: req.admin_id,
+			filter_ids: affected_filters.clone(),
+		});
+
+		Ok(DeletePlayerRecordsResponse { deleted, affected_filters })
 	}
 
 	/// Update an existing record.
@@ -366,3 +702,599 @@ async fn move_record(
 
 	Ok(())
 }
+
+/// Looks up the filters for all the given records in a single query.
+///
+/// The returned map is keyed by `(course, mode, has_teleports)`.
+#[tracing::instrument(level = "trace", skip(records), err(Debug, level = "debug"))]
+async fn resolve_filters(
+	records: &[SubmitRecordRequest],
+	txn: &mut Transaction<'_, MySql>,
+) -> Result<HashMap<(CourseID, Mode, bool), FilterID>>
+{
+	let course_ids = records
+		.iter()
+		.map(|record| record.course_id)
+		.collect::<HashSet<_>>();
+
+	let filters = QueryBuilder::new(
+		"SELECT course_id, mode, teleports, id FROM CourseFilters WHERE course_id IN (",
+	)
+	.tap_mut(|query| {
+		let mut course_ids_query = query.separated(", ");
+
+		for course_id in course_ids {
+			course_ids_query.push_bind(course_id);
+		}
+
+		query.push(")");
+	})
+	.build_query_as::<(CourseID, Mode, bool, FilterID)>()
+	.fetch_all(txn.as_mut())
+	.await?
+	.into_iter()
+	.map(|(course_id, mode, teleports, filter_id)| ((course_id, mode, teleports), filter_id))
+	.collect();
+
+	Ok(filters)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::collections::HashSet;
+	use std::time::Duration;
+
+	use axum::response::IntoResponse;
+	use cs2kz::{SteamID, Styles};
+	use sqlx::{MySql, Pool};
+	use time::format_description::well_known::Rfc3339;
+	use time::OffsetDateTime;
+
+	use super::*;
+	use crate::stats::BhopStats;
+	use crate::testing;
+	use crate::util::PlayerIdentifier;
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/records.sql",
+		)
+	)]
+	async fn fetch_records_with_cursor(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::record_svc(database);
+		let all = svc
+			.fetch_records(FetchRecordsRequest::default())
+			.await?
+			.records
+			.into_iter()
+			.map(|record| record.id)
+			.collect::<Vec<_>>();
+
+		testing::assert_eq!(all.len(), 8);
+		testing::assert!(all.windows(2).all(|ids| ids[0] > ids[1]));
+
+		let mut seen = Vec::new();
+		let mut after = None;
+
+		loop {
+			let req = FetchRecordsRequest { limit: 3.into(), after, ..Default::default() };
+			let res = svc.fetch_records(req).await?;
+
+			testing::assert!(res.records.len() <= 3);
+
+			seen.extend(res.records.iter().map(|record| record.id));
+
+			match res.next_cursor {
+				None => break,
+				Some(cursor) => after = Some(cursor),
+			}
+		}
+
+		let unique = seen.iter().collect::<HashSet<_>>();
+
+		testing::assert_eq!(unique.len(), seen.len());
+		testing::assert_eq!(seen, all);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/records.sql",
+		)
+	)]
+	async fn export_records_streams_all_records(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let filter_id = sqlx::query_scalar! {
+			r"
+			SELECT
+			  f.id `id: FilterID`
+			FROM
+			  CourseFilters f
+			  JOIN Courses c ON c.id = f.course_id
+			  JOIN Maps m ON m.id = c.map_id
+			WHERE
+			  m.name = 'kz_checkmate'
+			  AND f.mode = 1
+			  AND f.teleports = 0
+			",
+		}
+		.fetch_one(&database)
+		.await?;
+
+		let svc = testing::record_svc(database);
+		let res = svc
+			.export_records(ExportRecordsRequest { filter_id })
+			.into_response();
+		let content_type = res.headers().get(::http::header::CONTENT_TYPE);
+
+		testing::assert_eq!(
+			content_type.map(|value| value.as_bytes()),
+			Some(&b"application/x-ndjson"[..])
+		);
+
+		let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+		let records = body
+			.split(|&byte| byte == b'\n')
+			.filter(|line| !line.is_empty())
+			.map(serde_json::from_slice::<serde_json::Value>)
+			.collect::<Result<Vec<_>, _>>()?;
+
+		testing::assert_eq!(records.len(), 3);
+		testing::assert!(body.ends_with(b"\n"));
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/records.sql",
+		)
+	)]
+	async fn fetch_records_by_time(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::record_svc(database);
+		let req = FetchRecordsRequest {
+			min_time: Some(Duration::from_secs(60).into()),
+			max_time: Some(Duration::from_secs(70).into()),
+			..Default::default()
+		};
+
+		let res = svc.fetch_records(req).await?;
+		let mut times = res
+			.records
+			.iter()
+			.map(|record| record.time.as_secs_f64())
+			.collect::<Vec<_>>();
+
+		times.sort_by(f64::total_cmp);
+
+		testing::assert_eq!(times, [60.2, 62.5, 64.9, 69.3]);
+		testing::assert_eq!(res.total, 4);
+
+		let req = FetchRecordsRequest {
+			min_time: Some(Duration::from_secs(72).into()),
+			..Default::default()
+		};
+
+		let res = svc.fetch_records(req).await?;
+
+		testing::assert_eq!(res.records.len(), 1);
+		testing::assert_eq!(res.records[0].time.as_secs_f64(), 75.8);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/records.sql",
+		)
+	)]
+	async fn fetch_records_by_date(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::record_svc(database);
+
+		// "records set in March"
+		let req = FetchRecordsRequest {
+			created_after: Some(OffsetDateTime::parse("2024-03-01T00:00:00Z", &Rfc3339)?.into()),
+			created_before: Some(OffsetDateTime::parse("2024-04-01T00:00:00Z", &Rfc3339)?.into()),
+			..Default::default()
+		};
+
+		let res = svc.fetch_records(req).await?;
+
+		testing::assert_eq!(res.records.len(), 2);
+		testing::assert!(res
+			.records
+			.iter()
+			.all(|record| record.created_on.month() == time::Month::March));
+
+		let req = FetchRecordsRequest {
+			created_before: Some(OffsetDateTime::parse("2024-02-01T00:00:00Z", &Rfc3339)?.into()),
+			max_time: Some(Duration::from_secs(60).into()),
+			..Default::default()
+		};
+
+		let res = svc.fetch_records(req).await?;
+
+		testing::assert_eq!(res.records.len(), 1);
+		testing::assert_eq!(res.records[0].time.as_secs_f64(), 58.1);
+
+		Ok(())
+	}
+
+	/// Builds a record for [`RecordService::submit_records()`].
+	fn new_record(course_id: CourseID, mode: Mode, seconds: f64) -> SubmitRecordRequest
+	{
+		SubmitRecordRequest {
+			course_id,
+			mode,
+			styles: Styles::NONE,
+			teleports: 0,
+			time: Duration::from_secs_f64(seconds).into(),
+			player_id: testing::ALPHAKEKS_ID,
+			server_id: 1.into(),
+			bhop_stats: BhopStats { total: 20, perfs: 10, perfect_perfs: 5 },
+			plugin_version_id: 1.into(),
+		}
+	}
+
+	/// Returns the ID of a kz_checkmate course.
+	async fn checkmate_course(database: &Pool<MySql>) -> color_eyre::Result<CourseID>
+	{
+		let course_id = sqlx::query_scalar(
+			r"
+			SELECT
+			  c.id
+			FROM
+			  Courses c
+			  JOIN Maps m ON m.id = c.map_id
+			WHERE
+			  m.name = 'kz_checkmate'
+			LIMIT
+			  1
+			",
+		)
+		.fetch_one(database)
+		.await?;
+
+		Ok(course_id)
+	}
+
+	/// Counts all records in the database.
+	async fn count_records(database: &Pool<MySql>) -> color_eyre::Result<i64>
+	{
+		Ok(sqlx::query_scalar("SELECT COUNT(*) FROM Records")
+			.fetch_one(database)
+			.await?)
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/checkmate.sql")
+	)]
+	async fn submit_records_in_order(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::record_svc(database.clone());
+		let course_id = checkmate_course(&database).await?;
+		let req = SubmitRecordsRequest {
+			records: vec![
+				new_record(course_id, Mode::Vanilla, 61.0),
+				new_record(course_id, Mode::Classic, 55.5),
+				new_record(course_id, Mode::Vanilla, 59.0),
+			],
+		};
+
+		let res = svc.submit_records(req).await?;
+
+		testing::assert_eq!(res.record_ids.len(), 3);
+
+		for (record_id, expected) in res.record_ids.into_iter().zip([61.0, 55.5, 59.0]) {
+			let record = svc
+				.fetch_record(FetchRecordRequest { record_id })
+				.await?
+				.expect("record should exist");
+
+			testing::assert_eq!(record.time.as_secs_f64(), expected);
+		}
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/checkmate.sql")
+	)]
+	async fn submitted_records_dispatch_events(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::record_svc(database.clone());
+		let mut events = svc.events.subscribe();
+		let course_id = checkmate_course(&database).await?;
+		let filter_id = |mode: Mode, teleports: bool| {
+			sqlx::query_scalar::<_, FilterID>(
+				"SELECT id FROM CourseFilters WHERE course_id = ? AND mode = ? AND teleports = ?",
+			)
+			.bind(course_id)
+			.bind(mode)
+			.bind(teleports)
+			.fetch_one(&database)
+		};
+
+		let vanilla_pro = filter_id(Mode::Vanilla, false).await?;
+		let classic_tp = filter_id(Mode::Classic, true).await?;
+
+		let req = new_record(course_id, Mode::Vanilla, 61.0);
+		let record_id = svc.submit_record(req).await?.record_id;
+		let event = events.try_recv()?;
+
+		testing::assert_eq!(
+			event,
+			Some(Event::NewRecord {
+				record_id,
+				filter_id: vanilla_pro,
+				player_id: testing::ALPHAKEKS_ID
+			})
+		);
+
+		let req = SubmitRecordsRequest {
+			records: vec![
+				SubmitRecordRequest { teleports: 2, ..new_record(course_id, Mode::Classic, 70.0) },
+				new_record(course_id, Mode::Vanilla, 59.5),
+			],
+		};
+		let record_ids = svc.submit_records(req).await?.record_ids;
+		let events = [events.try_recv()?, events.try_recv()?];
+
+		testing::assert_eq!(events, [
+			Some(Event::NewRecord {
+				record_id: record_ids[0],
+				filter_id: classic_tp,
+				player_id: testing::ALPHAKEKS_ID,
+			}),
+			Some(Event::NewRecord {
+				record_id: record_ids[1],
+				filter_id: vanilla_pro,
+				player_id: testing::ALPHAKEKS_ID,
+			}),
+		]);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures("../../../database/fixtures/checkmate.sql")
+	)]
+	async fn submit_records_is_atomic(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::record_svc(database.clone());
+		let course_id = checkmate_course(&database).await?;
+		let req = SubmitRecordsRequest {
+			records: vec![
+				new_record(course_id, Mode::Vanilla, 61.0),
+				new_record(CourseID(u16::MAX), Mode::Vanilla, 55.5),
+				new_record(course_id, Mode::Classic, 59.0),
+			],
+		};
+
+		let err = svc.submit_records(req).await.unwrap_err();
+
+		testing::assert_matches!(err, Error::FilterDoesNotExist { index: 1 });
+
+		let count = count_records(&database).await?;
+
+		testing::assert_eq!(count, 0);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/records.sql",
+		)
+	)]
+	async fn fetch_records_excluding_styles(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::record_svc(database.clone());
+		let course_id = checkmate_course(&database).await?;
+		let styled = SubmitRecordRequest {
+			styles: Styles::AUTO_BHOP,
+			..new_record(course_id, Mode::Vanilla, 57.3)
+		};
+
+		svc.submit_records(SubmitRecordsRequest { records: vec![styled] })
+			.await?;
+
+		let all = svc.fetch_records(FetchRecordsRequest::default()).await?;
+
+		testing::assert_eq!(all.records.len(), 9);
+
+		let req = FetchRecordsRequest { exclude_styles: Some(Styles::ALL), ..Default::default() };
+		let without_styles = svc.fetch_records(req).await?;
+
+		testing::assert_eq!(without_styles.records.len(), 8);
+		testing::assert!(without_styles
+			.records
+			.iter()
+			.all(|record| record.styles.is_empty()));
+
+		let req = FetchRecordsRequest { styles: Some(Styles::AUTO_BHOP), ..Default::default() };
+		let with_styles = svc.fetch_records(req).await?;
+
+		testing::assert_eq!(with_styles.records.len(), 1);
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/players.sql",
+		)
+	)]
+	async fn fetch_player_rank_handles_ties(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::record_svc(database.clone());
+		let course_id = checkmate_course(&database).await?;
+		let record = |player_id: u64, seconds| SubmitRecordRequest {
+			player_id: SteamID::new(player_id).unwrap(),
+			..new_record(course_id, Mode::Vanilla, seconds)
+		};
+
+		let res = svc
+			.submit_records(SubmitRecordsRequest {
+				records: vec![
+					record(76561198282622073, 60.0),
+					record(76561198282622073, 58.0),
+					record(76561198165203332, 58.0),
+					record(76561198118681904, 61.0),
+					SubmitRecordRequest { teleports: 5, ..record(76561198264939817, 30.0) },
+				],
+			})
+			.await?;
+
+		let filter_id =
+			sqlx::query_scalar::<_, FilterID>("SELECT filter_id FROM Records WHERE id = ?")
+				.bind(res.record_ids[0])
+				.fetch_one(&database)
+				.await?;
+
+		let rank = |player_id: u64| {
+			svc.fetch_player_rank(FetchPlayerRankRequest {
+				filter_id,
+				player: PlayerIdentifier::ID(SteamID::new(player_id).unwrap()),
+			})
+		};
+
+		let alphakeks = rank(76561198282622073).await?.map(|res| res.rank);
+		let gamechaos = rank(76561198165203332).await?.map(|res| res.rank);
+		let zer0k = rank(76561198118681904).await?.map(|res| res.rank);
+		let ibrahizy = rank(76561198264939817).await?.map(|res| res.rank);
+
+		testing::assert_eq!(alphakeks, Some(1));
+		testing::assert_eq!(gamechaos, Some(1));
+		testing::assert_eq!(zer0k, Some(2));
+		testing::assert!(ibrahizy.is_none());
+
+		Ok(())
+	}
+
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/checkmate.sql",
+			"../../../database/fixtures/records.sql",
+		)
+	)]
+	async fn delete_player_records_reports_affected_filters(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::record_svc(database.clone());
+		let mut events = svc.events.subscribe();
+		let all_filters = sqlx::query_scalar! {
+			r"
+			SELECT DISTINCT
+			  filter_id `filter_id: FilterID`
+			FROM
+			  Records
+			ORDER BY
+			  filter_id
+			",
+		}
+		.fetch_all(&database)
+		.await?;
+
+		let vanilla_pro = sqlx::query_scalar! {
+			r"
+			SELECT
+			  f.id `id: FilterID`
+			FROM
+			  CourseFilters f
+			  JOIN Courses c ON c.id = f.course_id
+			  JOIN Maps m ON m.id = c.map_id
+			WHERE
+			  m.name = 'kz_checkmate'
+			  AND f.mode = 1
+			  AND f.teleports = 0
+			",
+		}
+		.fetch_one(&database)
+		.await?;
+
+		let req = DeletePlayerRecordsRequest {
+			player_id: testing::ALPHAKEKS_ID,
+			filter_id: Some(vanilla_pro),
+			admin_id: None,
+		};
+		let res = svc.delete_player_records(req).await?;
+
+		testing::assert_eq!(res.deleted, 3);
+		testing::assert_eq!(res.affected_filters, [vanilla_pro]);
+
+		let event = events.try_recv()?;
+
+		testing::assert_eq!(
+			event,
+			Some(Event::RecordsDeleted {
+				player_id: testing::ALPHAKEKS_ID,
+				admin_id: None,
+				filter_ids: vec![vanilla_pro],
+			})
+		);
+
+		let req = DeletePlayerRecordsRequest {
+			player_id: testing::ALPHAKEKS_ID,
+			filter_id: None,
+			admin_id: None,
+		};
+		let res = svc.delete_player_records(req).await?;
+		let remaining_filters = all_filters
+			.iter()
+			.copied()
+			.filter(|&filter_id| filter_id != vanilla_pro)
+			.collect_vec();
+
+		testing::assert_eq!(res.deleted, 5);
+		testing::assert_eq!(res.affected_filters, remaining_filters);
+
+		let records = sqlx::query_scalar!("SELECT COUNT(*) FROM Records")
+			.fetch_one(&database)
+			.await?;
+
+		let wiped_records = sqlx::query_scalar!("SELECT COUNT(*) FROM WipedRecords")
+			.fetch_one(&database)
+			.await?;
+
+		testing::assert_eq!((records, wiped_records), (0, 8));
+
+		let event = events.try_recv()?;
+
+		testing::assert!(event.is_some());
+
+		let req = DeletePlayerRecordsRequest {
+			player_id: testing::ALPHAKEKS_ID,
+			filter_id: None,
+			admin_id: None,
+		};
+		let res = svc.delete_player_records(req).await?;
+
+		testing::assert_eq!(res.deleted, 0);
+		testing::assert!(events.is_empty());
+
+		Ok(())
+	}
+}
@@ -1,18 +1,28 @@
 //! Request / Response types for this service.
 
+use std::collections::BTreeMap;
+
+use axum::body::Body;
 use axum::response::{AppendHeaders, IntoResponse, Response};
+use axum::BoxError;
 use cs2kz::{Mode, RankedStatus, SteamID, Styles, Tier};
+use http::HeaderValue;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use utoipa::openapi::{self, ContentBuilder, Ref, RefOr, ResponseBuilder};
 
 use crate::num::ClampedU64;
-use crate::services::maps::{CourseID, MapID};
+use crate::services::maps::{CourseID, FilterID, MapID};
 use crate::services::players::PlayerInfo;
 use crate::services::plugin::PluginVersionID;
 use crate::services::servers::{ServerID, ServerInfo};
 use crate::stats::BhopStats;
-use crate::time::Seconds;
-use crate::util::{CourseIdentifier, MapIdentifier, PlayerIdentifier, ServerIdentifier};
+use crate::time::{Seconds, Timestamp};
+use crate::util::{CourseIdentifier, Cursor, MapIdentifier, PlayerIdentifier, ServerIdentifier};
+
+/// The `Content-Type` of newline-delimited JSON.
+const NDJSON: &str = "application/x-ndjson";
 
 crate::macros::make_id! {
 	/// An ID uniquely identifying a record.
@@ -106,7 +116,7 @@ pub struct CourseInfo
 }
 
 /// Request payload for fetching records.
-#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
 pub struct FetchRecordsRequest
 {
 	/// Filter by mode.
@@ -118,6 +128,11 @@ pub struct FetchRecordsRequest
 	/// **include** these styles, but may also have more.
 	pub styles: Option<Styles>,
 
+	/// Exclude records that were performed with any of these styles.
+	///
+	/// Pass all styles to only get records performed without any styles.
+	pub exclude_styles: Option<Styles>,
+
 	/// Filter by whether records have teleports or not.
 	pub has_teleports: Option<bool>,
 
@@ -149,13 +164,19 @@ pub struct FetchRecordsRequest
 	#[serde(default)]
 	pub sort_by: SortRecordsBy,
 
+	/// Only include records with a time of at least this many seconds.
+	#[param(value_type = Option<f64>)]
+	pub min_time: Option<Seconds>,
+
+	/// Only include records with a time of at most this many seconds.
+	#[param(value_type = Option<f64>)]
+	pub max_time: Option<Seconds>,
+
 	/// Only include records submitted after this date.
-	#[serde(default, with = "time::serde::rfc3339::option")]
-	pub created_after: Option<OffsetDateTime>,
+	pub created_after: Option<Timestamp>,
 
 	/// Only include records submitted before this date.
-	#[serde(default, with = "time::serde::rfc3339::option")]
-	pub created_before: Option<OffsetDateTime>,
+	pub created_before: Option<Timestamp>,
 
 	/// The maximum amount of records to return.
 	#[serde(default)]
@@ -166,6 +187,13 @@ pub struct FetchRecordsRequest
 	#[serde(default)]
 	#[param(value_type = u64)]
 	pub offset: ClampedU64,
+
+	/// Only include records older than the one this cursor points at.
+	///
+	/// This is the `next_cursor` returned by a previous request, and is a more
+	/// efficient alternative to `offset`.
+	#[param(value_type = Option<String>)]
+	pub after: Option<Cursor<RecordID>>,
 }
 
 /// How to sort results.
@@ -210,10 +238,20 @@ pub enum SortRecordsBy
 pub struct FetchRecordsResponse
 {
 	/// The records.
+	///
+	/// These are always ordered by ID, newest first, whether or not a cursor
+	/// was passed.
 	pub records: Vec<FetchRecordResponse>,
 
 	/// How many records **could have been** fetched, if there was no limit.
 	pub total: u64,
+
+	/// A cursor pointing at the last record in this response.
+	///
+	/// Pass this as `after` to fetch the next page. If this is absent, there
+	/// are no more records.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub next_cursor: Option<Cursor<RecordID>>,
 }
 
 impl IntoResponse for FetchRecordsResponse
@@ -224,6 +262,89 @@ impl IntoResponse for FetchRecordsResponse
 	}
 }
 
+/// Request payload for fetching a player's rank on a leaderboard.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct FetchPlayerRankRequest
+{
+	/// The filter whose leaderboard to look at.
+	pub filter_id: FilterID,
+
+	/// The player whose rank to fetch.
+	pub player: PlayerIdentifier,
+}
+
+/// Response payload for fetching a player's rank on a leaderboard.
+#[derive(Debug, Serialize, utoipa::ToSchema, utoipa::IntoResponses)]
+#[response(status = OK)]
+pub struct FetchPlayerRankResponse
+{
+	/// The player's 1-based rank.
+	///
+	/// Players with the same time share a rank, and the next slower time is
+	/// ranked right after them.
+	pub rank: u64,
+}
+
+impl IntoResponse for FetchPlayerRankResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
+
+/// Request payload for exporting records.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ExportRecordsRequest
+{
+	/// The ID of the filter to export records for.
+	pub filter_id: FilterID,
+}
+
+/// Response payload for exporting records.
+///
+/// This is a stream of newline-delimited JSON objects, one for each record.
+/// The stream is fed by a background task while the response is being sent,
+/// so the full result set is never buffered in memory.
+#[derive(Debug)]
+pub struct ExportRecordsResponse
+{
+	/// The receiving end of the channel the records are sent through.
+	pub(super) lines: mpsc::Receiver<Result<Vec<u8>, BoxError>>,
+}
+
+impl IntoResponse for ExportRecordsResponse
+{
+	fn into_response(self) -> Response
+	{
+		let mut lines = self.lines;
+		let stream = futures::stream::poll_fn(move |cx| lines.poll_recv(cx));
+
+		(
+			[(http::header::CONTENT_TYPE, HeaderValue::from_static(NDJSON))],
+			Body::from_stream(stream),
+		)
+			.into_response()
+	}
+}
+
+impl utoipa::IntoResponses for ExportRecordsResponse
+{
+	fn responses() -> BTreeMap<String, RefOr<openapi::response::Response>>
+	{
+		let content = ContentBuilder::new()
+			.schema(Ref::from_schema_name("FetchRecordResponse"))
+			.build();
+
+		let response = ResponseBuilder::new()
+			.description("A stream of records, one JSON object per line.")
+			.content(NDJSON, content)
+			.build();
+
+		BTreeMap::from_iter([(String::from("200"), response.into())])
+	}
+}
+
 /// Request payload for fetching a record's replay.
 #[derive(Debug)]
 pub struct FetchReplayRequest
@@ -307,6 +428,68 @@ impl IntoResponse for SubmitRecordResponse
 	}
 }
 
+/// Request payload for submitting many records at once.
+#[derive(Debug)]
+pub struct SubmitRecordsRequest
+{
+	/// The records to submit.
+	pub records: Vec<SubmitRecordRequest>,
+}
+
+/// Response payload for submitting many records at once.
+#[derive(Debug, Serialize, utoipa::IntoResponses)]
+#[response(status = CREATED)]
+pub struct SubmitRecordsResponse
+{
+	/// The generated record IDs, in the same order as the submitted records.
+	pub record_ids: Vec<RecordID>,
+}
+
+impl IntoResponse for SubmitRecordsResponse
+{
+	fn into_response(self) -> Response
+	{
+		(http::StatusCode::CREATED, crate::http::extract::Json(self)).into_response()
+	}
+}
+
+/// Request payload for deleting a player's records.
+#[derive(Debug)]
+pub struct DeletePlayerRecordsRequest
+{
+	/// The player whose records should be deleted.
+	pub player_id: SteamID,
+
+	/// Only delete records on this filter.
+	pub filter_id: Option<FilterID>,
+
+	/// The admin deleting the records.
+	pub admin_id: Option<SteamID>,
+}
+
+/// Response payload for deleting a player's records.
+#[derive(Debug, Serialize, utoipa::IntoResponses)]
+#[cfg_attr(test, derive(Deserialize))]
+#[response(status = OK)]
+pub struct DeletePlayerRecordsResponse
+{
+	/// How many records were deleted.
+	pub deleted: u64,
+
+	/// The filters that had records deleted.
+	///
+	/// Points for these filters need to be recalculated.
+	pub affected_filters: Vec<FilterID>,
+}
+
+impl IntoResponse for DeletePlayerRecordsResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
+
 /// Request payload for updating a record.
 #[derive(Debug)]
 pub struct UpdateRecordRequest
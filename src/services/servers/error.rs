@@ -28,6 +28,16 @@ pub enum Error
 		steam_id: SteamID,
 	},
 
+	/// A request wanted to register / rename a server, but the name is already
+	/// used by another server.
+	#[error("server name is already taken")]
+	NameAlreadyTaken,
+
+	/// A request wanted to register / move a server, but another server is
+	/// already registered with the same host and port.
+	#[error("server address is already taken")]
+	AddressAlreadyTaken,
+
 	/// A request dedicated to a specific server was made, but the server could
 	/// not be found.
 	#[error("server does not exist")]
@@ -38,6 +48,15 @@ pub enum Error
 	#[error("invalid key or plugin version")]
 	InvalidKeyOrPluginVersion,
 
+	/// A request containing an API key was made, but the key has expired.
+	#[error("API key has expired")]
+	KeyExpired,
+
+	/// A request containing an API key and plugin version was made, but the
+	/// plugin version has been yanked.
+	#[error("plugin version has been yanked")]
+	PluginVersionYanked,
+
 	/// Something went wrong when interacting with the auth service.
 	#[error(transparent)]
 	Auth(#[from] auth::Error),
@@ -56,12 +75,24 @@ impl IntoProblemDetails for Error
 			Self::ServerOwnerDoesNotExist { .. } | Self::ServerDoesNotExist => {
 				ProblemType::ResourceNotFound
 			}
-			Self::InvalidKeyOrPluginVersion => ProblemType::Unauthorized,
+			Self::NameAlreadyTaken | Self::AddressAlreadyTaken => {
+				ProblemType::ResourceAlreadyExists
+			}
+			Self::InvalidKeyOrPluginVersion | Self::KeyExpired => ProblemType::Unauthorized,
+			Self::PluginVersionYanked => ProblemType::PluginVersionYanked,
 			Self::Auth(source) => source.problem_type(),
 			Self::Database(source) => source.problem_type(),
 		}
 	}
 
+	fn detail(&self) -> String
+	{
+		match self {
+			Self::Auth(source) => source.detail(),
+			_ => self.to_string(),
+		}
+	}
+
 	fn add_extension_members(&self, ext: &mut problem_details::ExtensionMembers)
 	{
 		match self {
@@ -10,6 +10,8 @@ use super::{
 	DeleteKeyRequest,
 	DeleteKeyResponse,
 	Error,
+	FetchKeyRequest,
+	FetchKeyResponse,
 	FetchServerRequest,
 	FetchServerResponse,
 	FetchServersRequest,
@@ -63,12 +65,25 @@ impl From<ServerService> for Router
 			.with_state(svc.clone());
 
 		let protected = Router::new()
-			.route("/", routing::post(register_server).route_layer(admin_only.clone()))
+			.route(
+				"/",
+				routing::post(register_server)
+					.layer(
+						// The response contains the new server's API key.
+						middleware::IdempotencyLayer::new(svc.database.clone())
+							.without_response_bodies(
+								"use `PUT /servers/{id}/key` to generate a new API key",
+							),
+					)
+					.route_layer(admin_only.clone()),
+			)
 			.route("/:server", routing::patch(update_server).route_layer(owner_auth.clone()))
+			.route("/:server/key", routing::get(get_api_key).route_layer(owner_auth.clone()))
 			.route("/:server/key", routing::put(reset_api_key).route_layer(owner_auth.clone()))
 			.route("/:server/key", routing::delete(delete_api_key).route_layer(admin_only.clone()))
 			.route_layer(middleware::cors::dashboard([
 				http::Method::OPTIONS,
+				http::Method::GET,
 				http::Method::POST,
 				http::Method::PATCH,
 				http::Method::PUT,
@@ -103,7 +118,7 @@ async fn get_many(
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(post, path = "/servers", tag = "Servers", security(("Browser serssion" = ["servers"])))]
+#[utoipa::path(post, path = "/servers", tag = "Servers", operation_id = "register_server", security(("Browser Session" = ["servers"])))]
 async fn register_server(
 	session: Session,
 	State(svc): State<ServerService>,
@@ -116,7 +131,12 @@ async fn register_server(
 }
 
 #[tracing::instrument(err(Debug, level = "debug"))]
-#[utoipa::path(post, path = "/servers/auth", tag = "Servers")]
+#[utoipa::path(
+	post,
+	path = "/servers/auth",
+	tag = "Servers",
+	operation_id = "generate_server_access_token"
+)]
 async fn generate_access_token(
 	State(svc): State<ServerService>,
 	Json(req): Json<GenerateAccessTokenRequest>,
@@ -169,6 +189,7 @@ pub(crate) struct UpdateServerRequestPayload
   patch,
   path = "/servers/{server_id}",
   tag = "Servers",
+  operation_id = "update_server",
   params(("server_id" = ServerID, Path, description = "a server's ID")),
   security(("Browser Session" = ["servers"])),
 )]
@@ -181,17 +202,46 @@ async fn update_server(
 	>,
 ) -> Result<UpdateServerResponse, ProblemDetails>
 {
-	let req = UpdateServerRequest { server_id, new_name, new_host, new_port, new_owner };
+	let req = UpdateServerRequest {
+		server_id,
+		new_name,
+		new_host,
+		new_port,
+		new_owner,
+		actor_id: Some(session.user().steam_id()),
+	};
 	let res = svc.update_server(req).await?;
 
 	Ok(res)
 }
 
+#[tracing::instrument(err(Debug, level = "debug"))]
+#[utoipa::path(
+  get,
+  path = "/servers/{server_id}/key",
+  tag = "Servers",
+  operation_id = "get_server_api_key",
+  params(("server_id" = ServerID, Path, description = "a server's ID")),
+  security(("Browser Session" = ["servers"])),
+)]
+async fn get_api_key(
+	session: Session,
+	State(svc): State<ServerService>,
+	Path(server_id): Path<ServerID>,
+) -> Result<FetchKeyResponse, ProblemDetails>
+{
+	let req = FetchKeyRequest { server_id };
+	let res = svc.fetch_key(req).await?.ok_or(Error::NoData)?;
+
+	Ok(res)
+}
+
 #[tracing::instrument(err(Debug, level = "debug"))]
 #[utoipa::path(
   put,
   path = "/servers/{server_id}/key",
   tag = "Servers",
+  operation_id = "reset_server_api_key",
   params(("server_id" = ServerID, Path, description = "a server's ID")),
   security(("Browser Session" = ["servers"])),
 )]
@@ -212,6 +262,7 @@ async fn reset_api_key(
   delete,
   path = "/servers/{server_id}/key",
   tag = "Servers",
+  operation_id = "delete_server_api_key",
   params(("server_id" = ServerID, Path, description = "a server's ID")),
   security(("Browser Session" = ["servers"])),
 )]
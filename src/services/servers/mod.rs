@@ -1,13 +1,20 @@
 //! A service for managing KZ servers.
 
 use std::fmt;
+use std::sync::Arc;
 use std::time::Duration;
 
+use arc_swap::ArcSwap;
 use axum::extract::FromRef;
+use cs2kz::SteamID;
 use sqlx::{MySql, Pool, Row};
 use tap::Pipe;
+use time::OffsetDateTime;
+use tokio::task::JoinHandle;
 
 use crate::database::{SqlErrorExt, TransactionExt};
+use crate::events::{Event, EventBus};
+use crate::services::audit::{self, AuditAction};
 use crate::services::auth::{jwt, Jwt};
 use crate::services::plugin::PluginVersionID;
 use crate::services::AuthService;
@@ -24,6 +31,8 @@ pub use models::{
 	ApiKey,
 	DeleteKeyRequest,
 	DeleteKeyResponse,
+	FetchKeyRequest,
+	FetchKeyResponse,
 	FetchServerRequest,
 	FetchServerResponse,
 	FetchServersRequest,
@@ -49,6 +58,10 @@ pub struct ServerService
 {
 	database: Pool<MySql>,
 	auth_svc: AuthService,
+	events: EventBus,
+	heartbeat_window: Arc<ArcSwap<Duration>>,
+	key_lifetime: Option<Duration>,
+	key_grace_period: Duration,
 }
 
 impl fmt::Debug for ServerService
@@ -61,11 +74,76 @@ impl fmt::Debug for ServerService
 
 impl ServerService
 {
+	/// The default for [`ServerService::heartbeat_window()`].
+	pub const DEFAULT_HEARTBEAT_WINDOW: Duration = Duration::from_secs(60 * 5);
+
+	/// How often [`ServerService::spawn_key_rotation()`] checks for expiring
+	/// keys.
+	const KEY_ROTATION_INTERVAL: Duration = Duration::HOUR;
+
+	/// The default for [`ServerService::key_grace_period()`].
+	pub const DEFAULT_KEY_GRACE_PERIOD: Duration = Duration::DAY;
+
 	/// Create a new [`ServerService`].
 	#[tracing::instrument]
-	pub fn new(database: Pool<MySql>, auth_svc: AuthService) -> Self
+	pub fn new(database: Pool<MySql>, auth_svc: AuthService, events: EventBus) -> Self
+	{
+		Self {
+			database,
+			auth_svc,
+			events,
+			heartbeat_window: Arc::new(ArcSwap::from_pointee(Self::DEFAULT_HEARTBEAT_WINDOW)),
+			key_lifetime: None,
+			key_grace_period: Self::DEFAULT_KEY_GRACE_PERIOD,
+		}
+	}
+
+	/// Sets how recently a server must have been seen to be considered online.
+	pub fn heartbeat_window(self, window: Duration) -> Self
+	{
+		self.set_heartbeat_window(window);
+		self
+	}
+
+	/// Changes the heartbeat window of this service and all of its clones.
+	///
+	/// See [`ServerService::heartbeat_window()`].
+	pub fn set_heartbeat_window(&self, window: Duration)
+	{
+		self.heartbeat_window.store(Arc::new(window));
+	}
+
+	/// Sets how long newly generated API keys stay valid.
+	///
+	/// If this is `None`, keys never expire.
+	pub fn key_lifetime(mut self, lifetime: Option<Duration>) -> Self
+	{
+		self.key_lifetime = lifetime;
+		self
+	}
+
+	/// Sets how long a rotated key keeps working after it has been replaced.
+	///
+	/// See [`ServerService::rotate_expiring_keys()`].
+	pub fn key_grace_period(mut self, grace_period: Duration) -> Self
+	{
+		self.key_grace_period = grace_period;
+		self
+	}
+
+	/// Returns the expiration date for a key generated right now.
+	fn new_key_expiration(&self) -> Option<OffsetDateTime>
 	{
-		Self { database, auth_svc }
+		self.key_lifetime
+			.map(|lifetime| OffsetDateTime::now_utc() + lifetime)
+	}
+
+	/// Fills in [`FetchServerResponse::is_online`].
+	fn mark_online(&self, server: &mut FetchServerResponse)
+	{
+		let since_last_seen = OffsetDateTime::now_utc() - server.last_seen_on;
+
+		server.is_online = since_last_seen <= **self.heartbeat_window.load();
 	}
 
 	/// Fetch information about a server.
@@ -87,7 +165,11 @@ impl ServerService
 		.bind(req.identifier.as_id())
 		.bind(req.identifier.as_name().map(|name| format!("%{name}%")))
 		.fetch_optional(&self.database)
-		.await?;
+		.await?
+		.map(|mut server| {
+			self.mark_online(&mut server);
+			server
+		});
 
 		Ok(res)
 	}
@@ -102,7 +184,7 @@ impl ServerService
 			Some(player) => Some(player.resolve_id(txn.as_mut()).await?),
 		};
 
-		let servers = sqlx::query_as::<_, FetchServerResponse>(&format!(
+		let mut servers = sqlx::query_as::<_, FetchServerResponse>(&format!(
 			r"
 			{}
 			WHERE
@@ -111,6 +193,7 @@ impl ServerService
 			  AND s.owner_id = COALESCE(?, s.owner_id)
 			  AND s.created_on > COALESCE(?, '1970-01-01 00:00:01')
 			  AND s.created_on < COALESCE(?, '2038-01-19 03:14:07')
+			  AND s.last_seen_on >= COALESCE(NOW() - INTERVAL ? SECOND, '1970-01-01 00:00:01')
 			LIMIT
 			  ? OFFSET ?
 			",
@@ -121,11 +204,16 @@ impl ServerService
 		.bind(owner_id)
 		.bind(req.created_after)
 		.bind(req.created_before)
+		.bind(req.online_within.map(|window| window.as_secs()))
 		.bind(*req.limit)
 		.bind(*req.offset)
 		.fetch_all(txn.as_mut())
 		.await?;
 
+		for server in &mut servers {
+			self.mark_online(server);
+		}
+
 		let total = txn.total_rows().await?;
 
 		txn.commit().await?;
@@ -146,9 +234,9 @@ impl ServerService
 		let server_id = sqlx::query! {
 			r"
 			INSERT INTO
-			  Servers (name, host, port, owner_id, `key`)
+			  Servers (name, host, port, owner_id, `key`, key_expires_on)
 			VALUES
-			  (?, ?, ?, ?, ?)
+			  (?, ?, ?, ?, ?, ?)
 			RETURNING id
 			",
 			req.name,
@@ -156,6 +244,7 @@ impl ServerService
 			req.port,
 			req.owner_id,
 			api_key,
+			self.new_key_expiration(),
 		}
 		.fetch_one(txn.as_mut())
 		.await
@@ -163,6 +252,10 @@ impl ServerService
 		.map_err(|error| {
 			if error.is_fk_violation("owner_id") {
 				Error::ServerOwnerDoesNotExist { steam_id: req.owner_id }
+			} else if error.is_unique_violation("name") {
+				Error::NameAlreadyTaken
+			} else if error.is_unique_violation("host") {
+				Error::AddressAlreadyTaken
 			} else {
 				Error::Database(error)
 			}
@@ -204,13 +297,36 @@ impl ServerService
 			req.server_id
 		}
 		.execute(txn.as_mut())
-		.await?;
+		.await
+		.map_err(|error| {
+			if error.is_unique_violation("name") {
+				Error::NameAlreadyTaken
+			} else if error.is_unique_violation("host") {
+				Error::AddressAlreadyTaken
+			} else {
+				Error::Database(error)
+			}
+		})?;
 
 		match query_result.rows_affected() {
 			0 => return Err(Error::ServerDoesNotExist),
 			n => assert_eq!(n, 1, "updated more than 1 server"),
 		}
 
+		audit::record(
+			&mut txn,
+			req.actor_id,
+			AuditAction::ServerUpdated,
+			u64::from(req.server_id.0),
+			serde_json::json!({
+				"name": req.new_name,
+				"host": req.new_host,
+				"port": req.new_port,
+				"owner_id": req.new_owner,
+			}),
+		)
+		.await?;
+
 		txn.commit().await?;
 
 		tracing::info!(server_id = %req.server_id, "updated server");
@@ -218,6 +334,33 @@ impl ServerService
 		Ok(UpdateServerResponse { _priv: () })
 	}
 
+	/// Fetch a server's current API key.
+	///
+	/// Returns `None` if the server has no key.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn fetch_key(&self, req: FetchKeyRequest) -> Result<Option<FetchKeyResponse>>
+	{
+		let server = sqlx::query! {
+			r"
+			SELECT
+			  `key` `key: ApiKey`,
+			  key_expires_on
+			FROM
+			  Servers
+			WHERE
+			  id = ?
+			",
+			req.server_id,
+		}
+		.fetch_optional(&self.database)
+		.await?
+		.ok_or(Error::ServerDoesNotExist)?;
+
+		Ok(server
+			.key
+			.map(|key| FetchKeyResponse { key, expires_on: server.key_expires_on }))
+	}
+
 	/// Resets a server's API key.
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn reset_key(&self, req: ResetKeyRequest) -> Result<ResetKeyResponse>
@@ -231,11 +374,15 @@ impl ServerService
 			UPDATE
 			  Servers
 			SET
-			  `key` = ?
+			  `key` = ?,
+			  key_expires_on = ?,
+			  previous_key = NULL,
+			  previous_key_expires_on = NULL
 			WHERE
 			  id = ?
 			",
 			new_key,
+			self.new_key_expiration(),
 			req.server_id,
 		}
 		.execute(txn.as_mut())
@@ -264,7 +411,9 @@ impl ServerService
 			UPDATE
 			  Servers
 			SET
-			  `key` = NULL
+			  `key` = NULL,
+			  previous_key = NULL,
+			  previous_key_expires_on = NULL
 			WHERE
 			  id = ?
 			",
@@ -294,24 +443,48 @@ impl ServerService
 	{
 		let mut txn = self.database.begin().await?;
 
-		let server_info = sqlx::query! {
+		let server = sqlx::query! {
 			r"
 			SELECT
 			  s.id `server_id: ServerID`,
-			  v.id `plugin_version_id: PluginVersionID`
+			  v.id `plugin_version_id: PluginVersionID`,
+			  v.yanked `plugin_version_yanked: bool`,
+			  IF(s.key = ?, s.key_expires_on, s.previous_key_expires_on) `key_expires_on: OffsetDateTime`
 			FROM
 			  Servers s
 			  JOIN PluginVersions v ON v.semver = ?
-			  AND s.key = ?
+			  AND (
+			    s.key = ?
+			    OR s.previous_key = ?
+			  )
 			",
+			req.key,
 			req.plugin_version,
 			req.key,
+			req.key,
 		}
 		.fetch_optional(txn.as_mut())
 		.await?
-		.map(|row| jwt::ServerInfo::new(row.server_id, row.plugin_version_id))
 		.ok_or(Error::InvalidKeyOrPluginVersion)?;
 
+		let server_id = server.server_id;
+		let plugin_version_id = server.plugin_version_id;
+
+		if server
+			.key_expires_on
+			.is_some_and(|expires_on| expires_on <= OffsetDateTime::now_utc())
+		{
+			return Err(Error::KeyExpired);
+		}
+
+		if server.plugin_version_yanked {
+			tracing::warn!(%server_id, version = %req.plugin_version, "server is running yanked plugin version");
+
+			return Err(Error::PluginVersionYanked);
+		}
+
+		let server_info = jwt::ServerInfo::new(server_id, plugin_version_id);
+
 		sqlx::query! {
 			r"
 			UPDATE
@@ -335,6 +508,109 @@ impl ServerService
 
 		Ok(GenerateAccessTokenResponse { token })
 	}
+
+	/// Replaces every API key that expires within the next `within` with a new
+	/// one.
+	///
+	/// The old key keeps working until its original expiration date, or for
+	/// [`ServerService::key_grace_period()`], whichever is later. Keys that
+	/// have already expired are replaced without a grace period.
+	///
+	/// An [`Event::ServerKeyRotated`] is dispatched for every rotated key, so
+	/// the server owners can be notified.
+	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
+	pub async fn rotate_expiring_keys(&self, within: Duration) -> Result<Vec<ServerID>>
+	{
+		let mut txn = self.database.begin().await?;
+
+		let expiring = sqlx::query! {
+			r"
+			SELECT
+			  id `server_id: ServerID`,
+			  owner_id `owner_id: SteamID`
+			FROM
+			  Servers
+			WHERE
+			  `key` IS NOT NULL
+			  AND key_expires_on <= ?
+			FOR UPDATE
+			",
+			OffsetDateTime::now_utc() + within,
+		}
+		.fetch_all(txn.as_mut())
+		.await?;
+
+		let mut server_ids = Vec::with_capacity(expiring.len());
+		let mut events = Vec::with_capacity(expiring.len());
+		let now = OffsetDateTime::now_utc();
+		let grace_period_end = now + self.key_grace_period;
+
+		for row in expiring {
+			let expires_on = self.new_key_expiration();
+
+			// MySQL evaluates the assignments from left to right, so the old
+			// key has to be moved before it is overwritten.
+			sqlx::query! {
+				r"
+				UPDATE
+				  Servers
+				SET
+				  previous_key = `key`,
+				  previous_key_expires_on = IF(
+				    key_expires_on > ?,
+				    GREATEST(key_expires_on, ?),
+				    key_expires_on
+				  ),
+				  `key` = ?,
+				  key_expires_on = ?
+				WHERE
+				  id = ?
+				",
+				now,
+				grace_period_end,
+				ApiKey::new(),
+				expires_on,
+				row.server_id,
+			}
+			.execute(txn.as_mut())
+			.await?;
+
+			server_ids.push(row.server_id);
+			events.push(Event::ServerKeyRotated {
+				server_id: row.server_id,
+				owner_id: row.owner_id,
+				expires_on,
+			});
+		}
+
+		txn.commit().await?;
+
+		tracing::info!(?server_ids, "rotated expiring API keys");
+
+		for event in events {
+			self.events.dispatch(event);
+		}
+
+		Ok(server_ids)
+	}
+
+	/// Spawns a background task that periodically calls
+	/// [`ServerService::rotate_expiring_keys()`].
+	pub fn spawn_key_rotation(self, within: Duration) -> JoinHandle<()>
+	{
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(Self::KEY_ROTATION_INTERVAL);
+			interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+			loop {
+				interval.tick().await;
+
+				if let Err(error) = self.rotate_expiring_keys(within).await {
+					tracing::error!(%error, "failed to rotate expiring API keys");
+				}
+			}
+		})
+	}
 }
 
 #[cfg(test)]
@@ -346,6 +622,7 @@ mod tests
 	use super::*;
 	use crate::services::plugin::PluginVersion;
 	use crate::testing::{self, ALPHAKEKS_ID};
+	use crate::time::Seconds;
 
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn fetch_server_works(database: Pool<MySql>) -> color_eyre::Result<()>
@@ -401,6 +678,41 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(
+		migrations = "database/migrations",
+		fixtures(
+			"../../../database/fixtures/servers.sql",
+			"../../../database/fixtures/server-heartbeats.sql",
+		)
+	)]
+	async fn fetch_servers_online_within(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::server_svc(database).heartbeat_window(Duration::MINUTE * 5);
+		let req = FetchServersRequest {
+			online_within: Some(Seconds(Duration::MINUTE * 10)),
+			..Default::default()
+		};
+		let res = svc.fetch_servers(req).await?;
+		let mut online = res
+			.servers
+			.iter()
+			.map(|s| (&*s.name, s.is_online))
+			.collect::<Vec<_>>();
+
+		online.sort_unstable();
+
+		testing::assert_eq!(online, [("balls.kz EU 1", true), ("balls.kz EU 2", false)]);
+
+		let req = FetchServersRequest::default();
+		let res = svc.fetch_servers(req).await?;
+		let online = res.servers.iter().filter(|s| s.is_online).count();
+
+		testing::assert_eq!(res.servers.len(), 5);
+		testing::assert_eq!(online, 1);
+
+		Ok(())
+	}
+
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn register_server_works(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
@@ -448,6 +760,45 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn register_server_rejects_duplicate_name(database: Pool<MySql>)
+		-> color_eyre::Result<()>
+	{
+		let svc = testing::server_svc(database);
+		let req = RegisterServerRequest {
+			name: String::from("Alpha's KZ"),
+			host: "123.456.789.420".parse()?,
+			port: 1337,
+			owner_id: ALPHAKEKS_ID,
+		};
+
+		let res = svc.register_server(req).await.unwrap_err();
+
+		testing::assert_matches!(res, Error::NameAlreadyTaken);
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn register_server_rejects_duplicate_address(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::server_svc(database);
+		let req = RegisterServerRequest {
+			name: String::from("my cool new server!"),
+			host: "::1".parse()?,
+			port: 27015,
+			owner_id: ALPHAKEKS_ID,
+		};
+
+		let res = svc.register_server(req).await.unwrap_err();
+
+		testing::assert_matches!(res, Error::AddressAlreadyTaken);
+
+		Ok(())
+	}
+
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn update_server_works(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
@@ -458,6 +809,7 @@ mod tests
 			new_host: None,
 			new_port: None,
 			new_owner: None,
+			actor_id: None,
 		};
 
 		let _res = svc.update_server(req).await?;
@@ -481,6 +833,7 @@ mod tests
 			new_host: None,
 			new_port: None,
 			new_owner: None,
+			actor_id: None,
 		};
 
 		let res = svc.update_server(req).await.unwrap_err();
@@ -490,6 +843,23 @@ mod tests
 		Ok(())
 	}
 
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn fetch_key_works(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::server_svc(database);
+
+		let key = sqlx::query_scalar!("SELECT `key` `key!: ApiKey` FROM Servers WHERE id = 1")
+			.fetch_one(&svc.database)
+			.await?;
+
+		let req = FetchKeyRequest { server_id: 1.into() };
+		let res = svc.fetch_key(req).await?;
+
+		testing::assert!(res.as_ref().is_some_and(|res| res.key == key));
+
+		Ok(())
+	}
+
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn reset_key_works(database: Pool<MySql>) -> color_eyre::Result<()>
 	{
@@ -591,6 +961,213 @@ mod tests
 		Ok(())
 	}
 
+	/// Builds a token request for the server with ID 1.
+	async fn token_request(svc: &ServerService) -> color_eyre::Result<GenerateAccessTokenRequest>
+	{
+		let key = sqlx::query_scalar! {
+			r"
+			SELECT
+			  `key` `key!: ApiKey`
+			FROM
+			  Servers
+			WHERE
+			  id = 1
+			",
+		}
+		.fetch_one(&svc.database)
+		.await?;
+
+		let plugin_version = sqlx::query_scalar! {
+			r"
+			SELECT
+			  semver `semver: PluginVersion`
+			FROM
+			  PluginVersions
+			ORDER BY
+			  created_on DESC
+			LIMIT
+			  1
+			",
+		}
+		.fetch_one(&svc.database)
+		.await?;
+
+		Ok(GenerateAccessTokenRequest { key, plugin_version })
+	}
+
+	/// Sets the expiration date of the server with ID 1.
+	async fn set_key_expiration(
+		svc: &ServerService,
+		expires_on: OffsetDateTime,
+	) -> color_eyre::Result<()>
+	{
+		sqlx::query! {
+			r"
+			UPDATE
+			  Servers
+			SET
+			  key_expires_on = ?
+			WHERE
+			  id = 1
+			",
+			expires_on,
+		}
+		.execute(&svc.database)
+		.await?;
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn generate_access_token_accepts_unexpired_key(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::server_svc(database);
+
+		set_key_expiration(&svc, OffsetDateTime::now_utc() + Duration::DAY).await?;
+
+		let req = token_request(&svc).await?;
+		let res = svc.generate_access_token(req).await;
+
+		testing::assert!(res.is_ok());
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn generate_access_token_rejects_expired_key(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::server_svc(database);
+
+		set_key_expiration(&svc, OffsetDateTime::now_utc() - Duration::MINUTE).await?;
+
+		let req = token_request(&svc).await?;
+		let res = svc.generate_access_token(req).await.unwrap_err();
+
+		testing::assert_matches!(res, Error::KeyExpired);
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn rotate_expiring_keys_works(database: Pool<MySql>) -> color_eyre::Result<()>
+	{
+		let svc = testing::server_svc(database).key_lifetime(Some(Duration::MONTH));
+		let mut events = svc.events.subscribe();
+
+		set_key_expiration(&svc, OffsetDateTime::now_utc() + Duration::HOUR).await?;
+
+		let old_req = token_request(&svc).await?;
+		let rotated = svc.rotate_expiring_keys(Duration::WEEK).await?;
+
+		testing::assert_eq!(rotated, [ServerID::from(1)]);
+
+		let event = events.try_recv()?;
+
+		testing::assert_matches!(
+			event,
+			Some(Event::ServerKeyRotated {
+				server_id,
+				owner_id: ALPHAKEKS_ID,
+				expires_on: Some(_),
+				..
+			}) if server_id == ServerID::from(1)
+		);
+
+		// The old key is still in its grace period.
+		let old_key_res = svc.generate_access_token(old_req.clone()).await;
+
+		testing::assert!(old_key_res.is_ok());
+
+		let new_req = token_request(&svc).await?;
+		let new_key_res = svc.generate_access_token(new_req).await;
+
+		testing::assert!(new_key_res.is_ok());
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  Servers
+			SET
+			  previous_key_expires_on = ?
+			WHERE
+			  id = 1
+			",
+			OffsetDateTime::now_utc() - Duration::MINUTE,
+		}
+		.execute(&svc.database)
+		.await?;
+
+		let old_key_res = svc.generate_access_token(old_req).await.unwrap_err();
+
+		testing::assert_matches!(old_key_res, Error::KeyExpired);
+
+		// The new key is valid for another month.
+		let rotated = svc.rotate_expiring_keys(Duration::WEEK).await?;
+
+		testing::assert!(rotated.is_empty());
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn rotating_expired_keys_has_no_grace_period(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::server_svc(database).key_lifetime(Some(Duration::MONTH));
+
+		set_key_expiration(&svc, OffsetDateTime::now_utc() - Duration::HOUR).await?;
+
+		let old_req = token_request(&svc).await?;
+		let rotated = svc.rotate_expiring_keys(Duration::WEEK).await?;
+
+		testing::assert_eq!(rotated, [ServerID::from(1)]);
+
+		let old_key_res = svc.generate_access_token(old_req).await.unwrap_err();
+
+		testing::assert_matches!(old_key_res, Error::KeyExpired);
+
+		let new_req = token_request(&svc).await?;
+		let new_key_res = svc.generate_access_token(new_req).await;
+
+		testing::assert!(new_key_res.is_ok());
+
+		Ok(())
+	}
+
+	#[sqlx::test(migrations = "database/migrations")]
+	async fn generate_access_token_rejects_yanked_version(
+		database: Pool<MySql>,
+	) -> color_eyre::Result<()>
+	{
+		let svc = testing::server_svc(database);
+		let req = token_request(&svc).await?;
+
+		sqlx::query! {
+			r"
+			UPDATE
+			  PluginVersions
+			SET
+			  yanked = TRUE
+			WHERE
+			  semver = ?
+			",
+			req.plugin_version,
+		}
+		.execute(&svc.database)
+		.await?;
+
+		let res = svc.generate_access_token(req).await.unwrap_err();
+
+		testing::assert_matches!(res, Error::PluginVersionYanked);
+
+		Ok(())
+	}
+
 	#[sqlx::test(migrations = "database/migrations")]
 	async fn generate_access_token_rejects_invalid_key(
 		database: Pool<MySql>,
@@ -7,7 +7,7 @@ use uuid::fmt::Hyphenated;
 use uuid::Uuid;
 
 /// An API key for CS2 servers.
-#[derive(PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(transparent)]
 pub struct ApiKey(Uuid);
 
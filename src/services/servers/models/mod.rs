@@ -7,6 +7,7 @@ use time::OffsetDateTime;
 
 use crate::num::ClampedU64;
 use crate::services::plugin::PluginVersion;
+use crate::time::Seconds;
 use crate::util::{PlayerIdentifier, ServerIdentifier};
 
 #[doc(hidden)]
@@ -68,6 +69,14 @@ pub struct FetchServerResponse
 	/// When this server was approved.
 	#[serde(with = "time::serde::rfc3339")]
 	pub created_on: OffsetDateTime,
+
+	/// When this server last authenticated with the API.
+	#[serde(with = "time::serde::rfc3339")]
+	pub last_seen_on: OffsetDateTime,
+
+	/// Whether this server has been seen recently.
+	#[sqlx(skip)]
+	pub is_online: bool,
 }
 
 impl IntoResponse for FetchServerResponse
@@ -112,6 +121,10 @@ pub struct FetchServersRequest
 	#[serde(default, with = "time::serde::rfc3339::option")]
 	pub created_before: Option<OffsetDateTime>,
 
+	/// Only include servers that have been seen within this many seconds.
+	#[param(value_type = Option<f64>)]
+	pub online_within: Option<Seconds>,
+
 	/// The maximum amount of servers to return.
 	#[serde(default)]
 	#[param(value_type = u64, default = 50, maximum = 500)]
@@ -205,6 +218,9 @@ pub struct UpdateServerRequest
 
 	/// SteamID of a new owner.
 	pub new_owner: Option<SteamID>,
+
+	/// The user who is making the update.
+	pub actor_id: Option<SteamID>,
 }
 
 impl UpdateServerRequest
@@ -212,7 +228,7 @@ impl UpdateServerRequest
 	/// Checks if this update does not contain any changes.
 	pub fn is_empty(&self) -> bool
 	{
-		let Self { server_id: _, new_name, new_host, new_port, new_owner } = self;
+		let Self { server_id: _, new_name, new_host, new_port, new_owner, actor_id: _ } = self;
 
 		new_name.is_none() && new_host.is_none() && new_port.is_none() && new_owner.is_none()
 	}
@@ -236,6 +252,35 @@ impl IntoResponse for UpdateServerResponse
 
 crate::openapi::responses::no_content!(UpdateServerResponse);
 
+/// Request payload for fetching a server's API key.
+#[derive(Debug)]
+pub struct FetchKeyRequest
+{
+	/// The server's ID.
+	pub server_id: ServerID,
+}
+
+/// Response payload for fetching a server's API key.
+#[derive(Debug, Serialize, utoipa::ToSchema, utoipa::IntoResponses)]
+#[response(status = OK)]
+pub struct FetchKeyResponse
+{
+	/// The server's current key.
+	pub key: ApiKey,
+
+	/// When the key expires.
+	#[serde(with = "time::serde::rfc3339::option")]
+	pub expires_on: Option<OffsetDateTime>,
+}
+
+impl IntoResponse for FetchKeyResponse
+{
+	fn into_response(self) -> Response
+	{
+		crate::http::extract::Json(self).into_response()
+	}
+}
+
 /// Request payload for resetting a server's API key.
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct ResetKeyRequest
@@ -291,7 +336,7 @@ impl IntoResponse for DeleteKeyResponse
 crate::openapi::responses::no_content!(DeleteKeyResponse);
 
 /// Request payload for generating a temporary access token.
-#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
 pub struct GenerateAccessTokenRequest
 {
 	/// The server's API key.
@@ -9,7 +9,8 @@ pub const SELECT: &str = r#"
 	  s.port,
 	  o.name owner_name,
 	  o.id owner_id,
-	  s.created_on
+	  s.created_on,
+	  s.last_seen_on
 	FROM
 	  Servers s
 	  JOIN Players o ON o.id = s.owner_id
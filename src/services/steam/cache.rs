@@ -0,0 +1,145 @@
+//! This module contains the [`MapNameCache`].
+//!
+//! Map approvals and `check_steam` updates look up the same workshop IDs over
+//! and over again, so [`SteamService::fetch_map_name()`] keeps recently fetched
+//! names around for a while instead of asking Steam every time.
+//!
+//! [`SteamService::fetch_map_name()`]: super::SteamService::fetch_map_name
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use hashlink::LruCache;
+
+use super::WorkshopID;
+
+/// The default for how long cached names stay valid.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The default for how many names are kept at most.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// A bounded cache of workshop map names with a fixed time-to-live.
+///
+/// When the cache is full, the least recently used entry is evicted. A
+/// capacity of 0 disables caching entirely.
+#[derive(Debug)]
+pub struct MapNameCache
+{
+	/// How long an entry stays valid after it was inserted.
+	ttl: Duration,
+
+	/// The cached names.
+	entries: Mutex<LruCache<WorkshopID, Entry>>,
+}
+
+/// A single cached name.
+#[derive(Debug)]
+struct Entry
+{
+	/// The map's name.
+	name: String,
+
+	/// When the entry was inserted.
+	inserted_at: Instant,
+}
+
+impl Default for MapNameCache
+{
+	fn default() -> Self
+	{
+		Self::new(DEFAULT_TTL, DEFAULT_CAPACITY)
+	}
+}
+
+impl MapNameCache
+{
+	/// Creates a new [`MapNameCache`].
+	pub fn new(ttl: Duration, capacity: usize) -> Self
+	{
+		Self { ttl, entries: Mutex::new(LruCache::new(capacity)) }
+	}
+
+	/// Returns the cached name for `workshop_id`, if there is one that hasn't
+	/// expired yet.
+	pub fn get(&self, workshop_id: WorkshopID) -> Option<String>
+	{
+		let mut entries = self.entries.lock().expect("mutex is not poisoned");
+		let entry = entries.get(&workshop_id)?;
+
+		if entry.inserted_at.elapsed() >= self.ttl {
+			entries.remove(&workshop_id);
+			return None;
+		}
+
+		Some(entry.name.clone())
+	}
+
+	/// Caches `name` as the name for `workshop_id`.
+	pub fn insert(&self, workshop_id: WorkshopID, name: String)
+	{
+		self.entries
+			.lock()
+			.expect("mutex is not poisoned")
+			.insert(workshop_id, Entry { name, inserted_at: Instant::now() });
+	}
+
+	/// Removes the cached name for `workshop_id`, if any.
+	pub fn invalidate(&self, workshop_id: WorkshopID)
+	{
+		self.entries
+			.lock()
+			.expect("mutex is not poisoned")
+			.remove(&workshop_id);
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn evicts_least_recently_used() -> color_eyre::Result<()>
+	{
+		let cache = MapNameCache::new(DEFAULT_TTL, 2);
+
+		cache.insert(1.into(), String::from("kz_checkmate"));
+		cache.insert(2.into(), String::from("kz_grotto"));
+
+		testing::assert_eq!(cache.get(1.into()).as_deref(), Some("kz_checkmate"));
+
+		cache.insert(3.into(), String::from("kz_igloo"));
+
+		testing::assert_eq!(cache.get(1.into()).as_deref(), Some("kz_checkmate"));
+		testing::assert!(cache.get(2.into()).is_none());
+		testing::assert_eq!(cache.get(3.into()).as_deref(), Some("kz_igloo"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn entries_expire() -> color_eyre::Result<()>
+	{
+		let cache = MapNameCache::new(Duration::ZERO, 2);
+
+		cache.insert(1.into(), String::from("kz_checkmate"));
+
+		testing::assert!(cache.get(1.into()).is_none());
+
+		Ok(())
+	}
+
+	#[test]
+	fn zero_capacity_disables_caching() -> color_eyre::Result<()>
+	{
+		let cache = MapNameCache::new(DEFAULT_TTL, 0);
+
+		cache.insert(1.into(), String::from("kz_checkmate"));
+
+		testing::assert!(cache.get(1.into()).is_none());
+
+		Ok(())
+	}
+}
@@ -1,11 +1,9 @@
 //! The errors that can occur when interacting with this service.
 
-use std::io;
-
 use thiserror::Error;
 
-use super::openid::OpenIDRejection;
-use super::WorkshopID;
+use super::openid::{InvalidReturnTo, OpenIDRejection};
+use super::{DownloadError, WorkshopID};
 use crate::http::problem_details::{self, IntoProblemDetails, ProblemType};
 
 /// Type alias with a default `Err` type of [`Error`].
@@ -21,6 +19,10 @@ pub enum Error
 	#[error(transparent)]
 	ExtractOpenIDPayload(#[from] OpenIDRejection),
 
+	/// The API's URL can't be used as an OpenID realm.
+	#[error("invalid OpenID realm: {0}")]
+	InvalidOpenIDRealm(#[from] InvalidReturnTo),
+
 	/// Steam's API returned an error when we tried to fetch information about a
 	/// map.
 	#[error("invalid workshop ID")]
@@ -41,7 +43,7 @@ pub enum Error
 
 	/// We failed to download a workshop map.
 	#[error("failed to download workshop map")]
-	DownloadWorkshopMap(#[from] io::Error),
+	DownloadWorkshopMap(#[from] DownloadError),
 
 	/// We failed to make an HTTP request to Steam's Web API.
 	#[error("failed to make http request")]
@@ -54,8 +56,10 @@ impl IntoProblemDetails for Error
 	{
 		match self {
 			Self::ExtractOpenIDPayload(source) => source.problem_type(),
+			Self::InvalidOpenIDRealm(_) => ProblemType::Internal,
 			Self::InvalidWorkshopID { .. } => ProblemType::ResourceNotFound,
 			Self::NotAMap { .. } => ProblemType::WorkshopItemNotAMap,
+			Self::DownloadWorkshopMap(DownloadError::Timeout { .. }) => ProblemType::Timeout,
 			Self::DownloadWorkshopMap(_) => ProblemType::DownloadWorkshopMap,
 			Self::Http(source) => source.problem_type(),
 		}
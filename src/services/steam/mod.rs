@@ -4,17 +4,23 @@
 //! verify their payloads.
 
 use std::fmt;
+use std::num::NonZero;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::extract::FromRef;
 use cs2kz::SteamID;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tokio::sync::Semaphore;
 use url::Url;
 
 mod error;
 pub use error::{Error, Result};
 
+pub mod cache;
+pub use cache::MapNameCache;
+
 pub mod openid;
 pub use openid::OpenIDPayload;
 
@@ -22,7 +28,7 @@ pub mod user;
 pub use user::User;
 
 pub mod workshop;
-pub use workshop::WorkshopID;
+pub use workshop::{DownloadError, WorkshopID};
 
 /// Steam Web API URL for fetching user information.
 const USER_URL: &str = "https://api.steampowered.com/ISteamUser/GetPlayerSummaries/v0002";
@@ -30,15 +36,31 @@ const USER_URL: &str = "https://api.steampowered.com/ISteamUser/GetPlayerSummari
 /// Steam Web API URL for fetching map information.
 const MAP_URL: &str = "https://api.steampowered.com/ISteamRemoteStorage/GetPublishedFileDetails/v1";
 
+/// Steam Web API URL that doesn't require an API key, used for checking if
+/// the Web API is reachable.
+const STATUS_URL: &str = "https://api.steampowered.com/ISteamWebAPIUtil/GetServerInfo/v1";
+
+/// The default for how long a single map download may take.
+const DEFAULT_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(240);
+
+/// The default for how many maps may be downloaded at the same time.
+const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 2;
+
 /// A service for interacting with Steam.
 #[derive(Clone)]
 #[allow(clippy::missing_docs_in_private_items)]
 pub struct SteamService
 {
 	pub(crate) api_url: Arc<Url>,
+	pub(crate) openid_login_url: Arc<Url>,
+	status_url: Arc<Url>,
+	map_url: Arc<Url>,
+	map_names: Arc<MapNameCache>,
 	steam_api_key: Arc<str>,
 	workshop_artifacts_path: Arc<Path>,
 	depot_downloader_path: Arc<Path>,
+	download_timeout: Duration,
+	downloads: Arc<Semaphore>,
 	pub(crate) http_client: reqwest::Client,
 }
 
@@ -75,18 +97,91 @@ impl SteamService
 	{
 		Self {
 			api_url: api_url.into(),
+			openid_login_url: Url::parse(openid::LoginForm::LOGIN_URL)
+				.expect("this is a valid url")
+				.into(),
+			status_url: Url::parse(STATUS_URL).expect("this is a valid url").into(),
+			map_url: Url::parse(MAP_URL).expect("this is a valid url").into(),
+			map_names: Arc::default(),
 			steam_api_key: steam_api_key.into(),
 			workshop_artifacts_path: workshop_artifacts_path.into(),
 			depot_downloader_path: depot_downloader_path.into(),
+			download_timeout: DEFAULT_DOWNLOAD_TIMEOUT,
+			downloads: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_DOWNLOADS)),
 			http_client,
 		}
 	}
 
+	/// Sets how long fetched map names are cached for, and how many of them
+	/// are kept at most.
+	///
+	/// A `capacity` of 0 disables caching.
+	pub fn map_name_cache(mut self, ttl: Duration, capacity: usize) -> Self
+	{
+		self.map_names = Arc::new(MapNameCache::new(ttl, capacity));
+		self
+	}
+
+	/// Sets how long a single map download may take, and how many maps may be
+	/// downloaded at the same time.
+	pub fn downloads(mut self, timeout: Duration, max_concurrent: NonZero<usize>) -> Self
+	{
+		self.download_timeout = timeout;
+		self.downloads = Arc::new(Semaphore::new(max_concurrent.get()));
+		self
+	}
+
+	/// Replaces Steam's OpenID endpoint with a different provider.
+	///
+	/// This is used to point the service at an
+	/// [`openid::testing::MockProvider`].
+	#[cfg(any(test, feature = "testing"))]
+	pub fn with_openid_login_url(mut self, login_url: Url) -> Self
+	{
+		self.openid_login_url = login_url.into();
+		self
+	}
+
+	/// Replaces the URL used by [`SteamService::is_reachable()`].
+	#[cfg(any(test, feature = "testing"))]
+	pub fn with_status_url(mut self, status_url: Url) -> Self
+	{
+		self.status_url = status_url.into();
+		self
+	}
+
+	/// Replaces the URL used by [`SteamService::fetch_map_name()`].
+	#[cfg(any(test, feature = "testing"))]
+	pub fn with_map_url(mut self, map_url: Url) -> Self
+	{
+		self.map_url = map_url.into();
+		self
+	}
+
+	/// Checks whether the Steam Web API responds within `timeout`.
+	#[tracing::instrument(level = "debug")]
+	pub async fn is_reachable(&self, timeout: Duration) -> bool
+	{
+		let response = self
+			.http_client
+			.get(Url::clone(&*self.status_url))
+			.timeout(timeout)
+			.send()
+			.await
+			.and_then(reqwest::Response::error_for_status);
+
+		if let Err(error) = &response {
+			tracing::warn!(%error, "steam web api is unreachable");
+		}
+
+		response.is_ok()
+	}
+
 	/// Builds OpenID form parameters to send to Steam.
 	#[tracing::instrument(level = "debug")]
 	pub fn openid_login_form(&self) -> openid::LoginForm
 	{
-		openid::LoginForm::new(Url::clone(&*self.api_url))
+		openid::LoginForm::new(Url::clone(&*self.api_url), Url::clone(&*self.openid_login_url))
 	}
 
 	/// Fetch information about a user.
@@ -130,8 +225,32 @@ impl SteamService
 	}
 
 	/// Fetches a map's name from the workshop.
+	///
+	/// Names are cached for a while; see [`SteamService::map_name_cache()`].
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn fetch_map_name(&self, workshop_id: WorkshopID) -> Result<String>
+	{
+		if let Some(name) = self.map_names.get(workshop_id) {
+			tracing::debug!(%name, "using cached map name");
+			return Ok(name);
+		}
+
+		let name = self.fetch_map_name_uncached(workshop_id).await?;
+
+		self.map_names.insert(workshop_id, name.clone());
+
+		Ok(name)
+	}
+
+	/// Removes the cached name for `workshop_id`, so the next call to
+	/// [`SteamService::fetch_map_name()`] asks Steam again.
+	pub fn invalidate_map_name(&self, workshop_id: WorkshopID)
+	{
+		self.map_names.invalidate(workshop_id);
+	}
+
+	/// Fetches a map's name from the workshop, bypassing the cache.
+	async fn fetch_map_name_uncached(&self, workshop_id: WorkshopID) -> Result<String>
 	{
 		#[allow(clippy::missing_docs_in_private_items)]
 		struct Params
@@ -225,11 +344,11 @@ impl SteamService
 			}
 		}
 
-		tracing::debug!(url = MAP_URL, "making http request to steam");
+		tracing::debug!(url = %self.map_url, "making http request to steam");
 
 		let response = self
 			.http_client
-			.post(MAP_URL)
+			.post(Url::clone(&*self.map_url))
 			.form(&Params { workshop_id })
 			.send()
 			.await?;
@@ -249,15 +368,82 @@ impl SteamService
 	}
 
 	/// Downloads a map from the workshop.
+	///
+	/// Only a limited amount of downloads run at the same time; see
+	/// [`SteamService::downloads()`].
 	#[tracing::instrument(level = "debug", err(Debug, level = "debug"))]
 	pub async fn download_map(&self, workshop_id: WorkshopID) -> Result<workshop::MapFile>
 	{
+		let _permit = self
+			.downloads
+			.acquire()
+			.await
+			.expect("we never close the semaphore");
+
 		workshop::MapFile::download(
 			workshop_id,
 			&self.workshop_artifacts_path,
 			&self.depot_downloader_path,
+			self.download_timeout,
 		)
 		.await
 		.map_err(Error::DownloadWorkshopMap)
 	}
 }
+
+#[cfg(test)]
+mod tests
+{
+	use std::sync::atomic::{AtomicU32, Ordering};
+
+	use axum::extract::State;
+	use axum::routing::post;
+	use axum::Router;
+
+	use super::*;
+	use crate::testing;
+
+	/// Serves a fake `GetPublishedFileDetails` endpoint that counts how many
+	/// requests it received, and returns its URL.
+	async fn spawn_workshop_api(requests: Arc<AtomicU32>) -> color_eyre::Result<Url>
+	{
+		let router = Router::new()
+			.route(
+				"/details",
+				post(|State(requests): State<Arc<AtomicU32>>| async move {
+					requests.fetch_add(1, Ordering::SeqCst);
+					axum::Json(serde_json::json!({
+						"response": {
+							"publishedfiledetails": [{ "title": "kz_checkmate" }],
+						},
+					}))
+				}),
+			)
+			.with_state(requests);
+
+		Ok(testing::spawn_mock_server(router).await?.join("details")?)
+	}
+
+	#[tokio::test]
+	async fn fetch_map_name_is_cached() -> color_eyre::Result<()>
+	{
+		let requests = Arc::new(AtomicU32::new(0));
+		let map_url = spawn_workshop_api(Arc::clone(&requests)).await?;
+		let svc = testing::steam_svc()
+			.map_name_cache(Duration::from_secs(60), 16)
+			.with_map_url(map_url);
+
+		let workshop_id = WorkshopID(3070194623);
+
+		testing::assert_eq!(svc.fetch_map_name(workshop_id).await?, "kz_checkmate");
+		testing::assert_eq!(svc.fetch_map_name(workshop_id).await?, "kz_checkmate");
+		testing::assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+		svc.invalidate_map_name(workshop_id);
+
+		testing::assert_eq!(svc.fetch_map_name(workshop_id).await?, "kz_checkmate");
+		testing::assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+		Ok(())
+	}
+}
@@ -26,6 +26,8 @@
 //! [extractors]: axum::extract
 //! [authentication service]: crate::services::auth
 
+use std::time::Duration;
+
 use axum::async_trait;
 use axum::extract::{FromRef, FromRequestParts};
 use axum_extra::extract::Query;
@@ -33,6 +35,7 @@ use cs2kz::SteamID;
 use http::request;
 use serde::{Deserialize, Serialize};
 use tap::Tap;
+use thiserror::Error;
 use url::Url;
 
 use crate::services::SteamService;
@@ -40,6 +43,9 @@ use crate::services::SteamService;
 mod rejection;
 pub use rejection::OpenIDRejection;
 
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
 /// Form parameters that will be sent to Steam when redirecting a user for
 /// login.
 #[derive(Debug, Serialize)]
@@ -63,24 +69,43 @@ pub struct LoginForm
 
 	#[serde(rename = "openid.return_to")]
 	return_to: Url,
+
+	/// The OpenID provider the user is redirected to.
+	#[serde(skip)]
+	login_url: Url,
 }
 
 impl LoginForm
 {
 	/// The API route that Steam should redirect back to after a successful
 	/// login.
-	pub const RETURN_ROUTE: &'static str = "/auth/callback";
+	///
+	/// This is relative to the realm, so the API can be served from a
+	/// sub-path.
+	pub const RETURN_ROUTE: &'static str = "auth/callback";
 
 	/// Steam URL to redirect the user in for login.
 	pub const LOGIN_URL: &'static str = "https://steamcommunity.com/openid/login";
 
 	/// Creates a new [`LoginForm`].
 	///
-	/// `realm` is the base URL of the API.
+	/// `realm` is the base URL of the API, and `login_url` is the OpenID
+	/// provider's URL (usually [`LoginForm::LOGIN_URL`]). If the realm's path
+	/// doesn't end in a slash, one is added, so [`LoginForm::RETURN_ROUTE`] is
+	/// resolved below it rather than replacing its last segment.
 	#[tracing::instrument(level = "trace", name = "LoginForm::new")]
-	pub(super) fn new(realm: Url) -> Self
+	pub(super) fn new(mut realm: Url, login_url: Url) -> Self
 	{
-		let return_to = realm.join(Self::RETURN_ROUTE).expect("this is valid");
+		if !realm.path().ends_with('/') {
+			let path = format!("{}/", realm.path());
+			realm.set_path(&path);
+		}
+
+		// This only fails if `realm` can't be a base URL, which
+		// `LoginUrlBuilder::build()` rejects.
+		let return_to = realm
+			.join(Self::RETURN_ROUTE)
+			.unwrap_or_else(|_| realm.clone());
 
 		Self {
 			namespace: "http://specs.openid.net/auth/2.0",
@@ -89,33 +114,207 @@ impl LoginForm
 			mode: "checkid_setup",
 			realm,
 			return_to,
+			login_url,
 		}
 	}
 
 	/// Generates an OpenID URL that can be used for logging in with Steam.
-	#[tracing::instrument(level = "trace", name = "LoginForm::redirect_to", skip(self))]
-	pub fn redirect_to(mut self, redirect_to: &Url) -> Url
+	///
+	/// This is a shorthand for [`LoginUrlBuilder`] with the realm and return
+	/// URL derived from the API's base URL.
+	#[tracing::instrument(
+		level = "trace",
+		name = "LoginForm::redirect_to",
+		skip(self),
+		err(Debug, level = "debug")
+	)]
+	pub fn redirect_to(self, redirect_to: &Url) -> Result<Url, InvalidReturnTo>
+	{
+		LoginUrlBuilder::new(self.realm, self.return_to)
+			.login_url(self.login_url)
+			.redirect_to(redirect_to)
+			.build()
+	}
+}
+
+/// A builder for OpenID login URLs with an explicit realm and return URL.
+///
+/// This is useful when the API is deployed behind a reverse proxy, and the
+/// realm Steam should show to the user differs from the API's own URL.
+#[derive(Debug, Clone)]
+pub struct LoginUrlBuilder
+{
+	/// The realm the user is asked to trust.
+	realm: Url,
+
+	/// The URL Steam will redirect the user back to.
+	return_to: Url,
+
+	/// The OpenID provider the user is redirected to.
+	login_url: Url,
+}
+
+/// The reasons a `return_to` URL might not be within its realm.
+///
+/// See [section 9.2 of the OpenID 2.0 spec][spec].
+///
+/// [spec]: https://openid.net/specs/openid-authentication-2_0.html#realms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum InvalidReturnTo
+{
+	/// The realm is not a URL that other URLs can be relative to, e.g.
+	/// `mailto:`.
+	#[error("realm must be a base URL")]
+	RealmCannotBeABase,
+
+	/// Realms are not allowed to contain fragments.
+	#[error("realm must not contain a fragment")]
+	RealmHasFragment,
+
+	/// The URL schemes differ.
+	#[error("`return_to` uses a different scheme than the realm")]
+	SchemeMismatch,
+
+	/// The hosts differ, and the realm's wildcard (if any) doesn't match.
+	#[error("`return_to` is on a different host than the realm")]
+	HostMismatch,
+
+	/// The ports differ.
+	#[error("`return_to` uses a different port than the realm")]
+	PortMismatch,
+
+	/// The path of `return_to` is not equal to, or a sub-directory of, the
+	/// realm's path.
+	#[error("`return_to` is not within the realm's path")]
+	PathMismatch,
+}
+
+impl LoginUrlBuilder
+{
+	/// Creates a new [`LoginUrlBuilder`].
+	pub fn new(realm: Url, return_to: Url) -> Self
+	{
+		let login_url = Url::parse(LoginForm::LOGIN_URL).expect("this is a valid url");
+
+		Self { realm, return_to, login_url }
+	}
+
+	/// Overrides the OpenID provider the user is redirected to.
+	///
+	/// This defaults to [`LoginForm::LOGIN_URL`].
+	pub fn login_url(mut self, login_url: Url) -> Self
+	{
+		self.login_url = login_url;
+		self
+	}
+
+	/// Adds a `redirect_to` query parameter to the return URL.
+	///
+	/// This will be passed back to us by Steam, and is available as
+	/// [`OpenIDPayload::redirect_to`] after a successful login.
+	pub fn redirect_to(mut self, redirect_to: &Url) -> Self
 	{
 		self.return_to
 			.query_pairs_mut()
 			.append_pair("redirect_to", redirect_to.as_str());
 
+		self
+	}
+
+	/// Validates that `return_to` is within `realm` and generates the final
+	/// URL the user should be redirected to.
+	#[tracing::instrument(
+		level = "trace",
+		name = "LoginUrlBuilder::build",
+		err(Debug, level = "debug")
+	)]
+	pub fn build(self) -> Result<Url, InvalidReturnTo>
+	{
+		validate_return_to(&self.realm, &self.return_to)?;
+
+		let form = LoginForm {
+			namespace: "http://specs.openid.net/auth/2.0",
+			identity: "http://specs.openid.net/auth/2.0/identifier_select",
+			claimed_id: "http://specs.openid.net/auth/2.0/identifier_select",
+			mode: "checkid_setup",
+			realm: self.realm,
+			return_to: self.return_to,
+			login_url: self.login_url,
+		};
+
 		let query_string =
-			serde_urlencoded::to_string(&self).expect("this is a valid query string");
+			serde_urlencoded::to_string(&form).expect("this is a valid query string");
+
+		Ok(form
+			.login_url
+			.clone()
+			.tap_mut(|url| url.set_query(Some(&query_string))))
+	}
+}
 
-		Url::parse(Self::LOGIN_URL)
-			.expect("this is a valid url")
-			.tap_mut(|url| url.set_query(Some(&query_string)))
+/// Checks whether `return_to` matches `realm`.
+///
+/// The realm's host may start with a `*.` wildcard, in which case any
+/// sub-domain (and the domain itself) matches.
+fn validate_return_to(realm: &Url, return_to: &Url) -> Result<(), InvalidReturnTo>
+{
+	if realm.cannot_be_a_base() {
+		return Err(InvalidReturnTo::RealmCannotBeABase);
+	}
+
+	if realm.fragment().is_some() {
+		return Err(InvalidReturnTo::RealmHasFragment);
+	}
+
+	if realm.scheme() != return_to.scheme() {
+		return Err(InvalidReturnTo::SchemeMismatch);
+	}
+
+	let (Some(realm_host), Some(return_to_host)) = (realm.host_str(), return_to.host_str()) else {
+		return Err(InvalidReturnTo::HostMismatch);
+	};
+
+	let host_matches = match realm_host.strip_prefix("*.") {
+		None => realm_host.eq_ignore_ascii_case(return_to_host),
+		Some(domain) => {
+			return_to_host.eq_ignore_ascii_case(domain)
+				|| return_to_host
+					.len()
+					.checked_sub(domain.len() + 1)
+					.and_then(|idx| return_to_host.get(idx..))
+					.and_then(|suffix| suffix.strip_prefix('.'))
+					.is_some_and(|suffix| suffix.eq_ignore_ascii_case(domain))
+		}
+	};
+
+	if !host_matches {
+		return Err(InvalidReturnTo::HostMismatch);
 	}
+
+	if realm.port_or_known_default() != return_to.port_or_known_default() {
+		return Err(InvalidReturnTo::PortMismatch);
+	}
+
+	let realm_path = realm.path();
+	let path_matches = return_to
+		.path()
+		.strip_prefix(realm_path)
+		.is_some_and(|rest| realm_path.ends_with('/') || rest.is_empty() || rest.starts_with('/'));
+
+	if !path_matches {
+		return Err(InvalidReturnTo::PathMismatch);
+	}
+
+	Ok(())
 }
 
 /// Form parameters that Steam will send to us after a successful login.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(clippy::missing_docs_in_private_items)]
 pub struct OpenIDPayload
 {
 	/// The injected query parameter that was passed as an argument to
-	/// [`LoginForm::redirect_to()`].
+	/// [`LoginUrlBuilder::redirect_to()`].
 	#[serde(skip_serializing)]
 	pub redirect_to: Url,
 
@@ -155,6 +354,16 @@ pub struct OpenIDPayload
 
 impl OpenIDPayload
 {
+	/// How long we wait for Steam to respond to a verification request.
+	pub const VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+	/// How many times we retry a verification request after a transient
+	/// failure.
+	pub const VERIFY_RETRIES: u8 = 2;
+
+	/// The delay before the first retry; it doubles after every attempt.
+	const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+
 	/// Verifies this payload with Steam and extracts the user's SteamID from
 	/// it.
 	#[tracing::instrument(
@@ -165,8 +374,9 @@ impl OpenIDPayload
 		fields(redirect_to = %self.redirect_to),
 	)]
 	async fn verify(
-		mut self,
+		self,
 		public_url: &Url,
+		login_url: &Url,
 		http_client: &reqwest::Client,
 	) -> Result<Self, OpenIDRejection>
 	{
@@ -174,11 +384,80 @@ impl OpenIDPayload
 			return Err(OpenIDRejection::VerifyOpenIDPayload);
 		}
 
-		self.mode = String::from("check_authentication");
+		self.verify_at(login_url.as_str(), http_client, Self::VERIFY_TIMEOUT, Self::VERIFY_RETRIES)
+			.await?;
+
+		Ok(self)
+	}
+
+	/// Verifies this payload with Steam.
+	///
+	/// Every attempt is bounded by `timeout`. Timeouts, connection errors, and
+	/// server errors are retried up to `retries` times with exponential
+	/// backoff. If every attempt fails, the last attempt's error is returned,
+	/// which is [`OpenIDRejection::Timeout`] if that attempt timed out.
+	pub async fn verify_with(
+		&self,
+		http_client: &reqwest::Client,
+		timeout: Duration,
+		retries: u8,
+	) -> Result<(), OpenIDRejection>
+	{
+		self.verify_at(LoginForm::LOGIN_URL, http_client, timeout, retries)
+			.await
+	}
+
+	/// The implementation of [`OpenIDPayload::verify_with()`], using
+	/// `login_url` as the OpenID provider.
+	async fn verify_at(
+		&self,
+		login_url: &str,
+		http_client: &reqwest::Client,
+		timeout: Duration,
+		retries: u8,
+	) -> Result<(), OpenIDRejection>
+	{
+		let payload = Self { mode: String::from("check_authentication"), ..self.clone() };
+		let mut backoff = Self::INITIAL_BACKOFF;
+		let mut attempt = 0;
+
+		loop {
+			let error = match tokio::time::timeout(
+				timeout,
+				payload.check_authentication(login_url, http_client),
+			)
+			.await
+			{
+				Ok(Ok(())) => return Ok(()),
+				Ok(Err(OpenIDRejection::Http(error))) if is_transient(&error) => {
+					OpenIDRejection::Http(error)
+				}
+				Ok(Err(error)) => return Err(error),
+				Err(_) => OpenIDRejection::Timeout,
+			};
+
+			if attempt == retries {
+				return Err(error);
+			}
+
+			tracing::debug!(%error, attempt, ?backoff, "retrying openid verification");
+			tokio::time::sleep(backoff).await;
+
+			backoff *= 2;
+			attempt += 1;
+		}
+	}
 
+	/// Sends this payload to `login_url` for verification.
+	async fn check_authentication(
+		&self,
+		login_url: &str,
+		http_client: &reqwest::Client,
+	) -> Result<(), OpenIDRejection>
+	{
 		let response = http_client
-			.post(LoginForm::LOGIN_URL)
-			.form(&self)
+			.post(login_url)
+			.form(self)
 			.send()
 			.await
 			.and_then(reqwest::Response::error_for_status)?
@@ -196,7 +475,7 @@ impl OpenIDPayload
 
 		tracing::debug!("user logged in");
 
-		Ok(self)
+		Ok(())
 	}
 
 	/// Extracts the SteamID from this form.
@@ -204,12 +483,22 @@ impl OpenIDPayload
 	{
 		self.claimed_id
 			.path_segments()
-			.and_then(|segments| segments.last())
+			.and_then(|mut segments| segments.next_back())
 			.and_then(|segment| segment.parse::<SteamID>().ok())
 			.expect("invalid response from steam")
 	}
 }
 
+/// Whether a failed request to Steam is worth retrying.
+fn is_transient(error: &reqwest::Error) -> bool
+{
+	error.is_timeout()
+		|| error.is_connect()
+		|| error
+			.status()
+			.is_some_and(|status| status.is_server_error())
+}
+
 #[async_trait]
 impl<S> FromRequestParts<S> for OpenIDPayload
 where
@@ -233,9 +522,235 @@ where
 		let payload = Query::<Self>::from_request_parts(req, state)
 			.await?
 			.0
-			.verify(&steam_service.api_url, &steam_service.http_client)
+			.verify(
+				&steam_service.api_url,
+				&steam_service.openid_login_url,
+				&steam_service.http_client,
+			)
 			.await?;
 
 		Ok(payload)
 	}
 }
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing;
+
+	/// Shorthand for validating two URL strings.
+	fn validate(realm: &str, return_to: &str) -> color_eyre::Result<Result<(), InvalidReturnTo>>
+	{
+		Ok(validate_return_to(&realm.parse()?, &return_to.parse()?))
+	}
+
+	#[test]
+	fn return_to_within_realm() -> color_eyre::Result<()>
+	{
+		for (realm, return_to) in [
+			("https://api.cs2kz.org", "https://api.cs2kz.org/auth/callback"),
+			("https://api.cs2kz.org/", "https://api.cs2kz.org/auth/callback?foo=bar"),
+			("https://cs2kz.org/api", "https://cs2kz.org/api"),
+			("https://cs2kz.org/api", "https://cs2kz.org/api/auth/callback"),
+			("https://cs2kz.org/api/", "https://cs2kz.org/api/auth/callback"),
+			("https://*.cs2kz.org", "https://api.cs2kz.org/auth/callback"),
+			("https://*.cs2kz.org", "https://cs2kz.org/auth/callback"),
+			("https://cs2kz.org:443", "https://CS2KZ.org/auth/callback"),
+		] {
+			let result = validate(realm, return_to)?;
+
+			testing::assert!(result.is_ok(), "`{return_to}` should be within `{realm}`");
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn return_to_outside_realm() -> color_eyre::Result<()>
+	{
+		for (realm, return_to, expected) in [
+			(
+				"https://api.cs2kz.org",
+				"https://evil.example.com/auth/callback",
+				InvalidReturnTo::HostMismatch,
+			),
+			(
+				"https://api.cs2kz.org",
+				"https://cs2kz.org/auth/callback",
+				InvalidReturnTo::HostMismatch,
+			),
+			(
+				"https://*.cs2kz.org",
+				"https://notcs2kz.org/auth/callback",
+				InvalidReturnTo::HostMismatch,
+			),
+			(
+				"https://api.cs2kz.org",
+				"http://api.cs2kz.org/auth/callback",
+				InvalidReturnTo::SchemeMismatch,
+			),
+			(
+				"https://api.cs2kz.org",
+				"https://api.cs2kz.org:8443/auth/callback",
+				InvalidReturnTo::PortMismatch,
+			),
+			("https://cs2kz.org/api", "https://cs2kz.org/apix", InvalidReturnTo::PathMismatch),
+			(
+				"https://cs2kz.org/api",
+				"https://cs2kz.org/auth/callback",
+				InvalidReturnTo::PathMismatch,
+			),
+			(
+				"https://cs2kz.org/#foo",
+				"https://cs2kz.org/auth/callback",
+				InvalidReturnTo::RealmHasFragment,
+			),
+		] {
+			let result = validate(realm, return_to)?;
+
+			testing::assert_eq!(result, Err::<(), _>(expected));
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn build_login_url() -> color_eyre::Result<()>
+	{
+		let realm = Url::parse("https://cs2kz.org/api/")?;
+		let return_to = realm.join("auth/callback")?;
+		let redirect_to = Url::parse("https://dashboard.cs2kz.org")?;
+		let url = LoginUrlBuilder::new(realm, return_to)
+			.redirect_to(&redirect_to)
+			.build()?;
+
+		let return_to = url
+			.query_pairs()
+			.find(|(key, _)| key == "openid.return_to")
+			.map(|(_, value)| value.into_owned());
+
+		testing::assert_eq!(url.host_str(), Some("steamcommunity.com"));
+		testing::assert_eq!(
+			return_to.as_deref(),
+			Some("https://cs2kz.org/api/auth/callback?redirect_to=https%3A%2F%2Fdashboard.cs2kz.org%2F"),
+		);
+
+		let result = LoginUrlBuilder::new(
+			Url::parse("https://cs2kz.org")?,
+			Url::parse("https://example.com/auth/callback")?,
+		)
+		.build();
+
+		testing::assert_matches!(result, Err(InvalidReturnTo::HostMismatch));
+
+		Ok(())
+	}
+
+	#[test]
+	fn login_form_keeps_realm_path() -> color_eyre::Result<()>
+	{
+		let redirect_to = Url::parse("https://dashboard.cs2kz.org")?;
+
+		for realm in ["https://cs2kz.org/api", "https://cs2kz.org/api/"] {
+			let url = LoginForm::new(realm.parse()?, LoginForm::LOGIN_URL.parse()?)
+				.redirect_to(&redirect_to)?;
+
+			let param = |name: &str| {
+				url.query_pairs()
+					.find(|(key, _)| key == name)
+					.map(|(_, value)| value.into_owned())
+			};
+
+			testing::assert_eq!(param("openid.realm").as_deref(), Some("https://cs2kz.org/api/"));
+			testing::assert_eq!(
+				param("openid.return_to").as_deref(),
+				Some("https://cs2kz.org/api/auth/callback?redirect_to=https%3A%2F%2Fdashboard.cs2kz.org%2F"),
+			);
+		}
+
+		let result =
+			LoginForm::new("mailto:admin@cs2kz.org".parse()?, LoginForm::LOGIN_URL.parse()?)
+				.redirect_to(&redirect_to);
+
+		testing::assert_matches!(result, Err(InvalidReturnTo::RealmCannotBeABase));
+
+		Ok(())
+	}
+
+	/// Spawns an OpenID provider that takes `delay` to respond to the first
+	/// `slow_requests` requests, and returns its URL.
+	async fn spawn_slow_provider(
+		slow_requests: usize,
+		delay: Duration,
+	) -> color_eyre::Result<String>
+	{
+		use std::sync::atomic::{AtomicUsize, Ordering};
+		use std::sync::Arc;
+
+		let requests = Arc::new(AtomicUsize::new(0));
+		let router = axum::Router::new().route(
+			"/openid/login",
+			axum::routing::post(move || async move {
+				if requests.fetch_add(1, Ordering::SeqCst) < slow_requests {
+					tokio::time::sleep(delay).await;
+				}
+
+				"ns:http://specs.openid.net/auth/2.0\nis_valid:true\n"
+			}),
+		);
+
+		let url = testing::spawn_mock_server(router)
+			.await?
+			.join("openid/login")?;
+
+		Ok(url.into())
+	}
+
+	/// A payload as Steam would send it to us.
+	fn payload() -> color_eyre::Result<OpenIDPayload>
+	{
+		Ok(OpenIDPayload {
+			redirect_to: Url::parse("https://dashboard.cs2kz.org")?,
+			namespace: String::from("http://specs.openid.net/auth/2.0"),
+			identity: None,
+			claimed_id: Url::parse("https://steamcommunity.com/openid/id/76561198282622073")?,
+			mode: String::from("id_res"),
+			return_to: Url::parse("https://api.cs2kz.org/auth/callback")?,
+			op_endpoint: String::from(LoginForm::LOGIN_URL),
+			response_nonce: String::from("2024-01-01T00:00:00Zabcdef"),
+			invalidate_handle: None,
+			assoc_handle: String::from("1234567890"),
+			signed: String::from(
+				"signed,op_endpoint,claimed_id,identity,return_to,response_nonce,assoc_handle",
+			),
+			sig: String::from("c2lnbmF0dXJl"),
+		})
+	}
+
+	#[tokio::test]
+	async fn verify_retries_after_timeout() -> color_eyre::Result<()>
+	{
+		let login_url = spawn_slow_provider(1, Duration::from_secs(2)).await?;
+		let result = payload()?
+			.verify_at(&login_url, &reqwest::Client::new(), Duration::from_millis(200), 1)
+			.await;
+
+		testing::assert_matches!(result, Ok(()));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn verify_times_out_after_exhausting_retries() -> color_eyre::Result<()>
+	{
+		let login_url = spawn_slow_provider(usize::MAX, Duration::from_secs(2)).await?;
+		let result = payload()?
+			.verify_at(&login_url, &reqwest::Client::new(), Duration::from_millis(100), 2)
+			.await;
+
+		testing::assert_matches!(result, Err(OpenIDRejection::Timeout));
+
+		Ok(())
+	}
+}
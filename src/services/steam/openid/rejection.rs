@@ -27,6 +27,11 @@ pub enum OpenIDRejection
 	/// invalid.
 	#[error("failed to verify openid payload with Steam")]
 	VerifyOpenIDPayload,
+
+	/// Steam did not respond to our verification request in time, even after
+	/// retrying.
+	#[error("timed out verifying openid payload with Steam")]
+	Timeout,
 }
 
 impl IntoProblemDetails for OpenIDRejection
@@ -37,6 +42,7 @@ impl IntoProblemDetails for OpenIDRejection
 			Self::Query(_) => ProblemType::InvalidQueryString,
 			Self::Http(source) => source.problem_type(),
 			Self::VerifyOpenIDPayload => ProblemType::InvalidOpenIDPayload,
+			Self::Timeout => ProblemType::ExternalService,
 		}
 	}
 }
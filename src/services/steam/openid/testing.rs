@@ -0,0 +1,287 @@
+//! A mock OpenID provider for tests.
+//!
+//! [`MockProvider`] serves a minimal version of Steam's OpenID endpoint on a
+//! local port, so the login flow can be tested without network access. Every
+//! login succeeds as the [`SteamID`] the provider was created with.
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use axum::extract::{RawQuery, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::get;
+use axum::Router;
+use cs2kz::SteamID;
+use url::Url;
+
+use crate::testing::spawn_mock_server;
+
+/// The route the provider serves its endpoint on.
+const LOGIN_ROUTE: &str = "/openid/login";
+
+/// The OpenID 2.0 namespace.
+const NAMESPACE: &str = "http://specs.openid.net/auth/2.0";
+
+/// The fields covered by the assertion's signature.
+const SIGNED_FIELDS: &str =
+	"signed,op_endpoint,claimed_id,identity,return_to,response_nonce,assoc_handle";
+
+/// A handle we hand out for every assertion; we don't use associations.
+const ASSOC_HANDLE: &str = "1234567890";
+
+/// A local OpenID provider that logs everyone in as a fixed user.
+#[derive(Debug)]
+pub struct MockProvider
+{
+	/// State shared with the server.
+	state: Arc<ProviderState>,
+}
+
+/// State shared between a [`MockProvider`] and its request handlers.
+#[derive(Debug)]
+struct ProviderState
+{
+	/// The URL of the provider's endpoint.
+	///
+	/// This is only known once the server is running.
+	login_url: OnceLock<Url>,
+
+	/// The user every login resolves to.
+	steam_id: SteamID,
+
+	/// Randomly seeded; used for "signing" assertions.
+	hasher: RandomState,
+
+	/// Used for generating unique nonces.
+	nonce: AtomicU64,
+}
+
+impl MockProvider
+{
+	/// Spawns a new provider on a random local port.
+	///
+	/// Every login will resolve to `steam_id`.
+	pub async fn spawn(steam_id: SteamID) -> io::Result<Self>
+	{
+		let state = Arc::new(ProviderState {
+			login_url: OnceLock::new(),
+			steam_id,
+			hasher: RandomState::new(),
+			nonce: AtomicU64::new(0),
+		});
+
+		let router = Router::new()
+			.route(LOGIN_ROUTE, get(login).post(check_authentication))
+			.with_state(Arc::clone(&state));
+
+		let login_url = spawn_mock_server(router)
+			.await?
+			.join(LOGIN_ROUTE)
+			.expect("this is a valid url");
+
+		state
+			.login_url
+			.set(login_url)
+			.expect("we only set this once");
+
+		Ok(Self { state })
+	}
+
+	/// The URL of this provider's endpoint.
+	///
+	/// This can be used in place of [`LoginForm::LOGIN_URL`].
+	///
+	/// [`LoginForm::LOGIN_URL`]: super::LoginForm::LOGIN_URL
+	pub fn login_url(&self) -> &Url
+	{
+		self.state.login_url()
+	}
+
+	/// Returns the URL a user would be sent back to after logging in, i.e.
+	/// `return_to` with a signed assertion encoded in its query string.
+	pub fn assertion(&self, return_to: &Url) -> Url
+	{
+		self.state.assertion(return_to)
+	}
+}
+
+impl ProviderState
+{
+	/// See [`MockProvider::login_url()`].
+	fn login_url(&self) -> &Url
+	{
+		self.login_url
+			.get()
+			.expect("the url is set before the server is reachable")
+	}
+
+	/// See [`MockProvider::assertion()`].
+	fn assertion(&self, return_to: &Url) -> Url
+	{
+		let claimed_id = format!("https://steamcommunity.com/openid/id/{}", self.steam_id.as_u64());
+		let nonce = format!("mock-{}", self.nonce.fetch_add(1, Ordering::Relaxed));
+		let sig = self.sign(&claimed_id, return_to.as_str(), &nonce);
+		let mut url = return_to.clone();
+
+		url.query_pairs_mut()
+			.append_pair("openid.ns", NAMESPACE)
+			.append_pair("openid.mode", "id_res")
+			.append_pair("openid.op_endpoint", self.login_url().as_str())
+			.append_pair("openid.claimed_id", &claimed_id)
+			.append_pair("openid.identity", &claimed_id)
+			.append_pair("openid.return_to", return_to.as_str())
+			.append_pair("openid.response_nonce", &nonce)
+			.append_pair("openid.assoc_handle", ASSOC_HANDLE)
+			.append_pair("openid.signed", SIGNED_FIELDS)
+			.append_pair("openid.sig", &sig);
+
+		url
+	}
+
+	/// Computes the signature for an assertion.
+	fn sign(&self, claimed_id: &str, return_to: &str, nonce: &str) -> String
+	{
+		format!("{:016x}", self.hasher.hash_one((claimed_id, return_to, nonce)))
+	}
+
+	/// Checks whether `params` contain an assertion signed by us.
+	fn is_valid(&self, params: &HashMap<String, String>) -> bool
+	{
+		let param = |name: &str| params.get(name).map(String::as_str);
+
+		let (Some(claimed_id), Some(return_to), Some(nonce), Some(sig)) = (
+			param("openid.claimed_id"),
+			param("openid.return_to"),
+			param("openid.response_nonce"),
+			param("openid.sig"),
+		) else {
+			return false;
+		};
+
+		param("openid.mode") == Some("check_authentication")
+			&& self.sign(claimed_id, return_to, nonce) == sig
+	}
+}
+
+/// Handles `checkid_setup` requests by immediately redirecting back to the
+/// relying party.
+async fn login(State(state): State<Arc<ProviderState>>, RawQuery(query): RawQuery) -> Response
+{
+	let params =
+		serde_urlencoded::from_str::<HashMap<String, String>>(query.as_deref().unwrap_or_default())
+			.unwrap_or_default();
+
+	if params.get("openid.mode").map(String::as_str) != Some("checkid_setup") {
+		return (StatusCode::BAD_REQUEST, "unsupported openid.mode").into_response();
+	}
+
+	let Some(return_to) = params
+		.get("openid.return_to")
+		.and_then(|return_to| return_to.parse::<Url>().ok())
+	else {
+		return (StatusCode::BAD_REQUEST, "invalid openid.return_to").into_response();
+	};
+
+	Redirect::to(state.assertion(&return_to).as_str()).into_response()
+}
+
+/// Handles `check_authentication` requests.
+async fn check_authentication(State(state): State<Arc<ProviderState>>, body: String) -> String
+{
+	let params = serde_urlencoded::from_str::<HashMap<String, String>>(&body).unwrap_or_default();
+	let is_valid = state.is_valid(&params);
+
+	format!("ns:{NAMESPACE}\nis_valid:{is_valid}\n")
+}
+
+#[cfg(test)]
+mod tests
+{
+	use axum::extract::FromRequestParts;
+	use tap::Tap;
+
+	use super::*;
+	use crate::services::steam::openid::OpenIDRejection;
+	use crate::services::steam::OpenIDPayload;
+	use crate::testing::{self, ALPHAKEKS_ID};
+
+	/// Goes through the login flow against a [`MockProvider`] and returns the
+	/// URL the provider redirected back to.
+	async fn login_as(steam_id: SteamID) -> color_eyre::Result<(MockProvider, Url)>
+	{
+		let provider = MockProvider::spawn(steam_id).await?;
+		let steam_svc = testing::steam_svc().with_openid_login_url(provider.login_url().clone());
+		let redirect_to = Url::parse("https://dashboard.cs2kz.org")?;
+		let login_url = steam_svc.openid_login_form().redirect_to(&redirect_to)?;
+
+		let response = reqwest::Client::builder()
+			.redirect(reqwest::redirect::Policy::none())
+			.build()?
+			.get(login_url)
+			.send()
+			.await?;
+
+		let callback_url = response
+			.headers()
+			.get(http::header::LOCATION)
+			.ok_or_else(|| color_eyre::eyre::eyre!("provider did not redirect"))?
+			.to_str()?
+			.parse::<Url>()?;
+
+		Ok((provider, callback_url))
+	}
+
+	/// Runs the [`OpenIDPayload`] extractor on a request to `url`.
+	async fn extract(url: &Url, login_url: &Url) -> Result<OpenIDPayload, OpenIDRejection>
+	{
+		let steam_svc = testing::steam_svc().with_openid_login_url(login_url.clone());
+		let (mut parts, ()) = http::Request::get(url.as_str())
+			.body(())
+			.expect("valid request")
+			.into_parts();
+
+		OpenIDPayload::from_request_parts(&mut parts, &steam_svc).await
+	}
+
+	#[tokio::test]
+	async fn login_as_fixed_user() -> color_eyre::Result<()>
+	{
+		let (provider, callback_url) = login_as(ALPHAKEKS_ID).await?;
+		let payload = extract(&callback_url, provider.login_url()).await?;
+
+		testing::assert_eq!(payload.steam_id(), ALPHAKEKS_ID);
+		testing::assert_eq!(payload.redirect_to.as_str(), "https://dashboard.cs2kz.org/");
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn rejects_tampered_assertion() -> color_eyre::Result<()>
+	{
+		let (provider, callback_url) = login_as(ALPHAKEKS_ID).await?;
+		let tampered = callback_url.clone().tap_mut(|url| {
+			let pairs = callback_url.query_pairs().map(|(key, value)| {
+				let value = match &*key {
+					"openid.claimed_id" => {
+						"https://steamcommunity.com/openid/id/76561197960265729".into()
+					}
+					_ => value,
+				};
+
+				(key, value)
+			});
+
+			url.query_pairs_mut().clear().extend_pairs(pairs);
+		});
+
+		let result = extract(&tampered, provider.login_url()).await;
+
+		testing::assert_matches!(result, Err(OpenIDRejection::VerifyOpenIDPayload));
+
+		Ok(())
+	}
+}
@@ -7,8 +7,11 @@
 
 use std::io;
 use std::path::Path;
+use std::process::ExitStatus;
+use std::time::Duration;
 
 use tap::{Pipe, TryConv};
+use thiserror::Error;
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::process::Command;
@@ -18,6 +21,35 @@ crate::macros::make_id! {
 	WorkshopID as u32
 }
 
+/// The errors that can occur when downloading a map.
+#[derive(Debug, Error)]
+pub enum DownloadError
+{
+	/// DepotDownloader did not finish in time and was killed.
+	#[error("download timed out after {after:?}")]
+	Timeout
+	{
+		/// How long we waited.
+		after: Duration,
+	},
+
+	/// DepotDownloader was killed by a signal.
+	#[error("DepotDownloader was killed")]
+	Killed,
+
+	/// DepotDownloader exited unsuccessfully.
+	#[error("DepotDownloader did not complete successfully ({status})")]
+	Failed
+	{
+		/// DepotDownloader's exit status.
+		status: ExitStatus,
+	},
+
+	/// We failed to run DepotDownloader or to open the downloaded file.
+	#[error(transparent)]
+	Io(#[from] io::Error),
+}
+
 /// A handle to a downloaded workshop map.
 #[derive(Debug)]
 #[must_use = "`MapFile` contains a file handle"]
@@ -32,27 +64,40 @@ impl MapFile
 	/// Downloads a map using [DepotDownloader] and returns a handle to the
 	/// `.vpk` file.
 	///
+	/// If DepotDownloader doesn't finish within `timeout`, it is killed. The
+	/// process is also killed if the returned future is dropped before it
+	/// completes, so cancelled requests don't leave stray downloads behind.
+	///
 	/// [DepotDownloader]: https://github.com/SteamRE/DepotDownloader
 	#[tracing::instrument(level = "trace", err(Debug, level = "debug"))]
 	pub(super) async fn download(
 		id: WorkshopID,
 		artifacts_path: &Path,
 		depot_downloader_path: &Path,
-	) -> io::Result<Self>
+		timeout: Duration,
+	) -> Result<Self, DownloadError>
 	{
 		let out_dir = artifacts_path;
 		let depot_downloader = depot_downloader_path;
 
 		tracing::debug!(?out_dir, "invoking {depot_downloader:?}");
 
-		let result = Command::new(depot_downloader)
+		let mut child = Command::new(depot_downloader)
 			.args(["-app", "730", "-pubfile"])
 			.arg(id.to_string())
 			.arg("-dir")
 			.arg(out_dir)
-			.spawn()?
-			.wait_with_output()
-			.await?;
+			.kill_on_drop(true)
+			.spawn()?;
+
+		let status = match tokio::time::timeout(timeout, child.wait()).await {
+			Ok(status) => status?,
+			Err(_) => {
+				tracing::warn!(?timeout, "DepotDownloader timed out; killing it");
+				child.kill().await?;
+				return Err(DownloadError::Timeout { after: timeout });
+			}
+		};
 
 		let mut stdout = tokio::io::stdout();
 		let mut stderr = tokio::io::stderr();
@@ -65,8 +110,12 @@ impl MapFile
 			};
 		}
 
-		if !result.status.success() {
-			return Err(io::Error::other("DepotDownloader did not complete successfully"));
+		if status.code().is_none() {
+			return Err(DownloadError::Killed);
+		}
+
+		if !status.success() {
+			return Err(DownloadError::Failed { status });
 		}
 
 		let out_file_path = out_dir.join(format!("{id}.vpk"));
@@ -105,3 +154,110 @@ impl MapFile
 		Ok(md5::compute(&buf))
 	}
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests
+{
+	use std::os::unix::fs::PermissionsExt;
+	use std::path::PathBuf;
+	use std::{env, fs, process};
+
+	use super::*;
+	use crate::testing;
+
+	/// A fake DepotDownloader that records its PID in the output directory
+	/// and then hangs.
+	const HANGING_DOWNLOADER: &str = "#!/bin/sh\necho $$ > \"$6/pid\"\nexec sleep 30\n";
+
+	/// Creates a fresh directory containing [`HANGING_DOWNLOADER`] and returns
+	/// its path.
+	fn setup(name: &str) -> color_eyre::Result<PathBuf>
+	{
+		let dir = env::temp_dir().join(format!("cs2kz-api-{name}-{}", process::id()));
+		let script = dir.join("DepotDownloader");
+
+		if dir.exists() {
+			fs::remove_dir_all(&dir)?;
+		}
+
+		fs::create_dir_all(&dir)?;
+		fs::write(&script, HANGING_DOWNLOADER)?;
+		fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+		Ok(dir)
+	}
+
+	/// Waits for the fake downloader in `dir` to write its PID.
+	async fn downloader_pid(dir: &Path) -> color_eyre::Result<u32>
+	{
+		for _ in 0..100 {
+			if let Ok(pid) = fs::read_to_string(dir.join("pid")) {
+				if let Ok(pid) = pid.trim().parse() {
+					return Ok(pid);
+				}
+			}
+
+			tokio::time::sleep(Duration::from_millis(20)).await;
+		}
+
+		color_eyre::eyre::bail!("fake DepotDownloader never started");
+	}
+
+	/// Waits for the process with the given `pid` to disappear entirely (i.e.
+	/// not even as a zombie) and returns whether it did.
+	async fn is_reaped(pid: u32) -> bool
+	{
+		for _ in 0..100 {
+			if !Path::new(&format!("/proc/{pid}")).exists() {
+				return true;
+			}
+
+			tokio::time::sleep(Duration::from_millis(20)).await;
+		}
+
+		false
+	}
+
+	#[tokio::test]
+	async fn download_times_out() -> color_eyre::Result<()>
+	{
+		let dir = setup("download-times-out")?;
+		let timeout = Duration::from_millis(500);
+		let error = MapFile::download(69.into(), &dir, &dir.join("DepotDownloader"), timeout)
+			.await
+			.unwrap_err();
+
+		testing::assert_matches!(error, DownloadError::Timeout { after } if after == timeout);
+
+		let pid = downloader_pid(&dir).await?;
+
+		testing::assert!(is_reaped(pid).await);
+
+		fs::remove_dir_all(&dir)?;
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn cancelled_download_is_reaped() -> color_eyre::Result<()>
+	{
+		let dir = setup("cancelled-download-is-reaped")?;
+		let depot_downloader = dir.join("DepotDownloader");
+		let download =
+			MapFile::download(69.into(), &dir, &depot_downloader, Duration::from_secs(60));
+
+		let (res, pid) = tokio::join!(
+			tokio::time::timeout(Duration::from_secs(1), download),
+			downloader_pid(&dir),
+		);
+
+		let pid = pid?;
+
+		testing::assert!(res.is_err(), "download should have been cancelled");
+		testing::assert!(is_reaped(pid).await);
+
+		fs::remove_dir_all(&dir)?;
+
+		Ok(())
+	}
+}
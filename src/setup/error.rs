@@ -18,4 +18,8 @@ pub enum Error
 	/// Something went wrong applying database migrations.
 	#[error("failed to run migrations")]
 	Migrations(#[from] sqlx::migrate::MigrateError),
+
+	/// The CORS configuration is invalid.
+	#[error("invalid CORS configuration")]
+	Cors(#[from] crate::middleware::cors::CorsConfigError),
 }
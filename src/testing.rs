@@ -1,85 +1,59 @@
 //! This module contains helpers for unit/integration tests.
+//!
+//! Only [`spawn_mock_server()`] is needed by the `testing` feature; everything
+//! else is only compiled for our own tests.
 
-use color_eyre::eyre::WrapErr;
-use cs2kz::SteamID;
-use serde::de::DeserializeOwned;
-use sqlx::{MySql, Pool};
+use std::io;
+
+use axum::Router;
+use tokio::net::TcpListener;
 use url::Url;
 
-use crate::services::{
-	AuthService,
-	BanService,
-	MapService,
-	PlayerService,
-	ServerService,
-	SteamService,
+#[cfg(test)]
+mod fixtures;
+
+#[cfg(test)]
+pub use fixtures::{
+	admin_svc,
+	audit_svc,
+	auth_svc,
+	ban_svc,
+	jumpstat_svc,
+	lazy_pool,
+	map_svc,
+	parse_body,
+	player_svc,
+	record_svc,
+	server_svc,
+	steam_svc,
+	ALPHAKEKS_ID,
 };
 
-pub const ALPHAKEKS_ID: SteamID = match SteamID::new(76561198282622073_u64) {
-	Some(id) => id,
-	None => unreachable!(),
-};
+#[cfg(test)]
+mod shared;
 
-pub fn steam_svc() -> SteamService
-{
-	let http_client = reqwest::Client::new();
-	let api_url = Url::parse("http://127.0.0.1").unwrap();
-	let steam_api_key = String::new();
-
-	SteamService::new(api_url, steam_api_key, Default::default(), Default::default(), http_client)
-}
+#[cfg(test)]
+pub use shared::LogBuffer;
+#[cfg(test)]
+pub(crate) use shared::{assert, assert_eq, assert_matches, assert_ne};
 
-pub fn auth_svc(database: Pool<MySql>) -> AuthService
+/// Serves `router` on a random local port and returns its base URL.
+///
+/// The server keeps running until the runtime it was spawned on shuts down.
+pub async fn spawn_mock_server(router: Router) -> io::Result<Url>
 {
-	let http_client = reqwest::Client::new();
-	let steam_svc = steam_svc();
-	let jwt_secret = String::from("Zm9vYmFyYmF6");
-	let cookie_domain = String::from("localhost");
+	let listener = TcpListener::bind("127.0.0.1:0").await?;
+	let url = format!("http://{}", listener.local_addr()?)
+		.parse::<Url>()
+		.expect("this is a valid url");
 
-	AuthService::new(database, http_client, steam_svc, jwt_secret, cookie_domain)
-}
-
-pub fn player_svc(database: Pool<MySql>) -> PlayerService
-{
-	let auth_svc = auth_svc(database.clone());
-	let steam_svc = steam_svc();
+	tokio::spawn(async move { axum::serve(listener, router).await });
 
-	PlayerService::new(database, auth_svc, steam_svc)
-}
-
-pub fn map_svc(database: Pool<MySql>) -> MapService
-{
-	let auth_svc = auth_svc(database.clone());
-	let steam_svc = steam_svc();
-
-	MapService::new(database, auth_svc, steam_svc)
-}
-
-pub fn server_svc(database: Pool<MySql>) -> ServerService
-{
-	let auth_svc = auth_svc(database.clone());
-
-	ServerService::new(database, auth_svc)
-}
-
-pub fn ban_svc(database: Pool<MySql>) -> BanService
-{
-	let auth_svc = auth_svc(database.clone());
-
-	BanService::new(database, auth_svc)
-}
-
-pub async fn parse_body<T>(body: axum::body::Body) -> color_eyre::Result<T>
-where
-	T: DeserializeOwned,
-{
-	let bytes = axum::body::to_bytes(body, usize::MAX).await?;
-	let parsed = serde_json::from_slice::<T>(&bytes).context("parse body")?;
-
-	Ok(parsed)
+	Ok(url)
 }
 
 /// Global constructor that will run before tests.
+#[cfg(test)]
 #[ctor::ctor]
 fn ctor()
 {
@@ -112,45 +86,3 @@ fn ctor()
 		env::set_var("DATABASE_URL", database_url.as_str());
 	}
 }
-
-macro_rules! assert {
-	($expr:expr $(, $($msg:tt)*)?) => {
-		::color_eyre::eyre::ensure!($expr $(, $($msg)*)?)
-	};
-}
-
-macro_rules! assert_eq {
-	($lhs:expr, $rhs:expr $(, $($msg:tt)*)?) => {
-		if &$lhs != &$rhs {
-			::color_eyre::eyre::bail!(
-				"assertion `{} == {}` failed\n  lhs: {:?}\n  rhs: {:?}",
-				stringify!($lhs),
-				stringify!($rhs),
-				$lhs,
-				$rhs,
-			);
-		}
-	};
-}
-
-macro_rules! assert_ne {
-	($lhs:expr, $rhs:expr $(, $($msg:tt)*)?) => {
-		if &$lhs == &$rhs {
-			::color_eyre::eyre::bail!(
-				"assertion `{} != {}` failed\n  lhs: {:?}\n  rhs: {:?}",
-				stringify!($lhs),
-				stringify!($rhs),
-				$lhs,
-				$rhs,
-			);
-		}
-	};
-}
-
-macro_rules! assert_matches {
-	($expr:expr, $pat:pat $(if $cond:expr)? $(, $($msg:tt)*)?) => {
-		::color_eyre::eyre::ensure!(matches!($expr, $pat $(if $cond)? $(, $($msg)*)?))
-	};
-}
-
-pub(crate) use {assert, assert_eq, assert_matches, assert_ne};
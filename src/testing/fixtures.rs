@@ -0,0 +1,128 @@
+//! Fixtures for our own tests.
+
+use color_eyre::eyre::WrapErr;
+use cs2kz::SteamID;
+use serde::de::DeserializeOwned;
+use sqlx::mysql::MySqlPoolOptions;
+use sqlx::{MySql, Pool};
+use url::Url;
+
+use crate::events::EventBus;
+use crate::runtime::config::CookieConfig;
+use crate::services::auth::JwtKeys;
+use crate::services::{
+	AdminService,
+	AuditService,
+	AuthService,
+	BanService,
+	JumpstatService,
+	MapService,
+	PlayerService,
+	RecordService,
+	ServerService,
+	SteamService,
+};
+
+pub const ALPHAKEKS_ID: SteamID = match SteamID::new(76561198282622073_u64) {
+	Some(id) => id,
+	None => unreachable!(),
+};
+
+/// Creates a connection pool that never actually connects.
+///
+/// This is for tests that need a service, but never reach the database.
+pub fn lazy_pool() -> Pool<MySql>
+{
+	MySqlPoolOptions::new()
+		.connect_lazy("mysql://localhost/cs2kz")
+		.expect("valid database url")
+}
+
+pub fn steam_svc() -> SteamService
+{
+	let http_client = reqwest::Client::new();
+	let api_url = Url::parse("http://127.0.0.1").unwrap();
+	let steam_api_key = String::new();
+
+	SteamService::new(api_url, steam_api_key, Default::default(), Default::default(), http_client)
+}
+
+pub fn auth_svc(database: Pool<MySql>) -> AuthService
+{
+	let http_client = reqwest::Client::new();
+	let steam_svc = steam_svc();
+	let jwt_keys = JwtKeys::new(None, "Zm9vYmFyYmF6");
+	let cookie_domain = String::from("localhost");
+
+	let cookie_config = CookieConfig::local();
+
+	AuthService::new(database, http_client, steam_svc, jwt_keys, cookie_domain, cookie_config)
+}
+
+pub fn admin_svc(database: Pool<MySql>) -> AdminService
+{
+	let auth_svc = auth_svc(database.clone());
+
+	AdminService::new(database, auth_svc)
+}
+
+pub fn audit_svc(database: Pool<MySql>) -> AuditService
+{
+	let auth_svc = auth_svc(database.clone());
+
+	AuditService::new(database, auth_svc)
+}
+
+pub fn player_svc(database: Pool<MySql>) -> PlayerService
+{
+	let auth_svc = auth_svc(database.clone());
+	let steam_svc = steam_svc();
+
+	PlayerService::new(database, auth_svc, steam_svc)
+}
+
+pub fn map_svc(database: Pool<MySql>) -> MapService
+{
+	let auth_svc = auth_svc(database.clone());
+	let steam_svc = steam_svc();
+
+	MapService::new(database, auth_svc, steam_svc)
+}
+
+pub fn server_svc(database: Pool<MySql>) -> ServerService
+{
+	let auth_svc = auth_svc(database.clone());
+
+	ServerService::new(database, auth_svc, EventBus::new())
+}
+
+pub fn ban_svc(database: Pool<MySql>) -> BanService
+{
+	let auth_svc = auth_svc(database.clone());
+
+	BanService::new(database, auth_svc, EventBus::new())
+}
+
+pub fn jumpstat_svc(database: Pool<MySql>) -> JumpstatService
+{
+	let auth_svc = auth_svc(database.clone());
+
+	JumpstatService::new(database, auth_svc)
+}
+
+pub fn record_svc(database: Pool<MySql>) -> RecordService
+{
+	let auth_svc = auth_svc(database.clone());
+
+	RecordService::new(database, auth_svc, EventBus::new())
+}
+
+pub async fn parse_body<T>(body: axum::body::Body) -> color_eyre::Result<T>
+where
+	T: DeserializeOwned,
+{
+	let bytes = axum::body::to_bytes(body, usize::MAX).await?;
+	let parsed = serde_json::from_slice::<T>(&bytes).context("parse body")?;
+
+	Ok(parsed)
+}
@@ -0,0 +1,92 @@
+//! Test helpers shared by the library and the `cs2kz-api` binary.
+//!
+//! The binary includes this file directly, as it cannot see the library's
+//! private test helpers.
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use tracing_subscriber::fmt::MakeWriter;
+
+/// An in-memory log destination for [`tracing_subscriber`] layers.
+#[derive(Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl LogBuffer
+{
+	/// Returns everything that has been logged so far.
+	pub fn contents(&self) -> String
+	{
+		let bytes = self.0.lock().expect("mutex is not poisoned");
+
+		String::from_utf8_lossy(&bytes).into_owned()
+	}
+}
+
+impl io::Write for LogBuffer
+{
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+	{
+		self.0.lock().expect("mutex is not poisoned").write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()>
+	{
+		Ok(())
+	}
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer
+{
+	type Writer = Self;
+
+	fn make_writer(&'a self) -> Self::Writer
+	{
+		self.clone()
+	}
+}
+
+macro_rules! assert {
+	($expr:expr $(, $($msg:tt)*)?) => {
+		::color_eyre::eyre::ensure!($expr $(, $($msg)*)?)
+	};
+}
+
+macro_rules! assert_eq {
+	($lhs:expr, $rhs:expr $(, $($msg:tt)*)?) => {
+		if &$lhs != &$rhs {
+			::color_eyre::eyre::bail!(
+				"assertion `{} == {}` failed\n  lhs: {:?}\n  rhs: {:?}",
+				stringify!($lhs),
+				stringify!($rhs),
+				$lhs,
+				$rhs,
+			);
+		}
+	};
+}
+
+macro_rules! assert_ne {
+	($lhs:expr, $rhs:expr $(, $($msg:tt)*)?) => {
+		if &$lhs == &$rhs {
+			::color_eyre::eyre::bail!(
+				"assertion `{} != {}` failed\n  lhs: {:?}\n  rhs: {:?}",
+				stringify!($lhs),
+				stringify!($rhs),
+				$lhs,
+				$rhs,
+			);
+		}
+	};
+}
+
+macro_rules! assert_matches {
+	($expr:expr, $pat:pat $(if $cond:expr)? $(, $($msg:tt)*)?) => {
+		::color_eyre::eyre::ensure!(matches!($expr, $pat $(if $cond)? $(, $($msg)*)?))
+	};
+}
+
+pub(crate) use assert;
+pub(crate) use assert_eq;
+pub(crate) use assert_matches;
+pub(crate) use assert_ne;
@@ -1,9 +1,13 @@
 //! This module contains extensions to [`std::time`].
 
-use std::ops;
+use std::str::FromStr;
 use std::time::Duration;
+use std::{fmt, ops};
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+use time::format_description::well_known::{Iso8601, Rfc3339};
+use time::{Date, OffsetDateTime};
 
 /// Extension trait for [`std::time::Duration`] which adds useful associated
 /// constants.
@@ -92,9 +96,9 @@ impl<'de> Deserialize<'de> for Seconds
 	where
 		D: Deserializer<'de>,
 	{
-		f64::deserialize(deserializer)
-			.map(Duration::from_secs_f64)
+		Duration::try_from_secs_f64(f64::deserialize(deserializer)?)
 			.map(Self)
+			.map_err(serde::de::Error::custom)
 	}
 }
 
@@ -102,3 +106,185 @@ crate::macros::sqlx_scalar_forward!(Seconds as f64 => {
 	encode: |self| { self.0.as_secs_f64() },
 	decode: |secs| { Self(Duration::from_secs_f64(secs)) },
 });
+
+/// A point in time that can be parsed from several formats.
+///
+/// This is intended for query parameters, which are typed by hand more often
+/// than not. See [`Timestamp::parse_flexible()`] for the accepted formats.
+/// Timestamps are always serialized as RFC 3339.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, utoipa::ToSchema)]
+#[schema(value_type = String, example = "2024-03-01T12:00:00Z")]
+pub struct Timestamp(pub OffsetDateTime);
+
+impl Timestamp
+{
+	/// Parses a timestamp from any of the following formats:
+	///
+	///    - an RFC 3339 date-time, e.g. `2024-03-01T12:00:00Z`
+	///    - a plain date, e.g. `2024-03-01`, which means midnight UTC
+	///    - a unix timestamp in seconds, e.g. `1709294400`
+	pub fn parse_flexible(value: &str) -> Result<Self, InvalidTimestamp>
+	{
+		let value = value.trim();
+
+		if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) {
+			return value
+				.parse::<i64>()
+				.ok()
+				.and_then(|secs| OffsetDateTime::from_unix_timestamp(secs).ok())
+				.map(Self)
+				.ok_or(InvalidTimestamp);
+		}
+
+		if let Ok(date_time) = OffsetDateTime::parse(value, &Rfc3339) {
+			return Ok(Self(date_time));
+		}
+
+		Date::parse(value, &Iso8601::DATE)
+			.map(|date| Self(date.midnight().assume_utc()))
+			.map_err(|_| InvalidTimestamp)
+	}
+}
+
+/// The error returned by [`Timestamp::parse_flexible()`].
+#[derive(Debug, Error)]
+#[error("invalid timestamp; expected an RFC 3339 date-time, a date (YYYY-MM-DD), or unix seconds")]
+pub struct InvalidTimestamp;
+
+impl ops::Deref for Timestamp
+{
+	type Target = OffsetDateTime;
+
+	fn deref(&self) -> &Self::Target
+	{
+		&self.0
+	}
+}
+
+impl From<OffsetDateTime> for Timestamp
+{
+	fn from(value: OffsetDateTime) -> Self
+	{
+		Self(value)
+	}
+}
+
+impl From<Timestamp> for OffsetDateTime
+{
+	fn from(value: Timestamp) -> Self
+	{
+		value.0
+	}
+}
+
+impl FromStr for Timestamp
+{
+	type Err = InvalidTimestamp;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err>
+	{
+		Self::parse_flexible(s)
+	}
+}
+
+impl fmt::Display for Timestamp
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		let formatted = self.0.format(&Rfc3339).map_err(|_| fmt::Error)?;
+
+		f.write_str(&formatted)
+	}
+}
+
+impl Serialize for Timestamp
+{
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		time::serde::rfc3339::serialize(&self.0, serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for Timestamp
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(serde::de::Error::custom)
+	}
+}
+
+crate::macros::sqlx_scalar_forward!(Timestamp as OffsetDateTime => {
+	encode: |self| { self.0 },
+	decode: |value| { Self(value) },
+});
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn parse_flexible_accepts_rfc3339() -> color_eyre::Result<()>
+	{
+		let parsed = Timestamp::parse_flexible("2024-03-01T12:30:00+02:00")?;
+
+		testing::assert_eq!(parsed.0, OffsetDateTime::from_unix_timestamp(1709289000)?);
+
+		Ok(())
+	}
+
+	#[test]
+	fn parse_flexible_accepts_dates() -> color_eyre::Result<()>
+	{
+		let parsed = Timestamp::parse_flexible("2024-03-01")?;
+
+		testing::assert_eq!(parsed.0, OffsetDateTime::from_unix_timestamp(1709251200)?);
+
+		Ok(())
+	}
+
+	#[test]
+	fn parse_flexible_accepts_unix_seconds() -> color_eyre::Result<()>
+	{
+		let parsed = Timestamp::parse_flexible("1709294400")?;
+
+		testing::assert_eq!(parsed.0, OffsetDateTime::from_unix_timestamp(1709294400)?);
+
+		Ok(())
+	}
+
+	#[test]
+	fn parse_flexible_rejects_garbage() -> color_eyre::Result<()>
+	{
+		for input in ["", "yesterday", "2024-13-01", "01/03/2024", "-5", "99999999999999999999"] {
+			let parsed = Timestamp::parse_flexible(input);
+
+			testing::assert!(parsed.is_err(), "`{input}` should be rejected");
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn deserializes_from_query_strings() -> color_eyre::Result<()>
+	{
+		#[derive(Debug, Deserialize)]
+		struct Params
+		{
+			after: Timestamp,
+		}
+
+		let params = serde_urlencoded::from_str::<Params>("after=2024-03-01")?;
+
+		testing::assert_eq!(params.after.to_string(), "2024-03-01T00:00:00Z");
+
+		Ok(())
+	}
+}
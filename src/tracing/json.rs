@@ -0,0 +1,97 @@
+//! Tracing layer for logging JSON.
+
+use std::fs::{self, OpenOptions};
+use std::io;
+
+use color_eyre::eyre::WrapErr;
+use cs2kz_api::runtime::config::TracingJsonConfig;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Creates a tracing layer that will emit logs as JSON, one object per line.
+///
+/// Every event includes the fields of the spans it was emitted in, such as
+/// `request.id`.
+///
+/// The returned [`WorkerGuard`] must be kept alive so it can flush pending
+/// logs when the application shuts down.
+pub fn layer<S>(
+	config: TracingJsonConfig,
+) -> color_eyre::Result<(impl tracing_subscriber::Layer<S>, WorkerGuard)>
+where
+	S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+	let (writer, guard) = match config.path {
+		None => tracing_appender::non_blocking(io::stdout()),
+		Some(path) => {
+			if let Some(dir) = path.parent().filter(|dir| !dir.exists()) {
+				fs::create_dir_all(dir).context("create log dir")?;
+			}
+
+			let file = OpenOptions::new()
+				.create(true)
+				.append(true)
+				.open(&path)
+				.with_context(|| format!("open {path:?}"))?;
+
+			tracing_appender::non_blocking(file)
+		}
+	};
+
+	Ok((fmt_layer(writer).with_filter(config.filter), guard))
+}
+
+/// Creates the unfiltered JSON layer writing to `writer`.
+fn fmt_layer<S, W>(writer: W) -> impl tracing_subscriber::Layer<S>
+where
+	S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+	W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+	tracing_subscriber::fmt::layer()
+		.json()
+		.with_current_span(true)
+		.with_span_list(true)
+		.with_file(true)
+		.with_level(true)
+		.with_line_number(true)
+		.with_target(true)
+		.with_thread_ids(true)
+		.with_thread_names(true)
+		.with_writer(writer)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use color_eyre::eyre::OptionExt;
+	use tracing_subscriber::layer::SubscriberExt;
+
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn events_are_json() -> color_eyre::Result<()>
+	{
+		let buffer = testing::LogBuffer::default();
+		let subscriber = tracing_subscriber::registry().with(fmt_layer(buffer.clone()));
+
+		tracing::subscriber::with_default(subscriber, || {
+			let _guard = tracing::info_span!("request", request.id = "0192-kz").entered();
+			tracing::info!(map = "kz_checkmate", "approved map");
+		});
+
+		let output = buffer.contents();
+		let line = output.lines().next().ok_or_eyre("no output")?;
+		let event = serde_json::from_str::<serde_json::Value>(line)?;
+
+		testing::assert_eq!(event["level"], "INFO");
+		testing::assert_eq!(event["fields"]["message"], "approved map");
+		testing::assert_eq!(event["fields"]["map"], "kz_checkmate");
+		testing::assert_eq!(event["span"]["request.id"], "0192-kz");
+		testing::assert_eq!(event["spans"][0]["name"], "request");
+
+		Ok(())
+	}
+}
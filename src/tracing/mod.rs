@@ -4,10 +4,11 @@ use color_eyre::eyre::WrapErr;
 use cs2kz_api::runtime::config::TracingConfig;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::Layer;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
 
 mod stderr;
 mod files;
+mod json;
 
 #[cfg(target_os = "linux")]
 mod journald;
@@ -15,6 +16,7 @@ mod journald;
 #[cfg(feature = "console")]
 mod console;
 
+/// Guard returned by [`init()`] that flushes pending logs when dropped.
 #[derive(Debug)]
 pub struct Guard
 {
@@ -24,6 +26,31 @@ pub struct Guard
 		reason = "`WorkerGuard` has a non-trivial destructor which we need to run"
 	)]
 	appender_guard: tracing_appender::non_blocking::WorkerGuard,
+
+	/// The guard returned by the JSON layer's logging thread, if it is enabled.
+	#[expect(
+		dead_code,
+		reason = "`WorkerGuard` has a non-trivial destructor which we need to run"
+	)]
+	json_guard: Option<tracing_appender::non_blocking::WorkerGuard>,
+
+	/// Handle for replacing the global filter at runtime.
+	filter: reload::Handle<EnvFilter, Registry>,
+}
+
+impl Guard
+{
+	/// Returns a function that replaces the global filter.
+	///
+	/// This is meant to be passed to [`Reloader::apply_filter()`].
+	///
+	/// [`Reloader::apply_filter()`]: cs2kz_api::runtime::Reloader::apply_filter
+	pub fn filter_reloader(&self) -> impl Fn(EnvFilter) -> Result<(), String> + Send + Sync
+	{
+		let filter = self.filter.clone();
+
+		move |new_filter| filter.reload(new_filter).map_err(|error| error.to_string())
+	}
 }
 
 /// Initializes [`tracing-subscriber`].
@@ -41,7 +68,15 @@ pub fn init(config: TracingConfig) -> color_eyre::Result<Option<Guard>>
 	let (files, appender_guard) =
 		files::layer(config.files).context("initialize files tracing layer")?;
 
-	let layer = Layer::and_then(stderr, files);
+	let (json, json_guard) = config
+		.json
+		.enable
+		.then(|| json::layer(config.json))
+		.transpose()
+		.context("initialize json tracing layer")?
+		.unzip();
+
+	let layer = Layer::and_then(stderr, files).and_then(json);
 
 	#[cfg(target_os = "linux")]
 	let layer = {
@@ -54,7 +89,8 @@ pub fn init(config: TracingConfig) -> color_eyre::Result<Option<Guard>>
 		Layer::and_then(layer, journald)
 	};
 
-	let registry = tracing_subscriber::registry().with(layer.with_filter(config.filter));
+	let (filter, filter_handle) = reload::Layer::new(config.filter);
+	let registry = tracing_subscriber::registry().with(layer.with_filter(filter));
 
 	#[cfg(feature = "console")]
 	let registry = registry.with(console::layer(config.console)?);
@@ -63,5 +99,5 @@ pub fn init(config: TracingConfig) -> color_eyre::Result<Option<Guard>>
 
 	tracing::info!("initialized tracing");
 
-	Ok(Some(Guard { appender_guard }))
+	Ok(Some(Guard { appender_guard, json_guard, filter: filter_handle }))
 }
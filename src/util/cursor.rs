@@ -0,0 +1,129 @@
+//! Opaque cursors for keyset pagination.
+//!
+//! Instead of skipping over `offset` rows, which is `O(offset)` in the
+//! database, paginated endpoints can hand out a [`Cursor`] pointing at the last
+//! item they returned. The next request then only fetches items "after" that
+//! cursor, e.g. with `WHERE id < ?`.
+
+use std::fmt;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// An opaque pagination cursor.
+///
+/// The wrapped value (usually a primary key) is encoded as URL-safe base64, so
+/// clients can't (and shouldn't) rely on its contents.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Cursor<T>(pub T);
+
+impl<T> Cursor<T>
+{
+	/// Returns the cursor that should be used to fetch the page after `items`.
+	///
+	/// If there are fewer than `limit` items, this is the last page, and
+	/// [`None`] is returned.
+	pub fn after_last<I>(items: &[I], limit: u64, key: impl FnOnce(&I) -> T) -> Option<Self>
+	{
+		if (items.len() as u64) < limit {
+			return None;
+		}
+
+		items.last().map(key).map(Self)
+	}
+}
+
+impl<T> fmt::Debug for Cursor<T>
+where
+	T: fmt::Debug,
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		f.debug_tuple("Cursor").field(&self.0).finish()
+	}
+}
+
+impl<T> Serialize for Cursor<T>
+where
+	T: Serialize,
+{
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let json = serde_json::to_vec(&self.0).map_err(serde::ser::Error::custom)?;
+
+		URL_SAFE_NO_PAD.encode(json).serialize(serializer)
+	}
+}
+
+impl<'de, T> Deserialize<'de> for Cursor<T>
+where
+	T: DeserializeOwned,
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let encoded = String::deserialize(deserializer)?;
+		let json = URL_SAFE_NO_PAD
+			.decode(encoded)
+			.map_err(|_| de::Error::custom("invalid cursor"))?;
+
+		serde_json::from_slice(&json)
+			.map(Self)
+			.map_err(|_| de::Error::custom("invalid cursor"))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::testing;
+
+	#[test]
+	fn round_trip() -> color_eyre::Result<()>
+	{
+		let cursor = Cursor(69_u64);
+		let encoded = serde_json::to_string(&cursor)?;
+
+		testing::assert_eq!(encoded, r#""Njk""#);
+		testing::assert_eq!(serde_json::from_str::<Cursor<u64>>(&encoded)?, cursor);
+
+		Ok(())
+	}
+
+	#[test]
+	fn rejects_garbage() -> color_eyre::Result<()>
+	{
+		let result = serde_json::from_str::<Cursor<u64>>(r#""not a cursor!""#);
+
+		testing::assert!(result.is_err());
+
+		let result = serde_json::from_str::<Cursor<u64>>(r#""Zm9v""#);
+
+		testing::assert!(result.is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn after_last() -> color_eyre::Result<()>
+	{
+		let items = [5_u64, 4, 3];
+
+		testing::assert_eq!(Cursor::after_last(&items, 3, |&id| id), Some(Cursor(3)));
+		let last_page = Cursor::after_last(&items, 4, |&id| id);
+
+		testing::assert!(last_page.is_none());
+
+		let empty = Cursor::after_last(&[] as &[u64], 0, |&id| id);
+
+		testing::assert!(empty.is_none());
+
+		Ok(())
+	}
+}
@@ -7,3 +7,6 @@ pub use name_or_id::{CourseIdentifier, MapIdentifier, PlayerIdentifier, ServerId
 
 mod is_empty;
 pub use is_empty::IsEmpty;
+
+mod cursor;
+pub use cursor::Cursor;